@@ -0,0 +1,158 @@
+// 宏键盘（Stream Deck、Loupedeck 等）插件使用的操作：GET /actions 列出全部操作及其参数的可选值，
+// POST /actions/{id} 执行。操作编号与参数名保持不变，插件可以据此生成按钮而不必写死
+
+use serde_json::{json, Map, Value};
+
+use crate::api::{self, PasteRequest};
+use crate::commands;
+use crate::i18n;
+use crate::profiles;
+use crate::snippets;
+
+/// 一个操作：编号、标题的键与参数
+struct Action {
+    id: &'static str,
+    title: &'static str,
+    param: Option<Param>,
+}
+
+/// 操作的参数：名称、标题的键与是否必填
+struct Param {
+    name: &'static str,
+    title: &'static str,
+    required: bool,
+}
+
+const ACTIONS: &[Action] = &[
+    Action {
+        id: "type_snippet",
+        title: "action.type_snippet",
+        param: Some(Param {
+            name: "snippet",
+            title: "action.param_snippet",
+            required: true,
+        }),
+    },
+    Action {
+        id: "paste_clipboard",
+        title: "action.paste_clipboard",
+        param: None,
+    },
+    Action {
+        id: "abort",
+        title: "action.abort",
+        param: None,
+    },
+    Action {
+        id: "toggle_pause",
+        title: "action.toggle_pause",
+        param: None,
+    },
+    Action {
+        id: "switch_profile",
+        title: "action.switch_profile",
+        param: Some(Param {
+            name: "profile",
+            title: "action.param_profile",
+            required: false,
+        }),
+    },
+];
+
+/// 参数的可选值：片段以编号为值（改标题后按钮仍然有效），配置方案以名称为值
+fn options(app_handle: &tauri::AppHandle, param: &str) -> Vec<Value> {
+    match param {
+        "snippet" => snippets::list_snippets(app_handle.clone())
+            .into_iter()
+            .map(|snippet| json!({ "value": snippet.id.to_string(), "label": snippet.title }))
+            .collect(),
+        "profile" => {
+            std::iter::once(json!({ "value": null, "label": i18n::t("tray.profile_none") }))
+                .chain(
+                    profiles::get_profiles(app_handle.clone())
+                        .profiles
+                        .into_iter()
+                        .map(|profile| json!({ "value": profile.name, "label": profile.name })),
+                )
+                .collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// GET /actions：全部操作，以及按钮可以显示的当前状态（暂停、选用的方案）
+pub fn list(app_handle: &tauri::AppHandle) -> Value {
+    let actions: Vec<Value> = ACTIONS
+        .iter()
+        .map(|action| {
+            let params: Vec<Value> = action
+                .param
+                .iter()
+                .map(|param| {
+                    json!({
+                        "name": param.name,
+                        "title": i18n::t(param.title),
+                        "required": param.required,
+                        "options": options(app_handle, param.name),
+                    })
+                })
+                .collect();
+            json!({
+                "id": action.id,
+                "title": i18n::t(action.title),
+                "method": "POST",
+                "path": format!("/actions/{}", action.id),
+                "params": params,
+            })
+        })
+        .collect();
+    json!({
+        "actions": actions,
+        "state": {
+            "paused": commands::get_pause(app_handle.clone()),
+            "profile": profiles::get_profiles(app_handle.clone()).active,
+        },
+    })
+}
+
+/// POST /actions/{id}：参数来自 JSON 请求体或查询参数；成功时返回 HTTP 状态码与应答
+pub fn run(
+    app_handle: &tauri::AppHandle,
+    id: &str,
+    params: &Map<String, Value>,
+) -> Result<(u16, Value), (u16, String)> {
+    // 片段编号可以写成数字
+    let param = |name: &str| match params.get(name) {
+        Some(Value::String(value)) => Some(value.clone()),
+        Some(Value::Number(value)) => Some(value.to_string()),
+        _ => None,
+    };
+    match id {
+        "type_snippet" => {
+            let name = param("snippet")
+                .ok_or_else(|| (400, i18n::tf("error.action_param", &[("name", &"snippet")])))?;
+            commands::check_idle(app_handle).map_err(|e| (409, e.to_string()))?;
+            snippets::start_snippet(app_handle, &name).map_err(|e| (404, e))?;
+            Ok((202, json!({ "accepted": true })))
+        }
+        "paste_clipboard" => {
+            PasteRequest::default().start(app_handle)?;
+            Ok((202, json!({ "accepted": true })))
+        }
+        "abort" => Ok((
+            200,
+            json!({ "aborted": api::abort_paste(app_handle, "api") }),
+        )),
+        "toggle_pause" => Ok((
+            200,
+            json!({ "paused": commands::toggle_paused(app_handle) }),
+        )),
+        "switch_profile" => {
+            let name = param("profile").filter(|name| !name.is_empty());
+            profiles::switch_profile(name, app_handle.clone()).map_err(|e| (404, e))?;
+            let active = profiles::get_profiles(app_handle.clone()).active;
+            Ok((200, json!({ "profile": active })))
+        }
+        _ => Err((404, i18n::tf("error.action_missing", &[("action", &id)]))),
+    }
+}
@@ -0,0 +1,793 @@
+use std::collections::HashMap;
+use std::sync::atomic::Ordering;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha1::{Digest, Sha1};
+use tauri::Manager;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{broadcast, mpsc};
+
+use crate::actions;
+use crate::commands::{self, PasteOptions, PasteState};
+use crate::i18n;
+use crate::profiles;
+use crate::settings;
+
+/// 默认监听端口
+pub const DEFAULT_PORT: u16 = 47215;
+/// 请求行与请求头的最大长度
+const MAX_HEAD_BYTES: usize = 16 * 1024;
+/// 请求体（要输入的文本）的最大长度
+const MAX_BODY_BYTES: usize = 4 * 1024 * 1024;
+/// 读取一个请求的最长时间，避免空闲连接一直占用
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// 重新开启时旧的监听可能还没释放端口，绑定失败时重试
+const BIND_ATTEMPTS: u32 = 5;
+const BIND_RETRY_DELAY: Duration = Duration::from_millis(100);
+/// 计算 Sec-WebSocket-Accept 时附加在客户端密钥后的固定 GUID（RFC 6455）
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC11B85";
+/// 订阅者来不及接收时最多缓存的事件数，超出后丢弃最旧的
+const EVENT_BUFFER: usize = 256;
+/// 客户端发来的单个 WebSocket 帧的最大长度；客户端只需发送控制帧
+const MAX_FRAME_BYTES: u64 = 64 * 1024;
+const OPCODE_TEXT: u8 = 0x1;
+const OPCODE_CLOSE: u8 = 0x8;
+const OPCODE_PING: u8 = 0x9;
+const OPCODE_PONG: u8 = 0xA;
+
+/// 本地 HTTP 接口：只监听 127.0.0.1，请求需携带令牌
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiSettings {
+    /// 默认关闭
+    pub enabled: bool,
+    pub port: u16,
+    /// 请求需在 Authorization: Bearer 中携带的令牌；开启时为空则自动生成
+    pub token: String,
+}
+
+impl ApiSettings {
+    const fn new() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_PORT,
+            token: String::new(),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.port == 0 {
+            return Err(i18n::t("error.port_zero"));
+        }
+        Ok(())
+    }
+}
+
+impl Default for ApiSettings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+static SETTINGS: Mutex<ApiSettings> = Mutex::new(ApiSettings::new());
+/// 正在运行的服务器；令牌修改后立即对新请求生效，端口修改或关闭时停止
+static SERVER: Mutex<Option<Server>> = Mutex::new(None);
+
+/// 推送给 GET /events 订阅者的引擎事件（已序列化的 JSON）
+static EVENTS: OnceLock<broadcast::Sender<String>> = OnceLock::new();
+
+struct Server {
+    port: u16,
+    task: tauri::async_runtime::JoinHandle<()>,
+}
+
+fn events() -> &'static broadcast::Sender<String> {
+    EVENTS.get_or_init(|| broadcast::channel(EVENT_BUFFER).0)
+}
+
+/// 把引擎事件推送给 WebSocket 订阅者，格式为 {"event": 名称, "payload": 内容}；
+/// 与前端收到的同名事件一致，没有订阅者时不做任何事
+pub fn publish<S: Serialize>(event: &str, payload: &S) {
+    let events = events();
+    if events.receiver_count() == 0 {
+        return;
+    }
+    let message = json!({ "event": event, "payload": payload });
+    let _ = events.send(message.to_string());
+}
+
+/// 随机生成 32 位十六进制的令牌
+pub fn generate_token() -> String {
+    let mut bytes = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// 当前的接口设置
+pub fn settings() -> ApiSettings {
+    SETTINGS.lock().unwrap().clone()
+}
+
+/// 应用接口设置：按需启动、重启或停止服务器。开启时没有令牌则生成一个并保存
+pub fn apply(app_handle: &tauri::AppHandle, mut settings: ApiSettings) {
+    let generated = settings.enabled && settings.token.is_empty();
+    if generated {
+        settings.token = generate_token();
+    }
+    *SETTINGS.lock().unwrap() = settings.clone();
+
+    {
+        let mut server = SERVER.lock().unwrap();
+        let port = settings.enabled.then_some(settings.port);
+        if server.as_ref().map(|server| server.port) != port {
+            if let Some(server) = server.take() {
+                server.task.abort();
+            }
+            if let Some(port) = port {
+                let task = tauri::async_runtime::spawn(serve(app_handle.clone(), port));
+                *server = Some(Server { port, task });
+            }
+        }
+    }
+
+    if generated {
+        // 调用方可能还在应用其余设置，稍后再保存
+        let app_handle = app_handle.clone();
+        tauri::async_runtime::spawn(async move { settings::persist(&app_handle) });
+    }
+}
+
+async fn bind(port: u16) -> std::io::Result<TcpListener> {
+    let mut attempt = 1;
+    loop {
+        match TcpListener::bind(("127.0.0.1", port)).await {
+            Ok(listener) => return Ok(listener),
+            Err(e) if attempt >= BIND_ATTEMPTS => return Err(e),
+            Err(_) => {
+                attempt += 1;
+                tokio::time::sleep(BIND_RETRY_DELAY).await;
+            }
+        }
+    }
+}
+
+/// 监听端口并逐个处理连接；端口被占用时发送 api-failed 事件
+async fn serve(app_handle: tauri::AppHandle, port: u16) {
+    let listener = match bind(port).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            let message = i18n::tf("error.api_bind", &[("port", &port), ("error", &e)]);
+            #[cfg(debug_assertions)]
+            eprintln!("{}", message);
+            let _ = app_handle.emit_all("api-failed", message);
+            return;
+        }
+    };
+
+    #[cfg(debug_assertions)]
+    println!("本地 HTTP 接口已启动: http://127.0.0.1:{}", port);
+
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(_e) => {
+                #[cfg(debug_assertions)]
+                eprintln!("接受连接失败: {}", _e);
+                tokio::time::sleep(BIND_RETRY_DELAY).await;
+                continue;
+            }
+        };
+        let app_handle = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(_e) = handle_connection(&app_handle, stream).await {
+                #[cfg(debug_assertions)]
+                eprintln!("处理 HTTP 请求失败: {}", _e);
+            }
+        });
+    }
+}
+
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub query: HashMap<String, String>,
+    /// 名称已转为小写
+    headers: HashMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).map(String::as_str)
+    }
+}
+
+pub struct Response {
+    status: u16,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Response {
+    pub fn new(status: u16, body: Value) -> Self {
+        Self {
+            status,
+            content_type: "application/json; charset=utf-8",
+            body: body.to_string(),
+        }
+    }
+
+    pub fn ok(body: Value) -> Self {
+        Self::new(200, body)
+    }
+
+    pub fn error(status: u16, message: impl Into<String>) -> Self {
+        Self::new(status, json!({ "error": message.into() }))
+    }
+
+    pub fn html(body: String) -> Self {
+        Self {
+            status: 200,
+            content_type: "text/html; charset=utf-8",
+            body,
+        }
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            202 => "Accepted",
+            400 => "Bad Request",
+            401 => "Unauthorized",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            408 => "Request Timeout",
+            409 => "Conflict",
+            411 => "Length Required",
+            413 => "Payload Too Large",
+            426 => "Upgrade Required",
+            431 => "Request Header Fields Too Large",
+            _ => "Internal Server Error",
+        }
+    }
+}
+
+async fn handle_connection(
+    app_handle: &tauri::AppHandle,
+    mut stream: TcpStream,
+) -> std::io::Result<()> {
+    let request = match tokio::time::timeout(REQUEST_TIMEOUT, read_request(&mut stream)).await {
+        Ok(Ok(request)) => request,
+        Ok(Err(response)) => return write_response(&mut stream, &response).await,
+        Err(_) => {
+            return write_response(&mut stream, &Response::error(408, "Request Timeout")).await
+        }
+    };
+    if request.path == "/events" && is_websocket_upgrade(&request) {
+        // 浏览器中的 WebSocket 无法设置请求头，令牌也可以放在查询参数 token 中
+        let token = bearer(&request).or(request.query.get("token").map(String::as_str));
+        if !authorized(token) {
+            let response = Response::error(401, i18n::t("error.api_unauthorized"));
+            return write_response(&mut stream, &response).await;
+        }
+        return stream_events(app_handle, stream, &request).await;
+    }
+    let response = route(app_handle, request).await;
+    write_response(&mut stream, &response).await
+}
+
+/// 解析 HTTP/1.1 请求；格式错误或超出长度限制时返回应答的错误响应
+pub async fn read_request(stream: &mut TcpStream) -> Result<Request, Response> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let head_end = loop {
+        if let Some(position) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+            break position;
+        }
+        if buffer.len() > MAX_HEAD_BYTES {
+            return Err(Response::error(431, "Request Header Fields Too Large"));
+        }
+        match stream.read(&mut chunk).await {
+            Ok(0) | Err(_) => return Err(Response::error(400, "Bad Request")),
+            Ok(read) => buffer.extend_from_slice(&chunk[..read]),
+        }
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..head_end]).into_owned();
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or_default().split(' ');
+    let (Some(method), Some(target)) = (request_line.next(), request_line.next()) else {
+        return Err(Response::error(400, "Bad Request"));
+    };
+    let headers: HashMap<String, String> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+        .collect();
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+    if headers.contains_key("transfer-encoding") {
+        return Err(Response::error(411, "Length Required"));
+    }
+    let length = match headers.get("content-length") {
+        Some(length) => length
+            .parse::<usize>()
+            .map_err(|_| Response::error(400, "Bad Request"))?,
+        None => 0,
+    };
+    if length > MAX_BODY_BYTES {
+        return Err(Response::error(413, "Payload Too Large"));
+    }
+    let mut body = buffer.split_off(head_end + 4);
+    while body.len() < length {
+        match stream.read(&mut chunk).await {
+            Ok(0) | Err(_) => return Err(Response::error(400, "Bad Request")),
+            Ok(read) => body.extend_from_slice(&chunk[..read]),
+        }
+    }
+    body.truncate(length);
+
+    Ok(Request {
+        method: method.to_string(),
+        path: path.to_string(),
+        query: parse_query(query),
+        headers,
+        body,
+    })
+}
+
+pub async fn write_response(stream: &mut TcpStream, response: &Response) -> std::io::Result<()> {
+    let head = format!(
+        "HTTP/1.1 {} {}\r\n\
+         Content-Type: {}\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\r\n",
+        response.status,
+        response.reason(),
+        response.content_type,
+        response.body.len()
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(response.body.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+/// 解析查询字符串（application/x-www-form-urlencoded）
+pub fn parse_query(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(name), percent_decode(value))
+        })
+        .collect()
+}
+
+pub fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+                match hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                    Some(byte) => {
+                        decoded.push(byte);
+                        i += 2;
+                    }
+                    None => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Authorization: Bearer 中携带的令牌
+pub fn bearer(request: &Request) -> Option<&str> {
+    request
+        .header("authorization")
+        .and_then(|value| value.strip_prefix("Bearer "))
+}
+
+/// 令牌是否正确
+fn authorized(given: Option<&str>) -> bool {
+    let token = SETTINGS.lock().unwrap().token.clone();
+    given.is_some_and(|given| tokens_match(given, &token))
+}
+
+/// 比较令牌：逐字节比较全部内容，耗时与令牌内容无关；令牌为空时总是不匹配
+pub fn tokens_match(given: &str, token: &str) -> bool {
+    !token.is_empty()
+        && given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+async fn route(app_handle: &tauri::AppHandle, request: Request) -> Response {
+    if !authorized(bearer(&request)) {
+        return Response::error(401, i18n::t("error.api_unauthorized"));
+    }
+    match (request.method.as_str(), request.path.as_str()) {
+        ("POST", "/paste") => paste(app_handle, &request),
+        ("POST", "/abort") => abort(app_handle),
+        ("GET", "/status") => status(app_handle),
+        ("GET", "/events") => Response::error(426, "Upgrade Required"),
+        ("GET", "/actions") => Response::ok(actions::list(app_handle)),
+        ("POST", path) if path.starts_with("/actions/") => run_action(app_handle, &request),
+        (_, "/paste" | "/abort" | "/status" | "/events" | "/actions") => {
+            Response::error(405, "Method Not Allowed")
+        }
+        (_, path) if path.starts_with("/actions/") => Response::error(405, "Method Not Allowed"),
+        _ => Response::error(404, "Not Found"),
+    }
+}
+
+/// 执行 POST /actions/{id}：参数为 JSON 对象请求体，或查询参数
+fn run_action(app_handle: &tauri::AppHandle, request: &Request) -> Response {
+    let id = percent_decode(&request.path["/actions/".len()..]);
+    let params = if request.body.iter().all(u8::is_ascii_whitespace) {
+        request
+            .query
+            .iter()
+            .map(|(name, value)| (name.clone(), Value::String(value.clone())))
+            .collect()
+    } else {
+        match serde_json::from_slice::<serde_json::Map<String, Value>>(&request.body) {
+            Ok(params) => params,
+            Err(e) => {
+                return Response::error(400, i18n::tf("error.api_bad_request", &[("error", &e)]))
+            }
+        }
+    };
+    match actions::run(app_handle, &id, &params) {
+        Ok((status, body)) => Response::new(status, body),
+        Err((status, e)) => Response::error(status, e),
+    }
+}
+
+/// POST /paste 的参数：JSON 请求体，或纯文本请求体加查询参数（stand、float、instant、profile、delay）；
+/// 进程间通道的 paste 命令使用同样的字段
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PasteRequest {
+    /// 要输入的文本，省略时输入剪贴板
+    text: Option<String>,
+    stand: Option<u32>,
+    float: Option<u32>,
+    instant: Option<bool>,
+    /// 使用指定的配置方案（名称）
+    profile: Option<String>,
+    /// 开始输入前至少倒计时的秒数
+    delay: Option<u32>,
+}
+
+impl PasteRequest {
+    /// 以默认参数输入 text
+    pub fn from_text(text: String) -> Self {
+        Self {
+            text: Some(text),
+            ..Self::default()
+        }
+    }
+
+    /// 未指定倒计时时使用 secs
+    pub fn with_default_delay(mut self, secs: u32) -> Self {
+        self.delay.get_or_insert(secs);
+        self
+    }
+
+    fn parse(request: &Request) -> Result<Self, String> {
+        let is_json = request
+            .header("content-type")
+            .is_some_and(|content_type| content_type.starts_with("application/json"));
+        if is_json {
+            return serde_json::from_slice(&request.body).map_err(|e| e.to_string());
+        }
+        let text = String::from_utf8(request.body.clone()).map_err(|e| e.to_string())?;
+        let param = |name: &str| request.query.get(name).map(String::as_str);
+        let number = |name: &str| {
+            param(name)
+                .map(|value| value.parse::<u32>().map_err(|e| format!("{}: {}", name, e)))
+                .transpose()
+        };
+        Ok(Self {
+            text: (!text.is_empty()).then_some(text),
+            stand: number("stand")?,
+            float: number("float")?,
+            instant: param("instant")
+                .map(|value| value.parse::<bool>().map_err(|e| format!("instant: {}", e)))
+                .transpose()?,
+            profile: param("profile").map(str::to_string),
+            delay: number("delay")?,
+        })
+    }
+
+    /// 检查配置方案与输入状态后开始输入，不等待输入结束；失败时返回 HTTP 状态码与错误信息
+    pub fn start(self, app_handle: &tauri::AppHandle) -> Result<(), (u16, String)> {
+        if let Some(name) = &self.profile {
+            if !profiles::exists(app_handle, name) {
+                return Err((400, i18n::tf("error.profile_missing", &[("name", name)])));
+            }
+        }
+        commands::check_idle(app_handle).map_err(|e| (409, e.to_string()))?;
+
+        let fixed_timing = self.stand.is_some() || self.float.is_some();
+        let (stand, float) = commands::resolve_timing(app_handle, self.stand, self.float);
+        let options = PasteOptions {
+            instant: self.instant,
+            fixed_timing,
+            profile: self.profile,
+            min_start_delay_secs: self.delay.unwrap_or(0).min(commands::MAX_START_DELAY_SECS),
+            ..PasteOptions::default()
+        };
+        let text = self
+            .text
+            .map(|text| text.encode_utf16().filter(|&unit| unit != 13).collect());
+        let app_handle = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(_e) = commands::paste_with(&app_handle, text, stand, float, options).await {
+                #[cfg(debug_assertions)]
+                println!("外部请求的粘贴失败: {}", _e);
+            }
+        });
+        Ok(())
+    }
+}
+
+/// 开始输入，不等待输入结束；结果通过 GET /status 查询
+fn paste(app_handle: &tauri::AppHandle, request: &Request) -> Response {
+    let paste = match PasteRequest::parse(request) {
+        Ok(paste) => paste,
+        Err(e) => return Response::error(400, i18n::tf("error.api_bad_request", &[("error", &e)])),
+    };
+    match paste.start(app_handle) {
+        Ok(()) => Response::new(202, json!({ "accepted": true })),
+        Err((status, e)) => Response::error(status, e),
+    }
+}
+
+/// 中止正在进行的粘贴
+fn abort(app_handle: &tauri::AppHandle) -> Response {
+    Response::ok(json!({ "aborted": abort_paste(app_handle, "api") }))
+}
+
+/// 以 reason 中止正在进行的粘贴，返回是否有粘贴被中止
+pub fn abort_paste(app_handle: &tauri::AppHandle, reason: &'static str) -> bool {
+    let state = app_handle.state::<Mutex<PasteState>>();
+    let mut locked = state.lock().unwrap();
+    let pasting = locked.is_pasting.load(Ordering::SeqCst);
+    locked.abort(reason);
+    pasting
+}
+
+/// 输入引擎的状态（同 get_typing_status），以及是否已暂停全部功能
+fn status(app_handle: &tauri::AppHandle) -> Response {
+    Response::ok(status_body(app_handle))
+}
+
+pub fn status_body(app_handle: &tauri::AppHandle) -> Value {
+    let paused = {
+        let state = app_handle.state::<Mutex<PasteState>>();
+        let locked = state.lock().unwrap();
+        locked.is_paused
+    };
+    let mut body = serde_json::to_value(commands::get_typing_status(app_handle.clone()))
+        .unwrap_or_else(|_| json!({}));
+    body["paused"] = Value::Bool(paused);
+    body
+}
+
+fn is_websocket_upgrade(request: &Request) -> bool {
+    request.method == "GET"
+        && request
+            .header("upgrade")
+            .is_some_and(|upgrade| upgrade.eq_ignore_ascii_case("websocket"))
+}
+
+/// GET /events：升级为 WebSocket 后先发送一次 status 事件（同 GET /status），
+/// 之后推送 paste-countdown、paste-progress、paste-interrupted、paste-finished、
+/// secure-input-blocked、pause-changed 与 profile-changed 事件。客户端发来的数据帧被忽略
+async fn stream_events(
+    app_handle: &tauri::AppHandle,
+    mut stream: TcpStream,
+    request: &Request,
+) -> std::io::Result<()> {
+    let Some(key) = request.header("sec-websocket-key") else {
+        return write_response(&mut stream, &Response::error(400, "Bad Request")).await;
+    };
+    let mut hasher = Sha1::new();
+    hasher.update(key.as_bytes());
+    hasher.update(WEBSOCKET_GUID.as_bytes());
+    let head = format!(
+        "HTTP/1.1 101 Switching Protocols\r\n\
+         Upgrade: websocket\r\n\
+         Connection: Upgrade\r\n\
+         Sec-WebSocket-Accept: {}\r\n\r\n",
+        STANDARD.encode(hasher.finalize())
+    );
+    stream.write_all(head.as_bytes()).await?;
+
+    // 先订阅再发送状态，避免错过其间的事件
+    let mut events = events().subscribe();
+    let (reader, mut writer) = stream.into_split();
+    let hello = json!({ "event": "status", "payload": status_body(app_handle) });
+    write_frame(&mut writer, OPCODE_TEXT, hello.to_string().as_bytes()).await?;
+
+    // 读取与推送分开进行：读取帧不能在中途取消，控制帧的应答交给推送循环发送
+    let (replies, mut pending) = mpsc::channel(8);
+    let reading = tauri::async_runtime::spawn(read_control_frames(reader, replies));
+    let result = loop {
+        tokio::select! {
+            event = events.recv() => match event {
+                Ok(message) => {
+                    if let Err(e) = write_frame(&mut writer, OPCODE_TEXT, message.as_bytes()).await {
+                        break Err(e);
+                    }
+                }
+                // 来不及接收的事件已被丢弃，继续推送之后的
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break Ok(()),
+            },
+            reply = pending.recv() => match reply {
+                Some((opcode, payload)) => {
+                    if let Err(e) = write_frame(&mut writer, opcode, &payload).await {
+                        break Err(e);
+                    }
+                    if opcode == OPCODE_CLOSE {
+                        break Ok(());
+                    }
+                }
+                // 连接已断开或客户端发送了无效的帧
+                None => break Ok(()),
+            },
+        }
+    };
+    reading.abort();
+    let _ = writer.shutdown().await;
+    result
+}
+
+/// 读取客户端的帧：ping 以 pong 应答，close 以 close 应答后结束
+async fn read_control_frames<R: AsyncRead + Unpin>(
+    mut reader: R,
+    replies: mpsc::Sender<(u8, Vec<u8>)>,
+) {
+    while let Ok((opcode, payload)) = read_frame(&mut reader).await {
+        let reply = match opcode {
+            OPCODE_PING => (OPCODE_PONG, payload),
+            OPCODE_CLOSE => (OPCODE_CLOSE, payload),
+            _ => continue,
+        };
+        let close = reply.0 == OPCODE_CLOSE;
+        if replies.send(reply).await.is_err() || close {
+            return;
+        }
+    }
+}
+
+/// 读取一个客户端帧并去掉掩码；分片帧按各自的 opcode 处理（延续帧会被忽略）
+async fn read_frame<R: AsyncRead + Unpin>(reader: &mut R) -> std::io::Result<(u8, Vec<u8>)> {
+    let mut header = [0u8; 2];
+    reader.read_exact(&mut header).await?;
+    let opcode = header[0] & 0x0F;
+    let masked = header[1] & 0x80 != 0;
+    let length = match header[1] & 0x7F {
+        126 => reader.read_u16().await? as u64,
+        127 => reader.read_u64().await?,
+        length => length as u64,
+    };
+    // 客户端发来的帧必须带掩码
+    if !masked || length > MAX_FRAME_BYTES {
+        return Err(std::io::ErrorKind::InvalidData.into());
+    }
+    let mut mask = [0u8; 4];
+    reader.read_exact(&mut mask).await?;
+    let mut payload = vec![0u8; length as usize];
+    reader.read_exact(&mut payload).await?;
+    for (i, byte) in payload.iter_mut().enumerate() {
+        *byte ^= mask[i % 4];
+    }
+    Ok((opcode, payload))
+}
+
+/// 发送一个不分片、不带掩码的帧
+async fn write_frame<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    opcode: u8,
+    payload: &[u8],
+) -> std::io::Result<()> {
+    let mut frame = Vec::with_capacity(payload.len() + 10);
+    frame.push(0x80 | opcode);
+    match payload.len() {
+        length @ 0..=125 => frame.push(length as u8),
+        length @ 126..=0xFFFF => {
+            frame.push(126);
+            frame.extend_from_slice(&(length as u16).to_be_bytes());
+        }
+        length => {
+            frame.push(127);
+            frame.extend_from_slice(&(length as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(payload);
+    writer.write_all(&frame).await
+}
+
+/// 获取本地 HTTP 接口的设置（含令牌）
+#[tauri::command]
+pub fn get_api_settings() -> ApiSettings {
+    settings()
+}
+
+/// 开启或关闭本地 HTTP 接口，返回生效后的设置（首次开启时生成令牌）
+#[tauri::command]
+pub fn set_api_enabled(enabled: bool, app_handle: tauri::AppHandle) -> ApiSettings {
+    apply(
+        &app_handle,
+        ApiSettings {
+            enabled,
+            ..settings()
+        },
+    );
+    settings::persist(&app_handle);
+    settings()
+}
+
+/// 修改监听端口，开启时立即在新端口上重启
+#[tauri::command]
+pub fn set_api_port(port: u16, app_handle: tauri::AppHandle) -> Result<(), String> {
+    let updated = ApiSettings { port, ..settings() };
+    updated.validate()?;
+    apply(&app_handle, updated);
+    settings::persist(&app_handle);
+    Ok(())
+}
+
+/// 重新生成令牌，旧令牌立即失效
+#[tauri::command]
+pub fn regenerate_api_token(app_handle: tauri::AppHandle) -> String {
+    let token = generate_token();
+    apply(
+        &app_handle,
+        ApiSettings {
+            token: token.clone(),
+            ..settings()
+        },
+    );
+    settings::persist(&app_handle);
+    token
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percent_decode_handles_escapes() {
+        assert_eq!(percent_decode("a+b%20c"), "a b c");
+        assert_eq!(percent_decode("%E4%BD%A0%e5%a5%bd"), "你好");
+        assert_eq!(percent_decode("%2B%25"), "+%");
+    }
+
+    #[test]
+    fn percent_decode_keeps_invalid_escapes() {
+        assert_eq!(percent_decode("%zz"), "%zz");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%4"), "%4");
+        assert_eq!(percent_decode("%FF"), "\u{FFFD}");
+    }
+}
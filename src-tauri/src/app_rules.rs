@@ -0,0 +1,113 @@
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+use crate::commands::{InputBackend, NewlineStrategy};
+use crate::foreground;
+use crate::i18n;
+use crate::settings;
+use crate::snippets::SnippetTiming;
+use crate::transform::Transform;
+
+/// 按前台程序覆盖输入设置的规则，如远程桌面中慢速按扫描码输入、记事本中极速输入。
+/// 开始输入时按顺序匹配，第一条匹配的规则生效；未填写的项不覆盖。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppRule {
+    /// 进程名（如 "mstsc.exe"），为空时不按进程名匹配
+    pub process: String,
+    /// 窗口类名（如 "TscShellContainerClass"），为空时不按窗口类匹配
+    pub window_class: String,
+    /// 速度与是否极速输入
+    pub timing: Option<SnippetTiming>,
+    pub newline: Option<NewlineStrategy>,
+    pub backend: Option<InputBackend>,
+    pub transforms: Option<Vec<Transform>>,
+}
+
+impl AppRule {
+    /// 填写的条件都满足时匹配（忽略大小写）
+    fn matches(&self, process: Option<&str>, window_class: Option<&str>) -> bool {
+        let field_matches = |pattern: &str, value: Option<&str>| {
+            pattern.is_empty() || value.is_some_and(|value| value.eq_ignore_ascii_case(pattern))
+        };
+        field_matches(&self.process, process) && field_matches(&self.window_class, window_class)
+    }
+}
+
+/// 检查每条规则至少填写了进程名或窗口类名
+pub fn validate(rules: &[AppRule]) -> Result<(), String> {
+    for (i, rule) in rules.iter().enumerate() {
+        if rule.process.trim().is_empty() && rule.window_class.trim().is_empty() {
+            return Err(i18n::tf("error.app_rule_criteria", &[("index", &(i + 1))]));
+        }
+    }
+    Ok(())
+}
+
+pub struct AppRuleState {
+    rules: Vec<AppRule>,
+}
+
+impl AppRuleState {
+    pub fn new() -> Self {
+        Self { rules: Vec::new() }
+    }
+
+    pub fn rules(&self) -> &[AppRule] {
+        &self.rules
+    }
+
+    pub fn set_rules(&mut self, rules: Vec<AppRule>) {
+        self.rules = rules;
+    }
+}
+
+/// 与当前前台窗口匹配的第一条规则
+pub fn for_foreground(app_handle: &tauri::AppHandle) -> Option<AppRule> {
+    let state = app_handle.state::<Mutex<AppRuleState>>();
+    let locked = state.lock().unwrap();
+    if locked.rules.is_empty() {
+        return None;
+    }
+    let hwnd = foreground::foreground_window();
+    if hwnd.0 == 0 {
+        return None;
+    }
+    let process = foreground::process_name(foreground::window_process_id(hwnd));
+    let window_class = foreground::window_class(hwnd);
+    locked
+        .rules
+        .iter()
+        .find(|rule| rule.matches(process.as_deref(), window_class.as_deref()))
+        .cloned()
+}
+
+/// 获取全部程序规则
+#[tauri::command]
+pub fn get_app_rules(app_handle: tauri::AppHandle) -> Vec<AppRule> {
+    let state = app_handle.state::<Mutex<AppRuleState>>();
+    let locked = state.lock().unwrap();
+    locked.rules.clone()
+}
+
+/// 替换全部程序规则（顺序即匹配顺序）
+#[tauri::command]
+pub fn set_app_rules(rules: Vec<AppRule>, app_handle: tauri::AppHandle) -> Result<(), String> {
+    let rules: Vec<AppRule> = rules
+        .into_iter()
+        .map(|rule| AppRule {
+            process: rule.process.trim().to_string(),
+            window_class: rule.window_class.trim().to_string(),
+            ..rule
+        })
+        .collect();
+    validate(&rules)?;
+    {
+        let state = app_handle.state::<Mutex<AppRuleState>>();
+        state.lock().unwrap().set_rules(rules);
+    }
+    settings::persist(&app_handle);
+    Ok(())
+}
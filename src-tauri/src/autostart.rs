@@ -0,0 +1,170 @@
+#[cfg(target_os = "linux")]
+use std::path::PathBuf;
+
+#[cfg(not(target_os = "linux"))]
+use auto_launch::{AutoLaunch, AutoLaunchBuilder};
+
+use crate::config;
+use crate::i18n;
+use crate::settings;
+use crate::tray;
+
+/// 开机自启时传给程序的参数：不显示窗口
+const AUTOSTART_ARGS: [&str; 1] = ["--silent"];
+
+/// 开机自启（以及打开 paster:// 链接）时启动的程序
+pub fn app_path() -> Result<String, String> {
+    // AppImage 运行时 current_exe 位于临时挂载目录，重启后不存在，需要使用 AppImage 文件本身
+    #[cfg(target_os = "linux")]
+    if let Some(appimage) = std::env::var_os("APPIMAGE").filter(|path| !path.is_empty()) {
+        return Ok(appimage.to_string_lossy().into_owned());
+    }
+    let exe =
+        std::env::current_exe().map_err(|e| i18n::tf("error.autostart_exe", &[("error", &e)]))?;
+    Ok(exe.to_string_lossy().into_owned())
+}
+
+/// Windows 为注册表 Run 项，macOS 为 ~/Library/LaunchAgents 中的 LaunchAgent
+/// （登录项方式无法传递 --silent）
+#[cfg(not(target_os = "linux"))]
+fn launcher() -> Result<AutoLaunch, String> {
+    let path = app_path()?;
+    // Run 项的值整体作为命令行解析，路径含空格（如 Program Files）时需要加引号
+    #[cfg(windows)]
+    let path = format!("\"{}\"", path);
+    AutoLaunchBuilder::new()
+        .set_app_name("Paster")
+        .set_app_path(&path)
+        .set_use_launch_agent(true)
+        .set_args(&AUTOSTART_ARGS)
+        .build()
+        .map_err(|e| i18n::tf("error.autostart_create", &[("error", &e)]))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn registered() -> Result<bool, String> {
+    launcher()?
+        .is_enabled()
+        .map_err(|e| i18n::tf("error.autostart_read", &[("error", &e)]))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn register() -> Result<(), String> {
+    launcher()?
+        .enable()
+        .map_err(|e| i18n::tf("error.autostart_enable", &[("error", &e)]))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn unregister() -> Result<(), String> {
+    launcher()?
+        .disable()
+        .map_err(|e| i18n::tf("error.autostart_disable", &[("error", &e)]))
+}
+
+/// XDG 自启目录中的 .desktop 文件：$XDG_CONFIG_HOME/autostart，默认 ~/.config/autostart
+#[cfg(target_os = "linux")]
+fn desktop_file() -> Result<PathBuf, String> {
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .ok_or_else(|| i18n::tf("error.autostart_create", &[("error", &"HOME")]))?;
+    Ok(config_home.join("autostart").join("paster.desktop"))
+}
+
+/// 按 Desktop Entry 规范为 Exec 中的参数加引号：先转义引号内的保留字符，再转义字符串中的反斜杠
+#[cfg(target_os = "linux")]
+pub fn quote_exec_arg(arg: &str) -> String {
+    if !arg.contains(|c: char| c.is_whitespace() || "\"'\\`$;&|<>()*?#~".contains(c)) {
+        return arg.to_string();
+    }
+    let mut quoted = String::from("\"");
+    for c in arg.chars() {
+        match c {
+            '"' | '`' | '$' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            '\\' => quoted.push_str("\\\\\\\\"),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(target_os = "linux")]
+fn registered() -> Result<bool, String> {
+    Ok(desktop_file()?.exists())
+}
+
+#[cfg(target_os = "linux")]
+fn register() -> Result<(), String> {
+    let file = desktop_file()?;
+    let exec = std::iter::once(app_path()?)
+        .chain(AUTOSTART_ARGS.iter().map(|arg| arg.to_string()))
+        .map(|arg| quote_exec_arg(&arg))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let entry = format!(
+        "[Desktop Entry]\n\
+         Type=Application\n\
+         Name=Paster\n\
+         Exec={}\n\
+         Terminal=false\n\
+         StartupNotify=false\n\
+         X-GNOME-Autostart-enabled=true\n",
+        exec
+    );
+    if let Some(dir) = file.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| i18n::tf("error.autostart_enable", &[("error", &e)]))?;
+    }
+    std::fs::write(&file, entry).map_err(|e| i18n::tf("error.autostart_enable", &[("error", &e)]))
+}
+
+#[cfg(target_os = "linux")]
+fn unregister() -> Result<(), String> {
+    std::fs::remove_file(desktop_file()?)
+        .map_err(|e| i18n::tf("error.autostart_disable", &[("error", &e)]))
+}
+
+/// 是否已注册开机自启（便携模式下总是 false）
+pub fn is_enabled() -> bool {
+    if config::is_portable() {
+        return false;
+    }
+    registered().unwrap_or(false)
+}
+
+/// 按设置注册或移除开机自启项；与当前状态相同时不做任何事
+pub fn set_enabled(enabled: bool) -> Result<(), String> {
+    if config::is_portable() {
+        return if enabled {
+            Err(i18n::t("error.autostart_portable").to_string())
+        } else {
+            Ok(())
+        };
+    }
+    match (registered()?, enabled) {
+        (false, true) => register(),
+        (true, false) => unregister(),
+        _ => Ok(()),
+    }
+}
+
+/// 获取是否开机自启
+#[tauri::command]
+pub fn get_autostart() -> bool {
+    is_enabled()
+}
+
+/// 开启或关闭开机自启，并保存到设置
+#[tauri::command]
+pub fn set_autostart(enabled: bool, app_handle: tauri::AppHandle) -> Result<(), String> {
+    set_enabled(enabled)?;
+    settings::persist(&app_handle);
+    tray::refresh(&app_handle);
+    Ok(())
+}
@@ -0,0 +1,128 @@
+// paster-cli：通过进程间通道控制正在运行的 Paster，供脚本与计划任务调用；
+// 由浏览器启动时担任浏览器扩展的原生消息主机
+
+use std::io::Read;
+use std::process::ExitCode;
+
+use serde_json::{json, Map, Value};
+
+#[path = "../../ipc/client.rs"]
+mod client;
+// 设置中的语言只由主程序读写
+#[path = "../../i18n/catalog.rs"]
+#[allow(dead_code)]
+mod i18n;
+mod native_host;
+
+/// 参数错误
+const EXIT_USAGE: u8 = 2;
+/// Paster 未在运行
+const EXIT_NOT_RUNNING: u8 = 3;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(origin) = native_host::origin(&args) {
+        return native_host::run(origin);
+    }
+    let request = match build_request(&args) {
+        Ok(request) => request,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, i18n::t("cli.usage"));
+            return ExitCode::from(EXIT_USAGE);
+        }
+    };
+
+    let response = match client::send(&request) {
+        Ok(response) => response,
+        Err(e) => {
+            eprintln!("{}", i18n::tf("cli.not_running", &[("error", &e)]));
+            return ExitCode::from(EXIT_NOT_RUNNING);
+        }
+    };
+    if response["ok"] == Value::Bool(true) {
+        match &response["result"] {
+            Value::Null => {}
+            result => println!(
+                "{}",
+                serde_json::to_string_pretty(result).unwrap_or_default()
+            ),
+        }
+        ExitCode::SUCCESS
+    } else {
+        eprintln!(
+            "{}",
+            response["error"]
+                .as_str()
+                .unwrap_or(i18n::t("cli.unknown_error"))
+        );
+        ExitCode::FAILURE
+    }
+}
+
+fn build_request(args: &[String]) -> Result<Value, String> {
+    let Some((command, rest)) = args.split_first() else {
+        return Err(i18n::t("cli.missing_command").to_string());
+    };
+    match command.as_str() {
+        "paste" => paste_request(rest),
+        "abort" | "status" if rest.is_empty() => Ok(json!({ "command": command })),
+        "snippet" => match rest {
+            [name] => Ok(json!({ "command": "snippet", "name": name })),
+            _ => Err(i18n::t("cli.snippet_name").to_string()),
+        },
+        "-h" | "--help" | "help" => {
+            println!("{}", i18n::t("cli.usage"));
+            std::process::exit(0);
+        }
+        _ => Err(i18n::tf(
+            "cli.unknown_command",
+            &[("command", &args.join(" "))],
+        )),
+    }
+}
+
+fn paste_request(args: &[String]) -> Result<Value, String> {
+    let mut request = Map::new();
+    request.insert("command".to_string(), json!("paste"));
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .cloned()
+                .ok_or_else(|| i18n::tf("cli.needs_value", &[("arg", arg)]))
+        };
+        match arg.as_str() {
+            "--text" => {
+                request.insert("text".to_string(), json!(value()?));
+            }
+            "--file" => {
+                let path = value()?;
+                let text = if path == "-" {
+                    let mut text = String::new();
+                    std::io::stdin()
+                        .read_to_string(&mut text)
+                        .map_err(|e| i18n::tf("cli.read_stdin", &[("error", &e)]))?;
+                    text
+                } else {
+                    std::fs::read_to_string(&path)
+                        .map_err(|e| i18n::tf("cli.read_file", &[("path", &path), ("error", &e)]))?
+                };
+                request.insert("text".to_string(), json!(text));
+            }
+            "--profile" => {
+                request.insert("profile".to_string(), json!(value()?));
+            }
+            "--stand" | "--float" => {
+                let ms = value()?
+                    .parse::<u32>()
+                    .map_err(|_| i18n::tf("cli.needs_ms", &[("arg", arg)]))?;
+                request.insert(arg[2..].to_string(), json!(ms));
+            }
+            "--instant" => {
+                request.insert("instant".to_string(), json!(true));
+            }
+            _ => return Err(i18n::tf("cli.unknown_arg", &[("arg", arg)])),
+        }
+    }
+    Ok(Value::Object(request))
+}
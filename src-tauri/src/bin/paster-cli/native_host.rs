@@ -0,0 +1,75 @@
+// 原生消息主机：浏览器按清单启动 paster-cli，扩展的消息经标准输入输出传递，
+// 每条消息为 4 字节本机字节序的长度加 UTF-8 JSON
+
+use std::io::{self, Read, Write};
+use std::process::ExitCode;
+
+use serde_json::{json, Value};
+
+use crate::client;
+use crate::i18n;
+
+const CHROME_ORIGIN_PREFIX: &str = "chrome-extension://";
+/// 扩展发来的单条消息的最大长度
+const MAX_MESSAGE_BYTES: u32 = 4 * 1024 * 1024;
+
+/// 由浏览器启动时返回扩展的来源：Chrome 系传入 chrome-extension://编号/，
+/// Firefox 传入清单路径与扩展的 id；否则为命令行调用
+pub fn origin(args: &[String]) -> Option<&str> {
+    match args {
+        [origin, ..] if origin.starts_with(CHROME_ORIGIN_PREFIX) => Some(origin),
+        [manifest, extension, ..] if manifest.ends_with(".json") => Some(extension),
+        _ => None,
+    }
+}
+
+/// 逐条转发扩展的消息给正在运行的 Paster 并回复应答，直到浏览器关闭标准输入
+pub fn run(origin: &str) -> ExitCode {
+    let mut stdin = io::stdin().lock();
+    let mut stdout = io::stdout().lock();
+    loop {
+        let message = match read_message(&mut stdin) {
+            Ok(Some(message)) => message,
+            Ok(None) => return ExitCode::SUCCESS,
+            Err(_) => return ExitCode::FAILURE,
+        };
+        let response = match serde_json::from_slice::<Value>(&message) {
+            Ok(request) => forward(origin, request),
+            Err(e) => json!({ "ok": false, "error": e.to_string() }),
+        };
+        if write_message(&mut stdout, &response).is_err() {
+            return ExitCode::FAILURE;
+        }
+    }
+}
+
+/// 应答与进程间通道相同：{"ok":true,"result":...} 或 {"ok":false,"error":"..."}
+fn forward(origin: &str, request: Value) -> Value {
+    let request = json!({ "command": "extension", "origin": origin, "request": request });
+    client::send(&request).unwrap_or_else(
+        |e| json!({ "ok": false, "error": i18n::tf("cli.not_running", &[("error", &e)]) }),
+    )
+}
+
+/// 读取一条消息，标准输入已关闭时返回 None
+fn read_message(reader: &mut impl Read) -> io::Result<Option<Vec<u8>>> {
+    let mut length = [0u8; 4];
+    match reader.read_exact(&mut length) {
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        result => result?,
+    }
+    let length = u32::from_ne_bytes(length);
+    if length > MAX_MESSAGE_BYTES {
+        return Err(io::ErrorKind::InvalidData.into());
+    }
+    let mut message = vec![0u8; length as usize];
+    reader.read_exact(&mut message)?;
+    Ok(Some(message))
+}
+
+fn write_message(writer: &mut impl Write, message: &Value) -> io::Result<()> {
+    let message = message.to_string();
+    writer.write_all(&(message.len() as u32).to_ne_bytes())?;
+    writer.write_all(message.as_bytes())?;
+    writer.flush()
+}
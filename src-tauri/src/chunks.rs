@@ -0,0 +1,282 @@
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+use crate::commands::{self, PasteState};
+use crate::i18n;
+use crate::settings;
+
+/// 分段方式
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ChunkDelimiter {
+    /// 以空行分段
+    BlankLine,
+    /// 以单独成行的标记分段（如 "---"）
+    Marker { marker: String },
+    /// 每 count 行一段
+    Lines { count: usize },
+}
+
+/// 分段输入设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChunkSettings {
+    pub delimiter: ChunkDelimiter,
+    pub stand: u32,
+    pub float: u32,
+}
+
+impl ChunkSettings {
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if matches!(self.delimiter, ChunkDelimiter::Lines { count: 0 }) {
+            return Err(i18n::t("error.chunk_lines"));
+        }
+        if matches!(&self.delimiter, ChunkDelimiter::Marker { marker } if marker.trim().is_empty())
+        {
+            return Err(i18n::t("error.chunk_marker_empty"));
+        }
+        Ok(())
+    }
+}
+
+impl Default for ChunkSettings {
+    fn default() -> Self {
+        Self {
+            delimiter: ChunkDelimiter::BlankLine,
+            stand: 5,
+            float: 0,
+        }
+    }
+}
+
+/// 分段输入：把剪贴板拆成若干段，每按一次快捷键输入一段
+pub struct ChunkState {
+    settings: ChunkSettings,
+    chunks: Vec<String>,
+    /// 下一次要输入的段落序号（从 0 开始）
+    position: usize,
+}
+
+impl ChunkState {
+    pub fn new() -> Self {
+        Self {
+            settings: ChunkSettings::default(),
+            chunks: Vec::new(),
+            position: 0,
+        }
+    }
+
+    pub fn settings(&self) -> &ChunkSettings {
+        &self.settings
+    }
+
+    /// 修改分段设置（已有的分段不变，下次重新分段时生效）
+    pub fn set_settings(&mut self, settings: ChunkSettings) {
+        self.settings = settings;
+    }
+
+    fn status(&self) -> ChunkStatus {
+        ChunkStatus {
+            position: self.position,
+            total: self.chunks.len(),
+            next_preview: self.chunks.get(self.position).map(|chunk| preview(chunk)),
+        }
+    }
+}
+
+/// chunk-progress 事件与查询命令的内容
+#[derive(Debug, Clone, Serialize)]
+pub struct ChunkStatus {
+    /// 已输入的段数（正在输入第 position 段，"3/10"）
+    pub position: usize,
+    pub total: usize,
+    /// 下一段的开头，全部输入完时为 None
+    pub next_preview: Option<String>,
+}
+
+/// 段落开头的若干字符，供界面提示
+fn preview(chunk: &str) -> String {
+    const PREVIEW_CHARS: usize = 40;
+    let first_line = chunk.lines().next().unwrap_or("");
+    if first_line.chars().count() > PREVIEW_CHARS || chunk.contains('\n') {
+        let head: String = first_line.chars().take(PREVIEW_CHARS).collect();
+        format!("{}…", head)
+    } else {
+        first_line.to_string()
+    }
+}
+
+/// 按分段方式拆分文本，去掉段落首尾的空行并丢弃空段
+fn split_chunks(text: &str, delimiter: &ChunkDelimiter) -> Vec<String> {
+    let text = text.replace("\r\n", "\n");
+    let lines: Vec<&str> = text.split('\n').collect();
+    let groups: Vec<Vec<&str>> = match delimiter {
+        ChunkDelimiter::BlankLine => lines
+            .split(|line| line.trim().is_empty())
+            .map(<[&str]>::to_vec)
+            .collect(),
+        ChunkDelimiter::Marker { marker } => lines
+            .split(|line| line.trim() == marker.trim())
+            .map(<[&str]>::to_vec)
+            .collect(),
+        ChunkDelimiter::Lines { count } => lines
+            .chunks((*count).max(1))
+            .map(<[&str]>::to_vec)
+            .collect(),
+    };
+    groups
+        .into_iter()
+        .map(|group| {
+            let start = group.iter().position(|line| !line.trim().is_empty());
+            let end = group.iter().rposition(|line| !line.trim().is_empty());
+            match (start, end) {
+                (Some(start), Some(end)) => group[start..=end].join("\n"),
+                _ => String::new(),
+            }
+        })
+        .filter(|chunk| !chunk.is_empty())
+        .collect()
+}
+
+fn emit_progress(app_handle: &tauri::AppHandle, status: &ChunkStatus) {
+    let _ = app_handle.emit_all("chunk-progress", status);
+}
+
+/// 读取剪贴板并按当前设置重新分段，从第一段开始
+fn load_clipboard(app_handle: &tauri::AppHandle) -> Result<ChunkStatus, &'static str> {
+    let text = String::from_utf16_lossy(&commands::get_clipboard()?);
+    let state = app_handle.state::<Mutex<ChunkState>>();
+    let mut locked = state.lock().unwrap();
+    let chunks = split_chunks(&text, &locked.settings.delimiter);
+    if chunks.is_empty() {
+        return Err(i18n::t("error.clipboard_empty"));
+    }
+    locked.chunks = chunks;
+    locked.position = 0;
+    Ok(locked.status())
+}
+
+/// 输入下一段；尚未分段或上次已全部输入完时先读取剪贴板。
+/// 正在输入时再次调用会中止当前段（位置不前进）。
+pub async fn type_next(app_handle: &tauri::AppHandle) -> Result<(), &'static str> {
+    {
+        let state = app_handle.state::<Mutex<PasteState>>();
+        let mut locked = state.lock().unwrap();
+        if locked.is_paused {
+            return Err(i18n::t("error.paused"));
+        }
+        if locked.is_pasting.load(Ordering::SeqCst) {
+            locked.abort("hotkey");
+            return Ok(());
+        }
+    }
+
+    let finished = {
+        let state = app_handle.state::<Mutex<ChunkState>>();
+        let locked = state.lock().unwrap();
+        locked.position >= locked.chunks.len()
+    };
+    if finished {
+        load_clipboard(app_handle)?;
+    }
+
+    let (chunk, stand, float, status) = {
+        let state = app_handle.state::<Mutex<ChunkState>>();
+        let mut locked = state.lock().unwrap();
+        let chunk = locked.chunks[locked.position].clone();
+        locked.position += 1;
+        (
+            chunk,
+            locked.settings.stand,
+            locked.settings.float,
+            locked.status(),
+        )
+    };
+
+    #[cfg(debug_assertions)]
+    println!("输入第 {}/{} 段", status.position, status.total);
+
+    emit_progress(app_handle, &status);
+    commands::paste_text(
+        app_handle,
+        Some(chunk.encode_utf16().collect()),
+        stand,
+        float,
+    )
+    .await
+}
+
+/// 按当前设置把剪贴板重新分段，回到第一段
+#[tauri::command]
+pub fn start_chunks(app_handle: tauri::AppHandle) -> Result<ChunkStatus, &'static str> {
+    let status = load_clipboard(&app_handle)?;
+    emit_progress(&app_handle, &status);
+    Ok(status)
+}
+
+/// 输入下一段
+#[tauri::command]
+pub async fn type_next_chunk(app_handle: tauri::AppHandle) -> Result<(), &'static str> {
+    type_next(&app_handle).await
+}
+
+/// 跳到第 position 段（从 0 开始），用于回退或跳过
+#[tauri::command]
+pub fn set_chunk_position(
+    position: usize,
+    app_handle: tauri::AppHandle,
+) -> Result<ChunkStatus, &'static str> {
+    let state = app_handle.state::<Mutex<ChunkState>>();
+    let mut locked = state.lock().unwrap();
+    if position >= locked.chunks.len() {
+        return Err(i18n::t("error.chunk_out_of_range"));
+    }
+    locked.position = position;
+    let status = locked.status();
+    emit_progress(&app_handle, &status);
+    Ok(status)
+}
+
+/// 获取分段进度
+#[tauri::command]
+pub fn get_chunk_status(app_handle: tauri::AppHandle) -> ChunkStatus {
+    let state = app_handle.state::<Mutex<ChunkState>>();
+    let locked = state.lock().unwrap();
+    locked.status()
+}
+
+/// 清除分段，下次输入时重新读取剪贴板
+#[tauri::command]
+pub fn reset_chunks(app_handle: tauri::AppHandle) {
+    let state = app_handle.state::<Mutex<ChunkState>>();
+    let mut locked = state.lock().unwrap();
+    locked.chunks.clear();
+    locked.position = 0;
+    let status = locked.status();
+    emit_progress(&app_handle, &status);
+}
+
+/// 修改分段设置（已有的分段不变，下次重新分段时生效）
+#[tauri::command]
+pub fn set_chunk_settings(
+    settings: ChunkSettings,
+    app_handle: tauri::AppHandle,
+) -> Result<(), &'static str> {
+    settings.validate()?;
+    {
+        let state = app_handle.state::<Mutex<ChunkState>>();
+        state.lock().unwrap().set_settings(settings);
+    }
+    settings::persist(&app_handle);
+    Ok(())
+}
+
+/// 获取分段设置
+#[tauri::command]
+pub fn get_chunk_settings(app_handle: tauri::AppHandle) -> ChunkSettings {
+    let state = app_handle.state::<Mutex<ChunkState>>();
+    let locked = state.lock().unwrap();
+    locked.settings.clone()
+}
@@ -1,42 +1,149 @@
-use std::ffi::c_void;
+use std::collections::HashMap;
 use std::sync::{
     atomic::{AtomicBool, Ordering},
     Mutex,
 };
+use rand::{rngs::StdRng, Rng, SeedableRng};
 use serde::{Deserialize, Serialize};
 use tauri::Manager;
-use tokio::time::{sleep, Duration};
-use windows::Win32::{
-    Foundation::{HGLOBAL, HWND},
-    System::{
-        DataExchange::{CloseClipboard, GetClipboardData, OpenClipboard},
-        Memory::{GlobalLock, GlobalUnlock},
-    },
-    UI::Input::KeyboardAndMouse::{
-        SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYBD_EVENT_FLAGS, KEYEVENTF_KEYUP,
-        KEYEVENTF_UNICODE, VIRTUAL_KEY, VK_RETURN,
-    },
+use crate::api;
+use crate::foreground;
+use crate::history;
+use crate::i18n;
+use crate::hotkeys::{self, HotkeyAction, HotkeyBindings, HotkeyTrigger};
+use crate::notify::{self, NotificationPrefs, PasteOutcome, PasteSummary, ShowWindowPrefs};
+use crate::overlay::{self, OverlayPrefs};
+use crate::platform::{
+    self, KeyInput, KeyLayout, TextSource, VirtualKey, VK_BACK, VK_CONTROL, VK_RETURN, VK_SHIFT,
+    VK_TAB,
 };
+use crate::profiles;
+use crate::settings;
+use crate::taskbar::{self, TaskbarProgress};
+use crate::templates;
+use crate::transform;
+use crate::tray::{self, TrayIcon};
+use tokio::time::{sleep, Duration};
 
 /// 程序状态：包含是否暂停、快捷键信息、是否正在粘贴。
 pub struct PasteState {
     pub is_paused: bool,
-    pub shortcut: HotkeyConfig,
+    pub hotkeys: HotkeyBindings,
     pub is_pasting: AtomicBool, // 用于跟踪粘贴状态
+    /// 调试/测试用：固定延迟随机数种子，相同文本与配置将得到相同的时间序列
+    pub timing_seed: Option<u64>,
+    /// 极速模式：忽略延迟设置
+    pub instant_mode: bool,
+    /// 上一次输入的文本及其 (stand, float)，供"重新输入"使用
+    pub last_text: Option<Vec<u16>>,
+    pub last_timing: (u32, u32),
+    /// 默认输入速度 (stand, float)，命令未指定速度以及从快捷键、托盘触发时使用
+    pub default_timing: (u32, u32),
+    /// 上一次粘贴被中止时停在 last_text 中的位置，供"从中断处继续"使用
+    pub interrupted_at: Option<usize>,
+    /// 正在进行的粘贴是否被暂停在当前字符处
+    pub typing_paused: bool,
+    /// 开始输入前的倒计时秒数，留出时间点击目标输入框
+    pub start_delay_secs: u32,
+    /// 粘贴期间用户自己敲键盘或点击鼠标时的处理方式
+    pub on_user_input: InterruptAction,
+    /// 粘贴期间目标窗口失去焦点时的处理方式
+    pub on_focus_loss: InterruptAction,
+    /// 因失去焦点而暂停后，焦点回到目标窗口时是否自动继续
+    pub auto_resume_on_focus: bool,
+    /// 本次粘贴的目标窗口（开始输入时的前台窗口句柄）
+    pub target_window: Option<isize>,
+    /// 本次粘贴的目标程序（开始输入时前台窗口所属进程的可执行文件名）
+    pub target_process: Option<String>,
+    /// 当前的暂停是否由失去焦点引起
+    pub paused_by_focus: bool,
+    /// 粘贴期间锁屏、远程会话断开或系统睡眠时的处理方式
+    pub on_session_change: InterruptAction,
+    /// 本次粘贴被中止的原因，用于结束通知
+    pub abort_reason: Option<&'static str>,
+    /// 粘贴结束时弹出哪些系统通知
+    pub notifications: NotificationPrefs,
+    /// 倒计时与进度悬浮窗
+    pub overlay: OverlayPrefs,
+    /// 正在粘贴时从托盘退出是否先请前端确认
+    pub confirm_quit_while_pasting: bool,
+    /// 启动时不显示窗口（与 --silent 相同）
+    pub start_hidden: bool,
+    /// 粘贴完成或失败时显示主窗口
+    pub show_window: ShowWindowPrefs,
+    /// 当前（或最近一次）粘贴任务的编号，每次开始粘贴时加一
+    pub job_id: u64,
+    /// 当前粘贴最近一次发送的进度，倒计时期间为 None
+    pub progress: Option<PasteProgress>,
+    /// 输入完最后一个字符后自动按下的按键
+    pub end_action: EndAction,
+    /// 粘贴剪贴板内容前是否展开其中的模板变量（片段总是展开）
+    pub expand_clipboard_templates: bool,
+    /// 粘贴时读取剪贴板还是 PRIMARY 选区（仅 Linux）
+    pub text_source: TextSource,
 }
 
 impl PasteState {
     pub fn new() -> Self {
         Self {
             is_paused: false,
-            shortcut: HotkeyConfig::default(),
+            hotkeys: hotkeys::default_bindings(),
             is_pasting: AtomicBool::new(false),
+            timing_seed: None,
+            instant_mode: false,
+            last_text: None,
+            last_timing: (0, 0),
+            default_timing: (10, 5),
+            interrupted_at: None,
+            typing_paused: false,
+            start_delay_secs: 0,
+            on_user_input: InterruptAction::Pause,
+            on_focus_loss: InterruptAction::Pause,
+            auto_resume_on_focus: true,
+            target_window: None,
+            target_process: None,
+            paused_by_focus: false,
+            on_session_change: InterruptAction::Abort,
+            abort_reason: None,
+            notifications: NotificationPrefs::default(),
+            overlay: OverlayPrefs::default(),
+            confirm_quit_while_pasting: false,
+            start_hidden: false,
+            show_window: ShowWindowPrefs::default(),
+            job_id: 0,
+            progress: None,
+            end_action: EndAction::None,
+            expand_clipboard_templates: false,
+            text_source: TextSource::Clipboard,
         }
     }
+
+    /// 中止正在进行的粘贴并记录原因（已记录过原因则保留最先的原因）
+    pub fn abort(&mut self, reason: &'static str) {
+        if self.is_pasting.swap(false, Ordering::SeqCst) {
+            self.abort_reason.get_or_insert(reason);
+        }
+    }
+}
+
+/// 粘贴被外部情况打断（用户操作、失去焦点等）时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InterruptAction {
+    /// 继续输入
+    Ignore,
+    /// 暂停在当前字符处，可通过 resume_typing 继续
+    Pause,
+    /// 中止，可通过 resume_last_paste 从中断处继续
+    Abort,
 }
 
+/// Paster 发送的按键在 dwExtraInfo 中携带的标记，钩子据此区分用户自己的输入
+#[cfg(windows)]
+pub const PASTER_INPUT_MARKER: usize = 0x5041_5354;
+
 /// 快捷键配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct HotkeyConfig {
     pub alt: bool,
     pub ctrl: bool,
@@ -47,6 +154,18 @@ pub struct HotkeyConfig {
 
     /// 新增字段：是否劫持系统的 Ctrl+V
     pub intercept_ctrl_v: bool,
+    /// 劫持 Ctrl+V 时放行的进程名（如 "code.exe"），在这些程序中 Ctrl+V 保持系统行为
+    #[serde(default)]
+    pub intercept_exclude: Vec<String>,
+
+    /// 通过 capture_hotkey 录制得到的规范化快捷键（如 "Super+Alt+F13"），
+    /// 设置后优先于上面的复选框字段
+    #[serde(default)]
+    pub accelerator: Option<String>,
+
+    /// 组合键以外的触发方式（如双击 Ctrl），设置后优先于组合键
+    #[serde(default)]
+    pub trigger: Option<HotkeyTrigger>,
 }
 
 impl Default for HotkeyConfig {
@@ -59,18 +178,77 @@ impl Default for HotkeyConfig {
             right_ctrl: false,
             key: "V".to_string(),
             intercept_ctrl_v: false,
+            intercept_exclude: Vec::new(),
+            accelerator: None,
+            trigger: None,
         }
     }
 }
 
 impl HotkeyConfig {
+    /// 验证快捷键是否合法。中止快捷键只在粘贴期间注册，可以是不带修饰键的 Esc 等按键
+    pub fn validate(&self, action: HotkeyAction) -> Result<(), String> {
+        let standalone_allowed = action == HotkeyAction::Abort;
+        if let Some(trigger) = &self.trigger {
+            return trigger.validate();
+        }
+        if let Some(accelerator) = self.accelerator.as_deref().filter(|_| !self.intercept_ctrl_v) {
+            let tokens: Vec<&str> = accelerator.split('+').map(str::trim).collect();
+            let (key, modifiers) = match tokens.split_last() {
+                Some((key, modifiers)) if !key.is_empty() && !is_modifier_token(key) => {
+                    (*key, modifiers)
+                }
+                _ => {
+                    return Err(i18n::tf(
+                        "error.hotkey_missing_key",
+                        &[("hotkey", &accelerator)],
+                    ))
+                }
+            };
+            if let Some(bad) = modifiers.iter().find(|m| !is_modifier_token(m)) {
+                return Err(i18n::tf(
+                    "error.hotkey_bad_modifier",
+                    &[("hotkey", &accelerator), ("modifier", bad)],
+                ));
+            }
+            // 单独的空格、回车、字母等键会影响正常输入，必须配合修饰键
+            if modifiers.is_empty()
+                && !standalone_allowed
+                && !crate::hook::is_standalone_key(key)
+            {
+                return Err(i18n::t("error.hotkey_single_key").to_string());
+            }
+            return Ok(());
+        }
+
+        if !(self.alt
+            || self.ctrl
+            || self.shift
+            || self.left_ctrl
+            || self.right_ctrl
+            || self.intercept_ctrl_v
+            || standalone_allowed
+            || crate::hook::is_standalone_key(&self.key))
+        {
+            return Err(i18n::t("error.hotkey_no_modifier").to_string());
+        }
+        Ok(())
+    }
+
     /// 转换为 Tauri 的加速器字符串 (如 "Alt+Control+V")。
-    /// 若 intercept_ctrl_v 为 true，则无视其他组合键，直接返回 "Control+V"。
+    /// 若 intercept_ctrl_v 为 true，则无视其他组合键，直接返回 "Control+V"（由键盘钩子处理）。
     pub fn to_tauri_accelerator(&self) -> String {
+        // 双击修饰键等特殊触发方式由键盘钩子识别，以其编号代替加速器
+        if let Some(trigger) = &self.trigger {
+            return trigger.id();
+        }
         // 如果勾选了"劫持系统 Ctrl+V"，则强制只注册 "Control+V"
         if self.intercept_ctrl_v {
             return "Control+V".to_string();
         }
+        if let Some(accelerator) = &self.accelerator {
+            return accelerator.clone();
+        }
 
         let mut parts = Vec::new();
         if self.alt {
@@ -94,20 +272,41 @@ impl HotkeyConfig {
     /// 用户可读的快捷键描述 (如 "Alt+Ctrl+V" 或 "Alt+左Ctrl+V")。
     /// 若 intercept_ctrl_v 为 true，则直接显示 "劫持系统Ctrl+V"。
     pub fn get_description(&self) -> String {
+        if let Some(trigger) = &self.trigger {
+            return trigger.get_description();
+        }
         if self.intercept_ctrl_v {
-            return "系统Ctrl+V (已被劫持)".to_string();
+            return i18n::t("hotkey.intercept_ctrl_v").to_string();
+        }
+        if let Some(accelerator) = &self.accelerator {
+            return accelerator
+                .split('+')
+                .map(|token| match token {
+                    "Control" => "Ctrl",
+                    "ControlLeft" => i18n::t("hotkey.left_ctrl"),
+                    "ControlRight" => i18n::t("hotkey.right_ctrl"),
+                    "Alt" => ALT_LABEL,
+                    "AltLeft" => i18n::t("hotkey.left_alt"),
+                    "AltRight" => i18n::t("hotkey.right_alt"),
+                    "ShiftLeft" => i18n::t("hotkey.left_shift"),
+                    "ShiftRight" => i18n::t("hotkey.right_shift"),
+                    "Super" | "SuperLeft" | "SuperRight" => SUPER_LABEL,
+                    other => other,
+                })
+                .collect::<Vec<_>>()
+                .join("+");
         }
 
         let mut parts = Vec::new();
         if self.alt {
-            parts.push("Alt".to_string());
+            parts.push(ALT_LABEL.to_string());
         }
         if self.ctrl {
             parts.push("Ctrl".to_string());
         } else if self.left_ctrl {
-            parts.push("左Ctrl".to_string());
+            parts.push(i18n::t("hotkey.left_ctrl").to_string());
         } else if self.right_ctrl {
-            parts.push("右Ctrl".to_string());
+            parts.push(i18n::t("hotkey.right_ctrl").to_string());
         }
         if self.shift {
             parts.push("Shift".to_string());
@@ -118,289 +317,1609 @@ impl HotkeyConfig {
     }
 }
 
-/// 打开剪贴板获取 UTF-16 内容
-fn get_clipboard() -> Result<Vec<u16>, &'static str> {
-    const CF_UNICODETEXT: u32 = 13;
-    let mut result: Vec<u16> = vec![];
+/// 快捷键描述中 Alt 与 Super 键的名称（macOS 上为 Option 与 Command）
+#[cfg(target_os = "macos")]
+const ALT_LABEL: &str = "Option";
+#[cfg(target_os = "macos")]
+const SUPER_LABEL: &str = "Cmd";
+#[cfg(not(target_os = "macos"))]
+const ALT_LABEL: &str = "Alt";
+#[cfg(not(target_os = "macos"))]
+const SUPER_LABEL: &str = "Win";
 
-    unsafe {
-        OpenClipboard(HWND(0)).or(Err("打开剪切板错误"))?;
-        let hglb = GetClipboardData(CF_UNICODETEXT).map_err(|_| {
-            let _ = CloseClipboard();
-            "获取剪切板数据错误"
-        })?;
-        let locker = HGLOBAL(hglb.0 as *mut c_void);
-        let raw_data = GlobalLock(locker);
-        let data = raw_data as *const u16;
-        let mut i = 0usize;
+/// 是否为加速器字符串中的修饰键
+fn is_modifier_token(token: &str) -> bool {
+    matches!(
+        token.to_uppercase().as_str(),
+        "ALT" | "OPTION" | "CONTROL" | "CTRL" | "SHIFT" | "SUPER" | "COMMAND" | "CMD"
+            | "COMMANDORCONTROL" | "CMDORCTRL"
+            | "CONTROLLEFT" | "CONTROLRIGHT" | "ALTLEFT" | "ALTRIGHT"
+            | "SHIFTLEFT" | "SHIFTRIGHT" | "SUPERLEFT" | "SUPERRIGHT"
+    )
+}
 
-        loop {
-            let item = *data.add(i);
-            i += 1;
-            if item == 0 {
-                break;
-            }
-            // 舍弃 '\r'
-            if item == 13 {
-                continue;
-            }
-            result.push(item);
-        }
+/// 获取剪贴板的 UTF-16 内容（已舍弃 '\r'）
+pub fn get_clipboard() -> Result<Vec<u16>, &'static str> {
+    platform::clipboard().read_text()
+}
 
-        GlobalUnlock(locker).map_err(|_| {
-            let _ = CloseClipboard();
-            "解除剪切板锁定失败"
-        })?;
-        CloseClipboard().or(Err("关闭剪切板失败"))?;
+/// 创建本次粘贴使用的延迟随机数生成器：设置了种子则可复现，否则使用系统熵
+fn timing_rng(seed: Option<u64>) -> StdRng {
+    match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
     }
-
-    Ok(result)
 }
 
-/// 粘贴命令：读取剪贴板，逐字符发送到前台
-#[tauri::command]
-pub async fn paste(stand: u32, float: u32, app_handle: tauri::AppHandle) -> Result<(), &'static str> {
-    #[cfg(debug_assertions)]
-    println!("paste函数被调用：stand={}, float={}", stand, float);
+/// 计算下一个字符前的延迟（毫秒）：stand + [0, float)
+fn next_delay(rng: &mut StdRng, stand: u32, float: u32) -> u32 {
+    if float == 0 {
+        return stand;
+    }
+    stand + rng.gen::<u32>() % float
+}
 
-    // 获取状态
-    let state = app_handle.state::<Mutex<PasteState>>();
+/// 开始一次粘贴：已暂停时返回错误；已经在粘贴中则中止当前粘贴并返回 Ok(false)
+fn begin_paste(state: &Mutex<PasteState>) -> Result<bool, &'static str> {
+    let mut locked = state.lock().unwrap();
 
     // 1. 是否暂停
-    let is_paused = {
-        let locked = state.lock().unwrap();
-        locked.is_paused
-    };
-    if is_paused {
+    if locked.is_paused {
         #[cfg(debug_assertions)]
         println!("函数退出：功能已暂停");
         
-        return Err("功能已暂停");
+        return Err(i18n::t("error.paused"));
     }
 
     // 2. 是否已经在粘贴
-    {
-        let locked = state.lock().unwrap();
-        let is_pasting = locked.is_pasting.load(Ordering::SeqCst);
+    if locked.is_pasting.load(Ordering::SeqCst) {
+        #[cfg(debug_assertions)]
+        println!("已经在粘贴中，停止粘贴过程");
+        
+        locked.abort("hotkey");
+        return Ok(false);
+    }
 
-        if is_pasting {
-            #[cfg(debug_assertions)]
-            println!("已经在粘贴中，停止粘贴过程");
-            
-            locked.is_pasting.store(false, Ordering::SeqCst);
-            return Ok(());
-        } else {
-            locked.is_pasting.store(true, Ordering::SeqCst);
-        }
+    // 3. 当前桌面能否注入按键（Wayland 合成器可能不支持）
+    platform::injector().check()?;
+
+    locked.is_pasting.store(true, Ordering::SeqCst);
+    locked.abort_reason = None;
+    locked.job_id += 1;
+    locked.target_process = None;
+    locked.progress = None;
+    locked.typing_paused = false;
+    locked.paused_by_focus = false;
+    Ok(true)
+}
+
+/// 从剪贴板或 PRIMARY 选区读取文本；expand 为 true 时先展开其中的模板变量
+fn read_clipboard(expand: bool, source: TextSource) -> Result<Vec<u16>, &'static str> {
+    let units = platform::read_source(source)?;
+    if !expand {
+        return Ok(units);
+    }
+    let text = templates::expand(&String::from_utf16_lossy(&units))?;
+    Ok(text.encode_utf16().collect())
+}
+
+/// 命令中未指定的 stand/float 使用设置中的默认速度
+pub fn resolve_timing(
+    app_handle: &tauri::AppHandle,
+    stand: Option<u32>,
+    float: Option<u32>,
+) -> (u32, u32) {
+    let state = app_handle.state::<Mutex<PasteState>>();
+    let (default_stand, default_float) = state.lock().unwrap().default_timing;
+    (stand.unwrap_or(default_stand), float.unwrap_or(default_float))
+}
+
+/// 外部请求（本地接口、链接等）开始输入前的检查：已暂停或正在输入时拒绝，
+/// 而不是像快捷键那样中止当前粘贴
+pub fn check_idle(app_handle: &tauri::AppHandle) -> Result<(), &'static str> {
+    let state = app_handle.state::<Mutex<PasteState>>();
+    let locked = state.lock().unwrap();
+    if locked.is_paused {
+        return Err(i18n::t("error.paused"));
+    }
+    if locked.is_pasting.load(Ordering::SeqCst) {
+        return Err(i18n::t("error.busy"));
+    }
+    Ok(())
+}
+
+/// 重新输入类命令的速度：都未指定时为 None，沿用上一次的速度
+fn timing_override(
+    app_handle: &tauri::AppHandle,
+    stand: Option<u32>,
+    float: Option<u32>,
+) -> Option<(u32, u32)> {
+    if stand.is_none() && float.is_none() {
+        return None;
+    }
+    Some(resolve_timing(app_handle, stand, float))
+}
+
+/// 粘贴命令：读取剪贴板，逐字符发送到前台；stand/float 省略时使用默认速度
+#[tauri::command]
+pub async fn paste(stand: Option<u32>, float: Option<u32>, app_handle: tauri::AppHandle) -> Result<(), &'static str> {
+    let (stand, float) = resolve_timing(&app_handle, stand, float);
+    #[cfg(debug_assertions)]
+    println!("paste函数被调用：stand={}, float={}", stand, float);
+
+    paste_text(&app_handle, None, stand, float).await
+}
+
+/// 单次粘贴的附加选项，默认与普通粘贴相同
+#[derive(Debug, Clone, Default)]
+pub struct PasteOptions {
+    /// 不记入输入历史（密码等敏感文本）
+    pub skip_history: bool,
+    /// 跳过开始前的倒计时
+    pub skip_start_delay: bool,
+    /// 倒计时至少持续的秒数（如从托盘开始输入时留出切换到目标窗口的时间）
+    pub min_start_delay_secs: u32,
+    /// 输入完后不按结束按键
+    pub skip_end_action: bool,
+    /// 开始输入前先按几次退格（文本扩展时删除已输入的缩写）
+    pub erase: usize,
+    /// 覆盖全局的极速模式设置
+    pub instant: Option<bool>,
+    /// 表单填充：把文本中的字段/行标记换成按键
+    pub form: Option<FormKeys>,
+    /// 宏动作：文本中第 n 个 PASTE_ACTION_MARKER 执行第 n 个动作
+    pub actions: Vec<PasteAction>,
+    /// 速度由调用方指定（如片段自带的速度），不使用配置方案的速度
+    pub fixed_timing: bool,
+    /// 换行与字符的输入方式，由配置方案决定
+    pub newline: NewlineStrategy,
+    pub backend: InputBackend,
+    /// 未给定文本时的读取来源，None 表示使用设置中的来源
+    pub text_source: Option<TextSource>,
+    /// 使用指定的配置方案（名称），None 表示按选用与自动选用规则
+    pub profile: Option<String>,
+}
+
+/// 换行的输入方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NewlineStrategy {
+    /// 回车键
+    #[default]
+    Enter,
+    /// Shift+Enter（聊天软件中换行而不发送）
+    ShiftEnter,
+    /// 以 Unicode 方式发送换行符，不触发编辑器的自动缩进
+    Unicode,
+}
+
+/// 字符的输入方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InputBackend {
+    /// 以 Unicode 方式发送，支持任意字符
+    #[default]
+    Unicode,
+    /// 按目标窗口的键盘布局换算为虚拟键（必要时加 Shift），适用于远程桌面、虚拟机等
+    /// 不接受 Unicode 输入的程序；无法换算的字符仍以 Unicode 方式发送
+    VirtualKey,
+    /// 与 VirtualKey 相同，但以扫描码发送，模拟物理按键（远程桌面、游戏等）
+    ScanCode,
+}
+
+/// 输入给定文本；text 为 None 时读取剪贴板
+pub async fn paste_text(
+    app_handle: &tauri::AppHandle,
+    text: Option<Vec<u16>>,
+    stand: u32,
+    float: u32,
+) -> Result<(), &'static str> {
+    paste_with(app_handle, text, stand, float, PasteOptions::default()).await
+}
+
+/// 按附加选项输入给定文本；text 为 None 时读取剪贴板
+pub async fn paste_with(
+    app_handle: &tauri::AppHandle,
+    text: Option<Vec<u16>>,
+    stand: u32,
+    float: u32,
+    mut options: PasteOptions,
+) -> Result<(), &'static str> {
+    // 获取状态
+    let state = app_handle.state::<Mutex<PasteState>>();
+    if !begin_paste(&state)? {
+        return Ok(());
     }
 
+    // 配置方案与程序规则：速度、极速模式、换行与输入方式，以及输入前的文本变换
+    let style = profiles::job_style(app_handle, options.profile.as_deref());
+    let (stand, float) = match style.timing {
+        Some(timing) if !options.fixed_timing => timing,
+        _ => (stand, float),
+    };
+    options.instant = options.instant.or(style.instant);
+    options.newline = style.newline;
+    options.backend = style.backend;
+
     // 3. 读取剪贴板内容
-    let utf16_units = get_clipboard()?;
+    let (expand, source) = {
+        let locked = state.lock().unwrap();
+        (
+            locked.expand_clipboard_templates,
+            options.text_source.unwrap_or(locked.text_source),
+        )
+    };
+    let utf16_units = match text.map_or_else(|| read_clipboard(expand, source), Ok) {
+        Ok(units) => units,
+        Err(e) => {
+            let (prefs, job_id) = {
+                let locked = state.lock().unwrap();
+                locked.is_pasting.store(false, Ordering::SeqCst);
+                (locked.notifications, locked.job_id)
+            };
+            let summary = PasteSummary {
+                outcome: PasteOutcome::Failed,
+                job_id,
+                target_process: None,
+                typed: 0,
+                total: 0,
+                elapsed_ms: 0,
+                reason: Some(e.to_string()),
+            };
+            notify::paste_finished(app_handle, prefs, summary);
+            return Err(e);
+        }
+    };
     
     #[cfg(debug_assertions)]
     println!("剪贴板内容长度：{}", utf16_units.len());
 
-    // 4. 逐字符发送
-    let mut i = 0;
-    for ch in utf16_units {
-        // 每次循环前检查是否中断
-        {
-            let locked = state.lock().unwrap();
-            if !locked.is_pasting.load(Ordering::SeqCst) {
-                #[cfg(debug_assertions)]
-                println!("粘贴被中断，在第{}个字符处停止", i);
-                
-                locked.is_pasting.store(false, Ordering::SeqCst);
-                return Ok(());
-            }
-        }
+    // 表单填充与宏的文本含有按键标记，不做变换
+    let utf16_units = if !style.transforms.is_empty()
+        && options.form.is_none()
+        && options.actions.is_empty()
+    {
+        let text = String::from_utf16_lossy(&utf16_units);
+        transform::apply(&text, &style.transforms)
+            .encode_utf16()
+            .collect()
+    } else {
+        utf16_units
+    };
 
-        if ch == 10 {
-            // 回车
-            let input = [
-                INPUT {
-                    r#type: INPUT_KEYBOARD,
-                    Anonymous: INPUT_0 {
-                        ki: KEYBDINPUT {
-                            wVk: VK_RETURN,
-                            wScan: 0,
-                            dwFlags: KEYBD_EVENT_FLAGS(0),
-                            time: 0,
-                            dwExtraInfo: 0,
-                        },
-                    },
-                },
-                INPUT {
-                    r#type: INPUT_KEYBOARD,
-                    Anonymous: INPUT_0 {
-                        ki: KEYBDINPUT {
-                            wVk: VK_RETURN,
-                            wScan: 0,
-                            dwFlags: KEYEVENTF_KEYUP,
-                            time: 0,
-                            dwExtraInfo: 0,
-                        },
-                    },
-                },
-            ];
-            unsafe {
-                SendInput(&input, std::mem::size_of::<INPUT>() as i32);
-            }
+    // 表单填充与宏的文本含有按键标记，不作为"重新输入"的内容
+    if options.form.is_none() && options.actions.is_empty() {
+        let mut locked = state.lock().unwrap();
+        locked.last_text = Some(utf16_units.clone());
+        locked.last_timing = (stand, float);
+    }
+    if !options.skip_history {
+        history::record(app_handle, &utf16_units);
+    }
+    type_units(app_handle, utf16_units, 0, stand, float, options).await
+}
+
+/// estimate_paste 的结果：文本规模与预计耗时分布（毫秒）
+#[derive(Debug, Clone, Serialize)]
+pub struct PasteEstimate {
+    /// UTF-16 单元数（即发送的按键数）
+    pub units: usize,
+    /// 字符数
+    pub chars: usize,
+    pub lines: usize,
+    pub instant_mode: bool,
+    /// 开始前的倒计时
+    pub start_delay_ms: u64,
+    /// 输入耗时（不含倒计时）的期望、标准差、最小/最大值与 5%/95% 分位
+    pub mean_ms: f64,
+    pub stddev_ms: f64,
+    pub min_ms: u64,
+    pub max_ms: u64,
+    pub p5_ms: f64,
+    pub p95_ms: f64,
+}
+
+/// 不发送任何按键，按当前设置估算输入剪贴板内容所需的时间。
+/// 每个字符后的延迟为 stand + [0, float) 的均匀整数，总耗时按正态近似给出分位数。
+#[tauri::command]
+pub fn estimate_paste(stand: Option<u32>, float: Option<u32>, app_handle: tauri::AppHandle) -> Result<PasteEstimate, &'static str> {
+    let (stand, float) = resolve_timing(&app_handle, stand, float);
+    let (instant_mode, start_delay_secs, expand, source) = {
+        let state = app_handle.state::<Mutex<PasteState>>();
+        let locked = state.lock().unwrap();
+        (locked.instant_mode, locked.start_delay_secs, locked.expand_clipboard_templates, locked.text_source)
+    };
+    let utf16_units = read_clipboard(expand, source)?;
+
+    let units = utf16_units.len();
+    let text = String::from_utf16_lossy(&utf16_units);
+    let lines = if text.is_empty() { 0 } else { text.lines().count() };
+
+    let n = units as f64;
+    let (mean_ms, stddev_ms, min_ms, max_ms) = if instant_mode || units == 0 {
+        (0.0, 0.0, 0, 0)
+    } else {
+        let per_unit_mean = stand as f64 + float.saturating_sub(1) as f64 / 2.0;
+        let per_unit_var = if float == 0 {
+            0.0
         } else {
-            // 普通字符
-            let input = [
-                // 按下
-                INPUT {
-                    r#type: INPUT_KEYBOARD,
-                    Anonymous: INPUT_0 {
-                        ki: KEYBDINPUT {
-                            wVk: VIRTUAL_KEY(0),
-                            wScan: ch,
-                            dwFlags: KEYEVENTF_UNICODE,
-                            time: 0,
-                            dwExtraInfo: 0,
-                        },
-                    },
-                },
-                // 抬起
-                INPUT {
-                    r#type: INPUT_KEYBOARD,
-                    Anonymous: INPUT_0 {
-                        ki: KEYBDINPUT {
-                            wVk: VIRTUAL_KEY(0),
-                            wScan: ch,
-                            dwFlags: KEYEVENTF_UNICODE | KEYEVENTF_KEYUP,
-                            time: 0,
-                            dwExtraInfo: 0,
-                        },
-                    },
-                },
-            ];
-            unsafe {
-                SendInput(&input, std::mem::size_of::<INPUT>() as i32);
-            }
-        }
+            (float as f64 * float as f64 - 1.0) / 12.0
+        };
+        (
+            n * per_unit_mean,
+            (n * per_unit_var).sqrt(),
+            units as u64 * stand as u64,
+            units as u64 * (stand + float.saturating_sub(1)) as u64,
+        )
+    };
+    // 标准正态分布的 95% 分位
+    const Z95: f64 = 1.645;
 
-        let random = rand::random::<u32>();
-        let delay = stand + random % float;
-        sleep(Duration::from_millis(delay as u64)).await;
-        i += 1;
-    }
+    Ok(PasteEstimate {
+        units,
+        chars: text.chars().count(),
+        lines,
+        instant_mode,
+        start_delay_ms: start_delay_secs as u64 * 1000,
+        mean_ms,
+        stddev_ms,
+        min_ms,
+        max_ms,
+        p5_ms: (mean_ms - Z95 * stddev_ms).max(min_ms as f64),
+        p95_ms: (mean_ms + Z95 * stddev_ms).min(max_ms as f64),
+    })
+}
 
-    // 5. 粘贴结束，重置状态
-    {
+/// 重新输入上一次粘贴的文本（不依赖剪贴板中仍有原内容）。
+/// timing 为 None 时沿用上一次的 (stand, float)，如由快捷键触发时。
+pub async fn retype_last_text(
+    app_handle: &tauri::AppHandle,
+    timing: Option<(u32, u32)>,
+) -> Result<(), &'static str> {
+    let state = app_handle.state::<Mutex<PasteState>>();
+    let (text, (stand, float)) = {
         let locked = state.lock().unwrap();
-        locked.is_pasting.store(false, Ordering::SeqCst);
+        match &locked.last_text {
+            Some(text) => (text.clone(), timing.unwrap_or(locked.last_timing)),
+            None => return Err(i18n::t("error.nothing_to_retype")),
+        }
+    };
+    if !begin_paste(&state)? {
+        return Ok(());
     }
-    #[cfg(debug_assertions)]
-    println!("paste函数成功完成");
-    Ok(())
+    state.lock().unwrap().last_timing = (stand, float);
+    let options = profiles::input_options(app_handle);
+    type_units(app_handle, text, 0, stand, float, options).await
 }
 
-/// 切换暂停状态
-#[tauri::command]
-pub fn toggle_pause(app_handle: tauri::AppHandle) -> bool {
+/// 从上一次被中止的位置继续输入上一次的文本
+pub async fn resume_last_text(
+    app_handle: &tauri::AppHandle,
+    timing: Option<(u32, u32)>,
+) -> Result<(), &'static str> {
     let state = app_handle.state::<Mutex<PasteState>>();
-    let mut locked = state.lock().unwrap();
-    locked.is_paused = !locked.is_paused;
-    locked.is_paused
+    let (text, offset, (stand, float)) = {
+        let locked = state.lock().unwrap();
+        match (&locked.last_text, locked.interrupted_at) {
+            (Some(text), Some(offset)) => (
+                text.clone(),
+                offset,
+                timing.unwrap_or(locked.last_timing),
+            ),
+            _ => return Err(i18n::t("error.nothing_to_resume")),
+        }
+    };
+    if !begin_paste(&state)? {
+        return Ok(());
+    }
+    let options = profiles::input_options(app_handle);
+    type_units(app_handle, text, offset, stand, float, options).await
 }
 
-/// 获取当前快捷键配置
+/// 从上一次被中止（快捷键、失去焦点或出错）的字符处继续输入，而不是从头重新输入；
+/// stand/float 都省略时沿用上一次的速度
 #[tauri::command]
-pub fn get_shortcut(app_handle: tauri::AppHandle) -> HotkeyConfig {
-    let state = app_handle.state::<Mutex<PasteState>>();
-    let locked = state.lock().unwrap();
-    locked.shortcut.clone()
+pub async fn resume_last_paste(stand: Option<u32>, float: Option<u32>, app_handle: tauri::AppHandle) -> Result<(), &'static str> {
+    #[cfg(debug_assertions)]
+    println!("resume_last_paste函数被调用：stand={:?}, float={:?}", stand, float);
+
+    let timing = timing_override(&app_handle, stand, float);
+    resume_last_text(&app_handle, timing).await
 }
 
-/// 更新快捷键（并尝试重新注册全局快捷键），并将新配置持久化到本地
+/// 重新输入上一次粘贴的文本；stand/float 都省略时沿用上一次的速度
 #[tauri::command]
-pub fn update_shortcut(config: HotkeyConfig, app_handle: tauri::AppHandle) -> Result<String, String> {
-    // 1. 验证快捷键是否合法
-    if !(config.alt || config.ctrl || config.shift || config.left_ctrl || config.right_ctrl) && !config.intercept_ctrl_v {
-        return Err("至少需要选择一个修饰键（Alt/Ctrl/Shift)".to_string());
+pub async fn retype_last(stand: Option<u32>, float: Option<u32>, app_handle: tauri::AppHandle) -> Result<(), &'static str> {
+    #[cfg(debug_assertions)]
+    println!("retype_last函数被调用：stand={:?}, float={:?}", stand, float);
+
+    let timing = timing_override(&app_handle, stand, float);
+    retype_last_text(&app_handle, timing).await
+}
+
+/// 极速模式下每次 SendInput 批量发送的字符数
+const INSTANT_BATCH_CHARS: usize = 32;
+
+/// 字符以 Unicode 方式发送的按下/抬起输入
+fn unicode_inputs(ch: u16) -> [KeyInput; 2] {
+    [
+        KeyInput::Unicode {
+            unit: ch,
+            key_up: false,
+        },
+        KeyInput::Unicode {
+            unit: ch,
+            key_up: true,
+        },
+    ]
+}
+
+/// 单个字符对应的按下/抬起输入：换行按 newline 的方式发送，其余字符按 backend 的方式发送
+fn char_inputs(ch: u16, options: &PasteOptions, layout: Option<KeyLayout>) -> Vec<KeyInput> {
+    if ch == 10 {
+        return match options.newline {
+            NewlineStrategy::Enter => key_stroke_inputs(&[VK_RETURN.0]),
+            NewlineStrategy::ShiftEnter => key_stroke_inputs(&[VK_SHIFT.0, VK_RETURN.0]),
+            NewlineStrategy::Unicode => unicode_inputs(ch).to_vec(),
+        };
     }
+    let scan_code = match options.backend {
+        InputBackend::Unicode => return unicode_inputs(ch).to_vec(),
+        InputBackend::VirtualKey => false,
+        InputBackend::ScanCode => true,
+    };
+    layout
+        .and_then(|layout| platform::injector().layout_inputs(ch, layout, scan_code))
+        .unwrap_or_else(|| unicode_inputs(ch).to_vec())
+}
 
-    let state = app_handle.state::<Mutex<PasteState>>();
-    {
-        let mut locked = state.lock().unwrap();
-        locked.shortcut = config.clone();
+/// 发送一组键盘输入
+fn send_inputs(inputs: &[KeyInput]) {
+    if inputs.is_empty() {
+        return;
     }
+    platform::injector().send(inputs);
+}
 
-    // 2. 保存到配置文件
-    if let Err(e) = save_shortcut_config(&app_handle, &config) {
-        #[cfg(debug_assertions)]
-        eprintln!("保存配置失败: {}", e);
+/// 以虚拟键方式按下或抬起一个按键
+fn vk_input(vk: VirtualKey, key_up: bool) -> KeyInput {
+    KeyInput::Key { vk, key_up }
+}
+
+/// 粘贴过程中 Paster 会以虚拟键方式按下的按键
+const INJECTED_VIRTUAL_KEYS: [VirtualKey; 5] = [VK_RETURN, VK_TAB, VK_CONTROL, VK_SHIFT, VK_BACK];
+
+/// 抬起所有仍处于按下状态的注入按键，避免中止后按键卡住
+pub fn release_pressed_keys() {
+    platform::injector().release_keys(&INJECTED_VIRTUAL_KEYS);
+}
+
+/// 输入完最后一个字符后自动按下的按键
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EndAction {
+    None,
+    /// 回车（提交）
+    Enter,
+    /// Tab（跳到下一个输入框）
+    Tab,
+    /// Ctrl+Enter
+    CtrlEnter,
+}
+
+/// 按键对应的按下/抬起输入
+fn end_action_inputs(action: EndAction) -> Vec<KeyInput> {
+    match action {
+        EndAction::None => Vec::new(),
+        EndAction::Enter => vec![vk_input(VK_RETURN, false), vk_input(VK_RETURN, true)],
+        EndAction::Tab => vec![vk_input(VK_TAB, false), vk_input(VK_TAB, true)],
+        EndAction::CtrlEnter => vec![
+            vk_input(VK_CONTROL, false),
+            vk_input(VK_RETURN, false),
+            vk_input(VK_RETURN, true),
+            vk_input(VK_CONTROL, true),
+        ],
     }
+}
+
+/// 执行粘贴结束后的按键
+fn send_end_action(action: EndAction) {
+    send_inputs(&end_action_inputs(action));
+}
 
-    // 3. 注册全局快捷键
-    match crate::register_global_shortcut(app_handle.clone(), &config) {
-        Ok(_) => {},
-        Err(e) => return Err(format!("{}。可能需要重启应用才能生效。", e)),
+/// 表单填充时文本中表示"切换到下一个字段"与"一行结束"的标记（Unicode 非字符，不会出现在正常文本中）
+pub const FORM_FIELD_MARKER: u16 = 0xFDD0;
+pub const FORM_ROW_MARKER: u16 = 0xFDD1;
+
+/// 宏等在文本中插入动作的位置标记
+pub const PASTE_ACTION_MARKER: u16 = 0xFDD2;
+
+/// 输入过程中插入的动作
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PasteAction {
+    /// 依次按下这些虚拟键，再倒序抬起（如 Ctrl+Enter）
+    Keys(Vec<u16>),
+    /// 等待毫秒数（可被中止或暂停打断）
+    Delay(u32),
+}
+
+/// 组合键的按下/抬起输入
+pub fn key_stroke_inputs(vks: &[u16]) -> Vec<KeyInput> {
+    let down = vks.iter().map(|&vk| vk_input(VirtualKey(vk), false));
+    let up = vks.iter().rev().map(|&vk| vk_input(VirtualKey(vk), true));
+    down.chain(up).collect()
+}
+
+/// 表单填充：遇到标记时按下的按键，以及每个字段之后额外等待的时间
+#[derive(Debug, Clone, Copy)]
+pub struct FormKeys {
+    pub field: EndAction,
+    pub row: EndAction,
+    pub field_delay_ms: u32,
+}
+
+impl FormKeys {
+    /// unit 为标记时返回要按下的按键
+    fn key_for(&self, unit: u16) -> Option<EndAction> {
+        match unit {
+            FORM_FIELD_MARKER => Some(self.field),
+            FORM_ROW_MARKER => Some(self.row),
+            _ => None,
+        }
     }
+}
 
-    Ok(config.get_description())
+/// 每输入多少个字符发送一次 paste-progress 事件
+const PROGRESS_INTERVAL_CHARS: usize = 10;
+
+/// 粘贴所处的阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PasteStage {
+    Started,
+    Typing,
+    Paused,
+    Resumed,
+    Completed,
+    Aborted,
 }
 
-/// 重启应用
-#[tauri::command]
-pub fn restart_app(app_handle: tauri::AppHandle) {
-    std::thread::spawn(move || {
-        let _ = app_handle.restart();
-    });
+/// paste-progress 事件的内容
+#[derive(Debug, Clone, Serialize)]
+pub struct PasteProgress {
+    pub stage: PasteStage,
+    /// 已输入 / 总共的 UTF-16 单元数
+    pub typed: usize,
+    pub total: usize,
+    pub percent: f64,
+    /// 预计剩余时间（毫秒）
+    pub eta_ms: u64,
+    /// 当前速度（每秒 UTF-16 单元数，不计暂停时间）
+    pub speed_cps: f64,
 }
 
-/// 保存快捷键配置到本地文件
-fn save_shortcut_config(app_handle: &tauri::AppHandle, config: &HotkeyConfig) -> Result<(), String> {
-    use tauri::api::path::{BaseDirectory, resolve_path};
-    use std::fs::{self, File};
-    use std::io::Write;
-
-    let store_path = match resolve_path(
-        &app_handle.config(),
-        app_handle.package_info(),
-        &app_handle.env(),
-        "shortcut_config.json",
-        Some(BaseDirectory::AppConfig),
-    ) {
-        Ok(path) => path,
-        Err(e) => return Err(format!("获取app_config_dir失败: {}", e)),
-    };
+/// 记录本次粘贴的开始时间，按已用时间估算剩余时间并发送进度事件
+struct ProgressReporter {
+    total: usize,
+    /// 本次从第几个单元开始输入（从中断处继续时不为 0）
+    offset: usize,
+    started: std::time::Instant,
+    /// 暂停的累计时长，不计入估算
+    paused: Duration,
+    /// 还没有输入任何字符时，按延迟设置估算的每个字符耗时（毫秒）
+    estimated_ms_per_unit: f64,
+}
 
-    // 确保目录存在
-    if let Some(parent) = store_path.parent() {
-        if !parent.exists() {
-            if let Err(e) = fs::create_dir_all(parent) {
-                #[cfg(debug_assertions)]
-                eprintln!("创建目录失败: {}", e);
-                
-                return Err(format!("创建目录失败: {}", e));
+impl ProgressReporter {
+    fn new(total: usize, offset: usize, stand: u32, float: u32) -> Self {
+        Self {
+            total,
+            offset,
+            started: std::time::Instant::now(),
+            paused: Duration::ZERO,
+            estimated_ms_per_unit: stand as f64 + float as f64 / 2.0,
+        }
+    }
+
+    /// 粘贴结束：发送最后一次进度以及结束通知
+    fn finish(&self, app_handle: &tauri::AppHandle, outcome: PasteOutcome, typed: usize) {
+        let stage = match outcome {
+            PasteOutcome::Completed => PasteStage::Completed,
+            _ => PasteStage::Aborted,
+        };
+        self.emit(app_handle, stage, typed);
+
+        let (prefs, reason, job_id, target_process) = {
+            let state = app_handle.state::<Mutex<PasteState>>();
+            let locked = state.lock().unwrap();
+            (
+                locked.notifications,
+                locked.abort_reason,
+                locked.job_id,
+                locked.target_process.clone(),
+            )
+        };
+        let summary = PasteSummary {
+            outcome,
+            job_id,
+            target_process,
+            typed,
+            total: self.total,
+            elapsed_ms: self.started.elapsed().saturating_sub(self.paused).as_millis() as u64,
+            reason: reason
+                .filter(|_| outcome != PasteOutcome::Completed)
+                .map(str::to_string),
+        };
+        notify::paste_finished(app_handle, prefs, summary);
+    }
+
+    fn emit(&self, app_handle: &tauri::AppHandle, stage: PasteStage, typed: usize) {
+        let remaining = self.total.saturating_sub(typed);
+        let typed_now = typed.saturating_sub(self.offset);
+        let elapsed_ms = self.started.elapsed().saturating_sub(self.paused).as_millis() as f64;
+        let ms_per_unit = if typed_now == 0 {
+            self.estimated_ms_per_unit
+        } else {
+            elapsed_ms / typed_now as f64
+        };
+        let speed_cps = if elapsed_ms > 0.0 {
+            typed_now as f64 * 1000.0 / elapsed_ms
+        } else {
+            0.0
+        };
+        let percent = if self.total == 0 {
+            100.0
+        } else {
+            typed as f64 * 100.0 / self.total as f64
+        };
+        let eta_ms = match stage {
+            PasteStage::Completed | PasteStage::Aborted => 0,
+            _ => (ms_per_unit * remaining as f64) as u64,
+        };
+
+        let (done, total) = (typed as u64, self.total as u64);
+        let taskbar_progress = match stage {
+            PasteStage::Completed | PasteStage::Aborted => TaskbarProgress::Hidden,
+            PasteStage::Paused => TaskbarProgress::Paused(done, total),
+            _ => TaskbarProgress::Normal(done, total),
+        };
+        taskbar::set_progress(app_handle, taskbar_progress);
+
+        let progress = PasteProgress {
+            stage,
+            typed,
+            total: self.total,
+            percent,
+            eta_ms,
+            speed_cps,
+        };
+        let icon = {
+            let state = app_handle.state::<Mutex<PasteState>>();
+            let mut locked = state.lock().unwrap();
+            locked.progress = Some(progress.clone());
+            match stage {
+                PasteStage::Completed | PasteStage::Aborted if locked.is_paused => {
+                    TrayIcon::Disabled
+                }
+                PasteStage::Completed | PasteStage::Aborted => TrayIcon::Idle,
+                PasteStage::Paused => TrayIcon::TypingPaused,
+                _ => TrayIcon::Typing,
             }
+        };
+        tray::set_icon(app_handle, icon);
+        match stage {
+            PasteStage::Completed | PasteStage::Aborted => tray::update_tooltip(app_handle, None),
+            _ => tray::update_tooltip(app_handle, Some(&progress)),
         }
+        api::publish("paste-progress", &progress);
+        let _ = app_handle.emit_all("paste-progress", progress);
     }
+}
 
-    // 序列化配置
-    let json = match serde_json::to_string_pretty(config) {
-        Ok(j) => j,
-        Err(e) => return Err(format!("序列化JSON失败: {}", e)),
+/// 从 offset 处开始逐字符发送 UTF-16 文本，调用前需已通过 begin_paste 置位 is_pasting。
+/// 粘贴期间注册中止快捷键，结束后注销并抬起残留按键。
+async fn type_units(
+    app_handle: &tauri::AppHandle,
+    utf16_units: Vec<u16>,
+    offset: usize,
+    stand: u32,
+    float: u32,
+    options: PasteOptions,
+) -> Result<(), &'static str> {
+    hotkeys::set_abort_hotkey_active(app_handle, true);
+    overlay::show(app_handle);
+    let started =
+        options.skip_start_delay || count_down(app_handle, options.min_start_delay_secs).await;
+    let result = if started {
+        // 倒计时结束时的前台窗口即为目标窗口
+        let (watch_user_input, watch_foreground, prefs) = {
+            let state = app_handle.state::<Mutex<PasteState>>();
+            let mut locked = state.lock().unwrap();
+            locked.target_window = Some(foreground::foreground_window().0);
+            locked.target_process = foreground::foreground_process_name();
+            (
+                locked.on_user_input != InterruptAction::Ignore,
+                locked.on_focus_loss != InterruptAction::Ignore,
+                locked.notifications,
+            )
+        };
+        notify::paste_started(app_handle, prefs, utf16_units.len().saturating_sub(offset));
+        crate::hook::set_user_input_watch(app_handle, watch_user_input);
+        crate::hook::set_foreground_watch(app_handle, watch_foreground);
+        let result = send_units(app_handle, utf16_units, offset, stand, float, options).await;
+        crate::hook::set_user_input_watch(app_handle, false);
+        crate::hook::set_foreground_watch(app_handle, false);
+        {
+            let state = app_handle.state::<Mutex<PasteState>>();
+            state.lock().unwrap().target_window = None;
+        }
+        result
+    } else {
+        Ok(())
     };
+    hotkeys::set_abort_hotkey_active(app_handle, false);
+    overlay::hide(app_handle);
+    release_pressed_keys();
+    result
+}
+
+/// paste-countdown 事件的内容
+#[derive(Debug, Clone, Serialize)]
+pub struct Countdown {
+    /// 剩余秒数，为 0 表示倒计时结束、开始输入
+    pub remaining: u32,
+    pub cancelled: bool,
+}
+
+/// 开始输入前倒计时（至少 min_secs 秒），每秒发送一次 paste-countdown 事件。
+/// 倒计时期间再次按下粘贴快捷键、中止快捷键或调用 cancel_paste 均可取消，返回 false。
+async fn count_down(app_handle: &tauri::AppHandle, min_secs: u32) -> bool {
+    let state = app_handle.state::<Mutex<PasteState>>();
+    let delay_secs = state.lock().unwrap().start_delay_secs.max(min_secs);
+
+    for remaining in (0..=delay_secs).rev() {
+        let cancelled = !state.lock().unwrap().is_pasting.load(Ordering::SeqCst);
+        if delay_secs > 0 {
+            let countdown = Countdown { remaining, cancelled };
+            api::publish("paste-countdown", &countdown);
+            let _ = app_handle.emit_all("paste-countdown", countdown);
+        }
+        if cancelled {
+            #[cfg(debug_assertions)]
+            println!("倒计时被取消");
+
+            return false;
+        }
+        if remaining == 0 {
+            break;
+        }
+        // 分段等待，以便及时响应取消
+        for _ in 0..20 {
+            sleep(Duration::from_millis(50)).await;
+            if !state.lock().unwrap().is_pasting.load(Ordering::SeqCst) {
+                break;
+            }
+        }
+    }
+    true
+}
 
-    // 写入文件
-    let mut file = match File::create(&store_path) {
-        Ok(f) => f,
-        Err(e) => return Err(format!("创建文件失败: {}", e)),
+async fn send_units(
+    app_handle: &tauri::AppHandle,
+    utf16_units: Vec<u16>,
+    offset: usize,
+    stand: u32,
+    float: u32,
+    options: PasteOptions,
+) -> Result<(), &'static str> {
+    let state = app_handle.state::<Mutex<PasteState>>();
+    let timing_seed = {
+        let mut locked = state.lock().unwrap();
+        locked.interrupted_at = None;
+        locked.timing_seed
     };
 
-    if let Err(e) = file.write_all(json.as_bytes()) {
-        #[cfg(debug_assertions)]
-        eprintln!("写入文件失败: {}", e);
-        
-        return Err(format!("写入文件失败: {}", e));
+    // 4. 逐字符发送
+    let mut progress = ProgressReporter::new(utf16_units.len(), offset, stand, float);
+    progress.emit(app_handle, PasteStage::Started, offset);
+
+    let mut rng = timing_rng(timing_seed);
+    // 倒计时结束时的前台窗口即为目标窗口，按其键盘布局换算虚拟键
+    let layout = match options.backend {
+        InputBackend::Unicode => None,
+        _ => platform::injector().key_layout(),
+    };
+    if options.erase > 0 {
+        let erase: Vec<KeyInput> = (0..options.erase)
+            .flat_map(|_| [vk_input(VK_BACK, false), vk_input(VK_BACK, true)])
+            .collect();
+        send_inputs(&erase);
     }
+    // 文本中第 n 个动作标记的位置 -> n
+    let action_index: HashMap<usize, usize> = if options.actions.is_empty() {
+        HashMap::new()
+    } else {
+        utf16_units
+            .iter()
+            .enumerate()
+            .filter(|(_, &unit)| unit == PASTE_ACTION_MARKER)
+            .enumerate()
+            .map(|(n, (position, _))| (position, n))
+            .collect()
+    };
+    let mut batch: Vec<KeyInput> = Vec::new();
+    // 第一个尚未发送的字符（极速模式下 batch 中的字符尚未发送）
+    let mut sent = offset;
+    let mut i = offset;
+    while i < utf16_units.len() {
+        // 暂停时停在第一个未发送的字符处，直到继续或被中止
+        if is_typing_paused(&state) {
+            batch.clear();
+            i = sent;
+            wait_while_paused(app_handle, &mut progress, i).await;
+        }
 
-    #[cfg(debug_assertions)]
-    println!("已将快捷键配置保存到: {}", store_path.display());
-    
-    Ok(())
+        // 每次循环前检查是否中断
+        let instant_mode = {
+            let mut locked = state.lock().unwrap();
+            if !locked.is_pasting.load(Ordering::SeqCst) {
+                #[cfg(debug_assertions)]
+                println!("粘贴被中断，在第{}个字符处停止", sent);
+                
+                locked.is_pasting.store(false, Ordering::SeqCst);
+                locked.interrupted_at = Some(sent);
+                drop(locked);
+                progress.finish(app_handle, PasteOutcome::Aborted, sent);
+                return Ok(());
+            }
+            options.instant.unwrap_or(locked.instant_mode)
+        };
+        if i > 0 && i.is_multiple_of(PROGRESS_INTERVAL_CHARS) {
+            progress.emit(app_handle, PasteStage::Typing, i);
+        }
+
+        let form_key = options.form.and_then(|form| form.key_for(utf16_units[i]));
+        let action = action_index.get(&i).and_then(|&n| options.actions.get(n));
+        let mut extra_delay = 0;
+        match (form_key, action) {
+            (Some(key), _) => {
+                batch.extend(end_action_inputs(key));
+                extra_delay = options.form.map_or(0, |form| form.field_delay_ms);
+            }
+            (None, Some(PasteAction::Keys(vks))) => batch.extend(key_stroke_inputs(vks)),
+            (None, Some(PasteAction::Delay(ms))) => extra_delay = *ms,
+            (None, None) => batch.extend(char_inputs(utf16_units[i], &options, layout)),
+        }
+        let is_action = form_key.is_some() || action.is_some();
+        i += 1;
+        // 极速模式：攒够一批再一次性发送，不做延迟（表单字段与宏动作处立即发送，以便等待目标程序切换焦点）
+        if instant_mode && !is_action && batch.len() < INSTANT_BATCH_CHARS * 2 && i < utf16_units.len() {
+            continue;
+        }
+
+        // 前台窗口切换事件是异步到达的，发送前再确认一次，避免字符落到其他窗口
+        if !target_focused(&state) {
+            handle_foreground_change(app_handle, foreground::foreground_window().0);
+            if is_typing_paused(&state) || !state.lock().unwrap().is_pasting.load(Ordering::SeqCst) {
+                batch.clear();
+                i = sent;
+                continue;
+            }
+        }
+        // Secure Input 开启时按键会被系统丢弃，暂停在未发送的字符处
+        if secure_input_blocked(app_handle) {
+            batch.clear();
+            i = sent;
+            continue;
+        }
+        send_inputs(&batch);
+        batch.clear();
+        sent = i;
+
+        if instant_mode {
+            tokio::task::yield_now().await;
+        } else {
+            let delay = next_delay(&mut rng, stand, float);
+            sleep(Duration::from_millis(delay as u64)).await;
+        }
+        if extra_delay > 0 {
+            wait_unless_interrupted(&state, extra_delay).await;
+        }
+    }
+
+    // 5. 粘贴结束：按设置提交或跳到下一个输入框（焦点已离开目标窗口则不按）
+    let end_action = state.lock().unwrap().end_action;
+    if !options.skip_end_action && target_focused(&state) {
+        send_end_action(end_action);
+    }
+
+    // 6. 重置状态
+    {
+        let locked = state.lock().unwrap();
+        locked.is_pasting.store(false, Ordering::SeqCst);
+    }
+    progress.finish(app_handle, PasteOutcome::Completed, progress.total);
+    #[cfg(debug_assertions)]
+    println!("paste函数成功完成");
+    Ok(())
+}
+
+/// 等待 ms 毫秒；期间粘贴被中止或暂停时提前返回
+async fn wait_unless_interrupted(state: &Mutex<PasteState>, ms: u32) {
+    let mut remaining = ms as u64;
+    while remaining > 0 {
+        if is_typing_paused(state) || !state.lock().unwrap().is_pasting.load(Ordering::SeqCst) {
+            return;
+        }
+        let step = remaining.min(50);
+        sleep(Duration::from_millis(step)).await;
+        remaining -= step;
+    }
+}
+
+/// 系统正在拦截合成的按键（macOS 的 Secure Input）时暂停粘贴，
+/// 并发送 secure-input-blocked 事件、弹出通知，告知开启它的进程
+fn secure_input_blocked(app_handle: &tauri::AppHandle) -> bool {
+    let Some(secure_input) = platform::injector().secure_input() else {
+        return false;
+    };
+    interrupt_paste(app_handle, "secure_input", InterruptAction::Pause);
+    api::publish("secure-input-blocked", &secure_input);
+    let _ = app_handle.emit_all("secure-input-blocked", &secure_input);
+    notify::secure_input_blocked(app_handle, &secure_input);
+    true
+}
+
+/// 前台窗口是否仍是本次粘贴的目标窗口（未记录目标窗口时视为是）
+fn target_focused(state: &Mutex<PasteState>) -> bool {
+    let target = state.lock().unwrap().target_window;
+    target.is_none_or(|target| target == foreground::foreground_window().0)
+}
+
+/// 前台窗口切换：离开目标窗口时按设置暂停或中止，因此暂停后回到目标窗口时可自动继续
+pub fn handle_foreground_change(app_handle: &tauri::AppHandle, hwnd: isize) {
+    let (action, auto_resume) = {
+        let state = app_handle.state::<Mutex<PasteState>>();
+        let mut locked = state.lock().unwrap();
+        let Some(target) = locked.target_window else {
+            return;
+        };
+        if !locked.is_pasting.load(Ordering::SeqCst) {
+            return;
+        }
+        if hwnd != target {
+            (locked.on_focus_loss, false)
+        } else if locked.paused_by_focus && locked.auto_resume_on_focus {
+            locked.paused_by_focus = false;
+            locked.typing_paused = false;
+            (InterruptAction::Ignore, true)
+        } else {
+            return;
+        }
+    };
+
+    if auto_resume {
+        #[cfg(debug_assertions)]
+        println!("焦点回到目标窗口，继续粘贴");
+
+        let _ = app_handle.emit_all("paste-focus-restored", ());
+        return;
+    }
+
+    let was_paused = is_typing_paused(&app_handle.state::<Mutex<PasteState>>());
+    interrupt_paste(app_handle, "focus_lost", action);
+    if action == InterruptAction::Pause && !was_paused {
+        let state = app_handle.state::<Mutex<PasteState>>();
+        state.lock().unwrap().paused_by_focus = true;
+    }
+}
+
+/// 输入引擎的状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EngineState {
+    Idle,
+    CountingDown,
+    Typing,
+    Paused,
+}
+
+/// get_typing_status 的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct TypingStatus {
+    pub state: EngineState,
+    /// 当前粘贴任务的编号，空闲时为最近一次任务的编号（从未粘贴过为 0）
+    pub job_id: u64,
+    pub typed: usize,
+    pub remaining: usize,
+    pub speed_cps: f64,
+    pub eta_ms: u64,
+}
+
+/// 查询输入引擎当前状态，供重新打开窗口后恢复界面
+#[tauri::command]
+pub fn get_typing_status(app_handle: tauri::AppHandle) -> TypingStatus {
+    let state = app_handle.state::<Mutex<PasteState>>();
+    let locked = state.lock().unwrap();
+    let pasting = locked.is_pasting.load(Ordering::SeqCst);
+    let engine_state = match (&locked.progress, pasting) {
+        (_, false) => EngineState::Idle,
+        (None, true) => EngineState::CountingDown,
+        (Some(_), true) if locked.typing_paused => EngineState::Paused,
+        (Some(_), true) => EngineState::Typing,
+    };
+    let (typed, remaining, speed_cps, eta_ms) = match (&locked.progress, pasting) {
+        (Some(progress), true) => (
+            progress.typed,
+            progress.total.saturating_sub(progress.typed),
+            progress.speed_cps,
+            progress.eta_ms,
+        ),
+        _ => (0, 0, 0.0, 0),
+    };
+    TypingStatus {
+        state: engine_state,
+        job_id: locked.job_id,
+        typed,
+        remaining,
+        speed_cps,
+        eta_ms,
+    }
+}
+
+/// 正在进行的粘贴是否处于暂停
+fn is_typing_paused(state: &Mutex<PasteState>) -> bool {
+    state.lock().unwrap().typing_paused
+}
+
+/// 等待暂停结束（继续或被中止），期间的时长不计入剩余时间估算
+async fn wait_while_paused(
+    app_handle: &tauri::AppHandle,
+    progress: &mut ProgressReporter,
+    typed: usize,
+) {
+    #[cfg(debug_assertions)]
+    println!("粘贴暂停在第{}个字符处", typed);
+
+    progress.emit(app_handle, PasteStage::Paused, typed);
+    let paused_at = std::time::Instant::now();
+    let state = app_handle.state::<Mutex<PasteState>>();
+    loop {
+        {
+            let locked = state.lock().unwrap();
+            if !locked.typing_paused || !locked.is_pasting.load(Ordering::SeqCst) {
+                break;
+            }
+        }
+        sleep(Duration::from_millis(50)).await;
+    }
+    progress.paused += paused_at.elapsed();
+
+    if state.lock().unwrap().is_pasting.load(Ordering::SeqCst) {
+        progress.emit(app_handle, PasteStage::Resumed, typed);
+    }
+}
+
+/// 暂停/继续正在进行的粘贴
+pub fn set_typing_paused(app_handle: &tauri::AppHandle, paused: bool) -> Result<(), &'static str> {
+    let state = app_handle.state::<Mutex<PasteState>>();
+    let mut locked = state.lock().unwrap();
+    if !locked.is_pasting.load(Ordering::SeqCst) {
+        return Err(i18n::t("error.not_pasting"));
+    }
+    locked.typing_paused = paused;
+    Ok(())
+}
+
+/// 暂停正在进行的粘贴，停在当前字符处
+#[tauri::command]
+pub fn pause_typing(app_handle: tauri::AppHandle) -> Result<(), &'static str> {
+    set_typing_paused(&app_handle, true)
+}
+
+/// 从暂停处继续输入
+#[tauri::command]
+pub fn resume_typing(app_handle: tauri::AppHandle) -> Result<(), &'static str> {
+    set_typing_paused(&app_handle, false)
+}
+
+/// paste-interrupted 事件的内容
+#[derive(Debug, Clone, Serialize)]
+pub struct PasteInterrupted {
+    /// 打断的原因："user_input"、"focus_lost"、"session_locked"、"session_disconnected"、"system_suspend"、"secure_input"
+    pub reason: &'static str,
+    pub action: InterruptAction,
+}
+
+/// 按设定的处理方式暂停或中止正在进行的粘贴，并通知前端
+pub fn interrupt_paste(app_handle: &tauri::AppHandle, reason: &'static str, action: InterruptAction) {
+    {
+        let state = app_handle.state::<Mutex<PasteState>>();
+        let mut locked = state.lock().unwrap();
+        if !locked.is_pasting.load(Ordering::SeqCst) {
+            return;
+        }
+        match action {
+            InterruptAction::Ignore => return,
+            // 已经暂停则无需重复通知
+            InterruptAction::Pause if locked.typing_paused => return,
+            InterruptAction::Pause => locked.typing_paused = true,
+            InterruptAction::Abort => locked.abort(reason),
+        }
+    }
+
+    #[cfg(debug_assertions)]
+    println!("粘贴被打断（{}）：{:?}", reason, action);
+
+    let interrupted = PasteInterrupted { reason, action };
+    api::publish("paste-interrupted", &interrupted);
+    let _ = app_handle.emit_all("paste-interrupted", interrupted);
+}
+
+/// 钩子检测到粘贴期间用户自己的键盘/鼠标操作
+#[cfg(windows)]
+pub fn handle_user_input(app_handle: &tauri::AppHandle) {
+    let action = {
+        let state = app_handle.state::<Mutex<PasteState>>();
+        let locked = state.lock().unwrap();
+        locked.on_user_input
+    };
+    interrupt_paste(app_handle, "user_input", action);
+}
+
+/// 设置粘贴期间用户操作键盘鼠标时的处理方式
+#[tauri::command]
+pub fn set_user_input_action(action: InterruptAction, app_handle: tauri::AppHandle) {
+    let state = app_handle.state::<Mutex<PasteState>>();
+    state.lock().unwrap().on_user_input = action;
+    settings::persist(&app_handle);
+}
+
+/// 获取粘贴期间用户操作键盘鼠标时的处理方式
+#[tauri::command]
+pub fn get_user_input_action(app_handle: tauri::AppHandle) -> InterruptAction {
+    let state = app_handle.state::<Mutex<PasteState>>();
+    let locked = state.lock().unwrap();
+    locked.on_user_input
+}
+
+/// 锁屏、会话断开或系统即将睡眠，避免继续向锁屏界面输入或恢复后输入到错误位置
+#[cfg(windows)]
+pub fn handle_session_change(app_handle: &tauri::AppHandle, reason: &'static str) {
+    let action = {
+        let state = app_handle.state::<Mutex<PasteState>>();
+        let locked = state.lock().unwrap();
+        locked.on_session_change
+    };
+    interrupt_paste(app_handle, reason, action);
+}
+
+/// 设置粘贴期间锁屏、会话断开或系统睡眠时的处理方式
+#[tauri::command]
+pub fn set_session_change_action(action: InterruptAction, app_handle: tauri::AppHandle) {
+    let state = app_handle.state::<Mutex<PasteState>>();
+    state.lock().unwrap().on_session_change = action;
+    settings::persist(&app_handle);
+}
+
+/// 获取锁屏、会话断开或系统睡眠时的处理方式
+#[tauri::command]
+pub fn get_session_change_action(app_handle: tauri::AppHandle) -> InterruptAction {
+    let state = app_handle.state::<Mutex<PasteState>>();
+    let locked = state.lock().unwrap();
+    locked.on_session_change
+}
+
+/// 设置粘贴期间目标窗口失去焦点时的处理方式，以及焦点回来后是否自动继续
+#[tauri::command]
+pub fn set_focus_loss_action(
+    action: InterruptAction,
+    auto_resume: bool,
+    app_handle: tauri::AppHandle,
+) {
+    {
+        let state = app_handle.state::<Mutex<PasteState>>();
+        let mut locked = state.lock().unwrap();
+        locked.on_focus_loss = action;
+        locked.auto_resume_on_focus = auto_resume;
+    }
+    settings::persist(&app_handle);
+}
+
+/// 获取失去焦点时的处理方式及是否自动继续
+#[tauri::command]
+pub fn get_focus_loss_action(app_handle: tauri::AppHandle) -> (InterruptAction, bool) {
+    let state = app_handle.state::<Mutex<PasteState>>();
+    let locked = state.lock().unwrap();
+    (locked.on_focus_loss, locked.auto_resume_on_focus)
+}
+
+/// 中止正在进行的粘贴（包括开始前的倒计时）
+#[tauri::command]
+pub fn cancel_paste(app_handle: tauri::AppHandle) {
+    let state = app_handle.state::<Mutex<PasteState>>();
+    let mut locked = state.lock().unwrap();
+    locked.abort("cancelled");
+}
+
+/// 设置输入完最后一个字符后自动按下的按键
+#[tauri::command]
+pub fn set_end_action(action: EndAction, app_handle: tauri::AppHandle) {
+    let state = app_handle.state::<Mutex<PasteState>>();
+    state.lock().unwrap().end_action = action;
+    settings::persist(&app_handle);
+}
+
+/// 获取粘贴结束后自动按下的按键
+#[tauri::command]
+pub fn get_end_action(app_handle: tauri::AppHandle) -> EndAction {
+    let state = app_handle.state::<Mutex<PasteState>>();
+    let locked = state.lock().unwrap();
+    locked.end_action
+}
+
+/// 设置粘贴剪贴板内容前是否展开模板变量
+#[tauri::command]
+pub fn set_clipboard_templates(enabled: bool, app_handle: tauri::AppHandle) {
+    let state = app_handle.state::<Mutex<PasteState>>();
+    state.lock().unwrap().expand_clipboard_templates = enabled;
+    settings::persist(&app_handle);
+}
+
+/// 获取粘贴剪贴板内容前是否展开模板变量
+#[tauri::command]
+pub fn get_clipboard_templates(app_handle: tauri::AppHandle) -> bool {
+    let state = app_handle.state::<Mutex<PasteState>>();
+    let locked = state.lock().unwrap();
+    locked.expand_clipboard_templates
+}
+
+/// 设置粘贴时读取剪贴板、PRIMARY 选区（仅 Linux 支持）还是剪贴板中转收到的文本
+#[tauri::command]
+pub fn set_text_source(source: TextSource, app_handle: tauri::AppHandle) -> Result<(), &'static str> {
+    if !source.is_supported() {
+        return Err(i18n::t("error.primary_unsupported"));
+    }
+    let state = app_handle.state::<Mutex<PasteState>>();
+    state.lock().unwrap().text_source = source;
+    settings::persist(&app_handle);
+    Ok(())
+}
+
+/// 获取粘贴时读取文本的来源
+#[tauri::command]
+pub fn get_text_source(app_handle: tauri::AppHandle) -> TextSource {
+    let state = app_handle.state::<Mutex<PasteState>>();
+    let locked = state.lock().unwrap();
+    locked.text_source
+}
+
+/// 设置粘贴完成/中止/失败时是否弹出系统通知
+#[tauri::command]
+pub fn set_notification_prefs(prefs: NotificationPrefs, app_handle: tauri::AppHandle) {
+    let state = app_handle.state::<Mutex<PasteState>>();
+    state.lock().unwrap().notifications = prefs;
+    settings::persist(&app_handle);
+}
+
+/// 获取结束通知设置
+#[tauri::command]
+pub fn get_notification_prefs(app_handle: tauri::AppHandle) -> NotificationPrefs {
+    let state = app_handle.state::<Mutex<PasteState>>();
+    let locked = state.lock().unwrap();
+    locked.notifications
+}
+
+/// 设置默认输入速度：每个字符后等待 stand + [0, float) 毫秒
+#[tauri::command]
+pub fn set_default_timing(stand: u32, float: u32, app_handle: tauri::AppHandle) {
+    let state = app_handle.state::<Mutex<PasteState>>();
+    state.lock().unwrap().default_timing = (stand, float);
+    settings::persist(&app_handle);
+    tray::refresh(&app_handle);
+}
+
+/// 速度预设
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct SpeedPreset {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub stand: u32,
+    pub float: u32,
+}
+
+/// 内置的速度预设，托盘中可直接切换
+pub const SPEED_PRESETS: [SpeedPreset; 4] = [
+    SpeedPreset {
+        id: "fast",
+        name: "快速",
+        stand: 3,
+        float: 2,
+    },
+    SpeedPreset {
+        id: "normal",
+        name: "正常",
+        stand: 10,
+        float: 5,
+    },
+    SpeedPreset {
+        id: "slow",
+        name: "慢速",
+        stand: 30,
+        float: 20,
+    },
+    SpeedPreset {
+        id: "human",
+        name: "模拟手打",
+        stand: 80,
+        float: 60,
+    },
+];
+
+/// 获取全部速度预设
+#[tauri::command]
+pub fn get_speed_presets() -> Vec<SpeedPreset> {
+    SPEED_PRESETS.to_vec()
+}
+
+/// 以速度预设作为默认输入速度，并关闭极速模式，从下一次粘贴开始生效
+#[tauri::command]
+pub fn apply_speed_preset(id: String, app_handle: tauri::AppHandle) -> Result<(), String> {
+    let preset = SPEED_PRESETS
+        .iter()
+        .find(|preset| preset.id == id)
+        .ok_or_else(|| i18n::tf("error.speed_preset_missing", &[("id", &id)]))?;
+    {
+        let state = app_handle.state::<Mutex<PasteState>>();
+        state.lock().unwrap().default_timing = (preset.stand, preset.float);
+    }
+    set_instant_mode(&app_handle, false);
+    settings::persist(&app_handle);
+    tray::refresh(&app_handle);
+    Ok(())
+}
+
+/// 获取默认输入速度 (stand, float)
+#[tauri::command]
+pub fn get_default_timing(app_handle: tauri::AppHandle) -> (u32, u32) {
+    let state = app_handle.state::<Mutex<PasteState>>();
+    let locked = state.lock().unwrap();
+    locked.default_timing
+}
+
+/// 开始输入前倒计时的上限（秒）
+pub const MAX_START_DELAY_SECS: u32 = 30;
+
+/// 设置开始输入前的倒计时秒数（0 表示立即开始）
+#[tauri::command]
+pub fn set_start_delay(secs: u32, app_handle: tauri::AppHandle) -> Result<(), &'static str> {
+    if secs > MAX_START_DELAY_SECS {
+        return Err(i18n::t("error.start_delay_too_long"));
+    }
+    let state = app_handle.state::<Mutex<PasteState>>();
+    state.lock().unwrap().start_delay_secs = secs;
+    settings::persist(&app_handle);
+    Ok(())
+}
+
+/// 获取开始输入前的倒计时秒数
+#[tauri::command]
+pub fn get_start_delay(app_handle: tauri::AppHandle) -> u32 {
+    let state = app_handle.state::<Mutex<PasteState>>();
+    let locked = state.lock().unwrap();
+    locked.start_delay_secs
+}
+
+/// 修改快捷键的暂停状态。托盘、快捷键与前端都经过这里，
+/// 以便同步托盘菜单文字、图标与提示，并发送 pause-changed 事件
+fn update_paused(app_handle: &tauri::AppHandle, change: impl FnOnce(bool) -> bool) -> bool {
+    let paused = {
+        let state = app_handle.state::<Mutex<PasteState>>();
+        let mut locked = state.lock().unwrap();
+        locked.is_paused = change(locked.is_paused);
+        tray::set_icon(app_handle, TrayIcon::for_state(&locked));
+        locked.is_paused
+    };
+
+    // 先直接修改菜单项文字，重建菜单在后台进行
+    let title = i18n::t(if paused { "tray.resume" } else { "tray.pause" });
+    let _ = app_handle.tray_handle().get_item("pause").set_title(title);
+    tray::refresh(app_handle);
+    api::publish("pause-changed", &paused);
+    let _ = app_handle.emit_all("pause-changed", paused);
+    paused
+}
+
+/// 切换暂停状态，返回切换后是否暂停
+pub fn toggle_paused(app_handle: &tauri::AppHandle) -> bool {
+    update_paused(app_handle, |paused| !paused)
+}
+
+/// 切换暂停状态
+#[tauri::command]
+pub fn toggle_pause(app_handle: tauri::AppHandle) -> bool {
+    toggle_paused(&app_handle)
+}
+
+/// 暂停或恢复快捷键
+#[tauri::command]
+pub fn set_pause(paused: bool, app_handle: tauri::AppHandle) {
+    update_paused(&app_handle, |_| paused);
+}
+
+/// 获取快捷键是否已暂停
+#[tauri::command]
+pub fn get_pause(app_handle: tauri::AppHandle) -> bool {
+    let state = app_handle.state::<Mutex<PasteState>>();
+    let locked = state.lock().unwrap();
+    locked.is_paused
+}
+
+/// 设置延迟随机数种子（None 表示恢复为随机），用于复现与时间相关的问题
+#[tauri::command]
+pub fn set_timing_seed(seed: Option<u64>, app_handle: tauri::AppHandle) {
+    let state = app_handle.state::<Mutex<PasteState>>();
+    state.lock().unwrap().timing_seed = seed;
+    settings::persist(&app_handle);
+}
+
+/// 获取当前延迟随机数种子
+#[tauri::command]
+pub fn get_timing_seed(app_handle: tauri::AppHandle) -> Option<u64> {
+    let state = app_handle.state::<Mutex<PasteState>>();
+    let locked = state.lock().unwrap();
+    locked.timing_seed
+}
+
+/// 设置极速模式，并同步托盘菜单勾选状态与提示文字
+pub fn set_instant_mode(app_handle: &tauri::AppHandle, enabled: bool) {
+    {
+        let state = app_handle.state::<Mutex<PasteState>>();
+        state.lock().unwrap().instant_mode = enabled;
+    }
+
+    let tray_handle = app_handle.tray_handle();
+    let _ = tray_handle.get_item("instant").set_selected(enabled);
+    tray::update_tooltip(app_handle, None);
+    let _ = app_handle.emit_all("instant-mode-changed", enabled);
+}
+
+/// 切换极速模式，返回切换后的状态
+pub fn toggle_instant(app_handle: &tauri::AppHandle) -> bool {
+    let enabled = {
+        let state = app_handle.state::<Mutex<PasteState>>();
+        let locked = state.lock().unwrap();
+        !locked.instant_mode
+    };
+    set_instant_mode(app_handle, enabled);
+    settings::persist(app_handle);
+    tray::refresh(app_handle);
+    enabled
+}
+
+/// 切换极速模式（近乎零延迟、批量发送）
+#[tauri::command]
+pub fn toggle_instant_mode(app_handle: tauri::AppHandle) -> bool {
+    toggle_instant(&app_handle)
+}
+
+/// 获取极速模式状态
+#[tauri::command]
+pub fn get_instant_mode(app_handle: tauri::AppHandle) -> bool {
+    let state = app_handle.state::<Mutex<PasteState>>();
+    let locked = state.lock().unwrap();
+    locked.instant_mode
+}
+
+/// 获取当前粘贴快捷键配置
+#[tauri::command]
+pub fn get_shortcut(app_handle: tauri::AppHandle) -> HotkeyConfig {
+    let state = app_handle.state::<Mutex<PasteState>>();
+    let locked = state.lock().unwrap();
+    locked.hotkeys.get(&HotkeyAction::Paste).cloned().unwrap_or_default()
+}
+
+/// 更新粘贴快捷键（并尝试重新注册全局快捷键），并将新配置持久化到本地
+#[tauri::command]
+pub fn update_shortcut(config: HotkeyConfig, app_handle: tauri::AppHandle) -> Result<String, String> {
+    let mut bindings = {
+        let state = app_handle.state::<Mutex<PasteState>>();
+        let locked = state.lock().unwrap();
+        locked.hotkeys.clone()
+    };
+    bindings.insert(HotkeyAction::Paste, config.clone());
+    update_hotkeys(bindings, app_handle)?;
+
+    Ok(config.get_description())
+}
+
+/// 获取全部动作的快捷键配置
+#[tauri::command]
+pub fn get_hotkeys(app_handle: tauri::AppHandle) -> HotkeyBindings {
+    let state = app_handle.state::<Mutex<PasteState>>();
+    let locked = state.lock().unwrap();
+    locked.hotkeys.clone()
+}
+
+/// 更新全部动作的快捷键：整组原子地重新注册（失败则保持原有快捷键不变），成功后持久化到本地
+#[tauri::command]
+pub fn update_hotkeys(bindings: HotkeyBindings, app_handle: tauri::AppHandle) -> Result<(), String> {
+    // 1. 验证快捷键是否合法（包括被选用的配置方案覆盖后）
+    hotkeys::validate_bindings(&bindings)?;
+    let effective = profiles::effective_hotkeys(&app_handle, &bindings);
+    hotkeys::validate_bindings(&effective)?;
+
+    // 2. 注册全局快捷键
+    hotkeys::switch_hotkeys(&app_handle, &effective)?;
+
+    let state = app_handle.state::<Mutex<PasteState>>();
+    {
+        let mut locked = state.lock().unwrap();
+        locked.hotkeys = bindings;
+    }
+
+    // 3. 保存到设置文件，并更新托盘中显示的快捷键
+    settings::persist(&app_handle);
+    tray::refresh(&app_handle);
+
+    Ok(())
+}
+
+/// 获取暂停快捷键的程序列表
+#[tauri::command]
+pub fn get_suspend_apps(app_handle: tauri::AppHandle) -> Vec<String> {
+    hotkeys::suspend_apps(&app_handle)
+}
+
+/// 设置暂停快捷键的程序列表（进程名，如 "vmconnect.exe"），并持久化到本地
+#[tauri::command]
+pub fn set_suspend_apps(apps: Vec<String>, app_handle: tauri::AppHandle) -> Result<(), String> {
+    let apps: Vec<String> = apps
+        .into_iter()
+        .map(|app| app.trim().to_string())
+        .filter(|app| !app.is_empty())
+        .collect();
+    hotkeys::set_suspend_apps(&app_handle, apps);
+    settings::save(&app_handle)
+}
+
+/// 录制下一次按下的组合键（支持 Win 键、功能键、小键盘），返回规范化的快捷键字符串。
+/// 前端可将结果写入 HotkeyConfig.accelerator 后调用 update_hotkeys。
+#[tauri::command]
+pub async fn capture_hotkey(timeout_ms: Option<u64>) -> Result<String, String> {
+    let timeout = Duration::from_millis(timeout_ms.unwrap_or(10_000));
+    tokio::task::spawn_blocking(move || crate::hook::capture_next_combo(timeout))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// 重启应用
+#[tauri::command]
+pub fn restart_app(app_handle: tauri::AppHandle) {
+    std::thread::spawn(move || {
+        app_handle.restart();
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delays(seed: Option<u64>) -> Vec<u32> {
+        let mut rng = timing_rng(seed);
+        (0..100).map(|_| next_delay(&mut rng, 20, 50)).collect()
+    }
+
+    #[test]
+    fn seeded_delays_are_reproducible() {
+        assert_eq!(delays(Some(42)), delays(Some(42)));
+        assert_ne!(delays(Some(42)), delays(Some(43)));
+    }
+
+    #[test]
+    fn delays_stay_in_range() {
+        assert!(delays(Some(7)).iter().all(|delay| (20..70).contains(delay)));
+        let mut rng = timing_rng(None);
+        assert_eq!(next_delay(&mut rng, 15, 0), 15);
+    }
 }
@@ -0,0 +1,189 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tauri::Manager;
+
+use crate::i18n;
+
+/// 程序所在目录中存在此标记文件（或以 --portable 启动）时进入便携模式
+const PORTABLE_MARKER: &str = "portable";
+/// 便携模式下保存配置与数据的目录（位于程序所在目录中）
+const PORTABLE_DATA_DIR: &str = "data";
+
+/// 文件被同步工具（OneDrive、Syncthing）或杀毒软件暂时占用时的重试次数与间隔
+const LOCK_RETRIES: u32 = 5;
+const LOCK_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+static PORTABLE_DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+/// 便携模式（如从 U 盘运行）下的数据目录；非便携模式时为 None
+pub fn portable_dir() -> Option<&'static Path> {
+    PORTABLE_DIR
+        .get_or_init(|| {
+            let exe_dir = std::env::current_exe().ok()?.parent()?.to_path_buf();
+            let enabled = std::env::args().any(|arg| arg == "--portable")
+                || exe_dir.join(PORTABLE_MARKER).exists();
+            enabled.then(|| exe_dir.join(PORTABLE_DATA_DIR))
+        })
+        .as_deref()
+}
+
+pub fn is_portable() -> bool {
+    portable_dir().is_some()
+}
+
+/// 获取配置文件路径（位于 app_config_dir 下，便携模式下位于程序旁的 data 目录中）
+pub fn config_path(app_handle: &tauri::AppHandle, file_name: &str) -> Result<PathBuf, String> {
+    use tauri::api::path::{resolve_path, BaseDirectory};
+
+    if let Some(dir) = portable_dir() {
+        return Ok(dir.join(file_name));
+    }
+
+    resolve_path(
+        &app_handle.config(),
+        app_handle.package_info(),
+        &app_handle.env(),
+        file_name,
+        Some(BaseDirectory::AppConfig),
+    )
+    .map_err(|e| i18n::tf("error.config_dir", &[("error", &e)]))
+}
+
+/// 配置目录（app_config_dir，便携模式下为程序旁的 data 目录）
+pub fn config_dir(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
+    if let Some(dir) = portable_dir() {
+        return Ok(dir.to_path_buf());
+    }
+    app_handle
+        .path_resolver()
+        .app_config_dir()
+        .ok_or_else(|| i18n::t("error.config_dir_missing").to_string())
+}
+
+/// 配置文件上一次保存前的备份（如 settings.json.bak）
+pub fn backup_path(path: &Path) -> PathBuf {
+    with_suffix(path, ".bak")
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// 文件被暂时占用时稍后重试；文件不存在时不重试
+fn retry<T>(mut op: impl FnMut() -> io::Result<T>) -> io::Result<T> {
+    let mut attempt = 0;
+    loop {
+        match op() {
+            Err(e) if e.kind() != io::ErrorKind::NotFound && attempt < LOCK_RETRIES => {
+                attempt += 1;
+                std::thread::sleep(LOCK_RETRY_DELAY);
+            }
+            result => return result,
+        }
+    }
+}
+
+/// 读取文本文件，文件被暂时占用时重试
+pub fn read_text(path: &Path) -> io::Result<String> {
+    retry(|| fs::read_to_string(path))
+}
+
+/// 原子地写入文件：先写入同目录下的临时文件并落盘，再替换原文件，
+/// 写到一半崩溃或断电时原文件保持完整
+pub fn write_atomic(path: &Path, content: &[u8]) -> Result<(), String> {
+    let temp = with_suffix(path, ".tmp");
+    let written = fs::File::create(&temp).and_then(|mut file| {
+        file.write_all(content)?;
+        file.sync_all()
+    });
+    if let Err(e) = written.and_then(|_| retry(|| fs::rename(&temp, path))) {
+        let _ = fs::remove_file(&temp);
+        return Err(i18n::tf("error.write_file", &[("error", &e)]));
+    }
+    Ok(())
+}
+
+fn parse_json<T: DeserializeOwned>(path: &Path) -> Option<T> {
+    let content = read_text(path).ok()?;
+    match serde_json::from_str(content.trim_start_matches('\u{feff}')) {
+        Ok(value) => Some(value),
+        Err(_e) => {
+            #[cfg(debug_assertions)]
+            eprintln!("解析 {} 失败: {}", path.display(), _e);
+
+            None
+        }
+    }
+}
+
+/// 读取 JSON 配置文件；文件损坏（如同步工具只同步了一半）时改读上一版备份，
+/// 都无法读取时返回 None
+pub fn read_json<T: DeserializeOwned>(app_handle: &tauri::AppHandle, file_name: &str) -> Option<T> {
+    let path = config_path(app_handle, file_name).ok()?;
+    if !path.exists() {
+        return None;
+    }
+    parse_json(&path).or_else(|| {
+        let value = parse_json(&backup_path(&path));
+
+        #[cfg(debug_assertions)]
+        if value.is_some() {
+            println!("{} 已损坏，使用备份", path.display());
+        }
+
+        value
+    })
+}
+
+/// 在资源管理器中打开配置目录（设置、片段库、历史及其备份都在这里），不存在时先创建
+#[tauri::command]
+pub fn open_config_dir(app_handle: tauri::AppHandle) -> Result<(), String> {
+    let dir = config_dir(&app_handle)?;
+    fs::create_dir_all(&dir).map_err(|e| i18n::tf("error.create_dir", &[("error", &e)]))?;
+    std::process::Command::new("explorer")
+        .arg(&dir)
+        .spawn()
+        .map_err(|e| i18n::tf("error.open_folder", &[("error", &e)]))?;
+    Ok(())
+}
+
+/// 将配置写入 JSON 文件，必要时创建目录。
+/// 写入是原子的，原文件（能正常解析时）先另存为 .bak 备份。
+pub fn write_json<T: Serialize + ?Sized>(
+    app_handle: &tauri::AppHandle,
+    file_name: &str,
+    value: &T,
+) -> Result<(), String> {
+    let store_path = config_path(app_handle, file_name)?;
+    if let Some(parent) = store_path.parent() {
+        if !parent.exists() {
+            fs::create_dir_all(parent)
+                .map_err(|e| i18n::tf("error.create_dir", &[("error", &e)]))?;
+        }
+    }
+
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| i18n::tf("error.serialize_json", &[("error", &e)]))?;
+
+    // 损坏的文件不作为备份，以免覆盖仍然完好的上一版备份
+    if parse_json::<serde_json::Value>(&store_path).is_some() {
+        if let Err(_e) = retry(|| fs::copy(&store_path, backup_path(&store_path))) {
+            #[cfg(debug_assertions)]
+            eprintln!("备份 {} 失败: {}", store_path.display(), _e);
+        }
+    }
+    write_atomic(&store_path, json.as_bytes())?;
+
+    #[cfg(debug_assertions)]
+    println!("已保存配置: {}", store_path.display());
+
+    Ok(())
+}
@@ -0,0 +1,197 @@
+// WM_COPYDATA 接口：供只能发送窗口消息的旧式自动化工具（AutoHotkey、AutoIt 等）调用。
+// 用 FindWindowEx(HWND_MESSAGE, 0, "PasterCopyData", 0) 找到窗口后发送 WM_COPYDATA，
+// 数据为 JSON：{"action": "paste", "payload": {"text": "..."}}，UTF-8 或 UTF-16 均可
+
+use std::sync::OnceLock;
+use std::thread;
+
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use windows::core::w;
+use windows::Win32::{
+    Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, WPARAM},
+    System::{DataExchange::COPYDATASTRUCT, LibraryLoader::GetModuleHandleW},
+    UI::WindowsAndMessaging::{
+        CreateWindowExW, DefWindowProcW, DispatchMessageW, GetMessageW, RegisterClassW,
+        SendMessageTimeoutW, HMENU, HWND_MESSAGE, MSG, SMTO_ABORTIFHUNG, WINDOW_EX_STYLE,
+        WINDOW_STYLE, WM_COPYDATA, WNDCLASSW,
+    },
+};
+
+use crate::i18n;
+use crate::ipc::{self, Request};
+
+/// 向发送方回复应答的等待上限（毫秒）
+const REPLY_TIMEOUT_MS: u32 = 5000;
+
+/// 消息处理中使用的 AppHandle
+static APP_HANDLE: OnceLock<tauri::AppHandle> = OnceLock::new();
+
+/// 消息中的 JSON
+#[derive(Debug, Deserialize)]
+struct Envelope {
+    /// paste、abort、status、snippet、set_profile 或 open_url，同进程间通道的 command
+    action: String,
+    /// 对象时为该操作的参数；字符串时为主要参数（paste 的 text、snippet 与 set_profile 的 name、open_url 的 url）
+    #[serde(default)]
+    payload: Value,
+}
+
+impl Envelope {
+    fn into_request(self) -> Result<Request, String> {
+        // 字符串参数对应的字段；launch 与 extension 只供 Paster 自身的进程使用
+        let field = match self.action.as_str() {
+            "paste" => Some("text"),
+            "snippet" | "set_profile" => Some("name"),
+            "open_url" => Some("url"),
+            "abort" | "status" => None,
+            _ => {
+                return Err(i18n::tf(
+                    "error.copydata_action",
+                    &[("action", &self.action)],
+                ))
+            }
+        };
+        let mut request = match (self.payload, field) {
+            (Value::Object(payload), _) => payload,
+            (Value::Null, _) => Map::new(),
+            (payload, Some(field)) => Map::from_iter([(field.to_string(), payload)]),
+            (_, None) => Map::new(),
+        };
+        request.insert("command".to_string(), Value::String(self.action));
+        serde_json::from_value(Value::Object(request)).map_err(|e| e.to_string())
+    }
+}
+
+/// 启动接收线程：创建仅接收消息的隐藏窗口
+pub fn start(app_handle: tauri::AppHandle) {
+    if APP_HANDLE.set(app_handle).is_err() {
+        return;
+    }
+    thread::spawn(|| unsafe {
+        let module = match GetModuleHandleW(None) {
+            Ok(module) => HINSTANCE(module.0),
+            Err(_) => return,
+        };
+        let class_name = w!("PasterCopyData");
+        let class = WNDCLASSW {
+            lpfnWndProc: Some(window_proc),
+            hInstance: module,
+            lpszClassName: class_name,
+            ..Default::default()
+        };
+        RegisterClassW(&class);
+
+        let hwnd = CreateWindowExW(
+            WINDOW_EX_STYLE(0),
+            class_name,
+            w!("Paster"),
+            WINDOW_STYLE(0),
+            0,
+            0,
+            0,
+            0,
+            HWND_MESSAGE,
+            HMENU(0),
+            module,
+            None,
+        );
+        if hwnd.0 == 0 {
+            #[cfg(debug_assertions)]
+            eprintln!("创建 WM_COPYDATA 窗口失败");
+
+            return;
+        }
+
+        let mut msg = MSG::default();
+        while GetMessageW(&mut msg, HWND(0), 0, 0).as_bool() {
+            DispatchMessageW(&msg);
+        }
+    });
+}
+
+/// 收到 WM_COPYDATA 时执行请求：成功返回 1，失败返回 0。
+/// wParam 为发送方的窗口时，再以 WM_COPYDATA 回复进程间通道格式的应答（dwData 与请求相同）
+unsafe extern "system" fn window_proc(
+    hwnd: HWND,
+    message: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    if message != WM_COPYDATA || lparam.0 == 0 {
+        return DefWindowProcW(hwnd, message, wparam, lparam);
+    }
+    let Some(app_handle) = APP_HANDLE.get() else {
+        return LRESULT(0);
+    };
+    let data = &*(lparam.0 as *const COPYDATASTRUCT);
+    let bytes = if data.lpData.is_null() {
+        &[][..]
+    } else {
+        std::slice::from_raw_parts(data.lpData as *const u8, data.cbData as usize)
+    };
+    let (text, wide) = decode(bytes);
+
+    let result = match serde_json::from_str::<Envelope>(&text) {
+        Ok(envelope) => match envelope.into_request() {
+            Ok(request) => tauri::async_runtime::block_on(ipc::handle(app_handle, request)),
+            Err(e) => Err(e),
+        },
+        Err(e) => Err(e.to_string()),
+    };
+    let succeeded = result.is_ok();
+
+    let sender = HWND(wparam.0 as isize);
+    if sender.0 != 0 {
+        let response = match result {
+            Ok(body) => json!({ "ok": true, "result": body }),
+            Err(e) => json!({ "ok": false, "error": e }),
+        }
+        .to_string();
+        reply(hwnd, sender, data.dwData, &response, wide);
+    }
+    LRESULT(succeeded as isize)
+}
+
+/// 按请求的编码解析文本，返回文本与是否为 UTF-16。
+/// JSON 以 ASCII 字符开头，UTF-16（小端）时第二个字节为 0；末尾的空字符忽略
+fn decode(bytes: &[u8]) -> (String, bool) {
+    if bytes.len() >= 2 && bytes[1] == 0 {
+        let units: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+            .collect();
+        let text = String::from_utf16_lossy(&units);
+        (text.trim_end_matches('\0').to_string(), true)
+    } else {
+        let text = String::from_utf8_lossy(bytes);
+        (text.trim_end_matches('\0').to_string(), false)
+    }
+}
+
+unsafe fn reply(hwnd: HWND, sender: HWND, id: usize, response: &str, wide: bool) {
+    let bytes: Vec<u8> = if wide {
+        response
+            .encode_utf16()
+            .chain([0])
+            .flat_map(u16::to_le_bytes)
+            .collect()
+    } else {
+        response.bytes().chain([0]).collect()
+    };
+    let data = COPYDATASTRUCT {
+        dwData: id,
+        cbData: bytes.len() as u32,
+        lpData: bytes.as_ptr() as *mut _,
+    };
+    // 发送方仍在等待 SendMessage 返回，此时它能处理发来的消息
+    SendMessageTimeoutW(
+        sender,
+        WM_COPYDATA,
+        WPARAM(hwnd.0 as usize),
+        LPARAM(&data as *const COPYDATASTRUCT as isize),
+        SMTO_ABORTIFHUNG,
+        REPLY_TIMEOUT_MS,
+        None,
+    );
+}
@@ -0,0 +1,230 @@
+use tauri::api::dialog;
+
+use crate::api;
+use crate::commands::{self, PasteOptions};
+use crate::config;
+use crate::i18n;
+use crate::notify;
+use crate::profiles;
+use crate::snippets;
+
+const PREFIX: &str = "paster://";
+/// 确认后开始输入前的倒计时，留出切回目标窗口的时间
+const START_DELAY_SECS: u32 = 3;
+/// 确认对话框中最多预览的字符数
+const PREVIEW_CHARS: usize = 200;
+
+/// 支持的链接，均需在对话框中确认后才会输入
+enum Link {
+    /// paster://type?text=...&profile=...&stand=...&float=...&instant=...
+    Type {
+        text: String,
+        profile: Option<String>,
+        stand: Option<u32>,
+        float: Option<u32>,
+        instant: Option<bool>,
+    },
+    /// paster://snippet/<标题或编号>
+    Snippet { id: u64, title: String },
+}
+
+/// 是否为 paster:// 链接（系统打开链接时作为启动参数传入）
+pub fn is_link(arg: &str) -> bool {
+    arg.to_ascii_lowercase().starts_with(PREFIX)
+}
+
+fn invalid(url: &str) -> String {
+    i18n::tf("error.deep_link_invalid", &[("url", &url)])
+}
+
+fn parse(app_handle: &tauri::AppHandle, url: &str) -> Result<Link, String> {
+    if !is_link(url) {
+        return Err(invalid(url));
+    }
+    let rest = &url[PREFIX.len()..];
+    let (target, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let query = api::parse_query(query);
+    let param = |name: &str| query.get(name).filter(|value| !value.is_empty());
+    let number = |name: &str| {
+        param(name)
+            .map(|value| value.parse::<u32>().map_err(|_| invalid(url)))
+            .transpose()
+    };
+
+    let (action, name) = target
+        .trim_end_matches('/')
+        .split_once('/')
+        .unwrap_or((target, ""));
+    match action.to_ascii_lowercase().as_str() {
+        "type" => {
+            let text = param("text").cloned().ok_or_else(|| invalid(url))?;
+            let profile = param("profile").cloned();
+            if let Some(name) = &profile {
+                if !profiles::exists(app_handle, name) {
+                    return Err(i18n::tf("error.profile_missing", &[("name", name)]));
+                }
+            }
+            let instant = param("instant")
+                .map(|value| value.parse::<bool>().map_err(|_| invalid(url)))
+                .transpose()?;
+            Ok(Link::Type {
+                text,
+                profile,
+                stand: number("stand")?,
+                float: number("float")?,
+                instant,
+            })
+        }
+        "snippet" if !name.is_empty() => {
+            let snippet = snippets::lookup(app_handle, &api::percent_decode(name))?;
+            Ok(Link::Snippet {
+                id: snippet.id,
+                title: snippet.title,
+            })
+        }
+        _ => Err(invalid(url)),
+    }
+}
+
+/// 打开 paster:// 链接：确认后开始输入。链接无效时弹出通知并返回错误
+pub fn open(app_handle: &tauri::AppHandle, url: &str) -> Result<(), String> {
+    let result = parse(app_handle, url).and_then(|link| {
+        commands::check_idle(app_handle)?;
+        confirm(app_handle, link);
+        Ok(())
+    });
+    if let Err(e) = &result {
+        notify::deep_link_failed(app_handle, e);
+    }
+    result
+}
+
+/// 链接可能来自任意网页，输入前总是请用户确认
+fn confirm(app_handle: &tauri::AppHandle, link: Link) {
+    let message = match &link {
+        Link::Type { text, .. } => {
+            let mut preview: String = text.chars().take(PREVIEW_CHARS).collect();
+            if text.chars().count() > PREVIEW_CHARS {
+                preview.push('…');
+            }
+            i18n::tf("dialog.deep_link_type", &[("text", &preview)])
+        }
+        Link::Snippet { title, .. } => i18n::tf("dialog.deep_link_snippet", &[("name", title)]),
+    };
+    let app_handle = app_handle.clone();
+    dialog::ask(
+        None::<&tauri::Window>,
+        i18n::t("dialog.deep_link_title"),
+        message,
+        move |confirmed| {
+            if !confirmed {
+                return;
+            }
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = run(&app_handle, link).await {
+                    notify::deep_link_failed(&app_handle, &e);
+                }
+            });
+        },
+    );
+}
+
+async fn run(app_handle: &tauri::AppHandle, link: Link) -> Result<(), String> {
+    // 对话框打开期间可能已开始了其他输入
+    commands::check_idle(app_handle)?;
+    match link {
+        Link::Type {
+            text,
+            profile,
+            stand,
+            float,
+            instant,
+        } => {
+            let fixed_timing = stand.is_some() || float.is_some();
+            let (stand, float) = commands::resolve_timing(app_handle, stand, float);
+            let options = PasteOptions {
+                instant,
+                fixed_timing,
+                profile,
+                min_start_delay_secs: START_DELAY_SECS,
+                ..PasteOptions::default()
+            };
+            let units = text.encode_utf16().filter(|&unit| unit != 13).collect();
+            commands::paste_with(app_handle, Some(units), stand, float, options)
+                .await
+                .map_err(String::from)
+        }
+        Link::Snippet { id, .. } => {
+            snippets::type_snippet(id, None, None, app_handle.clone()).await
+        }
+    }
+}
+
+/// 在系统中登记 paster:// 链接由本程序打开（便携模式下不登记）。
+/// macOS 的链接需写入 Info.plist，且通过 Apple Event 而非命令行参数传入，暂不支持
+pub fn register() {
+    if config::is_portable() {
+        return;
+    }
+    if let Err(_e) = register_handler() {
+        #[cfg(debug_assertions)]
+        eprintln!("登记 paster:// 链接失败: {}", _e);
+    }
+}
+
+/// HKEY_CURRENT_USER\Software\Classes\paster，无需管理员权限
+#[cfg(windows)]
+fn register_handler() -> Result<(), String> {
+    use crate::registry;
+
+    const KEY: &str = r"Software\Classes\paster";
+    const COMMAND_KEY: &str = r"Software\Classes\paster\shell\open\command";
+
+    let command = format!("\"{}\" \"%1\"", crate::autostart::app_path()?);
+    registry::set_string(KEY, None, "URL:Paster")?;
+    registry::set_string(KEY, Some("URL Protocol"), "")?;
+    registry::set_string(COMMAND_KEY, None, &command)
+}
+
+/// $XDG_DATA_HOME/applications 中的 .desktop 文件声明 x-scheme-handler/paster，
+/// 并通过 xdg-mime 设为默认打开方式；内容未变化时不重复登记
+#[cfg(target_os = "linux")]
+fn register_handler() -> Result<(), String> {
+    use std::path::PathBuf;
+
+    const DESKTOP_FILE_NAME: &str = "paster-url-handler.desktop";
+
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+        .ok_or("HOME")?;
+    let file = data_home.join("applications").join(DESKTOP_FILE_NAME);
+    let entry = format!(
+        "[Desktop Entry]\n\
+         Type=Application\n\
+         Name=Paster\n\
+         Exec={} %u\n\
+         Terminal=false\n\
+         NoDisplay=true\n\
+         MimeType=x-scheme-handler/paster;\n",
+        crate::autostart::quote_exec_arg(&crate::autostart::app_path()?)
+    );
+    if std::fs::read_to_string(&file).is_ok_and(|existing| existing == entry) {
+        return Ok(());
+    }
+    if let Some(dir) = file.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(&file, entry).map_err(|e| e.to_string())?;
+    std::process::Command::new("xdg-mime")
+        .args(["default", DESKTOP_FILE_NAME, "x-scheme-handler/paster"])
+        .status()
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn register_handler() -> Result<(), String> {
+    Ok(())
+}
@@ -0,0 +1,329 @@
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+use crate::commands::{self, PasteOptions};
+use crate::config;
+use crate::hook;
+use crate::i18n;
+use crate::snippets::{self, Snippet, SnippetStore};
+use crate::templates;
+
+const EXPANDER_FILE: &str = "expander.json";
+
+/// 一条缩写：输入 abbreviation 后按空格、Tab 或回车，替换为 snippet_id 指向的片段或 text
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Abbreviation {
+    pub id: u64,
+    /// 缩写，如 ";addr"
+    pub abbreviation: String,
+    /// 展开为片段库中的片段（优先于 text）
+    #[serde(default)]
+    pub snippet_id: Option<u64>,
+    /// 展开的文本，支持模板变量
+    #[serde(default)]
+    pub text: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// 文本扩展设置，默认关闭
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ExpanderSettings {
+    pub enabled: bool,
+    /// 输入扩展内容时的字符间隔（与粘贴相同的 stand/float）
+    pub stand: u32,
+    pub float: u32,
+}
+
+impl Default for ExpanderSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            stand: 5,
+            float: 0,
+        }
+    }
+}
+
+/// 文本扩展的设置与缩写表，保存在 app_config_dir/expander.json
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExpanderState {
+    #[serde(default)]
+    settings: ExpanderSettings,
+    #[serde(default)]
+    abbreviations: Vec<Abbreviation>,
+    #[serde(default = "first_id")]
+    next_id: u64,
+}
+
+fn first_id() -> u64 {
+    1
+}
+
+impl ExpanderState {
+    /// 启动时读取缩写表
+    pub fn load(app_handle: &tauri::AppHandle) -> Self {
+        let mut state: Self = config::read_json(app_handle, EXPANDER_FILE).unwrap_or(Self {
+            settings: ExpanderSettings::default(),
+            abbreviations: Vec::new(),
+            next_id: 1,
+        });
+        let max_id = state.abbreviations.iter().map(|a| a.id).max().unwrap_or(0);
+        state.next_id = state.next_id.max(max_id + 1);
+        state
+    }
+
+    fn save(&self, app_handle: &tauri::AppHandle) -> Result<(), String> {
+        config::write_json(app_handle, EXPANDER_FILE, self)
+    }
+
+    /// 把启用的缩写交给键盘钩子；总开关关闭时停止监视输入
+    pub fn apply(&self, app_handle: &tauri::AppHandle) {
+        let abbreviations = self.settings.enabled.then(|| {
+            self.abbreviations
+                .iter()
+                .filter(|a| a.enabled)
+                .map(|a| a.abbreviation.clone())
+                .collect()
+        });
+        hook::set_expander_abbreviations(app_handle, abbreviations);
+    }
+}
+
+/// 钩子检测到缩写后调用：删除已输入的缩写，输入扩展内容与触发的分隔符
+#[cfg_attr(not(windows), allow(dead_code))]
+pub async fn expand(app_handle: &tauri::AppHandle, abbreviation: &str, delimiter: char) {
+    let (entry, settings) = {
+        let state = app_handle.state::<Mutex<ExpanderState>>();
+        let locked = state.lock().unwrap();
+        let entry = locked
+            .abbreviations
+            .iter()
+            .find(|a| a.enabled && a.abbreviation == abbreviation)
+            .cloned();
+        (entry, locked.settings)
+    };
+    let Some(entry) = entry else {
+        return;
+    };
+
+    #[cfg(debug_assertions)]
+    println!("展开缩写 #{}", entry.id);
+
+    let snippet = entry.snippet_id.and_then(|id| {
+        let state = app_handle.state::<Mutex<SnippetStore>>();
+        let locked = state.lock().unwrap();
+        locked.get(id).cloned()
+    });
+    // 片段组在这里选出本次输入的成员
+    let snippet = match snippet
+        .map(|snippet| snippets::resolve_group(app_handle, snippet))
+        .transpose()
+    {
+        Ok(snippet) => snippet,
+        Err(_e) => {
+            #[cfg(debug_assertions)]
+            eprintln!("展开缩写失败: {}", _e);
+
+            return;
+        }
+    };
+    // 片段已被删除或片段库已锁定，且没有备用文本时保留已输入的缩写
+    if snippet.is_none() && entry.text.is_empty() {
+        return;
+    }
+    let result = match &snippet {
+        Some(snippet) => snippets::snippet_job(app_handle, snippet).await,
+        None => templates::expand(&entry.text)
+            .map(|text| {
+                let units = text.encode_utf16().filter(|&unit| unit != 13).collect();
+                (units, Vec::new())
+            })
+            .map_err(String::from),
+    };
+    let (mut units, actions) = match result {
+        Ok(job) => {
+            if let Some(snippet) = &snippet {
+                snippets::record_use(app_handle, snippet.id);
+                // 片段组本身也记一次
+                match entry.snippet_id {
+                    Some(id) if id != snippet.id => snippets::record_use(app_handle, id),
+                    _ => {}
+                }
+            }
+            job
+        }
+        Err(_e) => {
+            #[cfg(debug_assertions)]
+            eprintln!("展开缩写失败: {}", _e);
+
+            return;
+        }
+    };
+    // 补上被钩子吞掉的分隔符
+    let mut buffer = [0u16; 2];
+    units.extend_from_slice(delimiter.encode_utf16(&mut buffer));
+
+    let (stand, float, instant) = match &snippet {
+        Some(snippet) => snippet.paste_timing(settings.stand, settings.float),
+        None => (settings.stand, settings.float, None),
+    };
+    let options = PasteOptions {
+        skip_history: snippet.as_ref().is_some_and(Snippet::skips_history),
+        skip_start_delay: true,
+        skip_end_action: true,
+        erase: abbreviation.chars().count(),
+        instant,
+        form: None,
+        actions,
+        // 文本扩展有自己的速度设置
+        fixed_timing: true,
+        ..PasteOptions::default()
+    };
+    if let Err(_e) = commands::paste_with(app_handle, Some(units), stand, float, options).await {
+        #[cfg(debug_assertions)]
+        eprintln!("输入扩展内容失败: {}", _e);
+    }
+}
+
+/// 列出全部缩写
+#[tauri::command]
+pub fn list_abbreviations(app_handle: tauri::AppHandle) -> Vec<Abbreviation> {
+    let state = app_handle.state::<Mutex<ExpanderState>>();
+    let locked = state.lock().unwrap();
+    locked.abbreviations.clone()
+}
+
+/// 新建（id 为 None）或更新缩写
+#[tauri::command]
+pub fn save_abbreviation(
+    id: Option<u64>,
+    abbreviation: String,
+    snippet_id: Option<u64>,
+    text: String,
+    enabled: bool,
+    app_handle: tauri::AppHandle,
+) -> Result<Abbreviation, String> {
+    let abbreviation = abbreviation.trim().to_string();
+    if abbreviation.is_empty() || abbreviation.chars().any(char::is_whitespace) {
+        return Err(i18n::t("error.abbreviation_invalid").to_string());
+    }
+    if snippet_id.is_none() && text.is_empty() {
+        return Err(i18n::t("error.abbreviation_target").to_string());
+    }
+
+    let state = app_handle.state::<Mutex<ExpanderState>>();
+    let mut locked = state.lock().unwrap();
+    if locked
+        .abbreviations
+        .iter()
+        .any(|a| a.abbreviation == abbreviation && Some(a.id) != id)
+    {
+        return Err(i18n::tf(
+            "error.abbreviation_duplicate",
+            &[("abbreviation", &abbreviation)],
+        ));
+    }
+    let entry = match id {
+        Some(id) => {
+            let existing = locked
+                .abbreviations
+                .iter_mut()
+                .find(|a| a.id == id)
+                .ok_or_else(|| i18n::tf("error.abbreviation_missing", &[("id", &id)]))?;
+            existing.abbreviation = abbreviation;
+            existing.snippet_id = snippet_id;
+            existing.text = text;
+            existing.enabled = enabled;
+            existing.clone()
+        }
+        None => {
+            let entry = Abbreviation {
+                id: locked.next_id,
+                abbreviation,
+                snippet_id,
+                text,
+                enabled,
+            };
+            locked.next_id += 1;
+            locked.abbreviations.push(entry.clone());
+            entry
+        }
+    };
+    locked.save(&app_handle)?;
+    locked.apply(&app_handle);
+    Ok(entry)
+}
+
+/// 删除缩写，返回是否找到
+#[tauri::command]
+pub fn delete_abbreviation(id: u64, app_handle: tauri::AppHandle) -> Result<bool, String> {
+    let state = app_handle.state::<Mutex<ExpanderState>>();
+    let mut locked = state.lock().unwrap();
+    let before = locked.abbreviations.len();
+    locked.abbreviations.retain(|a| a.id != id);
+    if locked.abbreviations.len() == before {
+        return Ok(false);
+    }
+    locked.save(&app_handle)?;
+    locked.apply(&app_handle);
+    Ok(true)
+}
+
+/// 修改文本扩展设置
+#[tauri::command]
+pub fn set_expander_settings(
+    settings: ExpanderSettings,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    let state = app_handle.state::<Mutex<ExpanderState>>();
+    let mut locked = state.lock().unwrap();
+    locked.settings = settings;
+    locked.save(&app_handle)?;
+    locked.apply(&app_handle);
+    Ok(())
+}
+
+/// 获取文本扩展设置
+#[tauri::command]
+pub fn get_expander_settings(app_handle: tauri::AppHandle) -> ExpanderSettings {
+    let state = app_handle.state::<Mutex<ExpanderState>>();
+    let locked = state.lock().unwrap();
+    locked.settings
+}
+
+/// 总开关：立即开启/关闭文本扩展（关闭后不再监视输入），返回新的状态
+pub fn set_enabled(app_handle: &tauri::AppHandle, enabled: bool) -> bool {
+    let state = app_handle.state::<Mutex<ExpanderState>>();
+    let mut locked = state.lock().unwrap();
+    locked.settings.enabled = enabled;
+    locked.apply(app_handle);
+    if let Err(_e) = locked.save(app_handle) {
+        #[cfg(debug_assertions)]
+        eprintln!("保存文本扩展设置失败: {}", _e);
+    }
+    let _ = app_handle.emit_all("expander-toggled", enabled);
+    enabled
+}
+
+/// 快捷键切换文本扩展总开关
+pub fn toggle(app_handle: &tauri::AppHandle) -> bool {
+    let enabled = {
+        let state = app_handle.state::<Mutex<ExpanderState>>();
+        let locked = state.lock().unwrap();
+        locked.settings.enabled
+    };
+    set_enabled(app_handle, !enabled)
+}
+
+/// 立即开启/关闭文本扩展
+#[tauri::command]
+pub fn set_expander_enabled(enabled: bool, app_handle: tauri::AppHandle) -> bool {
+    set_enabled(&app_handle, enabled)
+}
@@ -0,0 +1,96 @@
+use std::fs;
+
+use tauri::api::dialog::FileDialogBuilder;
+
+use crate::commands::{self, PasteOptions};
+use crate::i18n;
+
+/// 从文件输入时至少倒计时的秒数，留出切换到目标窗口的时间
+const FILE_START_DELAY_SECS: u32 = 3;
+/// 可输入的文件大小上限
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+
+/// 读取文本文件：支持 UTF-8（可带 BOM）与带 BOM 的 UTF-16
+fn read_text_file(path: &str) -> Result<String, String> {
+    let size = fs::metadata(path)
+        .map_err(|e| i18n::tf("error.read_file", &[("error", &e)]))?
+        .len();
+    if size > MAX_FILE_BYTES {
+        return Err(i18n::tf(
+            "error.file_too_large",
+            &[("kb", &(MAX_FILE_BYTES / 1024))],
+        ));
+    }
+    let bytes = fs::read(path).map_err(|e| i18n::tf("error.read_file", &[("error", &e)]))?;
+    let utf16 = |bytes: &[u8], decode: fn([u8; 2]) -> u16| {
+        let units: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|pair| decode([pair[0], pair[1]]))
+            .collect();
+        String::from_utf16(&units).map_err(|_| i18n::t("error.invalid_utf16").to_string())
+    };
+    match bytes.as_slice() {
+        [0xff, 0xfe, rest @ ..] => utf16(rest, u16::from_le_bytes),
+        [0xfe, 0xff, rest @ ..] => utf16(rest, u16::from_be_bytes),
+        [0xef, 0xbb, 0xbf, rest @ ..] => {
+            String::from_utf8(rest.to_vec()).map_err(|_| i18n::t("error.invalid_utf8").to_string())
+        }
+        _ => String::from_utf8(bytes).map_err(|_| i18n::t("error.invalid_utf8").to_string()),
+    }
+}
+
+/// 输入文本文件的内容，开始前至少倒计时 3 秒以便切换到目标窗口
+#[tauri::command]
+pub async fn type_file(
+    path: String,
+    stand: Option<u32>,
+    float: Option<u32>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    let (stand, float) = commands::resolve_timing(&app_handle, stand, float);
+    let text = read_text_file(&path)?;
+    if text.is_empty() {
+        return Err(i18n::t("error.file_empty").to_string());
+    }
+
+    #[cfg(debug_assertions)]
+    println!("从文件输入: {}，共 {} 个字符", path, text.chars().count());
+
+    let options = PasteOptions {
+        min_start_delay_secs: FILE_START_DELAY_SECS,
+        ..PasteOptions::default()
+    };
+    commands::paste_with(
+        &app_handle,
+        Some(text.encode_utf16().collect()),
+        stand,
+        float,
+        options,
+    )
+    .await
+    .map_err(str::to_string)
+}
+
+/// 打开文件选择框，选中文本文件后以默认速度输入（托盘中的 "从文件输入…"）
+pub fn pick_and_type(app_handle: &tauri::AppHandle) {
+    let app_handle = app_handle.clone();
+    FileDialogBuilder::new()
+        .set_title(i18n::t("dialog.type_file"))
+        .add_filter(
+            i18n::t("dialog.text_files"),
+            &["txt", "md", "csv", "json", "xml", "sql", "log"],
+        )
+        .add_filter(i18n::t("dialog.all_files"), &["*"])
+        .pick_file(move |path| {
+            let Some(path) = path else {
+                return;
+            };
+            tauri::async_runtime::spawn(async move {
+                let path = path.to_string_lossy().to_string();
+                if let Err(_e) = type_file(path, None, None, app_handle).await {
+                    #[cfg(debug_assertions)]
+                    eprintln!("从文件输入失败: {}", _e);
+                }
+            });
+        });
+}
@@ -0,0 +1,115 @@
+// 前台窗口与进程。其他系统上没有对应的实现：前台窗口为空句柄，进程名与窗口类名均为 None，
+// 依赖前台程序的功能（按程序切换方案、暂停快捷键等）不生效
+
+#[cfg(windows)]
+use std::path::Path;
+
+#[cfg(windows)]
+use windows::core::PWSTR;
+#[cfg(windows)]
+use windows::Win32::Foundation::HWND;
+#[cfg(windows)]
+use windows::Win32::{
+    Foundation::CloseHandle,
+    System::Threading::{
+        OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
+        PROCESS_QUERY_LIMITED_INFORMATION,
+    },
+    UI::WindowsAndMessaging::{GetClassNameW, GetForegroundWindow, GetWindowThreadProcessId},
+};
+
+/// 其他系统上的窗口句柄占位，布局与 Win32 的 HWND 相同
+#[cfg(not(windows))]
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HWND(pub isize);
+
+/// 当前前台窗口句柄
+#[cfg(windows)]
+pub fn foreground_window() -> HWND {
+    unsafe { GetForegroundWindow() }
+}
+
+#[cfg(not(windows))]
+pub fn foreground_window() -> HWND {
+    HWND(0)
+}
+
+/// 窗口所属进程 ID
+#[cfg(windows)]
+pub fn window_process_id(hwnd: HWND) -> u32 {
+    let mut pid = 0u32;
+    unsafe {
+        GetWindowThreadProcessId(hwnd, Some(&mut pid));
+    }
+    pid
+}
+
+#[cfg(not(windows))]
+pub fn window_process_id(_hwnd: HWND) -> u32 {
+    0
+}
+
+/// 进程的可执行文件名（如 "notepad.exe"）
+#[cfg(windows)]
+pub fn process_name(pid: u32) -> Option<String> {
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+        let mut buffer = [0u16; 260];
+        let mut size = buffer.len() as u32;
+        let result = QueryFullProcessImageNameW(
+            handle,
+            PROCESS_NAME_WIN32,
+            PWSTR(buffer.as_mut_ptr()),
+            &mut size,
+        );
+        let _ = CloseHandle(handle);
+        result.ok()?;
+
+        let path = String::from_utf16_lossy(&buffer[..size as usize]);
+        Path::new(&path)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+    }
+}
+
+#[cfg(not(windows))]
+pub fn process_name(_pid: u32) -> Option<String> {
+    None
+}
+
+/// 前台窗口所属进程的可执行文件名
+pub fn foreground_process_name() -> Option<String> {
+    let hwnd = foreground_window();
+    if hwnd.0 == 0 {
+        return None;
+    }
+    process_name(window_process_id(hwnd))
+}
+
+/// 窗口类名（如远程桌面的 "TscShellContainerClass"）
+#[cfg(windows)]
+pub fn window_class(hwnd: HWND) -> Option<String> {
+    let mut buffer = [0u16; 256];
+    let len = unsafe { GetClassNameW(hwnd, &mut buffer) };
+    if len <= 0 {
+        return None;
+    }
+    Some(String::from_utf16_lossy(&buffer[..len as usize]))
+}
+
+#[cfg(not(windows))]
+pub fn window_class(_hwnd: HWND) -> Option<String> {
+    None
+}
+
+/// 前台窗口是否属于 Paster 自身
+#[cfg(windows)]
+pub fn is_own_window_foreground() -> bool {
+    window_process_id(foreground_window()) == std::process::id()
+}
+
+/// 进程名是否在列表中（忽略大小写）
+pub fn matches_process(list: &[String], name: &str) -> bool {
+    list.iter().any(|item| item.eq_ignore_ascii_case(name))
+}
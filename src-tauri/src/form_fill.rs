@@ -0,0 +1,129 @@
+use serde::{Deserialize, Serialize};
+
+use crate::commands::{
+    self, EndAction, FormKeys, PasteOptions, FORM_FIELD_MARKER, FORM_ROW_MARKER,
+};
+use crate::i18n;
+use crate::snippet_io;
+
+/// 剪贴板中表格数据的分隔方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FormDelimiter {
+    /// 第一行含制表符时按制表符分隔（从 Excel 等表格复制），否则按逗号分隔
+    #[default]
+    Auto,
+    Tab,
+    Comma,
+    Semicolon,
+}
+
+impl FormDelimiter {
+    fn resolve(self, text: &str) -> char {
+        match self {
+            FormDelimiter::Auto if text.lines().next().is_some_and(|line| line.contains('\t')) => {
+                '\t'
+            }
+            FormDelimiter::Auto | FormDelimiter::Comma => ',',
+            FormDelimiter::Tab => '\t',
+            FormDelimiter::Semicolon => ';',
+        }
+    }
+}
+
+/// 表单填充选项
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct FormFillOptions {
+    #[serde(default)]
+    pub delimiter: FormDelimiter,
+    /// 每个字段之后按下的按键（最后一个字段除外）
+    #[serde(default = "default_field_key")]
+    pub field_key: EndAction,
+    /// 每行结束时按下的按键
+    #[serde(default = "default_row_key")]
+    pub row_key: EndAction,
+    /// 每个字段之后额外等待的毫秒数，给老旧程序切换焦点留出时间
+    #[serde(default)]
+    pub field_delay_ms: u32,
+    /// 跳过第一行（表头）
+    #[serde(default)]
+    pub skip_header: bool,
+}
+
+fn default_field_key() -> EndAction {
+    EndAction::Tab
+}
+
+fn default_row_key() -> EndAction {
+    EndAction::Enter
+}
+
+/// 把剪贴板文本解析为行与字段
+fn parse_rows(text: &str, options: &FormFillOptions) -> Vec<Vec<String>> {
+    let mut rows = snippet_io::parse_delimited(text, options.delimiter.resolve(text));
+    if options.skip_header && !rows.is_empty() {
+        rows.remove(0);
+    }
+    rows
+}
+
+/// 生成要输入的 UTF-16 文本：字段之间插入字段标记，每行末尾插入行标记
+fn form_units(rows: &[Vec<String>]) -> Vec<u16> {
+    let mut units = Vec::new();
+    for row in rows {
+        for (i, field) in row.iter().enumerate() {
+            if i > 0 {
+                units.push(FORM_FIELD_MARKER);
+            }
+            units.extend(field.encode_utf16().filter(|&unit| unit != 13));
+        }
+        units.push(FORM_ROW_MARKER);
+    }
+    units
+}
+
+/// 预览剪贴板按当前选项解析出的行与字段
+#[tauri::command]
+pub fn preview_form_rows(options: FormFillOptions) -> Result<Vec<Vec<String>>, &'static str> {
+    let text = String::from_utf16_lossy(&commands::get_clipboard()?);
+    Ok(parse_rows(&text, &options))
+}
+
+/// 表单填充：把剪贴板中的表格数据逐行输入到表单中，
+/// 每个字段后按 field_key（默认 Tab），每行末尾按 row_key（默认回车）
+#[tauri::command]
+pub async fn fill_form(
+    options: FormFillOptions,
+    stand: Option<u32>,
+    float: Option<u32>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), &'static str> {
+    let (stand, float) = commands::resolve_timing(&app_handle, stand, float);
+    let text = String::from_utf16_lossy(&commands::get_clipboard()?);
+    let rows = parse_rows(&text, &options);
+    if rows.is_empty() {
+        return Err(i18n::t("error.form_no_rows"));
+    }
+
+    #[cfg(debug_assertions)]
+    println!("fill_form函数被调用：{} 行, {:?}", rows.len(), options);
+
+    let paste_options = PasteOptions {
+        skip_history: true,
+        skip_end_action: true,
+        form: Some(FormKeys {
+            field: options.field_key,
+            row: options.row_key,
+            field_delay_ms: options.field_delay_ms,
+        }),
+        ..PasteOptions::default()
+    };
+    commands::paste_with(
+        &app_handle,
+        Some(form_units(&rows)),
+        stand,
+        float,
+        paste_options,
+    )
+    .await
+}
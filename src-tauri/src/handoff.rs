@@ -0,0 +1,274 @@
+use std::net::{IpAddr, Ipv4Addr, UdpSocket};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use qrcode::render::svg;
+use qrcode::QrCode;
+use serde::Serialize;
+use serde_json::json;
+use tauri::Manager;
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::api::{self, Request, Response};
+use crate::commands::{self, PasteOptions};
+use crate::i18n;
+
+/// 二维码在多长时间内未被扫描则停止监听
+const PAIRING_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+/// 收到文本后开始输入前的倒计时，留出在电脑上切换到目标窗口的时间
+const START_DELAY_SECS: u32 = 3;
+/// 二维码图片的最小边长（像素）
+const QR_SIZE: u32 = 240;
+/// 手机上打开的页面，__PASTER__ 替换为令牌与当前语言的文字
+const PAGE: &str = include_str!("handoff.html");
+
+/// 正在进行的传输；同一时间只有一个，重新配对时替换
+static HANDOFF: Mutex<Option<Handoff>> = Mutex::new(None);
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+struct Handoff {
+    id: u64,
+    /// 二维码中的一次性密钥，手机打开页面后作废
+    pairing_key: Option<String>,
+    /// 配对后手机发送文本时在 Authorization: Bearer 中携带的令牌
+    session_token: String,
+    task: tauri::async_runtime::JoinHandle<()>,
+}
+
+/// 配对信息：在电脑上显示二维码，用手机扫描后在浏览器中打开 url
+#[derive(Debug, Clone, Serialize)]
+pub struct HandoffPairing {
+    pub url: String,
+    /// 二维码图片（SVG）
+    pub qr_svg: String,
+    /// 二维码的有效时间
+    pub expires_in_secs: u64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct HandoffStatus {
+    /// 正在监听（等待配对或已配对）
+    pub active: bool,
+    /// 手机已打开页面
+    pub paired: bool,
+}
+
+/// 本机在局域网中的地址：连接外部地址（UDP 不会实际发送数据）时系统选用的地址
+fn local_address() -> Option<IpAddr> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).ok()?;
+    socket.connect((Ipv4Addr::new(8, 8, 8, 8), 53)).ok()?;
+    let address = socket.local_addr().ok()?.ip();
+    (!address.is_loopback() && !address.is_unspecified()).then_some(address)
+}
+
+fn status() -> HandoffStatus {
+    let handoff = HANDOFF.lock().unwrap();
+    HandoffStatus {
+        active: handoff.is_some(),
+        paired: handoff
+            .as_ref()
+            .is_some_and(|handoff| handoff.pairing_key.is_none()),
+    }
+}
+
+fn emit_status(app_handle: &tauri::AppHandle) {
+    let _ = app_handle.emit_all("handoff-changed", status());
+}
+
+/// 停止监听，返回之前是否在监听
+fn stop() -> bool {
+    let handoff = HANDOFF.lock().unwrap().take();
+    if let Some(handoff) = &handoff {
+        handoff.task.abort();
+    }
+    handoff.is_some()
+}
+
+/// 第 id 次配对超时仍未完成时停止监听
+fn expire(id: u64) -> bool {
+    let mut handoff = HANDOFF.lock().unwrap();
+    match handoff.as_ref() {
+        Some(current) if current.id == id && current.pairing_key.is_some() => {
+            current.task.abort();
+            *handoff = None;
+            true
+        }
+        _ => false,
+    }
+}
+
+async fn serve(app_handle: tauri::AppHandle, listener: TcpListener) {
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(_e) => {
+                #[cfg(debug_assertions)]
+                eprintln!("接受手机连接失败: {}", _e);
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                continue;
+            }
+        };
+        let app_handle = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(_e) = handle_connection(&app_handle, stream).await {
+                #[cfg(debug_assertions)]
+                eprintln!("处理手机请求失败: {}", _e);
+            }
+        });
+    }
+}
+
+async fn handle_connection(
+    app_handle: &tauri::AppHandle,
+    mut stream: TcpStream,
+) -> std::io::Result<()> {
+    let response =
+        match tokio::time::timeout(api::REQUEST_TIMEOUT, api::read_request(&mut stream)).await {
+            Ok(Ok(request)) => route(app_handle, &request),
+            Ok(Err(response)) => response,
+            Err(_) => Response::error(408, "Request Timeout"),
+        };
+    api::write_response(&mut stream, &response).await
+}
+
+fn route(app_handle: &tauri::AppHandle, request: &Request) -> Response {
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/") => page(app_handle, request),
+        ("POST", "/send") => send(app_handle, request),
+        (_, "/" | "/send") => Response::error(405, "Method Not Allowed"),
+        _ => Response::error(404, "Not Found"),
+    }
+}
+
+/// 手机打开的页面；携带正确的一次性密钥时完成配对，把令牌交给页面
+fn page(app_handle: &tauri::AppHandle, request: &Request) -> Response {
+    let token = {
+        let mut handoff = HANDOFF.lock().unwrap();
+        handoff.as_mut().and_then(|handoff| {
+            let given = request.query.get("key")?;
+            let key = handoff.pairing_key.as_deref()?;
+            if !api::tokens_match(given, key) {
+                return None;
+            }
+            handoff.pairing_key = None;
+            Some(handoff.session_token.clone())
+        })
+    };
+    if token.is_some() {
+        #[cfg(debug_assertions)]
+        println!("手机已配对");
+        emit_status(app_handle);
+    }
+
+    let data = json!({
+        "token": token.unwrap_or_default(),
+        "placeholder": i18n::t("handoff.placeholder"),
+        "send": i18n::t("handoff.send"),
+        "sent": i18n::t("handoff.sent"),
+        "unpaired": i18n::t("handoff.unpaired"),
+    });
+    Response::html(PAGE.replace("__PASTER__", &data.to_string()))
+}
+
+/// 手机发来的文本：倒计时后在电脑当前的窗口中输入，不等待输入结束
+fn send(app_handle: &tauri::AppHandle, request: &Request) -> Response {
+    let authorized = {
+        let handoff = HANDOFF.lock().unwrap();
+        handoff.as_ref().is_some_and(|handoff| {
+            handoff.pairing_key.is_none()
+                && api::bearer(request)
+                    .is_some_and(|given| api::tokens_match(given, &handoff.session_token))
+        })
+    };
+    if !authorized {
+        return Response::error(401, i18n::t("handoff.unpaired"));
+    }
+    let text = match String::from_utf8(request.body.clone()) {
+        Ok(text) if !text.is_empty() => text,
+        _ => return Response::error(400, "Bad Request"),
+    };
+    if let Err(e) = commands::check_idle(app_handle) {
+        return Response::error(409, e);
+    }
+
+    let (stand, float) = commands::resolve_timing(app_handle, None, None);
+    let options = PasteOptions {
+        min_start_delay_secs: START_DELAY_SECS,
+        ..PasteOptions::default()
+    };
+    let units = text.encode_utf16().filter(|&unit| unit != 13).collect();
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(_e) = commands::paste_with(&app_handle, Some(units), stand, float, options).await
+        {
+            #[cfg(debug_assertions)]
+            println!("输入手机发来的文本失败: {}", _e);
+        }
+    });
+    Response::new(202, json!({ "accepted": true }))
+}
+
+/// 开始配对：在局域网地址上监听，返回包含一次性密钥与地址的二维码。
+/// 文本以明文 HTTP 在局域网中传输，仅在可信的网络中使用
+#[tauri::command]
+pub async fn start_handoff(app_handle: tauri::AppHandle) -> Result<HandoffPairing, String> {
+    stop();
+    let address = local_address().ok_or(i18n::t("error.handoff_no_address"))?;
+    let listener = TcpListener::bind((address, 0))
+        .await
+        .map_err(|e| i18n::tf("error.handoff_bind", &[("error", &e)]))?;
+    let port = listener
+        .local_addr()
+        .map_err(|e| i18n::tf("error.handoff_bind", &[("error", &e)]))?
+        .port();
+
+    let key = api::generate_token();
+    let url = format!("http://{}:{}/?key={}", address, port, key);
+    let qr_svg = QrCode::new(url.as_bytes())
+        .map_err(|e| e.to_string())?
+        .render::<svg::Color>()
+        .min_dimensions(QR_SIZE, QR_SIZE)
+        .build();
+
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    let task = tauri::async_runtime::spawn(serve(app_handle.clone(), listener));
+    *HANDOFF.lock().unwrap() = Some(Handoff {
+        id,
+        pairing_key: Some(key),
+        session_token: api::generate_token(),
+        task,
+    });
+
+    #[cfg(debug_assertions)]
+    println!("等待手机配对: {}", url);
+
+    // 超时仍未配对则停止监听
+    let expiring = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(PAIRING_TIMEOUT).await;
+        if expire(id) {
+            emit_status(&expiring);
+        }
+    });
+
+    emit_status(&app_handle);
+    Ok(HandoffPairing {
+        url,
+        qr_svg,
+        expires_in_secs: PAIRING_TIMEOUT.as_secs(),
+    })
+}
+
+/// 停止监听，已配对的手机无法再发送文本
+#[tauri::command]
+pub fn stop_handoff(app_handle: tauri::AppHandle) {
+    if stop() {
+        emit_status(&app_handle);
+    }
+}
+
+#[tauri::command]
+pub fn get_handoff_status() -> HandoffStatus {
+    status()
+}
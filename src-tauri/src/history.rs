@@ -0,0 +1,185 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+use crate::commands;
+use crate::i18n;
+use crate::tray;
+use crate::vault;
+
+pub const HISTORY_FILE: &str = "history.json";
+/// 最多保留的条数上限
+const MAX_ITEMS_LIMIT: usize = 200;
+/// 超过该长度（UTF-16 单元）的文本不记入历史，避免历史文件过大
+const MAX_ENTRY_UNITS: usize = 100_000;
+
+/// 一条输入历史
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// 实际输入的文本（模板变量等已展开）
+    pub text: String,
+    /// 输入时间（Unix 毫秒时间戳）
+    pub typed_at: u64,
+}
+
+/// 历史记录设置，默认关闭
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct HistorySettings {
+    pub enabled: bool,
+    pub max_items: usize,
+}
+
+impl Default for HistorySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_items: 20,
+        }
+    }
+}
+
+/// 最近输入过的文本，保存在 app_config_dir/history.json（开启静态加密时为 history.vault），最新的在最前
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HistoryState {
+    #[serde(default)]
+    settings: HistorySettings,
+    #[serde(default)]
+    entries: VecDeque<HistoryEntry>,
+}
+
+impl HistoryState {
+    /// 启动时读取历史记录
+    pub fn load(app_handle: &tauri::AppHandle) -> Self {
+        let mut state: Self = vault::read_json(app_handle, HISTORY_FILE).unwrap_or_default();
+        if !state.settings.enabled {
+            state.entries.clear();
+        }
+        state.entries.truncate(state.settings.max_items);
+        state
+    }
+
+    pub fn save(&self, app_handle: &tauri::AppHandle) -> Result<(), String> {
+        vault::write_json(app_handle, HISTORY_FILE, self)?;
+        tray::refresh(app_handle);
+        Ok(())
+    }
+
+    /// 历史记录，最新的在最前
+    pub fn entries(&self) -> impl Iterator<Item = &HistoryEntry> {
+        self.entries.iter()
+    }
+}
+
+/// 当前 Unix 毫秒时间戳
+pub fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// 开启历史记录时记下一次输入的文本；相同文本只保留最新一条
+pub fn record(app_handle: &tauri::AppHandle, units: &[u16]) {
+    if units.is_empty() || units.len() > MAX_ENTRY_UNITS {
+        return;
+    }
+    let state = app_handle.state::<Mutex<HistoryState>>();
+    let mut locked = state.lock().unwrap();
+    if !locked.settings.enabled {
+        return;
+    }
+
+    let text = String::from_utf16_lossy(units);
+    locked.entries.retain(|entry| entry.text != text);
+    locked.entries.push_front(HistoryEntry {
+        text,
+        typed_at: now_ms(),
+    });
+    let max_items = locked.settings.max_items;
+    locked.entries.truncate(max_items);
+
+    if let Err(_e) = locked.save(app_handle) {
+        #[cfg(debug_assertions)]
+        eprintln!("保存输入历史失败: {}", _e);
+    }
+}
+
+/// 获取输入历史，最新的在最前
+#[tauri::command]
+pub fn get_paste_history(app_handle: tauri::AppHandle) -> Vec<HistoryEntry> {
+    let state = app_handle.state::<Mutex<HistoryState>>();
+    let locked = state.lock().unwrap();
+    locked.entries.iter().cloned().collect()
+}
+
+/// 重新输入第 index 条历史（0 为最新）
+#[tauri::command]
+pub async fn retype_history_item(
+    index: usize,
+    stand: Option<u32>,
+    float: Option<u32>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), &'static str> {
+    let (stand, float) = commands::resolve_timing(&app_handle, stand, float);
+    let units: Vec<u16> = {
+        let state = app_handle.state::<Mutex<HistoryState>>();
+        let locked = state.lock().unwrap();
+        let entry = locked
+            .entries
+            .get(index)
+            .ok_or(i18n::t("error.history_missing"))?;
+        entry.text.encode_utf16().collect()
+    };
+
+    #[cfg(debug_assertions)]
+    println!(
+        "retype_history_item函数被调用：index={}, stand={}, float={}",
+        index, stand, float
+    );
+
+    commands::paste_text(&app_handle, Some(units), stand, float).await
+}
+
+/// 清空输入历史
+#[tauri::command]
+pub fn clear_paste_history(app_handle: tauri::AppHandle) -> Result<(), String> {
+    let state = app_handle.state::<Mutex<HistoryState>>();
+    let mut locked = state.lock().unwrap();
+    locked.entries.clear();
+    locked.save(&app_handle)
+}
+
+/// 设置是否记录输入历史及保留条数；关闭时同时清空已有历史
+#[tauri::command]
+pub fn set_history_settings(
+    enabled: bool,
+    max_items: usize,
+    app_handle: tauri::AppHandle,
+) -> Result<HistorySettings, String> {
+    if max_items == 0 || max_items > MAX_ITEMS_LIMIT {
+        return Err(i18n::tf(
+            "error.history_limit",
+            &[("max", &MAX_ITEMS_LIMIT)],
+        ));
+    }
+    let state = app_handle.state::<Mutex<HistoryState>>();
+    let mut locked = state.lock().unwrap();
+    locked.settings = HistorySettings { enabled, max_items };
+    if !enabled {
+        locked.entries.clear();
+    }
+    locked.entries.truncate(max_items);
+    locked.save(&app_handle)?;
+    Ok(locked.settings)
+}
+
+/// 获取输入历史设置
+#[tauri::command]
+pub fn get_history_settings(app_handle: tauri::AppHandle) -> HistorySettings {
+    let state = app_handle.state::<Mutex<HistoryState>>();
+    let locked = state.lock().unwrap();
+    locked.settings
+}
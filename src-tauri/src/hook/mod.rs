@@ -0,0 +1,232 @@
+// 快捷键的解析（加速器字符串、修饰键、虚拟键码），以及由低级键盘鼠标钩子识别的快捷键。
+// 钩子只有 Windows 实现；其他系统上这些快捷键不生效，录制快捷键会报错
+
+#[cfg(not(windows))]
+use std::time::Duration;
+
+#[cfg(not(windows))]
+use crate::hotkeys::{HotkeyAction, HotkeyTrigger};
+#[cfg(not(windows))]
+use crate::i18n;
+
+#[cfg(windows)]
+mod win32;
+
+#[cfg(windows)]
+pub use win32::{
+    capture_next_combo, set_ctrl_v_intercept, set_expander_abbreviations, set_foreground_watch,
+    set_key_bindings, set_trigger_bindings, set_user_input_watch,
+};
+
+/// 修饰键族及其左右虚拟键码，顺序与 KeyCombo::modifiers 一致
+const MODIFIER_FAMILIES: [(i32, i32); 4] = [
+    (0xA2, 0xA3), // Control
+    (0xA4, 0xA5), // Alt
+    (0xA0, 0xA1), // Shift
+    (0x5B, 0x5C), // Super (Win)
+];
+
+/// 对修饰键左右的要求
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Side {
+    Either,
+    Left,
+    Right,
+}
+
+/// 低级钩子匹配用的组合键：每个修饰键族的要求（None 表示不能按下）+ 主键虚拟键码
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyCombo {
+    modifiers: [Option<Side>; 4],
+    vk: u32,
+}
+
+impl KeyCombo {
+    /// 解析加速器字符串，支持 "ControlLeft"、"AltRight" 等区分左右的修饰键
+    pub fn parse(accelerator: &str) -> Option<Self> {
+        let tokens: Vec<&str> = accelerator.split('+').map(str::trim).collect();
+        let (key, modifiers) = tokens.split_last()?;
+
+        Some(KeyCombo {
+            modifiers: parse_modifiers(modifiers)?,
+            vk: key_name_to_vk(key)?,
+        })
+    }
+
+    /// 是否包含修饰键
+    pub fn has_modifiers(&self) -> bool {
+        self.modifiers.iter().any(Option::is_some)
+    }
+
+    /// 主键是否可以不配合修饰键单独使用
+    pub fn is_standalone(&self) -> bool {
+        vk_to_key_name(self.vk).is_some_and(|key| is_standalone_key(&key))
+    }
+}
+
+/// 解析一组修饰键名称为各修饰键族的要求
+fn parse_modifiers<S: AsRef<str>>(tokens: &[S]) -> Option<[Option<Side>; 4]> {
+    let mut modifiers = [None; 4];
+    for token in tokens {
+        let (family, side) = parse_modifier(token.as_ref().trim())?;
+        modifiers[family] = Some(side);
+    }
+    Some(modifiers)
+}
+
+/// 解析单个修饰键：返回 (修饰键族下标, 左右要求)
+pub(crate) fn parse_modifier(token: &str) -> Option<(usize, Side)> {
+    let modifier = match token.to_uppercase().as_str() {
+        "CONTROL" | "CTRL" => (0, Side::Either),
+        "CONTROLLEFT" => (0, Side::Left),
+        "CONTROLRIGHT" => (0, Side::Right),
+        "ALT" | "OPTION" => (1, Side::Either),
+        "ALTLEFT" => (1, Side::Left),
+        "ALTRIGHT" => (1, Side::Right),
+        "SHIFT" => (2, Side::Either),
+        "SHIFTLEFT" => (2, Side::Left),
+        "SHIFTRIGHT" => (2, Side::Right),
+        "SUPER" | "COMMAND" | "CMD" => (3, Side::Either),
+        "SUPERLEFT" => (3, Side::Left),
+        "SUPERRIGHT" => (3, Side::Right),
+        _ => return None,
+    };
+    Some(modifier)
+}
+
+/// 把 "Tab"、"Control+Enter" 等按键描述解析为要依次按下的虚拟键码（修饰键在前），供宏发送按键
+pub fn parse_key_stroke(accelerator: &str) -> Option<Vec<u16>> {
+    let tokens: Vec<&str> = accelerator.split('+').map(str::trim).collect();
+    let (key, modifiers) = tokens.split_last()?;
+    let mut vks = Vec::new();
+    for token in modifiers {
+        let (family, side) = parse_modifier(token)?;
+        let (left, right) = MODIFIER_FAMILIES[family];
+        let vk = match (family, side) {
+            (_, Side::Right) => right,
+            // Win 键没有不分左右的虚拟键码
+            (3, Side::Either) | (_, Side::Left) => left,
+            // 不分左右的 Control / Alt / Shift
+            (0, Side::Either) => 0x11,
+            (1, Side::Either) => 0x12,
+            _ => 0x10,
+        };
+        vks.push(vk as u16);
+    }
+    vks.push(key_name_to_vk(key)? as u16);
+    Some(vks)
+}
+
+/// Tauri 加速器无法解析、只能由低级钩子识别的主键
+const HOOK_ONLY_KEYS: [&str; 1] = ["MediaStop"];
+
+/// 加速器是否用到了区分左右的修饰键或 Tauri 不认识的主键，需要走低级钩子而非 Tauri 全局快捷键
+pub fn requires_hook(accelerator: &str) -> bool {
+    let side_specific = accelerator
+        .split('+')
+        .filter_map(|token| parse_modifier(token.trim()))
+        .any(|(_, side)| side != Side::Either);
+    let hook_only_key = accelerator
+        .rsplit('+')
+        .next()
+        .is_some_and(|key| HOOK_ONLY_KEYS.iter().any(|k| k.eq_ignore_ascii_case(key.trim())));
+    side_specific || hook_only_key
+}
+
+/// 可以不配合修饰键单独使用的主键：F13~F24 与多媒体键（通常是专门留给此类工具的空闲按键）
+pub fn is_standalone_key(key: &str) -> bool {
+    match key_name_to_vk(key.trim()) {
+        Some(vk) => (0x7C..=0x87).contains(&vk) || (0xAD..=0xB4).contains(&vk),
+        None => false,
+    }
+}
+
+/// 按键名称 -> 虚拟键码（vk_to_key_name 的逆映射，忽略大小写）
+fn key_name_to_vk(name: &str) -> Option<u32> {
+    (0u32..=0xFE).find(|vk| vk_to_key_name(*vk).is_some_and(|n| n.eq_ignore_ascii_case(name)))
+}
+
+/// 虚拟键码 -> 加速器中的按键名称（与 Tauri 的解析规则一致）
+pub fn vk_to_key_name(vk: u32) -> Option<String> {
+    let name = match vk {
+        0x41..=0x5A | 0x30..=0x39 => char::from_u32(vk)?.to_string(),
+        0x60..=0x69 => format!("Num{}", vk - 0x60),
+        0x70..=0x87 => format!("F{}", vk - 0x70 + 1),
+        0x08 => "Backspace".to_string(),
+        0x09 => "Tab".to_string(),
+        0x0D => "Enter".to_string(),
+        0x13 => "Pause".to_string(),
+        0x14 => "CapsLock".to_string(),
+        0x1B => "Escape".to_string(),
+        0x20 => "Space".to_string(),
+        0x21 => "PageUp".to_string(),
+        0x22 => "PageDown".to_string(),
+        0x23 => "End".to_string(),
+        0x24 => "Home".to_string(),
+        0x25 => "Left".to_string(),
+        0x26 => "Up".to_string(),
+        0x27 => "Right".to_string(),
+        0x28 => "Down".to_string(),
+        0x2C => "PrintScreen".to_string(),
+        0x2D => "Insert".to_string(),
+        0x2E => "Delete".to_string(),
+        0x6B => "NumAdd".to_string(),
+        0x6D => "NumSubstract".to_string(),
+        0x6F => "NumDivide".to_string(),
+        0x90 => "NumLock".to_string(),
+        0x91 => "ScrollLock".to_string(),
+        0xAD => "VolumeMute".to_string(),
+        0xAE => "VolumeDown".to_string(),
+        0xAF => "VolumeUp".to_string(),
+        0xB0 => "MediaNextTrack".to_string(),
+        0xB1 => "MediaPreviousTrack".to_string(),
+        0xB2 => "MediaStop".to_string(),
+        0xB3 => "MediaPlayPause".to_string(),
+        0xB4 => "LaunchMail".to_string(),
+        0xBA => "Semicolon".to_string(),
+        0xBB => "=".to_string(),
+        0xBC => "Comma".to_string(),
+        0xBD => "-".to_string(),
+        0xBE => "Period".to_string(),
+        0xBF => "Slash".to_string(),
+        0xC0 => "Backquote".to_string(),
+        0xDB => "BracketLeft".to_string(),
+        0xDC => "Backslash".to_string(),
+        0xDD => "BracketRight".to_string(),
+        0xDE => "Quote".to_string(),
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// 录制快捷键需要低级键盘钩子
+#[cfg(not(windows))]
+pub fn capture_next_combo(_timeout: Duration) -> Result<String, String> {
+    Err(i18n::t("error.capture_unsupported").to_string())
+}
+
+#[cfg(not(windows))]
+pub fn set_key_bindings(_app_handle: &tauri::AppHandle, _bindings: Vec<(KeyCombo, HotkeyAction)>) {}
+
+#[cfg(not(windows))]
+pub fn set_ctrl_v_intercept(_app_handle: &tauri::AppHandle, _exclude: Option<Vec<String>>) {}
+
+#[cfg(not(windows))]
+pub fn set_user_input_watch(_app_handle: &tauri::AppHandle, _enabled: bool) {}
+
+#[cfg(not(windows))]
+pub fn set_foreground_watch(_app_handle: &tauri::AppHandle, _enabled: bool) {}
+
+#[cfg(not(windows))]
+pub fn set_expander_abbreviations(
+    _app_handle: &tauri::AppHandle,
+    _abbreviations: Option<Vec<String>>,
+) {
+}
+
+#[cfg(not(windows))]
+pub fn set_trigger_bindings(
+    _app_handle: &tauri::AppHandle,
+    _bindings: Vec<(HotkeyTrigger, HotkeyAction)>,
+) {
+}
@@ -0,0 +1,987 @@
+// Windows 的低级键盘鼠标钩子：录制快捷键、识别 Tauri 无法注册的快捷键与特殊触发方式、
+// 劫持 Ctrl+V、文本扩展，以及粘贴期间监视用户操作与前台窗口切换
+
+use std::cell::{Cell, RefCell};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Mutex, Once, OnceLock};
+use std::thread;
+use std::time::Duration;
+
+use windows::Win32::{
+    Foundation::{HINSTANCE, HMODULE, HWND, LPARAM, LRESULT, WPARAM},
+    System::{LibraryLoader::GetModuleHandleW, Threading::GetCurrentThreadId},
+    UI::{
+        Accessibility::{SetWinEventHook, UnhookWinEvent, HWINEVENTHOOK},
+        Input::KeyboardAndMouse::{GetAsyncKeyState, GetKeyState, GetKeyboardLayout, ToUnicodeEx},
+        WindowsAndMessaging::{
+            CallNextHookEx, GetMessageW, GetWindowThreadProcessId, PeekMessageW, PostQuitMessage,
+            PostThreadMessageW, SetWindowsHookExW, UnhookWindowsHookEx, EVENT_SYSTEM_FOREGROUND,
+            HHOOK, KBDLLHOOKSTRUCT, LLKHF_INJECTED, LLMHF_INJECTED, MSG, MSLLHOOKSTRUCT,
+            PM_NOREMOVE, WH_KEYBOARD_LL, WH_MOUSE_LL, WINEVENT_OUTOFCONTEXT, WM_KEYDOWN, WM_KEYUP,
+            WM_LBUTTONDOWN, WM_MBUTTONDOWN, WM_MBUTTONUP, WM_MOUSEWHEEL, WM_QUIT, WM_RBUTTONDOWN,
+            WM_SYSKEYDOWN, WM_SYSKEYUP, WM_USER, WM_XBUTTONDOWN, WM_XBUTTONUP,
+        },
+    },
+};
+
+use super::{
+    is_standalone_key, parse_modifier, parse_modifiers, vk_to_key_name, KeyCombo, Side,
+    MODIFIER_FAMILIES,
+};
+use crate::commands::{self, PasteState, PASTER_INPUT_MARKER};
+use crate::foreground;
+use crate::hotkeys::{self, HotkeyAction, HotkeyTrigger, MouseButton};
+use crate::i18n;
+use tauri::Manager;
+
+thread_local! {
+    /// 录制线程上用于回传录制结果的通道
+    static CAPTURE_SENDER: RefCell<Option<Sender<String>>> = const { RefCell::new(None) };
+    /// 钩子线程上最近一次触发快捷键的主键，用于忽略按住不放时的自动重复
+    static TRIGGERED_VK: Cell<Option<u32>> = const { Cell::new(None) };
+    /// 当前按下、且期间没有按过其他键的修饰键
+    static CLEAN_MODIFIER: Cell<Option<u32>> = const { Cell::new(None) };
+    /// 上一次单独轻按的修饰键及其时间戳（毫秒）
+    static LAST_TAP: Cell<Option<(u32, u32)>> = const { Cell::new(None) };
+    /// 纯修饰键组合：当前按下的修饰键位掩码、本轮按下过的全部修饰键、本轮是否已失效
+    static CHORD_DOWN: Cell<u8> = const { Cell::new(0) };
+    static CHORD_PEAK: Cell<u8> = const { Cell::new(0) };
+    static CHORD_DIRTY: Cell<bool> = const { Cell::new(false) };
+    /// 已按下、正在等待第二个键的引导键及其时间戳（毫秒）
+    static PENDING_LEADER: RefCell<Option<(String, u32)>> = const { RefCell::new(None) };
+    /// 正在按住的长按绑定键
+    static HELD_KEY: Cell<Option<HeldKey>> = const { Cell::new(None) };
+    /// 已被吞掉按下事件的鼠标按键，其抬起事件也需要吞掉
+    static SWALLOWED_BUTTON: Cell<Option<MouseButton>> = const { Cell::new(None) };
+    /// 文本扩展：最近输入的字符
+    static TYPED_BUFFER: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+/// 由低级钩子处理的快捷键（Tauri 加速器无法表达的组合，如区分左右 Ctrl）
+static KEY_BINDINGS: Mutex<Vec<(KeyCombo, HotkeyAction)>> = Mutex::new(Vec::new());
+/// 由低级钩子识别的特殊触发方式
+static TRIGGER_BINDINGS: Mutex<Vec<(HotkeyTrigger, HotkeyAction)>> = Mutex::new(Vec::new());
+/// 劫持系统 Ctrl+V：Some(排除的进程名列表) 表示已启用
+static CTRL_V_INTERCEPT: Mutex<Option<Vec<String>>> = Mutex::new(None);
+/// 钩子线程触发动作时使用的 AppHandle
+static APP_HANDLE: OnceLock<tauri::AppHandle> = OnceLock::new();
+/// 文本扩展启用的缩写；None 表示关闭
+static EXPANDER_ABBREVIATIONS: Mutex<Option<Vec<String>>> = Mutex::new(None);
+/// 文本扩展缓冲区最多保留的字符数
+const TYPED_BUFFER_LIMIT: usize = 64;
+/// 是否存在鼠标按键绑定
+static HAS_MOUSE_BINDINGS: AtomicBool = AtomicBool::new(false);
+/// 粘贴期间是否监视用户的键盘/鼠标操作
+static WATCH_USER_INPUT: AtomicBool = AtomicBool::new(false);
+static HOOK_THREAD: Once = Once::new();
+static HOOK_THREAD_ID: AtomicU32 = AtomicU32::new(0);
+
+/// 发给钩子线程的消息：安装/卸载低级鼠标钩子
+const WM_HOOK_MOUSE_ON: u32 = WM_USER + 1;
+const WM_HOOK_MOUSE_OFF: u32 = WM_USER + 2;
+/// 发给钩子线程的消息：开始/停止监视前台窗口切换
+const WM_HOOK_FOREGROUND_ON: u32 = WM_USER + 3;
+const WM_HOOK_FOREGROUND_OFF: u32 = WM_USER + 4;
+
+impl KeyCombo {
+    /// 当前修饰键状态是否与要求完全一致
+    fn modifiers_match(&self) -> bool {
+        modifiers_match(&self.modifiers)
+    }
+
+    /// 作为引导键序列的第二个键时是否匹配：不带修饰键的第二个键允许引导键的修饰键仍未松开
+    fn matches_second_key(&self, vk: u32) -> bool {
+        self.vk == vk && (!self.has_modifiers() || self.modifiers_match())
+    }
+}
+
+/// 当前修饰键状态是否与要求完全一致（未要求的修饰键不能按下）
+fn modifiers_match(modifiers: &[Option<Side>; 4]) -> bool {
+    MODIFIER_FAMILIES
+        .iter()
+        .zip(modifiers)
+        .all(|(&(left, right), side)| side_matches(*side, is_down(left), is_down(right)))
+}
+
+/// 某一修饰键族的左右按下状态是否满足要求
+fn side_matches(side: Option<Side>, left: bool, right: bool) -> bool {
+    match side {
+        None => !left && !right,
+        Some(Side::Either) => left || right,
+        Some(Side::Left) => left && !right,
+        Some(Side::Right) => right && !left,
+    }
+}
+
+/// 修饰键在位掩码中的位置：族下标 * 2 + (右侧 ? 1 : 0)
+fn modifier_bit(vk: u32) -> Option<u8> {
+    MODIFIER_FAMILIES
+        .iter()
+        .enumerate()
+        .find_map(|(family, &(left, right))| {
+            if vk == left as u32 {
+                Some(1 << (family * 2))
+            } else if vk == right as u32 {
+                Some(1 << (family * 2 + 1))
+            } else {
+                None
+            }
+        })
+}
+
+/// 位掩码记录的修饰键组合是否与要求完全一致
+fn mask_matches(modifiers: &[Option<Side>; 4], mask: u8) -> bool {
+    modifiers.iter().enumerate().all(|(family, side)| {
+        let left = mask & (1 << (family * 2)) != 0;
+        let right = mask & (1 << (family * 2 + 1)) != 0;
+        side_matches(*side, left, right)
+    })
+}
+
+/// 修饰键的虚拟键码（含左右区分版本）
+fn is_modifier_vk(vk: u32) -> bool {
+    matches!(
+        vk,
+        0x10 | 0x11 | 0x12 // Shift / Control / Alt
+            | 0xA0..=0xA5 // 左右 Shift / Control / Alt
+            | 0x5B | 0x5C // 左右 Win
+    )
+}
+
+/// 按下状态检测（GetAsyncKeyState 最高位）
+fn is_down(vk: i32) -> bool {
+    unsafe { GetAsyncKeyState(vk) as u16 & 0x8000 != 0 }
+}
+
+/// 当前按住的修饰键，按加速器字符串的顺序排列
+fn pressed_modifiers() -> Vec<&'static str> {
+    let mut mods = Vec::new();
+    if is_down(0x5B) || is_down(0x5C) {
+        mods.push("Super");
+    }
+    if is_down(0x11) {
+        mods.push("Control");
+    }
+    if is_down(0x12) {
+        mods.push("Alt");
+    }
+    if is_down(0x10) {
+        mods.push("Shift");
+    }
+    mods
+}
+
+/// 录制期间的钩子回调：吞掉按键，遇到第一个非修饰键时回传组合键并退出消息循环
+unsafe extern "system" fn capture_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    if code >= 0 && (wparam.0 as u32 == WM_KEYDOWN || wparam.0 as u32 == WM_SYSKEYDOWN) {
+        let info = &*(lparam.0 as *const KBDLLHOOKSTRUCT);
+        if is_modifier_vk(info.vkCode) {
+            return CallNextHookEx(HHOOK(0), code, wparam, lparam);
+        }
+        if let Some(key) = vk_to_key_name(info.vkCode) {
+            let mut parts = pressed_modifiers();
+            parts.push(&key);
+            let accelerator = parts.join("+");
+
+            CAPTURE_SENDER.with(|sender| {
+                if let Some(sender) = sender.borrow_mut().take() {
+                    let _ = sender.send(accelerator);
+                }
+            });
+            PostQuitMessage(0);
+        }
+        // 录制期间不让按键传递到其他程序
+        return LRESULT(1);
+    }
+    CallNextHookEx(HHOOK(0), code, wparam, lparam)
+}
+
+/// 临时安装低级键盘钩子，录制下一次按下的组合键并返回规范化的加速器字符串
+pub fn capture_next_combo(timeout: Duration) -> Result<String, String> {
+    let (tx, rx) = mpsc::channel();
+    let (thread_id_tx, thread_id_rx) = mpsc::channel();
+
+    let handle = thread::spawn(move || -> Result<(), String> {
+        unsafe {
+            // 确保线程已有消息队列，以便接收 WM_QUIT
+            let mut msg = MSG::default();
+            let _ = PeekMessageW(&mut msg, HWND(0), WM_USER, WM_USER, PM_NOREMOVE);
+            let _ = thread_id_tx.send(GetCurrentThreadId());
+
+            CAPTURE_SENDER.with(|sender| *sender.borrow_mut() = Some(tx));
+
+            let module = GetModuleHandleW(None).map_err(|e| e.to_string())?;
+            let hook = SetWindowsHookExW(
+                WH_KEYBOARD_LL,
+                Some(capture_proc),
+                HINSTANCE(module.0),
+                0,
+            )
+            .map_err(|e| i18n::tf("error.hook_install", &[("error", &e)]))?;
+
+            while GetMessageW(&mut msg, HWND(0), 0, 0).as_bool() {}
+
+            let _ = UnhookWindowsHookEx(hook);
+            CAPTURE_SENDER.with(|sender| *sender.borrow_mut() = None);
+        }
+        Ok(())
+    });
+
+    let thread_id = thread_id_rx
+        .recv()
+        .map_err(|_| i18n::t("error.capture_thread").to_string())?;
+
+    match rx.recv_timeout(timeout) {
+        Ok(accelerator) => {
+            let _ = handle.join();
+
+            #[cfg(debug_assertions)]
+            println!("录制到快捷键: {}", accelerator);
+
+            Ok(accelerator)
+        }
+        Err(RecvTimeoutError::Timeout) => {
+            unsafe {
+                let _ = PostThreadMessageW(thread_id, WM_QUIT, WPARAM(0), LPARAM(0));
+            }
+            let _ = handle.join();
+            Err(i18n::t("error.capture_timeout").to_string())
+        }
+        Err(RecvTimeoutError::Disconnected) => match handle.join() {
+            Ok(Err(e)) => Err(e),
+            _ => Err(i18n::t("error.capture_failed").to_string()),
+        },
+    }
+}
+
+/// 替换由低级钩子处理的快捷键，必要时启动钩子线程
+pub fn set_key_bindings(app_handle: &tauri::AppHandle, bindings: Vec<(KeyCombo, HotkeyAction)>) {
+    let has_bindings = !bindings.is_empty();
+    *KEY_BINDINGS.lock().unwrap() = bindings;
+    if has_bindings {
+        ensure_hook_thread(app_handle);
+    }
+}
+
+/// 启用/关闭 Ctrl+V 劫持。启用后仅在 Paster 可用时吞掉 Ctrl+V 并触发粘贴，
+/// 暂停、前台为 Paster 自身或前台进程在排除列表中时按键照常传给系统。
+pub fn set_ctrl_v_intercept(app_handle: &tauri::AppHandle, exclude: Option<Vec<String>>) {
+    let enabled = exclude.is_some();
+    *CTRL_V_INTERCEPT.lock().unwrap() = exclude;
+    if enabled {
+        ensure_hook_thread(app_handle);
+    }
+}
+
+/// 当前按键是否应作为劫持的 Ctrl+V 处理
+fn should_intercept_ctrl_v(vk: u32) -> bool {
+    const VK_V: u32 = 0x56;
+    if vk != VK_V {
+        return false;
+    }
+    let exclude = match CTRL_V_INTERCEPT.lock().unwrap().clone() {
+        Some(exclude) => exclude,
+        None => return false,
+    };
+    let ctrl_v = KeyCombo {
+        modifiers: [Some(Side::Either), None, None, None],
+        vk: VK_V,
+    };
+    if !ctrl_v.modifiers_match() || foreground::is_own_window_foreground() {
+        return false;
+    }
+    if let Some(name) = foreground::foreground_process_name() {
+        if foreground::matches_process(&exclude, &name) {
+            return false;
+        }
+    }
+    match APP_HANDLE.get() {
+        Some(app_handle) => {
+            let state = app_handle.state::<Mutex<PasteState>>();
+            let is_paused = state.lock().unwrap().is_paused;
+            !is_paused
+        }
+        None => false,
+    }
+}
+
+/// 启动常驻的低级钩子线程（只会启动一次）。键盘钩子一直安装；
+/// 鼠标钩子只在存在鼠标按键绑定时通过线程消息安装，避免无谓地处理每次鼠标移动。
+fn ensure_hook_thread(app_handle: &tauri::AppHandle) {
+    let _ = APP_HANDLE.set(app_handle.clone());
+    HOOK_THREAD.call_once(|| {
+        let (thread_id_tx, thread_id_rx) = mpsc::channel();
+        thread::spawn(move || unsafe {
+            // 确保线程已有消息队列，以便接收线程消息
+            let mut msg = MSG::default();
+            let _ = PeekMessageW(&mut msg, HWND(0), WM_USER, WM_USER, PM_NOREMOVE);
+            let _ = thread_id_tx.send(GetCurrentThreadId());
+
+            let module = match GetModuleHandleW(None) {
+                Ok(module) => HINSTANCE(module.0),
+                Err(_) => return,
+            };
+            let hook = match SetWindowsHookExW(WH_KEYBOARD_LL, Some(keyboard_proc), module, 0) {
+                Ok(hook) => hook,
+                Err(e) => {
+                    #[cfg(debug_assertions)]
+                    eprintln!("安装键盘钩子失败: {}", e);
+
+                    return;
+                }
+            };
+
+            #[cfg(debug_assertions)]
+            println!("低级键盘钩子已安装");
+
+            let mut mouse_hook: Option<HHOOK> = None;
+            let mut foreground_hook: Option<HWINEVENTHOOK> = None;
+            while GetMessageW(&mut msg, HWND(0), 0, 0).as_bool() {
+                match msg.message {
+                    WM_HOOK_MOUSE_ON if mouse_hook.is_none() => {
+                        mouse_hook =
+                            SetWindowsHookExW(WH_MOUSE_LL, Some(mouse_proc), module, 0).ok();
+                    }
+                    WM_HOOK_MOUSE_OFF => {
+                        if let Some(mouse_hook) = mouse_hook.take() {
+                            let _ = UnhookWindowsHookEx(mouse_hook);
+                        }
+                    }
+                    WM_HOOK_FOREGROUND_ON if foreground_hook.is_none() => {
+                        let hook = SetWinEventHook(
+                            EVENT_SYSTEM_FOREGROUND,
+                            EVENT_SYSTEM_FOREGROUND,
+                            HMODULE(0),
+                            Some(foreground_proc),
+                            0,
+                            0,
+                            WINEVENT_OUTOFCONTEXT,
+                        );
+                        if !hook.is_invalid() {
+                            foreground_hook = Some(hook);
+                        }
+                    }
+                    WM_HOOK_FOREGROUND_OFF => {
+                        if let Some(foreground_hook) = foreground_hook.take() {
+                            let _ = UnhookWinEvent(foreground_hook);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            if let Some(mouse_hook) = mouse_hook {
+                let _ = UnhookWindowsHookEx(mouse_hook);
+            }
+            if let Some(foreground_hook) = foreground_hook {
+                let _ = UnhookWinEvent(foreground_hook);
+            }
+            let _ = UnhookWindowsHookEx(hook);
+        });
+        if let Ok(thread_id) = thread_id_rx.recv() {
+            HOOK_THREAD_ID.store(thread_id, Ordering::SeqCst);
+        }
+    });
+}
+
+/// 常驻钩子回调：匹配到快捷键时吞掉主键并在后台线程执行对应动作
+unsafe extern "system" fn keyboard_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    if code >= 0 {
+        let info = &*(lparam.0 as *const KBDLLHOOKSTRUCT);
+        let message = wparam.0 as u32;
+        let is_keydown = message == WM_KEYDOWN || message == WM_SYSKEYDOWN;
+        let is_keyup = message == WM_KEYUP || message == WM_SYSKEYUP;
+        // 忽略注入的按键（包括 Paster 自己发送的字符）
+        let injected = info.flags.0 & LLKHF_INJECTED.0 != 0;
+
+        if !injected {
+            // 双击修饰键等触发方式不吞掉按键
+            if let Some(action) = process_triggers(info.vkCode, is_keydown, info.time) {
+                dispatch(action);
+            }
+        }
+
+        if !injected {
+            if let Some(swallow) = process_long_press(info.vkCode, is_keydown, is_keyup, info.time)
+            {
+                return swallow;
+            }
+        }
+
+        if !injected && is_keydown {
+            if TRIGGERED_VK.get() == Some(info.vkCode) {
+                // 按住不放产生的自动重复
+                return LRESULT(1);
+            }
+            match process_sequence(info.vkCode, info.time) {
+                Some(SequenceStep::Leader) => {
+                    TRIGGERED_VK.set(Some(info.vkCode));
+                    return LRESULT(1);
+                }
+                Some(SequenceStep::Completed(action)) => {
+                    TRIGGERED_VK.set(Some(info.vkCode));
+                    dispatch(action);
+                    return LRESULT(1);
+                }
+                None => {}
+            }
+            let action = if should_intercept_ctrl_v(info.vkCode) {
+                Some(HotkeyAction::Paste)
+            } else {
+                match_binding(info.vkCode)
+            };
+            if let Some(action) = action {
+                TRIGGERED_VK.set(Some(info.vkCode));
+                dispatch(action);
+                return LRESULT(1);
+            }
+        } else if is_keyup && TRIGGERED_VK.get() == Some(info.vkCode) {
+            TRIGGERED_VK.set(None);
+        }
+
+        if !injected && is_keydown {
+            if let Some(swallow) = process_expander(info.vkCode, info.scanCode) {
+                return swallow;
+            }
+        }
+
+        if is_keydown && info.dwExtraInfo != PASTER_INPUT_MARKER && is_user_typing(info.vkCode) {
+            notify_user_input();
+        }
+    }
+    CallNextHookEx(HHOOK(0), code, wparam, lparam)
+}
+
+/// 粘贴期间按下的键是否算作用户在打字：修饰键、按住修饰键的组合（多半是快捷键）
+/// 以及可单独作为快捷键的 F13~F24、多媒体键都不算
+fn is_user_typing(vk: u32) -> bool {
+    if !WATCH_USER_INPUT.load(Ordering::SeqCst) || is_modifier_vk(vk) {
+        return false;
+    }
+    let modifiers_held = MODIFIER_FAMILIES
+        .iter()
+        .any(|&(left, right)| is_down(left) || is_down(right));
+    let standalone = vk_to_key_name(vk).is_some_and(|key| is_standalone_key(&key));
+    !modifiers_held && !standalone
+}
+
+/// 在后台线程处理粘贴期间的用户操作
+fn notify_user_input() {
+    if let Some(app_handle) = APP_HANDLE.get() {
+        let app_handle = app_handle.clone();
+        tauri::async_runtime::spawn_blocking(move || commands::handle_user_input(&app_handle));
+    }
+}
+
+/// 粘贴开始/结束时开启/关闭对用户键盘鼠标操作的监视（需要时临时安装鼠标钩子）
+pub fn set_user_input_watch(app_handle: &tauri::AppHandle, enabled: bool) {
+    WATCH_USER_INPUT.store(enabled, Ordering::SeqCst);
+    if enabled {
+        ensure_hook_thread(app_handle);
+    }
+    update_mouse_hook();
+}
+
+/// 按是否存在鼠标按键绑定、是否在监视用户操作安装或卸载鼠标钩子
+fn update_mouse_hook() {
+    let thread_id = HOOK_THREAD_ID.load(Ordering::SeqCst);
+    if thread_id == 0 {
+        return;
+    }
+    let message =
+        if HAS_MOUSE_BINDINGS.load(Ordering::SeqCst) || WATCH_USER_INPUT.load(Ordering::SeqCst) {
+            WM_HOOK_MOUSE_ON
+        } else {
+            WM_HOOK_MOUSE_OFF
+        };
+    unsafe {
+        let _ = PostThreadMessageW(thread_id, message, WPARAM(0), LPARAM(0));
+    }
+}
+
+/// 粘贴开始/结束时开始/停止监视前台窗口切换
+pub fn set_foreground_watch(app_handle: &tauri::AppHandle, enabled: bool) {
+    if enabled {
+        ensure_hook_thread(app_handle);
+    }
+    let thread_id = HOOK_THREAD_ID.load(Ordering::SeqCst);
+    if thread_id == 0 {
+        return;
+    }
+    let message = if enabled {
+        WM_HOOK_FOREGROUND_ON
+    } else {
+        WM_HOOK_FOREGROUND_OFF
+    };
+    unsafe {
+        let _ = PostThreadMessageW(thread_id, message, WPARAM(0), LPARAM(0));
+    }
+}
+
+/// 前台窗口切换回调：在后台线程交给粘贴流程判断是否需要暂停/继续
+unsafe extern "system" fn foreground_proc(
+    _hook: HWINEVENTHOOK,
+    _event: u32,
+    hwnd: HWND,
+    _id_object: i32,
+    _id_child: i32,
+    _event_thread: u32,
+    _event_time: u32,
+) {
+    if let Some(app_handle) = APP_HANDLE.get() {
+        let app_handle = app_handle.clone();
+        let hwnd = hwnd.0;
+        tauri::async_runtime::spawn_blocking(move || {
+            commands::handle_foreground_change(&app_handle, hwnd)
+        });
+    }
+}
+
+/// 启用（传入缩写列表）或关闭（None）文本扩展
+pub fn set_expander_abbreviations(
+    app_handle: &tauri::AppHandle,
+    abbreviations: Option<Vec<String>>,
+) {
+    let enabled = abbreviations.is_some();
+    *EXPANDER_ABBREVIATIONS.lock().unwrap() = abbreviations;
+    if enabled {
+        ensure_hook_thread(app_handle);
+    }
+}
+
+/// 按当前键盘布局把按键转换为输入的字符（不影响死键状态）
+fn key_to_char(vk: u32, scan_code: u32) -> Option<char> {
+    // 告诉 ToUnicodeEx 不要修改键盘状态（Windows 10 1607 起支持）
+    const TO_UNICODE_NO_STATE_CHANGE: u32 = 0x4;
+    const VK_CAPITAL: usize = 0x14;
+
+    let mut key_state = [0u8; 256];
+    for vk in [0x10, 0x11, 0x12, 0xA0, 0xA1, 0xA2, 0xA3, 0xA4, 0xA5] {
+        if is_down(vk) {
+            key_state[vk as usize] = 0x80;
+        }
+    }
+    unsafe {
+        if GetKeyState(VK_CAPITAL as i32) & 1 != 0 {
+            key_state[VK_CAPITAL] = 1;
+        }
+        let thread_id = GetWindowThreadProcessId(foreground::foreground_window(), None);
+        let layout = GetKeyboardLayout(thread_id);
+        let mut buffer = [0u16; 4];
+        let len = ToUnicodeEx(
+            vk,
+            scan_code,
+            &key_state,
+            &mut buffer,
+            TO_UNICODE_NO_STATE_CHANGE,
+            layout,
+        );
+        if len != 1 {
+            return None;
+        }
+        char::from_u32(buffer[0] as u32).filter(|c| !c.is_control())
+    }
+}
+
+/// 缩写前面是单词边界时才算匹配，避免 "addr" 在 "myaddr" 中被展开
+fn ends_with_abbreviation(buffer: &str, abbreviation: &str) -> bool {
+    match buffer.strip_suffix(abbreviation) {
+        Some(before) => before.chars().last().is_none_or(|c| !c.is_alphanumeric()),
+        None => false,
+    }
+}
+
+/// 当前是否可以展开缩写：Paster 未暂停、没有在粘贴、前台不是 Paster 自身
+fn expander_available() -> bool {
+    if foreground::is_own_window_foreground() {
+        return false;
+    }
+    match APP_HANDLE.get() {
+        Some(app_handle) => {
+            let state = app_handle.state::<Mutex<PasteState>>();
+            let locked = state.lock().unwrap();
+            !locked.is_paused && !locked.is_pasting.load(Ordering::SeqCst)
+        }
+        None => false,
+    }
+}
+
+/// 文本扩展：记录用户输入的字符，输入缩写后按下空格、Tab 或回车时吞掉该键，
+/// 在后台删除缩写并输入扩展内容
+fn process_expander(vk: u32, scan_code: u32) -> Option<LRESULT> {
+    const VK_BACK: u32 = 0x08;
+
+    let abbreviations = match EXPANDER_ABBREVIATIONS.lock().unwrap().as_ref() {
+        Some(abbreviations) if !abbreviations.is_empty() => abbreviations.clone(),
+        _ => {
+            TYPED_BUFFER.with(|buffer| buffer.borrow_mut().clear());
+            return None;
+        }
+    };
+    if is_modifier_vk(vk) {
+        return None;
+    }
+    // Ctrl、Alt 或 Win 组合多半是快捷键（AltGr 即 Ctrl+Alt 除外），光标可能已经移动
+    let ctrl = is_down(0x11);
+    let alt = is_down(0x12);
+    if ctrl != alt || is_down(0x5B) || is_down(0x5C) {
+        TYPED_BUFFER.with(|buffer| buffer.borrow_mut().clear());
+        return None;
+    }
+    if vk == VK_BACK {
+        TYPED_BUFFER.with(|buffer| buffer.borrow_mut().pop());
+        return None;
+    }
+
+    let delimiter = match vk {
+        0x20 => Some(' '),
+        0x09 => Some('\t'),
+        0x0D => Some('\n'),
+        _ => None,
+    };
+    if let Some(delimiter) = delimiter {
+        let typed = TYPED_BUFFER.with(|buffer| std::mem::take(&mut *buffer.borrow_mut()));
+        let abbreviation = abbreviations
+            .into_iter()
+            .find(|abbreviation| ends_with_abbreviation(&typed, abbreviation))?;
+        if !expander_available() {
+            return None;
+        }
+        if let Some(app_handle) = APP_HANDLE.get() {
+            let app_handle = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                crate::expander::expand(&app_handle, &abbreviation, delimiter).await;
+            });
+        }
+        return Some(LRESULT(1));
+    }
+
+    TYPED_BUFFER.with(|buffer| {
+        let mut buffer = buffer.borrow_mut();
+        match key_to_char(vk, scan_code) {
+            Some(c) => {
+                buffer.push(c);
+                if buffer.chars().count() > TYPED_BUFFER_LIMIT {
+                    buffer.remove(0);
+                }
+            }
+            // 方向键、Esc 等会移动光标或取消输入
+            None => buffer.clear(),
+        }
+    });
+    None
+}
+
+/// 在后台线程执行动作，避免阻塞钩子回调
+fn dispatch(action: HotkeyAction) {
+    if let Some(app_handle) = APP_HANDLE.get() {
+        let app_handle = app_handle.clone();
+        tauri::async_runtime::spawn_blocking(move || hotkeys::trigger_action(&app_handle, action));
+    }
+}
+
+/// 查找与当前按键及修饰键状态匹配的动作
+fn match_binding(vk: u32) -> Option<HotkeyAction> {
+    let bindings = KEY_BINDINGS.lock().unwrap();
+    bindings
+        .iter()
+        .find(|(combo, _)| combo.vk == vk && combo.modifiers_match())
+        .map(|(_, action)| *action)
+}
+
+/// 替换由钩子识别的特殊触发方式（双击修饰键、鼠标按键等），必要时启动钩子线程
+pub fn set_trigger_bindings(
+    app_handle: &tauri::AppHandle,
+    bindings: Vec<(HotkeyTrigger, HotkeyAction)>,
+) {
+    let has_mouse_bindings = bindings
+        .iter()
+        .any(|(trigger, _)| matches!(trigger, HotkeyTrigger::MouseButton { .. }));
+    let has_bindings = !bindings.is_empty();
+    *TRIGGER_BINDINGS.lock().unwrap() = bindings;
+    if has_bindings {
+        ensure_hook_thread(app_handle);
+    }
+
+    HAS_MOUSE_BINDINGS.store(has_mouse_bindings, Ordering::SeqCst);
+    update_mouse_hook();
+}
+
+/// 修饰键名称是否对应该虚拟键码（"Control" 匹配左右两个键，"ControlLeft" 只匹配左键）
+fn modifier_matches_vk(token: &str, vk: u32) -> bool {
+    let Some((family, side)) = parse_modifier(token) else {
+        return false;
+    };
+    let (left, right) = MODIFIER_FAMILIES[family];
+    match side {
+        Side::Either => vk == left as u32 || vk == right as u32,
+        Side::Left => vk == left as u32,
+        Side::Right => vk == right as u32,
+    }
+}
+
+/// 推进特殊触发方式的状态机，返回被触发的动作
+fn process_triggers(vk: u32, is_keydown: bool, time: u32) -> Option<HotkeyAction> {
+    let chord = process_modifier_chord(vk, is_keydown);
+    let double_tap = process_double_tap(vk, is_keydown, time);
+    chord.or(double_tap)
+}
+
+/// 纯修饰键组合（如 Ctrl+Alt）：期间没有按下其他键，并在第一个修饰键松开时触发
+fn process_modifier_chord(vk: u32, is_keydown: bool) -> Option<HotkeyAction> {
+    let Some(bit) = modifier_bit(vk) else {
+        if is_keydown {
+            CHORD_DIRTY.set(true);
+        }
+        return None;
+    };
+
+    let down = CHORD_DOWN.get();
+    if is_keydown {
+        if down == 0 {
+            // 新的一轮组合
+            CHORD_PEAK.set(0);
+            CHORD_DIRTY.set(false);
+        }
+        CHORD_DOWN.set(down | bit);
+        CHORD_PEAK.set(CHORD_PEAK.get() | bit);
+        return None;
+    }
+
+    CHORD_DOWN.set(down & !bit);
+    if CHORD_DIRTY.get() || down & bit == 0 {
+        return None;
+    }
+    // 第一个松开的修饰键决定是否触发，之后直到全部松开都不再触发
+    CHORD_DIRTY.set(true);
+
+    let peak = CHORD_PEAK.get();
+    let bindings = TRIGGER_BINDINGS.lock().unwrap();
+    bindings.iter().find_map(|(trigger, action)| match trigger {
+        HotkeyTrigger::ModifierOnly { modifiers }
+            if parse_modifiers(modifiers).is_some_and(|m| mask_matches(&m, peak)) =>
+        {
+            Some(*action)
+        }
+        _ => None,
+    })
+}
+
+/// 双击修饰键：两次单独轻按同一修饰键的间隔不超过设定值
+fn process_double_tap(vk: u32, is_keydown: bool, time: u32) -> Option<HotkeyAction> {
+    if is_keydown {
+        match CLEAN_MODIFIER.get() {
+            // 同一修饰键的自动重复
+            Some(current) if current == vk => {}
+            None if is_modifier_vk(vk) => CLEAN_MODIFIER.set(Some(vk)),
+            // 按下了其他键，本次不算单独轻按
+            _ => {
+                CLEAN_MODIFIER.set(None);
+                LAST_TAP.set(None);
+            }
+        }
+        return None;
+    }
+
+    if CLEAN_MODIFIER.get() != Some(vk) {
+        return None;
+    }
+    CLEAN_MODIFIER.set(None);
+
+    let bindings = TRIGGER_BINDINGS.lock().unwrap();
+    let fired = LAST_TAP.get().and_then(|(last_vk, last_time)| {
+        if last_vk != vk {
+            return None;
+        }
+        let elapsed = time.wrapping_sub(last_time);
+        bindings.iter().find_map(|(trigger, action)| match trigger {
+            HotkeyTrigger::DoubleTap {
+                modifier,
+                interval_ms,
+            } if elapsed <= *interval_ms && modifier_matches_vk(modifier, vk) => Some(*action),
+            _ => None,
+        })
+    });
+
+    if fired.is_some() {
+        LAST_TAP.set(None);
+    } else {
+        LAST_TAP.set(Some((vk, time)));
+    }
+    fired
+}
+
+/// 按住中的长按绑定键：按住超过 hold_ms 触发 long，提前松开则触发同一组合键的 tap 动作
+#[derive(Clone, Copy)]
+struct HeldKey {
+    vk: u32,
+    start: u32,
+    hold_ms: u32,
+    tap: Option<HotkeyAction>,
+    long: HotkeyAction,
+    fired: bool,
+}
+
+/// 长按触发：吞掉绑定键的按下、自动重复与抬起，在按住足够久（自动重复或松开时判断）后触发长按动作，
+/// 否则松开时触发普通动作。返回 Some 表示该事件已处理。
+fn process_long_press(vk: u32, is_keydown: bool, is_keyup: bool, time: u32) -> Option<LRESULT> {
+    if let Some(mut held) = HELD_KEY.get().filter(|held| held.vk == vk) {
+        let long_enough = time.wrapping_sub(held.start) >= held.hold_ms;
+        if is_keydown {
+            // 自动重复：按住时间足够则立即触发长按，无需等待松开
+            if !held.fired && long_enough {
+                held.fired = true;
+                HELD_KEY.set(Some(held));
+                dispatch(held.long);
+            }
+        } else if is_keyup {
+            HELD_KEY.set(None);
+            if !held.fired {
+                if long_enough {
+                    dispatch(held.long);
+                } else if let Some(tap) = held.tap {
+                    dispatch(tap);
+                }
+            }
+        }
+        return Some(LRESULT(1));
+    }
+
+    if !is_keydown {
+        return None;
+    }
+    let (long, hold_ms) = {
+        let bindings = TRIGGER_BINDINGS.lock().unwrap();
+        bindings
+            .iter()
+            .find_map(|(trigger, action)| match trigger {
+                HotkeyTrigger::LongPress {
+                    accelerator,
+                    hold_ms,
+                } if KeyCombo::parse(accelerator)
+                    .is_some_and(|combo| combo.vk == vk && combo.modifiers_match()) =>
+                {
+                    Some((*action, *hold_ms))
+                }
+                _ => None,
+            })?
+    };
+    HELD_KEY.set(Some(HeldKey {
+        vk,
+        start: time,
+        hold_ms,
+        tap: match_binding(vk),
+        long,
+        fired: false,
+    }));
+    Some(LRESULT(1))
+}
+
+/// 引导键序列的进展
+enum SequenceStep {
+    /// 按下了引导键，等待第二个键
+    Leader,
+    /// 在超时前按下了第二个键
+    Completed(HotkeyAction),
+}
+
+/// 引导键序列（如 "Control+Alt+P，然后 S"）：匹配第二个键或新的引导键。
+/// 等待期间按下其他键则放弃本次序列，该键照常传给系统。
+fn process_sequence(vk: u32, time: u32) -> Option<SequenceStep> {
+    if is_modifier_vk(vk) {
+        return None;
+    }
+    let bindings = TRIGGER_BINDINGS.lock().unwrap();
+
+    if let Some((pending, start)) = PENDING_LEADER.take() {
+        let elapsed = time.wrapping_sub(start);
+        let action = bindings.iter().find_map(|(trigger, action)| match trigger {
+            HotkeyTrigger::Sequence {
+                leader,
+                key,
+                timeout_ms,
+            } if *leader == pending
+                && elapsed <= *timeout_ms
+                && KeyCombo::parse(key).is_some_and(|combo| combo.matches_second_key(vk)) =>
+            {
+                Some(*action)
+            }
+            _ => None,
+        });
+        if let Some(action) = action {
+            return Some(SequenceStep::Completed(action));
+        }
+    }
+
+    let leader = bindings.iter().find_map(|(trigger, _)| match trigger {
+        HotkeyTrigger::Sequence { leader, .. }
+            if KeyCombo::parse(leader)
+                .is_some_and(|combo| combo.vk == vk && combo.modifiers_match()) =>
+        {
+            Some(leader.clone())
+        }
+        _ => None,
+    })?;
+    PENDING_LEADER.set(Some((leader, time)));
+    Some(SequenceStep::Leader)
+}
+
+/// 低级鼠标钩子回调：匹配到鼠标按键绑定时吞掉按下与对应的抬起
+unsafe extern "system" fn mouse_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    if code >= 0 {
+        let info = &*(lparam.0 as *const MSLLHOOKSTRUCT);
+        let message = wparam.0 as u32;
+        let injected = info.flags & LLMHF_INJECTED != 0;
+        let button = match message {
+            WM_MBUTTONDOWN | WM_MBUTTONUP => Some(MouseButton::Middle),
+            WM_XBUTTONDOWN | WM_XBUTTONUP => match info.mouseData >> 16 {
+                1 => Some(MouseButton::X1),
+                2 => Some(MouseButton::X2),
+                _ => None,
+            },
+            _ => None,
+        };
+
+        if let (false, Some(button)) = (injected, button) {
+            if message == WM_MBUTTONDOWN || message == WM_XBUTTONDOWN {
+                if let Some(action) = match_mouse_binding(button) {
+                    SWALLOWED_BUTTON.set(Some(button));
+                    dispatch(action);
+                    return LRESULT(1);
+                }
+            } else if SWALLOWED_BUTTON.get() == Some(button) {
+                SWALLOWED_BUTTON.set(None);
+                return LRESULT(1);
+            }
+        }
+
+        // 粘贴期间的点击与滚轮（移动鼠标不算）
+        let user_action = matches!(
+            message,
+            WM_LBUTTONDOWN | WM_RBUTTONDOWN | WM_MBUTTONDOWN | WM_XBUTTONDOWN | WM_MOUSEWHEEL
+        );
+        if user_action
+            && info.dwExtraInfo != PASTER_INPUT_MARKER
+            && WATCH_USER_INPUT.load(Ordering::SeqCst)
+        {
+            notify_user_input();
+        }
+    }
+    CallNextHookEx(HHOOK(0), code, wparam, lparam)
+}
+
+/// 查找与鼠标按键及当前修饰键状态匹配的动作
+fn match_mouse_binding(button: MouseButton) -> Option<HotkeyAction> {
+    let bindings = TRIGGER_BINDINGS.lock().unwrap();
+    bindings.iter().find_map(|(trigger, action)| match trigger {
+        HotkeyTrigger::MouseButton {
+            button: bound,
+            modifiers,
+        } if *bound == button
+            && parse_modifiers(modifiers).is_some_and(|m| modifiers_match(&m)) =>
+        {
+            Some(*action)
+        }
+        _ => None,
+    })
+}
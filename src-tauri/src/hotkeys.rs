@@ -0,0 +1,657 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{GlobalShortcutManager, Manager};
+
+use crate::commands::{self, HotkeyConfig, PasteOptions, PasteState};
+use crate::foreground;
+use crate::hook::{self, KeyCombo};
+use crate::i18n;
+use crate::notify;
+use crate::platform::TextSource;
+use crate::snippets::{self, SnippetStore};
+
+/// 可以绑定全局快捷键的动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HotkeyAction {
+    /// 粘贴剪贴板内容（再次按下则中止）
+    Paste,
+    /// 暂停/继续
+    TogglePause,
+    /// 中止当前粘贴
+    Abort,
+    /// 切换极速模式
+    ToggleInstant,
+    /// 重新输入上一次粘贴的文本
+    RetypeLast,
+    /// 暂停/继续正在进行的粘贴
+    ToggleTypingPause,
+    /// 开启/关闭文本扩展（缩写误触发时的紧急开关）
+    ToggleExpander,
+    /// 分段输入：输入剪贴板的下一段
+    NextChunk,
+    /// 输入当前选中的文本（Linux 的 PRIMARY 选区），不经过剪贴板
+    PasteSelection,
+    /// 输入指定编号的片段。快捷键保存在片段自身（Snippet::hotkey），不出现在设置中
+    #[serde(skip)]
+    Snippet(u64),
+}
+
+/// 组合键以外的触发方式，由低级键盘钩子识别
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum HotkeyTrigger {
+    /// 在 interval_ms 内单独连按两次同一修饰键（如 Ctrl Ctrl、Shift Shift）
+    DoubleTap {
+        modifier: String,
+        #[serde(default = "default_double_tap_interval")]
+        interval_ms: u32,
+    },
+    /// 只由修饰键组成的组合（如 Ctrl+Alt），期间未按下其他键、松开时触发
+    ModifierOnly { modifiers: Vec<String> },
+    /// 引导键序列：先按引导键（如 "Control+Alt+P"），再在 timeout_ms 内按第二个键（如 "S"）。
+    /// 多个序列可共用同一引导键，只占用很少的全局组合键。
+    Sequence {
+        leader: String,
+        key: String,
+        #[serde(default = "default_sequence_timeout")]
+        timeout_ms: u32,
+    },
+    /// 长按组合键超过 hold_ms 触发；同一组合键也可绑定为其他动作的普通快捷键，短按时触发该动作
+    LongPress {
+        accelerator: String,
+        #[serde(default = "default_hold_ms")]
+        hold_ms: u32,
+    },
+    /// 鼠标中键或侧键，可选配合修饰键（如 Ctrl+中键）
+    MouseButton {
+        button: MouseButton,
+        #[serde(default)]
+        modifiers: Vec<String>,
+    },
+}
+
+/// 可绑定的鼠标按键
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MouseButton {
+    Middle,
+    /// 侧键"后退"
+    X1,
+    /// 侧键"前进"
+    X2,
+}
+
+fn default_double_tap_interval() -> u32 {
+    300
+}
+
+fn default_sequence_timeout() -> u32 {
+    1500
+}
+
+fn default_hold_ms() -> u32 {
+    500
+}
+
+impl HotkeyTrigger {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            HotkeyTrigger::DoubleTap {
+                modifier,
+                interval_ms,
+            } => {
+                if hook::parse_modifier(modifier).is_none() {
+                    return Err(i18n::tf("error.not_modifier", &[("modifier", modifier)]));
+                }
+                if !(100..=1000).contains(interval_ms) {
+                    return Err(i18n::t("error.double_tap_interval").to_string());
+                }
+            }
+            HotkeyTrigger::ModifierOnly { modifiers } => {
+                if let Some(bad) = modifiers.iter().find(|m| hook::parse_modifier(m).is_none()) {
+                    return Err(i18n::tf("error.not_modifier", &[("modifier", bad)]));
+                }
+                // 单个修饰键松开即触发会与正常使用冲突（单键请使用双击触发）
+                if modifiers.len() < 2 {
+                    return Err(i18n::t("error.modifier_only_count").to_string());
+                }
+            }
+            HotkeyTrigger::Sequence {
+                leader,
+                key,
+                timeout_ms,
+            } => {
+                let leader_combo = KeyCombo::parse(leader)
+                    .ok_or_else(|| i18n::tf("error.unknown_leader", &[("key", leader)]))?;
+                if !leader_combo.has_modifiers() && !leader_combo.is_standalone() {
+                    return Err(i18n::t("error.leader_needs_modifier").to_string());
+                }
+                KeyCombo::parse(key)
+                    .ok_or_else(|| i18n::tf("error.unknown_key", &[("key", key)]))?;
+                if !(200..=5000).contains(timeout_ms) {
+                    return Err(i18n::t("error.sequence_timeout").to_string());
+                }
+            }
+            HotkeyTrigger::LongPress {
+                accelerator,
+                hold_ms,
+            } => {
+                let combo = KeyCombo::parse(accelerator)
+                    .ok_or_else(|| i18n::tf("error.unknown_hotkey", &[("hotkey", accelerator)]))?;
+                if !combo.has_modifiers() && !combo.is_standalone() {
+                    return Err(i18n::t("error.long_press_needs_modifier").to_string());
+                }
+                if !(200..=3000).contains(hold_ms) {
+                    return Err(i18n::t("error.long_press_duration").to_string());
+                }
+            }
+            HotkeyTrigger::MouseButton { modifiers, .. } => {
+                if let Some(bad) = modifiers.iter().find(|m| hook::parse_modifier(m).is_none()) {
+                    return Err(i18n::tf("error.not_modifier", &[("modifier", bad)]));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 用于检测重复绑定的唯一标识
+    pub fn id(&self) -> String {
+        match self {
+            HotkeyTrigger::DoubleTap { modifier, .. } => format!("DoubleTap:{}", modifier),
+            HotkeyTrigger::ModifierOnly { modifiers } => {
+                format!("ModifierOnly:{}", modifiers.join("+"))
+            }
+            HotkeyTrigger::Sequence { leader, key, .. } => {
+                format!("Sequence:{},{}", leader, key)
+            }
+            HotkeyTrigger::LongPress { accelerator, .. } => format!("LongPress:{}", accelerator),
+            HotkeyTrigger::MouseButton { button, modifiers } => {
+                format!("Mouse:{}{:?}", modifier_prefix(modifiers), button)
+            }
+        }
+    }
+
+    /// 用户可读的描述
+    pub fn get_description(&self) -> String {
+        match self {
+            HotkeyTrigger::DoubleTap { modifier, .. } => {
+                i18n::tf("hotkey.double_tap", &[("key", modifier)])
+            }
+            HotkeyTrigger::ModifierOnly { modifiers } => {
+                i18n::tf("hotkey.modifier_only", &[("keys", &modifiers.join("+"))])
+            }
+            HotkeyTrigger::Sequence { leader, key, .. } => {
+                i18n::tf("hotkey.sequence", &[("leader", leader), ("key", key)])
+            }
+            HotkeyTrigger::LongPress { accelerator, .. } => {
+                i18n::tf("hotkey.long_press", &[("key", accelerator)])
+            }
+            HotkeyTrigger::MouseButton { button, modifiers } => {
+                let button = match button {
+                    MouseButton::Middle => i18n::t("hotkey.mouse_middle"),
+                    MouseButton::X1 => i18n::t("hotkey.mouse_x1"),
+                    MouseButton::X2 => i18n::t("hotkey.mouse_x2"),
+                };
+                format!("{}{}", modifier_prefix(modifiers), button)
+            }
+        }
+    }
+}
+
+/// 修饰键前缀，如 "Control+Alt+"
+fn modifier_prefix(modifiers: &[String]) -> String {
+    modifiers.iter().map(|m| format!("{}+", m)).collect()
+}
+
+/// 动作 -> 快捷键 的映射，未出现在映射中的动作即为未绑定
+pub type HotkeyBindings = BTreeMap<HotkeyAction, HotkeyConfig>;
+
+/// 默认绑定粘贴动作，以及仅在粘贴过程中生效的 Esc 中止
+pub fn default_bindings() -> HotkeyBindings {
+    let mut bindings = HotkeyBindings::new();
+    bindings.insert(HotkeyAction::Paste, HotkeyConfig::default());
+    bindings.insert(
+        HotkeyAction::Abort,
+        HotkeyConfig {
+            accelerator: Some("Escape".to_string()),
+            ..HotkeyConfig::default()
+        },
+    );
+    bindings
+}
+
+/// 记录当前已注册的全局快捷键，以便下次更新或注销
+pub struct GlobalShortcutState {
+    registered: BTreeMap<HotkeyAction, String>,
+    /// 当前生效的整组快捷键，切换失败时用于回滚
+    active: HotkeyBindings,
+    /// 这些程序（进程名，如 "vmconnect.exe"）位于前台时暂停全部快捷键，让按键直达该程序
+    suspend_apps: Vec<String>,
+    /// 快捷键是否因前台程序而暂停
+    suspended: bool,
+}
+
+impl GlobalShortcutState {
+    pub fn new() -> Self {
+        Self {
+            registered: BTreeMap::new(),
+            active: HotkeyBindings::new(),
+            suspend_apps: Vec::new(),
+            suspended: false,
+        }
+    }
+}
+
+/// 校验整组快捷键：每个绑定都需合法，且不同动作不能使用相同的快捷键
+pub fn validate_bindings(bindings: &HotkeyBindings) -> Result<(), String> {
+    let mut seen: BTreeMap<String, HotkeyAction> = BTreeMap::new();
+    for (action, config) in bindings {
+        config.validate(*action)?;
+        if config.intercept_ctrl_v && *action != HotkeyAction::Paste {
+            return Err(i18n::t("error.intercept_paste_only").to_string());
+        }
+        // 中止快捷键只在粘贴期间临时注册为全局快捷键
+        if *action == HotkeyAction::Abort
+            && (config.trigger.is_some() || hook::requires_hook(&config.to_tauri_accelerator()))
+        {
+            return Err(i18n::t("error.abort_plain_only").to_string());
+        }
+        let accelerator = config.to_tauri_accelerator();
+        if let Some(other) = seen.insert(accelerator.clone(), *action) {
+            return Err(i18n::tf(
+                "error.hotkey_conflict",
+                &[
+                    ("hotkey", &accelerator),
+                    ("first", &format!("{:?}", other)),
+                    ("second", &format!("{:?}", action)),
+                ],
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// 当前生效的快捷键（全局设置被选用的配置方案覆盖后；被前台程序暂停时也是恢复后的快捷键）
+pub fn active_bindings(app_handle: &tauri::AppHandle) -> HotkeyBindings {
+    let shortcut_state = app_handle.state::<Mutex<GlobalShortcutState>>();
+    let locked_state = shortcut_state.lock().unwrap();
+    locked_state.active.clone()
+}
+
+/// 尽力注册一组快捷键：注册失败的快捷键被跳过，其余照常生效（启动时使用）
+pub fn register_hotkeys(
+    app_handle: &tauri::AppHandle,
+    bindings: &HotkeyBindings,
+) -> Result<(), String> {
+    {
+        let shortcut_state = app_handle.state::<Mutex<GlobalShortcutState>>();
+        let mut locked_state = shortcut_state.lock().unwrap();
+        locked_state.active = bindings.clone();
+        if locked_state.suspended {
+            return Ok(());
+        }
+    }
+    apply_bindings(app_handle, &with_snippet_hotkeys(app_handle, bindings))
+}
+
+/// 原子地切换到另一组快捷键（修改快捷键、切换配置方案时使用）：
+/// 只要有一个快捷键注册失败，就撤销本次注册并恢复原来的整组快捷键。
+pub fn switch_hotkeys(
+    app_handle: &tauri::AppHandle,
+    bindings: &HotkeyBindings,
+) -> Result<(), String> {
+    let shortcut_state = app_handle.state::<Mutex<GlobalShortcutState>>();
+    let previous = {
+        let mut locked_state = shortcut_state.lock().unwrap();
+        // 暂停期间只记录新的快捷键，恢复时再注册
+        if locked_state.suspended {
+            locked_state.active = bindings.clone();
+            return Ok(());
+        }
+        locked_state.active.clone()
+    };
+
+    if let Err(e) = apply_bindings(app_handle, &with_snippet_hotkeys(app_handle, bindings)) {
+        let _ = apply_bindings(app_handle, &with_snippet_hotkeys(app_handle, &previous));
+        return Err(i18n::tf("error.hotkey_restored", &[("error", &e)]));
+    }
+    shortcut_state.lock().unwrap().active = bindings.clone();
+    Ok(())
+}
+
+/// 片段库变化后（保存或删除片段、解锁或锁定片段库）按当前的片段重新注册快捷键
+pub fn refresh_snippet_hotkeys(app_handle: &tauri::AppHandle) -> Result<(), String> {
+    switch_hotkeys(app_handle, &active_bindings(app_handle))
+}
+
+/// 在整组快捷键中加入各片段的快捷键；启动时片段库尚未读取，此时不加入
+fn with_snippet_hotkeys(
+    app_handle: &tauri::AppHandle,
+    bindings: &HotkeyBindings,
+) -> HotkeyBindings {
+    let mut bindings = bindings.clone();
+    if let Some(state) = app_handle.try_state::<Mutex<SnippetStore>>() {
+        bindings.extend(state.lock().unwrap().hotkey_bindings());
+    }
+    bindings
+}
+
+/// 注销旧的全部快捷键，并按新的映射重新注册。
+/// 区分左右修饰键的快捷键交给低级键盘钩子处理，其余走 Tauri 全局快捷键。
+/// 注册与注销要等主线程处理，期间不能持有 GlobalShortcutState 的锁：
+/// 主线程上的命令或快捷键回调也会获取这把锁，持锁等待会造成死锁。
+fn apply_bindings(app_handle: &tauri::AppHandle, bindings: &HotkeyBindings) -> Result<(), String> {
+    let shortcut_state = app_handle.state::<Mutex<GlobalShortcutState>>();
+    let previous = std::mem::take(&mut shortcut_state.lock().unwrap().registered);
+    let mut manager = app_handle.global_shortcut_manager();
+
+    // 先注销之前注册过的快捷键
+    for old_accel in previous.values() {
+        let _ = manager.unregister(old_accel);
+    }
+
+    // 同时绑定了长按的组合键需要由钩子区分短按与长按
+    let long_press: Vec<&str> = bindings
+        .values()
+        .filter_map(|config| match &config.trigger {
+            Some(HotkeyTrigger::LongPress { accelerator, .. }) => Some(accelerator.as_str()),
+            _ => None,
+        })
+        .collect();
+
+    let mut registered = BTreeMap::new();
+    let mut errors = Vec::new();
+    let mut hook_bindings = Vec::new();
+    let mut trigger_bindings = Vec::new();
+    let mut ctrl_v_intercept = None;
+    for (action, config) in bindings {
+        let action = *action;
+        let accelerator = config.to_tauri_accelerator();
+
+        // 中止快捷键（如 Esc）只在粘贴期间注册，平时保持按键原有行为
+        if action == HotkeyAction::Abort {
+            continue;
+        }
+
+        if let Some(trigger) = &config.trigger {
+            trigger_bindings.push((trigger.clone(), action));
+            continue;
+        }
+
+        // 劫持 Ctrl+V 由钩子按需放行，不能注册为全局快捷键
+        if config.intercept_ctrl_v {
+            ctrl_v_intercept = Some(config.intercept_exclude.clone());
+            continue;
+        }
+
+        if hook::requires_hook(&accelerator)
+            || long_press
+                .iter()
+                .any(|a| a.eq_ignore_ascii_case(&accelerator))
+        {
+            match KeyCombo::parse(&accelerator) {
+                Some(combo) => hook_bindings.push((combo, action)),
+                None => errors.push(i18n::tf(
+                    "error.unknown_hotkey",
+                    &[("hotkey", &accelerator)],
+                )),
+            }
+            continue;
+        }
+
+        let app_handle_clone = app_handle.clone();
+
+        match manager.register(&accelerator, move || trigger_action(&app_handle_clone, action)) {
+            Ok(_) => {
+                #[cfg(debug_assertions)]
+                println!("全局快捷键 \"{}\" ({:?}) 已注册成功", accelerator, action);
+
+                registered.insert(action, accelerator);
+            }
+            Err(e) => {
+                #[cfg(debug_assertions)]
+                println!("全局快捷键 \"{}\" ({:?}) 注册失败: {}", accelerator, action, e);
+
+                errors.push(i18n::tf(
+                    "error.hotkey_register",
+                    &[("hotkey", &accelerator), ("error", &e)],
+                ));
+            }
+        }
+    }
+
+    // 其他线程可能在此期间也完成了注册：以本次为准，注销对方留下的快捷键
+    let stale = std::mem::replace(
+        &mut shortcut_state.lock().unwrap().registered,
+        registered.clone(),
+    );
+    for accelerator in stale.values() {
+        if !registered.values().any(|a| a == accelerator) {
+            let _ = manager.unregister(accelerator);
+        }
+    }
+
+    hook::set_key_bindings(app_handle, hook_bindings);
+    hook::set_trigger_bindings(app_handle, trigger_bindings);
+    hook::set_ctrl_v_intercept(app_handle, ctrl_v_intercept);
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join("；"))
+    }
+}
+
+/// 设置暂停快捷键的程序列表，并立即按当前前台程序更新暂停状态
+pub fn set_suspend_apps(app_handle: &tauri::AppHandle, apps: Vec<String>) {
+    let shortcut_state = app_handle.state::<Mutex<GlobalShortcutState>>();
+    shortcut_state.lock().unwrap().suspend_apps = apps;
+    update_suspended(app_handle);
+}
+
+/// 获取暂停快捷键的程序列表
+pub fn suspend_apps(app_handle: &tauri::AppHandle) -> Vec<String> {
+    let shortcut_state = app_handle.state::<Mutex<GlobalShortcutState>>();
+    let locked_state = shortcut_state.lock().unwrap();
+    locked_state.suspend_apps.clone()
+}
+
+/// 后台线程：轮询前台程序，进入/离开列表中的程序时注销/恢复全部快捷键
+pub fn spawn_suspend_watcher(app_handle: tauri::AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(std::time::Duration::from_millis(300));
+
+        let shortcut_state = app_handle.state::<Mutex<GlobalShortcutState>>();
+        {
+            let locked_state = shortcut_state.lock().unwrap();
+            if locked_state.suspend_apps.is_empty() && !locked_state.suspended {
+                continue;
+            }
+        }
+        update_suspended(&app_handle);
+    });
+}
+
+/// 根据当前前台程序暂停或恢复快捷键
+fn update_suspended(app_handle: &tauri::AppHandle) {
+    let process_name = foreground::foreground_process_name();
+    let bindings = {
+        let shortcut_state = app_handle.state::<Mutex<GlobalShortcutState>>();
+        let mut locked_state = shortcut_state.lock().unwrap();
+        let suspended = process_name
+            .map(|name| foreground::matches_process(&locked_state.suspend_apps, &name))
+            .unwrap_or(false);
+        if suspended == locked_state.suspended {
+            return;
+        }
+
+        #[cfg(debug_assertions)]
+        println!(
+            "前台程序切换，{}全局快捷键",
+            if suspended { "暂停" } else { "恢复" }
+        );
+
+        locked_state.suspended = suspended;
+        if suspended {
+            HotkeyBindings::new()
+        } else {
+            locked_state.active.clone()
+        }
+    };
+    let bindings = with_snippet_hotkeys(app_handle, &bindings);
+    if let Err(e) = apply_bindings(app_handle, &bindings) {
+        #[cfg(debug_assertions)]
+        println!("恢复快捷键失败: {}", e);
+        notify::hotkey_failed(app_handle, e);
+    }
+}
+
+/// 退出前注销全部快捷键；之后前台程序切换也不会再注册
+pub fn unregister_all(app_handle: &tauri::AppHandle) {
+    let shortcut_state = app_handle.state::<Mutex<GlobalShortcutState>>();
+    shortcut_state.lock().unwrap().active = HotkeyBindings::new();
+    let _ = apply_bindings(app_handle, &HotkeyBindings::new());
+    let _ = app_handle.global_shortcut_manager().unregister_all();
+}
+
+/// 粘贴开始/结束时注册/注销中止快捷键（与 apply_bindings 相同，注册时不持有锁）
+pub fn set_abort_hotkey_active(app_handle: &tauri::AppHandle, active: bool) {
+    let shortcut_state = app_handle.state::<Mutex<GlobalShortcutState>>();
+    let mut manager = app_handle.global_shortcut_manager();
+
+    if !active {
+        let accelerator = shortcut_state
+            .lock()
+            .unwrap()
+            .registered
+            .remove(&HotkeyAction::Abort);
+        if let Some(accelerator) = accelerator {
+            let _ = manager.unregister(&accelerator);
+        }
+        return;
+    }
+
+    let accelerator = {
+        let locked_state = shortcut_state.lock().unwrap();
+        let Some(config) = locked_state.active.get(&HotkeyAction::Abort) else {
+            return;
+        };
+        let accelerator = config.to_tauri_accelerator();
+        if locked_state.registered.values().any(|a| *a == accelerator) {
+            return;
+        }
+        accelerator
+    };
+    let app_handle_clone = app_handle.clone();
+    match manager.register(&accelerator, move || {
+        trigger_action(&app_handle_clone, HotkeyAction::Abort)
+    }) {
+        Ok(_) => {
+            shortcut_state
+                .lock()
+                .unwrap()
+                .registered
+                .insert(HotkeyAction::Abort, accelerator);
+        }
+        Err(e) => {
+            #[cfg(debug_assertions)]
+            println!("中止快捷键 \"{}\" 注册失败: {}", accelerator, e);
+        }
+    }
+}
+
+/// 执行快捷键对应的动作
+pub fn trigger_action(app_handle: &tauri::AppHandle, action: HotkeyAction) {
+    #[cfg(debug_assertions)]
+    println!("全局快捷键被触发: {:?}", action);
+
+    let state = app_handle.state::<Mutex<PasteState>>();
+    match action {
+        HotkeyAction::Paste => {
+            if state.lock().unwrap().is_paused {
+                #[cfg(debug_assertions)]
+                println!("应用已暂停，忽略快捷键");
+
+                return;
+            }
+            // 以设置中的默认速度输入
+            let (stand, float) = commands::resolve_timing(app_handle, None, None);
+            let app_handle = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(_e) = commands::paste_text(&app_handle, None, stand, float).await {
+                    #[cfg(debug_assertions)]
+                    println!("粘贴失败: {}", _e);
+                }
+            });
+        }
+        HotkeyAction::TogglePause => {
+            commands::toggle_paused(app_handle);
+        }
+        HotkeyAction::Abort => {
+            let mut locked = state.lock().unwrap();
+            locked.abort("hotkey");
+        }
+        HotkeyAction::ToggleInstant => {
+            commands::toggle_instant(app_handle);
+        }
+        HotkeyAction::ToggleTypingPause => {
+            let paused = {
+                let locked = state.lock().unwrap();
+                locked.typing_paused
+            };
+            let _ = commands::set_typing_paused(app_handle, !paused);
+        }
+        HotkeyAction::ToggleExpander => {
+            crate::expander::toggle(app_handle);
+        }
+        HotkeyAction::NextChunk => {
+            let app_handle = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(_e) = crate::chunks::type_next(&app_handle).await {
+                    #[cfg(debug_assertions)]
+                    println!("分段输入失败: {}", _e);
+                }
+            });
+        }
+        HotkeyAction::PasteSelection => {
+            if state.lock().unwrap().is_paused {
+                return;
+            }
+            let (stand, float) = commands::resolve_timing(app_handle, None, None);
+            let options = PasteOptions {
+                text_source: Some(TextSource::Primary),
+                ..PasteOptions::default()
+            };
+            let app_handle = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(_e) =
+                    commands::paste_with(&app_handle, None, stand, float, options).await
+                {
+                    #[cfg(debug_assertions)]
+                    println!("输入选中文本失败: {}", _e);
+                }
+            });
+        }
+        HotkeyAction::RetypeLast => {
+            let app_handle = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(_e) = commands::retype_last_text(&app_handle, None).await {
+                    #[cfg(debug_assertions)]
+                    println!("重新输入失败: {}", _e);
+                }
+            });
+        }
+        HotkeyAction::Snippet(id) => {
+            if state.lock().unwrap().is_paused {
+                return;
+            }
+            if let Err(_e) = snippets::start_snippet(app_handle, &id.to_string()) {
+                #[cfg(debug_assertions)]
+                println!("输入片段 #{} 失败: {}", id, _e);
+            }
+        }
+    }
+}
@@ -0,0 +1,138 @@
+// 界面文字的查找。paster-cli 也直接包含此文件，只能使用标准库、serde、serde_json 与 windows；
+// paster-cli 不读取设置，总是跟随系统语言
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::{OnceLock, RwLock};
+
+use serde::{Deserialize, Serialize};
+#[cfg(windows)]
+use windows::Win32::Globalization::GetUserDefaultLocaleName;
+
+/// 内置的界面文字，键为 "tray.quit" 这样的编号
+const ZH_CN: &str = include_str!("../locales/zh-CN.json");
+const EN_US: &str = include_str!("../locales/en-US.json");
+
+/// LOCALE_NAME_MAX_LENGTH
+#[cfg(windows)]
+const LOCALE_NAME_CHARS: usize = 85;
+
+/// 设置中选择的语言，None 表示跟随系统
+static PREFERENCE: RwLock<Option<Language>> = RwLock::new(None);
+static SYSTEM_LANGUAGE: OnceLock<Language> = OnceLock::new();
+static CATALOGS: OnceLock<HashMap<Language, HashMap<String, String>>> = OnceLock::new();
+
+/// 托盘、通知与命令错误信息使用的语言
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Language {
+    #[serde(rename = "zh-CN")]
+    ZhCn,
+    #[serde(rename = "en-US")]
+    EnUs,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::ZhCn, Language::EnUs];
+
+    /// 按区域名称（如 "zh-CN"、"zh-Hans-SG"、"en-GB"）选择语言，中文以外都使用英文
+    fn from_locale(locale: &str) -> Self {
+        if locale.to_ascii_lowercase().starts_with("zh") {
+            Language::ZhCn
+        } else {
+            Language::EnUs
+        }
+    }
+
+    fn source(self) -> &'static str {
+        match self {
+            Language::ZhCn => ZH_CN,
+            Language::EnUs => EN_US,
+        }
+    }
+}
+
+/// 系统的区域名称（如 "zh-CN"）
+#[cfg(windows)]
+fn system_locale() -> Option<String> {
+    let mut buffer = [0u16; LOCALE_NAME_CHARS];
+    let len = unsafe { GetUserDefaultLocaleName(&mut buffer) };
+    if len <= 1 {
+        return None;
+    }
+    Some(String::from_utf16_lossy(&buffer[..len as usize - 1]))
+}
+
+/// 按 POSIX 的优先顺序读取区域环境变量（如 "zh_CN.UTF-8"）
+#[cfg(not(windows))]
+fn system_locale() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|locale| !locale.is_empty())
+}
+
+/// 系统的界面语言，读取失败时使用中文
+fn system_language() -> Language {
+    *SYSTEM_LANGUAGE.get_or_init(|| {
+        let Some(locale) = system_locale() else {
+            return Language::ZhCn;
+        };
+
+        #[cfg(debug_assertions)]
+        eprintln!("系统区域: {}", locale);
+
+        Language::from_locale(&locale)
+    })
+}
+
+fn catalog(language: Language) -> &'static HashMap<String, String> {
+    let catalogs = CATALOGS.get_or_init(|| {
+        Language::ALL
+            .iter()
+            .map(|&language| {
+                let entries = serde_json::from_str(language.source()).unwrap_or_else(|_e| {
+                    #[cfg(debug_assertions)]
+                    eprintln!("解析语言文件 {:?} 失败: {}", language, _e);
+                    HashMap::new()
+                });
+                (language, entries)
+            })
+            .collect()
+    });
+    &catalogs[&language]
+}
+
+/// 设置中选择的语言（None 表示跟随系统）
+pub fn preference() -> Option<Language> {
+    *PREFERENCE.read().unwrap()
+}
+
+/// 切换语言；调用方负责重建托盘
+pub fn set_preference(language: Option<Language>) {
+    *PREFERENCE.write().unwrap() = language;
+}
+
+/// 当前使用的语言
+pub fn current() -> Language {
+    preference().unwrap_or_else(system_language)
+}
+
+/// 查找当前语言的文字，缺少时使用中文
+pub fn lookup(key: &str) -> Option<&'static str> {
+    catalog(current())
+        .get(key)
+        .or_else(|| catalog(Language::ZhCn).get(key))
+        .map(String::as_str)
+}
+
+/// 当前语言的文字；两种语言都缺少时返回编号本身
+pub fn t(key: &'static str) -> &'static str {
+    lookup(key).unwrap_or(key)
+}
+
+/// 带参数的文字：把 "{name}" 替换为对应的值
+pub fn tf(key: &'static str, args: &[(&str, &dyn Display)]) -> String {
+    args.iter().fold(t(key).to_string(), |text, (name, value)| {
+        text.replace(&format!("{{{}}}", name), &value.to_string())
+    })
+}
@@ -0,0 +1,38 @@
+mod catalog;
+
+use serde::Serialize;
+use tauri::Manager;
+
+pub use catalog::{current, lookup, preference, set_preference, t, tf, Language};
+
+use crate::settings;
+use crate::tray;
+
+/// get_language 的返回值
+#[derive(Debug, Clone, Serialize)]
+pub struct LanguageInfo {
+    /// 设置中选择的语言，null 表示跟随系统
+    pub preference: Option<Language>,
+    /// 当前使用的语言
+    pub current: Language,
+    pub available: Vec<Language>,
+}
+
+/// 获取语言设置
+#[tauri::command]
+pub fn get_language() -> LanguageInfo {
+    LanguageInfo {
+        preference: preference(),
+        current: current(),
+        available: Language::ALL.to_vec(),
+    }
+}
+
+/// 切换托盘、通知与错误信息的语言（null 表示跟随系统），保存到设置并重建托盘
+#[tauri::command]
+pub fn set_language(language: Option<Language>, app_handle: tauri::AppHandle) {
+    set_preference(language);
+    settings::persist(&app_handle);
+    tray::refresh(&app_handle);
+    let _ = app_handle.emit_all("language-changed", get_language());
+}
@@ -0,0 +1,75 @@
+// 连接已运行实例的进程间通道。paster-cli 也直接包含此文件，只能使用标准库与 serde_json
+
+use std::io::{BufRead, BufReader, Write};
+#[cfg(unix)]
+use std::path::PathBuf;
+
+use serde_json::Value;
+
+/// 本机进程间通道：Windows 为命名管道，其余平台为 Unix 套接字，供 AutoHotkey、PowerShell 等脚本调用。
+/// 每行一条 JSON 请求（如 {"command":"paste","text":"..."}），
+/// 每条请求应答一行 {"ok":true,"result":...} 或 {"ok":false,"error":"..."}
+#[cfg(windows)]
+pub const PIPE_NAME: &str = r"\\.\pipe\paster";
+#[cfg(unix)]
+const SOCKET_FILE_NAME: &str = "paster.sock";
+
+/// Unix 套接字的位置：$XDG_RUNTIME_DIR，没有时为 private_dir()
+#[cfg(unix)]
+pub fn socket_path() -> PathBuf {
+    runtime_dir()
+        .unwrap_or_else(private_dir)
+        .join(SOCKET_FILE_NAME)
+}
+
+/// 系统按用户创建、只有本用户能访问的 $XDG_RUNTIME_DIR
+#[cfg(unix)]
+pub fn runtime_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_RUNTIME_DIR")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+}
+
+/// 没有 $XDG_RUNTIME_DIR 时存放套接字的目录：临时目录下的 paster-$USER，
+/// 由监听的实例以 0700 权限创建，其他用户无法进入
+#[cfg(unix)]
+pub fn private_dir() -> PathBuf {
+    let user = std::env::var("USER").unwrap_or_default();
+    std::env::temp_dir().join(format!("paster-{}", user))
+}
+
+/// 检查 private_dir() 确实是只有所有者能访问的目录（不是符号链接），
+/// 避免连接或监听其他用户预先放置的目录中的套接字
+#[cfg(unix)]
+pub fn check_private_dir(dir: &std::path::Path) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let meta = std::fs::symlink_metadata(dir)?;
+    if !meta.is_dir() || meta.permissions().mode() & 0o077 != 0 {
+        return Err(std::io::ErrorKind::PermissionDenied.into());
+    }
+    Ok(())
+}
+
+/// 向已运行的实例发送一条请求并等待应答；没有实例在运行时返回错误
+pub fn send(request: &Value) -> std::io::Result<Value> {
+    #[cfg(windows)]
+    let stream = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(PIPE_NAME)?;
+    #[cfg(unix)]
+    let stream = {
+        if runtime_dir().is_none() {
+            check_private_dir(&private_dir())?;
+        }
+        std::os::unix::net::UnixStream::connect(socket_path())?
+    };
+
+    let mut line = request.to_string();
+    line.push('\n');
+    (&stream).write_all(line.as_bytes())?;
+    let mut response = String::new();
+    BufReader::new(&stream).read_line(&mut response)?;
+    Ok(serde_json::from_str(&response)?)
+}
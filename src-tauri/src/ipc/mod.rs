@@ -0,0 +1,209 @@
+mod client;
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+
+#[cfg(unix)]
+use client::{check_private_dir, private_dir, runtime_dir, socket_path};
+#[cfg(windows)]
+use client::PIPE_NAME;
+
+use crate::api::{self, PasteRequest};
+use crate::deeplink;
+use crate::i18n;
+use crate::launch;
+use crate::native_host;
+use crate::profiles;
+use crate::snippets;
+
+/// 单条请求的最大长度
+const MAX_LINE_BYTES: u64 = 4 * 1024 * 1024;
+/// 浏览器扩展发来的文本开始输入前的倒计时，留出切换到目标程序的时间
+const EXTENSION_START_DELAY_SECS: u32 = 3;
+
+/// 通道上的请求，以 command 字段区分
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum Request {
+    /// 开始输入，不等待输入结束；参数同 HTTP 接口的 POST /paste
+    Paste(PasteRequest),
+    /// 中止正在进行的粘贴，结果为 {"aborted": 是否有粘贴被中止}
+    Abort,
+    /// 选用配置方案，name 为 null 时取消选用
+    SetProfile { name: Option<String> },
+    /// 输入引擎的状态，同 HTTP 接口的 GET /status
+    Status,
+    /// 输入片段（按编号或标题查找），不等待输入结束
+    Snippet { name: String },
+    /// 打开 paster:// 链接（确认后输入）
+    OpenUrl { url: String },
+    /// 之后启动的进程转发的启动参数与工作目录
+    Launch {
+        args: Vec<String>,
+        cwd: Option<String>,
+    },
+    /// 浏览器扩展经原生消息主机（paster-cli）发来的请求，origin 为浏览器告知主机的扩展来源
+    Extension {
+        origin: String,
+        request: ExtensionRequest,
+    },
+}
+
+/// 浏览器扩展可以发送的请求，只接受允许列表中的扩展
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum ExtensionRequest {
+    /// 输入网页中选中的文本，未指定 delay 时倒计时 3 秒
+    Paste(PasteRequest),
+    Abort,
+    Status,
+}
+
+/// 向已运行的实例发送一条请求并等待应答；没有实例在运行时返回错误
+pub fn send(request: &Request) -> std::io::Result<Value> {
+    client::send(&serde_json::to_value(request)?)
+}
+
+/// 开始监听；已有实例在监听时不做任何事
+pub fn start(app_handle: &tauri::AppHandle) {
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(_e) = listen(app_handle).await {
+            #[cfg(debug_assertions)]
+            eprintln!("启动进程间通道失败: {}", _e);
+        }
+    });
+}
+
+#[cfg(windows)]
+async fn listen(app_handle: tauri::AppHandle) -> std::io::Result<()> {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    // first_pipe_instance：管道已被其他实例创建时失败
+    let mut server = ServerOptions::new()
+        .first_pipe_instance(true)
+        .create(PIPE_NAME)?;
+    loop {
+        server.connect().await?;
+        let connected = server;
+        server = ServerOptions::new().create(PIPE_NAME)?;
+        let app_handle = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            handle_connection(&app_handle, connected).await;
+        });
+    }
+}
+
+#[cfg(unix)]
+async fn listen(app_handle: tauri::AppHandle) -> std::io::Result<()> {
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+    use tokio::net::UnixListener;
+
+    // 没有 $XDG_RUNTIME_DIR 时在只有本用户能访问的目录中创建套接字，
+    // 其他用户既无法抢先占用套接字，也无法在设置权限前连接
+    if runtime_dir().is_none() {
+        let dir = private_dir();
+        match std::fs::DirBuilder::new().mode(0o700).create(&dir) {
+            Err(e) if e.kind() != std::io::ErrorKind::AlreadyExists => return Err(e),
+            _ => {}
+        }
+        check_private_dir(&dir)?;
+    }
+    let path = socket_path();
+    if path.exists() {
+        // 能连上说明其他实例正在监听；否则是上次异常退出留下的文件
+        if std::os::unix::net::UnixStream::connect(&path).is_ok() {
+            return Err(std::io::ErrorKind::AddrInUse.into());
+        }
+        std::fs::remove_file(&path)?;
+    }
+    let listener = UnixListener::bind(&path)?;
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+
+    #[cfg(debug_assertions)]
+    println!("进程间通道已启动: {}", path.display());
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let app_handle = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            handle_connection(&app_handle, stream).await;
+        });
+    }
+}
+
+/// 逐行读取请求并应答，直到对方断开连接
+async fn handle_connection<S: AsyncRead + AsyncWrite>(app_handle: &tauri::AppHandle, stream: S) {
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut reader = BufReader::new(reader);
+    loop {
+        let mut line = String::new();
+        match (&mut reader)
+            .take(MAX_LINE_BYTES)
+            .read_line(&mut line)
+            .await
+        {
+            Ok(0) | Err(_) => return,
+            // 超出长度限制，无法再分辨之后的请求
+            Ok(read) if read as u64 >= MAX_LINE_BYTES => return,
+            Ok(_) => {}
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(request) => handle(app_handle, request).await,
+            Err(e) => Err(e.to_string()),
+        };
+        let mut response = match response {
+            Ok(body) => json!({ "ok": true, "result": body }),
+            Err(e) => json!({ "ok": false, "error": e }),
+        }
+        .to_string();
+        response.push('\n');
+        if writer.write_all(response.as_bytes()).await.is_err() {
+            return;
+        }
+    }
+}
+
+pub async fn handle(app_handle: &tauri::AppHandle, request: Request) -> Result<Value, String> {
+    match request {
+        Request::Paste(paste) => paste
+            .start(app_handle)
+            .map(|_| json!({ "accepted": true }))
+            .map_err(|(_, e)| e),
+        Request::Abort => Ok(json!({ "aborted": api::abort_paste(app_handle, "ipc") })),
+        Request::SetProfile { name } => {
+            profiles::switch_profile(name, app_handle.clone()).map(|_| Value::Null)
+        }
+        Request::Status => Ok(api::status_body(app_handle)),
+        Request::Snippet { name } => {
+            snippets::start_snippet(app_handle, &name).map(|_| json!({ "accepted": true }))
+        }
+        Request::OpenUrl { url } => deeplink::open(app_handle, &url).map(|_| Value::Null),
+        Request::Launch { args, cwd } => {
+            launch::handle_forwarded(app_handle, &args, cwd.as_deref().map(Path::new));
+            Ok(Value::Null)
+        }
+        Request::Extension { origin, request } => {
+            if !native_host::is_allowed(&origin) {
+                return Err(i18n::tf("error.native_host_origin", &[("origin", &origin)]));
+            }
+            match request {
+                ExtensionRequest::Paste(paste) => paste
+                    .with_default_delay(EXTENSION_START_DELAY_SECS)
+                    .start(app_handle)
+                    .map(|_| json!({ "accepted": true }))
+                    .map_err(|(_, e)| e),
+                ExtensionRequest::Abort => Ok(json!({
+                    "aborted": api::abort_paste(app_handle, "extension")
+                })),
+                ExtensionRequest::Status => Ok(api::status_body(app_handle)),
+            }
+        }
+    }
+}
@@ -0,0 +1,137 @@
+// KeePassXC 凭据片段：片段只保存条目名称，每次输入时通过 KeePassXC 的 Secret Service 集成
+// （freedesktop.org Secret Service API）读取用户名与密码，密码不写入 Paster 的任何文件
+
+use zeroize::Zeroizing;
+
+use crate::commands::{PasteAction, PASTE_ACTION_MARKER};
+use crate::i18n;
+use crate::platform::{VK_RETURN, VK_TAB};
+
+/// 当前构建能否读取 KeePassXC 的凭据
+pub const SUPPORTED: bool = cfg!(all(target_os = "linux", feature = "keepassxc"));
+
+/// 从 KeePassXC 读取的凭据，用完即清零
+struct Credential {
+    username: Zeroizing<String>,
+    password: Zeroizing<String>,
+}
+
+/// 把条目展开为一次输入任务：用户名、Tab、密码，submit 为 true 时再按 Enter
+pub async fn compile(entry: &str, submit: bool) -> Result<(Vec<u16>, Vec<PasteAction>), String> {
+    let entry = entry.trim().to_string();
+    if entry.is_empty() {
+        return Err(i18n::t("error.keepassxc_no_entry").to_string());
+    }
+    // D-Bus 调用是阻塞的，且 KeePassXC 可能弹出访问确认
+    let credential = tauri::async_runtime::spawn_blocking(move || fetch(&entry))
+        .await
+        .map_err(|e| e.to_string())??;
+
+    let mut units: Vec<u16> = credential.username.encode_utf16().collect();
+    let mut actions = Vec::new();
+    units.push(PASTE_ACTION_MARKER);
+    actions.push(PasteAction::Keys(vec![VK_TAB.0]));
+    units.extend(credential.password.encode_utf16());
+    if submit {
+        units.push(PASTE_ACTION_MARKER);
+        actions.push(PasteAction::Keys(vec![VK_RETURN.0]));
+    }
+    Ok((units, actions))
+}
+
+#[cfg(all(target_os = "linux", feature = "keepassxc"))]
+fn fetch(entry: &str) -> Result<Credential, String> {
+    secret_service::fetch(entry)
+}
+
+#[cfg(not(all(target_os = "linux", feature = "keepassxc")))]
+fn fetch(_entry: &str) -> Result<Credential, String> {
+    Err(i18n::t("error.keepassxc_unsupported").to_string())
+}
+
+#[cfg(all(target_os = "linux", feature = "keepassxc"))]
+mod secret_service {
+    use std::collections::HashMap;
+
+    use zbus::blocking::{Connection, Proxy};
+    use zbus::zvariant::{OwnedObjectPath, OwnedValue, Value};
+    use zeroize::Zeroizing;
+
+    use super::Credential;
+    use crate::i18n;
+
+    const DESTINATION: &str = "org.freedesktop.secrets";
+    const SERVICE_PATH: &str = "/org/freedesktop/secrets";
+    const SERVICE: &str = "org.freedesktop.Secret.Service";
+    const ITEM: &str = "org.freedesktop.Secret.Item";
+    const SESSION: &str = "org.freedesktop.Secret.Session";
+    /// KeePassXC 为每个条目提供的属性：标题、用户名，以及含分组的路径（如 "工作/邮箱"）
+    const TITLE_ATTRIBUTE: &str = "Title";
+    const PATH_ATTRIBUTE: &str = "Path";
+    const USERNAME_ATTRIBUTE: &str = "UserName";
+
+    fn dbus_error(_e: zbus::Error) -> String {
+        #[cfg(debug_assertions)]
+        eprintln!("调用 Secret Service 失败: {}", _e);
+        i18n::t("error.keepassxc_unavailable").to_string()
+    }
+
+    /// 按标题查找条目；名称中含 "/" 时按分组路径查找
+    pub fn fetch(entry: &str) -> Result<Credential, String> {
+        let connection = Connection::session().map_err(dbus_error)?;
+        let service =
+            Proxy::new(&connection, DESTINATION, SERVICE_PATH, SERVICE).map_err(dbus_error)?;
+
+        let attribute = if entry.contains('/') {
+            PATH_ATTRIBUTE
+        } else {
+            TITLE_ATTRIBUTE
+        };
+        let query = HashMap::from([(attribute, entry)]);
+        let (unlocked, locked): (Vec<OwnedObjectPath>, Vec<OwnedObjectPath>) =
+            service.call("SearchItems", &(query,)).map_err(dbus_error)?;
+        let item = match (unlocked.as_slice(), locked.is_empty()) {
+            ([item], _) => item.clone(),
+            ([], false) => return Err(i18n::t("error.keepassxc_locked").to_string()),
+            ([], true) => {
+                return Err(i18n::tf("error.keepassxc_missing", &[("entry", &entry)]));
+            }
+            _ => return Err(i18n::tf("error.keepassxc_ambiguous", &[("entry", &entry)])),
+        };
+
+        // plain 会话：密码以明文经会话总线传输，与 libsecret 的默认方式相同
+        let (_, session): (OwnedValue, OwnedObjectPath) = service
+            .call("OpenSession", &("plain", Value::from("")))
+            .map_err(dbus_error)?;
+        let result = read_item(&connection, &item, &session);
+        if let Ok(session) = Proxy::new(&connection, DESTINATION, session.as_str(), SESSION) {
+            let _ = session.call_method("Close", &());
+        }
+        result
+    }
+
+    fn read_item(
+        connection: &Connection,
+        item: &OwnedObjectPath,
+        session: &OwnedObjectPath,
+    ) -> Result<Credential, String> {
+        let item = Proxy::new(connection, DESTINATION, item.as_str(), ITEM).map_err(dbus_error)?;
+        let attributes: HashMap<String, String> =
+            item.get_property("Attributes").map_err(dbus_error)?;
+        // Secret 结构：会话、参数、值、内容类型
+        let (_, _, value, _): (OwnedObjectPath, Vec<u8>, Vec<u8>, String) =
+            item.call("GetSecret", &(session,)).map_err(dbus_error)?;
+        let value = Zeroizing::new(value);
+        let password = std::str::from_utf8(&value)
+            .map_err(|_| i18n::t("error.keepassxc_unavailable").to_string())?;
+        Ok(Credential {
+            username: Zeroizing::new(
+                attributes
+                    .get(USERNAME_ATTRIBUTE)
+                    .cloned()
+                    .unwrap_or_default(),
+            ),
+            password: Zeroizing::new(password.to_string()),
+        })
+    }
+}
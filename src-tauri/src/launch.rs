@@ -0,0 +1,113 @@
+use std::path::{Path, PathBuf};
+
+use tauri::Manager;
+
+use crate::deeplink;
+use crate::file_input;
+use crate::ipc;
+use crate::notify;
+use crate::profiles;
+
+/// 启动参数。首次启动与之后启动的进程转发给已运行实例的参数使用同样的解析
+#[derive(Debug, Default)]
+pub struct LaunchArgs {
+    /// --silent / -s：不显示窗口
+    pub silent: bool,
+    /// --paste-file 路径：倒计时后输入文件内容（相对路径按启动时的工作目录解析）
+    pub paste_file: Option<PathBuf>,
+    /// --profile 名称：选用配置方案
+    pub profile: Option<String>,
+    /// 系统打开 paster:// 链接时传入的链接
+    pub url: Option<String>,
+}
+
+impl LaunchArgs {
+    /// 解析参数（不含程序路径），无法识别的参数被忽略
+    pub fn parse(args: &[String], cwd: Option<&Path>) -> Self {
+        let mut parsed = Self::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--silent" | "-s" => parsed.silent = true,
+                "--paste-file" => {
+                    parsed.paste_file = args.next().map(|path| match cwd {
+                        Some(cwd) => cwd.join(path),
+                        None => PathBuf::from(path),
+                    });
+                }
+                "--profile" => parsed.profile = args.next().cloned(),
+                _ if deeplink::is_link(arg) => parsed.url = Some(arg.clone()),
+                _ => {}
+            }
+        }
+        parsed
+    }
+
+    /// 本进程的启动参数
+    pub fn from_env() -> Self {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        Self::parse(&args, None)
+    }
+
+    /// 参数中有要在后台执行的操作（输入文件、打开链接），此时不显示主窗口
+    pub fn runs_in_background(&self) -> bool {
+        self.paste_file.is_some() || self.url.is_some()
+    }
+}
+
+/// 已有实例在运行时，把本次启动的参数与工作目录交给它处理；返回 true 表示本进程应直接退出
+pub fn forward_to_running_instance() -> bool {
+    let request = ipc::Request::Launch {
+        args: std::env::args().skip(1).collect(),
+        cwd: std::env::current_dir()
+            .ok()
+            .map(|dir| dir.to_string_lossy().into_owned()),
+    };
+    match ipc::send(&request) {
+        Ok(_) => true,
+        Err(_e) => {
+            #[cfg(debug_assertions)]
+            println!("没有正在运行的实例: {}", _e);
+            false
+        }
+    }
+}
+
+/// 执行启动参数中的操作：选用配置方案、打开链接、输入文件
+pub fn run(app_handle: &tauri::AppHandle, args: LaunchArgs) {
+    if let Some(name) = args.profile {
+        if let Err(e) = profiles::switch_profile(Some(name), app_handle.clone()) {
+            notify::launch_failed(app_handle, &e);
+        }
+    }
+    if let Some(url) = args.url {
+        // 链接无效时 open 已弹出通知
+        let _ = deeplink::open(app_handle, &url);
+    }
+    if let Some(path) = args.paste_file {
+        let app_handle = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            let path = path.to_string_lossy().into_owned();
+            if let Err(e) = file_input::type_file(path, None, None, app_handle.clone()).await {
+                notify::launch_failed(&app_handle, &e);
+            }
+        });
+    }
+}
+
+/// 之后启动的进程转发的参数：没有要在后台执行的操作且未指定 --silent 时显示主窗口，
+/// 而不是再启动一个重复注册快捷键的实例
+pub fn handle_forwarded(app_handle: &tauri::AppHandle, args: &[String], cwd: Option<&Path>) {
+    let args = LaunchArgs::parse(args, cwd);
+
+    #[cfg(debug_assertions)]
+    println!("收到后续启动的参数: {:?}", args);
+
+    if !args.silent && !args.runs_in_background() {
+        if let Some(window) = app_handle.get_window("main") {
+            let _ = window.show();
+            let _ = window.set_focus();
+        }
+    }
+    run(app_handle, args);
+}
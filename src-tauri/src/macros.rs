@@ -0,0 +1,125 @@
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+use crate::commands::{PasteAction, PASTE_ACTION_MARKER};
+use crate::hook;
+use crate::i18n;
+use crate::keepassxc;
+use crate::snippets::{self, SnippetKind, SnippetStore};
+use crate::templates;
+
+/// 单个等待步骤的上限（毫秒）
+const MAX_DELAY_MS: u32 = 60_000;
+
+/// 宏片段的一步
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MacroStep {
+    /// 输入片段库中的另一个片段（不能是宏）
+    Snippet { snippet_id: u64 },
+    /// 输入文本，支持模板变量
+    Text { text: String },
+    /// 按键，写法与快捷键相同，如 "Tab"、"Control+Enter"
+    Key { key: String },
+    /// 等待毫秒数
+    Delay { ms: u32 },
+}
+
+/// 保存宏片段前检查各步骤
+pub fn validate(steps: &[MacroStep], store: &SnippetStore) -> Result<(), String> {
+    if steps.is_empty() {
+        return Err(i18n::t("error.macro_empty").to_string());
+    }
+    for (i, step) in steps.iter().enumerate() {
+        let step_no = i + 1;
+        match step {
+            MacroStep::Snippet { snippet_id } => match store.get(*snippet_id) {
+                None => {
+                    return Err(i18n::tf(
+                        "error.macro_step_snippet_missing",
+                        &[("step", &step_no), ("id", snippet_id)],
+                    ))
+                }
+                Some(snippet) if snippet.kind == SnippetKind::Macro => {
+                    return Err(i18n::tf("error.macro_step_nested", &[("step", &step_no)]))
+                }
+                Some(_) => {}
+            },
+            MacroStep::Text { text } => {
+                templates::expand(text).map_err(|e| {
+                    i18n::tf("error.macro_step", &[("step", &step_no), ("error", &e)])
+                })?;
+            }
+            MacroStep::Key { key } => {
+                if hook::parse_key_stroke(key).is_none() {
+                    return Err(i18n::tf(
+                        "error.macro_step_key",
+                        &[("step", &step_no), ("key", key)],
+                    ));
+                }
+            }
+            MacroStep::Delay { ms } => {
+                if *ms > MAX_DELAY_MS {
+                    return Err(i18n::tf(
+                        "error.macro_step_delay",
+                        &[("step", &step_no), ("max", &MAX_DELAY_MS)],
+                    ));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 把宏展开为一次输入任务：文本部分直接输入，按键与等待以动作标记插入文本中
+pub async fn compile(
+    app_handle: &tauri::AppHandle,
+    steps: &[MacroStep],
+) -> Result<(Vec<u16>, Vec<PasteAction>), String> {
+    let mut units = Vec::new();
+    let mut actions = Vec::new();
+    for step in steps {
+        match step {
+            MacroStep::Snippet { snippet_id } => {
+                let snippet = {
+                    let state = app_handle.state::<Mutex<SnippetStore>>();
+                    let locked = state.lock().unwrap();
+                    locked.get(*snippet_id).cloned().ok_or_else(|| {
+                        i18n::tf("error.macro_snippet_missing", &[("id", snippet_id)])
+                    })?
+                };
+                let snippet = snippets::resolve_group(app_handle, snippet)?;
+                if snippet.kind == SnippetKind::Macro {
+                    return Err(i18n::t("error.macro_nested").to_string());
+                }
+                if snippet.kind == SnippetKind::Keepassxc {
+                    // 凭据片段带有 Tab（与 Enter）按键动作
+                    let (credential_units, credential_actions) =
+                        keepassxc::compile(&snippet.body, snippet.submit).await?;
+                    units.extend(credential_units);
+                    actions.extend(credential_actions);
+                } else {
+                    units.extend(snippets::snippet_units(app_handle, &snippet).await?);
+                }
+                snippets::record_use(app_handle, snippet.id);
+            }
+            MacroStep::Text { text } => {
+                let text = templates::expand(text)?;
+                units.extend(text.encode_utf16().filter(|&unit| unit != 13));
+            }
+            MacroStep::Key { key } => {
+                let vks = hook::parse_key_stroke(key)
+                    .ok_or_else(|| i18n::tf("error.unknown_key", &[("key", key)]))?;
+                units.push(PASTE_ACTION_MARKER);
+                actions.push(PasteAction::Keys(vks));
+            }
+            MacroStep::Delay { ms } => {
+                units.push(PASTE_ACTION_MARKER);
+                actions.push(PasteAction::Delay(*ms));
+            }
+        }
+    }
+    Ok((units, actions))
+}
@@ -3,175 +3,156 @@
     windows_subsystem = "windows"
 )]
 
+mod actions;
+mod api;
+mod app_rules;
+mod autostart;
+mod chunks;
 mod commands;
+mod config;
+#[cfg(windows)]
+mod copydata;
+mod deeplink;
+mod expander;
+mod file_input;
+mod foreground;
+mod form_fill;
+mod handoff;
+mod history;
+mod hook;
+mod hotkeys;
+mod i18n;
+mod ipc;
+mod keepassxc;
+mod launch;
+mod macros;
+mod mqtt;
+mod native_host;
+mod notify;
+mod overlay;
+mod permissions;
+mod platform;
+mod profiles;
+#[cfg(windows)]
+mod registry;
+mod relay;
+mod schedule;
+mod secret;
+#[cfg(windows)]
+mod session;
+mod settings;
+mod settings_io;
+mod shutdown;
+mod snippet_io;
+mod snippets;
+mod taskbar;
+mod templates;
+mod totp;
+mod transform;
+mod tray;
+mod vault;
+mod webhook;
 
 use std::sync::Mutex;
-use auto_launch::AutoLaunchBuilder;
-use tauri::{
-    CustomMenuItem, GlobalShortcutManager, Manager, SystemTray, SystemTrayEvent, SystemTrayMenu,
-    SystemTrayMenuItem,
+use tauri::{Manager, SystemTray, SystemTrayEvent};
+use api::{get_api_settings, regenerate_api_token, set_api_enabled, set_api_port};
+use app_rules::{get_app_rules, set_app_rules, AppRuleState};
+use autostart::{get_autostart, set_autostart};
+use chunks::{
+    get_chunk_settings, get_chunk_status, reset_chunks, set_chunk_position, set_chunk_settings,
+    start_chunks, type_next_chunk, ChunkState,
 };
-use commands::{paste, toggle_pause, get_shortcut, update_shortcut, restart_app, PasteState, HotkeyConfig};
-
-/// 记录当前全局快捷键，以便下次更新或注销
-struct GlobalShortcutState {
-    registered_shortcut: Option<String>,
-}
-
-impl GlobalShortcutState {
-    fn new() -> Self {
-        Self {
-            registered_shortcut: None,
-        }
-    }
-}
-
-/// 注册全局快捷键
-pub fn register_global_shortcut(
-    app_handle: tauri::AppHandle,
-    config: &HotkeyConfig,
-) -> Result<(), String> {
-    let shortcut_state = app_handle.state::<Mutex<GlobalShortcutState>>();
-    let mut locked_state = shortcut_state.lock().unwrap();
-
-    // 根据当前配置生成要注册的加速器字符串
-    let accelerator = config.to_tauri_accelerator();
-
-    // 如果已注册过其他快捷键，则先注销
-    if let Some(old_accel) = &locked_state.registered_shortcut {
-        let _ = app_handle.global_shortcut_manager().unregister(old_accel);
-    }
-
-    let app_handle_clone = app_handle.clone();
-    let paste_handler = move || {
-        #[cfg(debug_assertions)]
-        println!("全局快捷键被触发");
-        
-        let state = app_handle_clone.state::<Mutex<PasteState>>();
-        let locked = state.lock().unwrap();
-        if !locked.is_paused {
-            let window = app_handle_clone.get_window("main").unwrap();
-            let _ = window.emit("trigger-paste", ());
-        } else {
-            #[cfg(debug_assertions)]
-            println!("应用已暂停，忽略快捷键");
-        }
-    };
-
-    match app_handle
-        .global_shortcut_manager()
-        .register(&accelerator, paste_handler)
-    {
-        Ok(_) => {
-            locked_state.registered_shortcut = Some(accelerator.clone());
-            #[cfg(debug_assertions)]
-            println!("全局快捷键 \"{}\" 已注册成功", accelerator);
-            
-            Ok(())
-        }
-        Err(e) => {
-            #[cfg(debug_assertions)]
-            println!("全局快捷键 \"{}\" 注册失败: {}", accelerator, e);
-            
-            Err(e.to_string())
-        }
-    }
-}
-
-/// 启动时从本地配置文件读取快捷键信息
-fn load_shortcut_config(app_handle: &tauri::AppHandle) -> HotkeyConfig {
-    use tauri::api::path::{BaseDirectory, resolve_path};
-    use std::fs;
-
-    let default = HotkeyConfig::default();
-
-    let store_path = match resolve_path(
-        &app_handle.config(),
-        app_handle.package_info(),
-        &app_handle.env(),
-        "shortcut_config.json",
-        Some(BaseDirectory::AppConfig),
-    ) {
-        Ok(path) => path,
-        Err(e) => {
-            #[cfg(debug_assertions)]
-            eprintln!("获取app_config_dir失败: {}", e);
-            
-            return default;
-        }
-    };
-
-    if !store_path.exists() {
-        // 文件不存在就返回默认
-        return default;
-    }
-
-    let content = match fs::read_to_string(&store_path) {
-        Ok(s) => s,
-        Err(e) => {
-            #[cfg(debug_assertions)]
-            eprintln!("读取配置文件失败: {}", e);
-            
-            return default;
-        }
-    };
-
-    let config = match serde_json::from_str::<HotkeyConfig>(&content) {
-        Ok(cfg) => cfg,
-        Err(e) => {
-            #[cfg(debug_assertions)]
-            eprintln!("解析JSON失败: {}", e);
-            
-            return default;
-        }
-    };
-
-    #[cfg(debug_assertions)]
-    println!("已从 {} 读取快捷键配置: {:?}", store_path.display(), config);
-    
-    config
-}
+use config::open_config_dir;
+use commands::{
+    paste, retype_last, resume_last_paste, toggle_pause, get_shortcut, update_shortcut,
+    get_hotkeys, update_hotkeys, capture_hotkey, restart_app, set_timing_seed, get_timing_seed,
+    toggle_instant_mode, get_instant_mode, get_suspend_apps, set_suspend_apps, pause_typing,
+    resume_typing, cancel_paste, set_start_delay, get_start_delay, set_user_input_action,
+    get_user_input_action, set_focus_loss_action, get_focus_loss_action,
+    set_session_change_action, get_session_change_action, set_notification_prefs,
+    get_notification_prefs, estimate_paste, get_typing_status, set_end_action, get_end_action,
+    set_clipboard_templates, get_clipboard_templates, set_default_timing, get_default_timing,
+    get_speed_presets, apply_speed_preset, set_pause, get_pause, set_text_source, get_text_source,
+    PasteState,
+};
+use expander::{
+    delete_abbreviation, get_expander_settings, list_abbreviations, save_abbreviation,
+    set_expander_enabled, set_expander_settings, ExpanderState,
+};
+use history::{
+    clear_paste_history, get_history_settings, get_paste_history, retype_history_item,
+    set_history_settings, HistoryState,
+};
+use file_input::type_file;
+use form_fill::{fill_form, preview_form_rows};
+use handoff::{get_handoff_status, start_handoff, stop_handoff};
+use hotkeys::GlobalShortcutState;
+use i18n::{get_language, set_language};
+use mqtt::{get_mqtt_settings, get_mqtt_status, set_mqtt_settings};
+use native_host::{
+    get_native_host_status, install_native_host, set_native_host_origins, uninstall_native_host,
+};
+use overlay::{get_overlay_labels, get_overlay_prefs, set_overlay_prefs};
+use permissions::{check_permissions, open_permission_settings};
+use tray::{get_tray_click_prefs, set_tray_click_prefs};
+use profiles::{
+    delete_profile, get_profiles, save_profile, set_profile_auto_switch, switch_profile,
+    ProfileState,
+};
+use relay::{get_relay_settings, get_relay_status, set_relay_settings};
+use schedule::{cancel_scheduled_paste, list_scheduled_pastes, schedule_paste, ScheduleState};
+use settings::{
+    get_settings, get_settings_errors, get_settings_schema, reset_settings, restore_config_backup,
+    update_settings,
+};
+use settings_io::{export_settings, import_settings};
+use shutdown::quit_app;
+use snippet_io::{export_snippets, import_credentials_csv, import_snippets};
+use snippets::{
+    delete_snippet, list_snippet_folders, list_snippet_kinds, list_snippet_tags, list_snippets,
+    most_used_snippets, save_snippet, search_snippets, type_snippet, SnippetStore,
+};
+use templates::{preview_template, provide_placeholder_values, FillInState};
+use vault::{
+    disable_vault, enable_vault, get_vault_status, lock_vault, set_vault_auto_lock, unlock_vault,
+    VaultState,
+};
+use webhook::{get_webhook_settings, set_webhook_settings, test_webhook};
 
 #[tokio::main]
 async fn main() {
-    let auto_start = AutoLaunchBuilder::new()
-        .set_app_name("Paster")
-        .set_app_path(std::env::current_exe().unwrap().to_str().unwrap())
-        .set_args("--silent")
-        .build()
-        .unwrap();
-
-    // 创建托盘
-    let quit = CustomMenuItem::new("quit".to_string(), "退出");
-    let show = CustomMenuItem::new("show".to_string(), "显示窗口");
-    let pause = CustomMenuItem::new("pause".to_string(), "暂停");
-    let tray_menu = SystemTrayMenu::new()
-        .add_item(show)
-        .add_item(pause)
-        .add_native_item(SystemTrayMenuItem::Separator)
-        .add_item(quit);
+    // 程序已在运行时把启动参数（如 --paste-file、paster:// 链接）交给已运行的实例处理后退出，
+    // 避免重复注册快捷键
+    if launch::forward_to_running_instance() {
+        return;
+    }
+
+    // 创建托盘（"最近使用" 子菜单在读取片段库后加入）
+    let tray_menu = tray::build_menu(None, false, false, false, None, None, None);
     let tray = SystemTray::new().with_menu(tray_menu);
 
     tauri::Builder::default()
-        // 管理状态：PasteState & GlobalShortcutState
+        // 管理状态：PasteState & GlobalShortcutState & ScheduleState & FillInState & ChunkState（SnippetStore、HistoryState、ExpanderState 在 setup 中读取）
         .manage(Mutex::new(PasteState::new()))
         .manage(Mutex::new(GlobalShortcutState::new()))
+        .manage(Mutex::new(ScheduleState::new()))
+        .manage(Mutex::new(FillInState::new()))
+        .manage(Mutex::new(ChunkState::new()))
+        .manage(Mutex::new(ProfileState::new()))
+        .manage(Mutex::new(AppRuleState::new()))
         .system_tray(tray)
         .on_system_tray_event(|app, event| match event {
-            // 左键单击：显示/隐藏窗口
+            // 单击、双击：按设置执行（默认单击显示/隐藏窗口）
             SystemTrayEvent::LeftClick { .. } => {
-                let window = app.get_window("main").unwrap();
-                if window.is_visible().unwrap() {
-                    let _ = window.hide();
-                } else {
-                    let _ = window.show();
-                    let _ = window.set_focus();
-                }
+                tray::handle_click(app, tray::click_prefs().left_click);
+            }
+            SystemTrayEvent::DoubleClick { .. } => {
+                tray::handle_click(app, tray::click_prefs().double_click);
             }
             // 菜单点击
             SystemTrayEvent::MenuItemClick { id, .. } => match id.as_str() {
                 "quit" => {
-                    std::process::exit(0);
+                    shutdown::request_quit(app, false);
                 }
                 "show" => {
                     let window = app.get_window("main").unwrap();
@@ -179,41 +160,105 @@ async fn main() {
                     let _ = window.set_focus();
                 }
                 "pause" => {
-                    let state = app.state::<Mutex<PasteState>>();
-                    let mut locked = state.lock().unwrap();
-                    locked.is_paused = !locked.is_paused;
-
-                    // 修改菜单项文字
-                    let tray_handle = app.tray_handle();
-                    if locked.is_paused {
-                        let _ = tray_handle.get_item("pause").set_title("继续");
-                    } else {
-                        let _ = tray_handle.get_item("pause").set_title("暂停");
+                    commands::toggle_paused(app);
+                }
+                "instant" => {
+                    commands::toggle_instant(app);
+                }
+                "autostart" => {
+                    let enabled = !autostart::is_enabled();
+                    if let Err(_e) = autostart::set_autostart(enabled, app.clone()) {
+                        #[cfg(debug_assertions)]
+                        eprintln!("切换开机自启失败: {}", _e);
+                    }
+                }
+                "type_file" => {
+                    file_input::pick_and_type(app);
+                }
+                "open_config_dir" => {
+                    if let Err(_e) = config::open_config_dir(app.clone()) {
+                        #[cfg(debug_assertions)]
+                        eprintln!("打开配置文件夹失败: {}", _e);
                     }
                 }
-                _ => {}
+                // "最近使用" 子菜单中的片段与历史
+                other => {
+                    tray::handle_menu_click(app, other);
+                }
             },
             _ => {}
         })
         .setup(move |app| {
-            // 1. 启动时先从文件读取快捷键，写入PasteState
+            // 读取加密设置（开启静态加密时启动后处于锁定状态，解锁后再读取片段库、历史与加密的设置）
+            app.manage(Mutex::new(VaultState::load(&app.app_handle())));
+
+            // 1. 启动时先从 settings.json 读取设置，写入PasteState 等状态，并按设置注册或移除开机自启项
+            let loaded = settings::load(&app.app_handle());
+            settings::apply(&app.app_handle(), &loaded);
+
+            // 调试/测试：通过环境变量 PASTER_TIMING_SEED 固定延迟随机数种子
+            if let Some(seed) = std::env::var("PASTER_TIMING_SEED")
+                .ok()
+                .and_then(|s| s.parse::<u64>().ok())
             {
-                let config = load_shortcut_config(&app.app_handle());
                 let state = app.state::<Mutex<PasteState>>();
-                let mut locked = state.lock().unwrap();
-                locked.shortcut = config;
+                state.lock().unwrap().timing_seed = Some(seed);
             }
 
             // 2. 注册全局快捷键
             {
                 let state = app.state::<Mutex<PasteState>>();
-                let config = {
+                let bindings = {
                     let locked = state.lock().unwrap();
-                    locked.shortcut.clone()
+                    locked.hotkeys.clone()
                 };
-                register_global_shortcut(app.app_handle().clone(), &config).ok();
+                let bindings = profiles::effective_hotkeys(&app.app_handle(), &bindings);
+                if let Err(e) = hotkeys::register_hotkeys(&app.app_handle(), &bindings) {
+                    notify::hotkey_failed(&app.app_handle(), e);
+                }
+
+                // 指定程序位于前台时暂停快捷键（程序列表已随设置读取）
+                hotkeys::spawn_suspend_watcher(app.app_handle());
+            }
+
+            // 读取片段库与输入历史
+            vault::spawn_auto_lock(app.app_handle());
+            app.manage(Mutex::new(SnippetStore::load(&app.app_handle())));
+            app.manage(Mutex::new(HistoryState::load(&app.app_handle())));
+            // 片段的快捷键在读取片段库后注册
+            if let Err(e) = hotkeys::refresh_snippet_hotkeys(&app.app_handle()) {
+                notify::hotkey_failed(&app.app_handle(), e);
             }
 
+            tray::refresh(&app.app_handle());
+
+            // settings.json 被外部修改时重新读取
+            settings::spawn_watcher(app.app_handle());
+
+            // 读取文本扩展的缩写表，开启时开始监视输入
+            {
+                let expander = ExpanderState::load(&app.app_handle());
+                expander.apply(&app.app_handle());
+                app.manage(Mutex::new(expander));
+            }
+
+            // 锁屏、会话断开或睡眠时打断正在进行的粘贴
+            #[cfg(windows)]
+            session::start_session_watcher(app.app_handle());
+
+            // macOS 缺少辅助功能权限时无法模拟按键，提示用户授权
+            permissions::check_on_startup(&app.app_handle());
+
+            // Flatpak 中读取上次保存的门户授权
+            platform::init(&app.app_handle());
+
+            // 接收脚本的命令与后续启动的进程转发的参数，并登记 paster:// 链接的打开方式
+            ipc::start(&app.app_handle());
+            // 只能发送窗口消息的旧式自动化工具经 WM_COPYDATA 发送同样的请求
+            #[cfg(windows)]
+            copydata::start(app.app_handle());
+            deeplink::register();
+
             // 3. 关闭主窗口时隐藏而非退出
             let window = app.get_window("main").unwrap();
             let window_clone = window.clone();
@@ -224,31 +269,169 @@ async fn main() {
                 }
             });
 
-            // 4. 设置开机自启
-            if !auto_start.is_enabled().unwrap() {
-                let _ = auto_start.enable();
-            }
-            
-            // 5. 处理静默启动参数
+            // 4. 处理静默启动参数与 "启动时隐藏窗口" 设置
             let matches = app.get_cli_matches().unwrap();
             let is_silent = matches.args.get("silent").and_then(|arg| arg.value.as_bool()).unwrap_or(false);
-            
-            // 如果启动参数包含 --silent，则隐藏窗口
-            if is_silent {
+            // 启动参数中有输入文件、打开链接等操作时不显示主窗口
+            let launch_args = launch::LaunchArgs::from_env();
+            let is_silent = is_silent || launch_args.runs_in_background();
+            let start_hidden = {
+                let state = app.state::<Mutex<PasteState>>();
+                let locked = state.lock().unwrap();
+                locked.start_hidden
+            };
+
+            // 静默启动或设置了启动时隐藏则只显示托盘图标；设置已加密时仍显示窗口以输入主密码
+            if (is_silent || start_hidden) && !vault::settings_locked(&app.app_handle()) {
                 #[cfg(debug_assertions)]
                 println!("以静默模式启动");
-                
+
                 let _ = window.hide();
+            } else {
+                let _ = window.show();
+                let _ = window.set_focus();
             }
 
+            launch::run(&app.app_handle(), launch_args);
+
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             paste,
+            retype_last,
+            resume_last_paste,
             toggle_pause,
+            set_pause,
+            get_pause,
+            quit_app,
+            check_permissions,
+            open_permission_settings,
             get_shortcut,
             update_shortcut,
-            restart_app
+            get_hotkeys,
+            update_hotkeys,
+            capture_hotkey,
+            restart_app,
+            set_timing_seed,
+            get_timing_seed,
+            toggle_instant_mode,
+            get_instant_mode,
+            get_suspend_apps,
+            set_suspend_apps,
+            pause_typing,
+            resume_typing,
+            cancel_paste,
+            set_start_delay,
+            get_start_delay,
+            set_user_input_action,
+            get_user_input_action,
+            set_focus_loss_action,
+            get_focus_loss_action,
+            set_session_change_action,
+            get_session_change_action,
+            schedule_paste,
+            list_scheduled_pastes,
+            cancel_scheduled_paste,
+            set_notification_prefs,
+            get_notification_prefs,
+            estimate_paste,
+            get_typing_status,
+            set_end_action,
+            get_end_action,
+            list_snippets,
+            save_snippet,
+            delete_snippet,
+            type_snippet,
+            search_snippets,
+            list_snippet_folders,
+            list_snippet_tags,
+            list_snippet_kinds,
+            most_used_snippets,
+            export_snippets,
+            import_snippets,
+            import_credentials_csv,
+            set_clipboard_templates,
+            get_clipboard_templates,
+            set_text_source,
+            get_text_source,
+            preview_template,
+            provide_placeholder_values,
+            get_paste_history,
+            retype_history_item,
+            clear_paste_history,
+            set_history_settings,
+            get_history_settings,
+            list_abbreviations,
+            save_abbreviation,
+            delete_abbreviation,
+            set_expander_settings,
+            get_expander_settings,
+            set_expander_enabled,
+            get_vault_status,
+            enable_vault,
+            disable_vault,
+            unlock_vault,
+            lock_vault,
+            set_vault_auto_lock,
+            fill_form,
+            preview_form_rows,
+            start_chunks,
+            type_next_chunk,
+            set_chunk_position,
+            get_chunk_status,
+            reset_chunks,
+            set_chunk_settings,
+            get_chunk_settings,
+            get_settings,
+            get_settings_errors,
+            get_settings_schema,
+            restore_config_backup,
+            reset_settings,
+            open_config_dir,
+            update_settings,
+            get_profiles,
+            save_profile,
+            delete_profile,
+            switch_profile,
+            set_profile_auto_switch,
+            export_settings,
+            import_settings,
+            get_autostart,
+            set_autostart,
+            get_language,
+            set_language,
+            get_overlay_labels,
+            set_overlay_prefs,
+            get_overlay_prefs,
+            get_tray_click_prefs,
+            set_tray_click_prefs,
+            get_app_rules,
+            set_app_rules,
+            set_default_timing,
+            get_default_timing,
+            get_speed_presets,
+            apply_speed_preset,
+            type_file,
+            get_api_settings,
+            set_api_enabled,
+            set_api_port,
+            regenerate_api_token,
+            start_handoff,
+            stop_handoff,
+            get_handoff_status,
+            get_native_host_status,
+            install_native_host,
+            uninstall_native_host,
+            set_native_host_origins,
+            get_webhook_settings,
+            set_webhook_settings,
+            test_webhook,
+            get_relay_settings,
+            set_relay_settings,
+            get_relay_status,
+            get_mqtt_settings,
+            set_mqtt_settings,
+            get_mqtt_status
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,428 @@
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::Manager;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+
+use crate::api::{self, PasteRequest};
+use crate::i18n;
+use crate::settings;
+use crate::snippets;
+
+const DEFAULT_PORT: u16 = 1883;
+const DEFAULT_TOPIC: &str = "paster/type";
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// 心跳间隔：每隔一半时间发送一次 PINGREQ，超过 1.5 倍仍未收到任何数据视为断开
+const KEEP_ALIVE: Duration = Duration::from_secs(60);
+/// 断开后重连的等待时间，连续失败时加倍，最长 RECONNECT_MAX
+const RECONNECT_MIN: Duration = Duration::from_secs(2);
+const RECONNECT_MAX: Duration = Duration::from_secs(60);
+const MAX_PACKET_BYTES: usize = 4 * 1024 * 1024;
+const SUBSCRIBE_PACKET_ID: u16 = 1;
+
+// MQTT 3.1.1 的报文类型（固定报头的高 4 位）
+const CONNECT: u8 = 1;
+const CONNACK: u8 = 2;
+const PUBLISH: u8 = 3;
+const PUBACK: u8 = 4;
+const SUBSCRIBE: u8 = 8;
+const SUBACK: u8 = 9;
+const PINGREQ: u8 = 12;
+/// PUBLISH 固定报头中的保留标志
+const RETAIN: u8 = 0x01;
+
+/// MQTT 触发：订阅 topic，收到的消息为纯文本时输入该文本；
+/// 为 JSON 时同 POST /paste 的参数，或 {"snippet": "标题或编号"} 输入片段。
+/// 使用 MQTT 3.1.1、不含 TLS，用于局域网中的 Home Assistant、测试台架等
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MqttSettings {
+    /// 默认关闭
+    pub enabled: bool,
+    /// 服务器的主机名或地址
+    pub host: String,
+    pub port: u16,
+    /// 订阅的主题，可使用 + 与 # 通配符
+    pub topic: String,
+    /// 为空时不认证
+    pub username: String,
+    pub password: String,
+}
+
+impl MqttSettings {
+    const fn new() -> Self {
+        Self {
+            enabled: false,
+            host: String::new(),
+            port: DEFAULT_PORT,
+            topic: String::new(),
+            username: String::new(),
+            password: String::new(),
+        }
+    }
+
+    /// 未填写时使用 paster/type
+    fn topic(&self) -> &str {
+        if self.topic.is_empty() {
+            DEFAULT_TOPIC
+        } else {
+            &self.topic
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.port == 0 {
+            return Err(i18n::t("error.port_zero"));
+        }
+        if self.enabled && self.host.is_empty() {
+            return Err(i18n::t("error.mqtt_host_empty"));
+        }
+        if self.topic.contains('\0') {
+            return Err(i18n::t("error.mqtt_topic_nul"));
+        }
+        Ok(())
+    }
+}
+
+impl Default for MqttSettings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// mqtt-changed 事件的内容
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MqttStatus {
+    /// 已连接并订阅
+    pub connected: bool,
+    /// 最近一次连接失败的原因，连接成功后清空
+    pub error: Option<String>,
+}
+
+static SETTINGS: Mutex<MqttSettings> = Mutex::new(MqttSettings::new());
+/// 正在运行的客户端及其使用的设置，设置改变时重新连接
+static RUNNING: Mutex<Option<(MqttSettings, tauri::async_runtime::JoinHandle<()>)>> =
+    Mutex::new(None);
+static STATUS: Mutex<MqttStatus> = Mutex::new(MqttStatus {
+    connected: false,
+    error: None,
+});
+
+/// 当前的 MQTT 设置
+pub fn settings() -> MqttSettings {
+    SETTINGS.lock().unwrap().clone()
+}
+
+/// 应用 MQTT 设置：按需连接、重新连接或断开
+pub fn apply(app_handle: &tauri::AppHandle, settings: MqttSettings) {
+    *SETTINGS.lock().unwrap() = settings.clone();
+
+    let mut running = RUNNING.lock().unwrap();
+    let wanted = settings.enabled.then_some(&settings);
+    if running.as_ref().map(|(settings, _)| settings) == wanted {
+        return;
+    }
+    if let Some((_, task)) = running.take() {
+        task.abort();
+        set_status(app_handle, MqttStatus::default());
+    }
+    if settings.enabled {
+        let task = tauri::async_runtime::spawn(run(app_handle.clone(), settings.clone()));
+        *running = Some((settings, task));
+    }
+}
+
+fn set_status(app_handle: &tauri::AppHandle, status: MqttStatus) {
+    *STATUS.lock().unwrap() = status.clone();
+    let _ = app_handle.emit_all("mqtt-changed", status);
+}
+
+/// 保持连接：断开或连接失败后等待一段时间重连
+async fn run(app_handle: tauri::AppHandle, settings: MqttSettings) {
+    let mut delay = RECONNECT_MIN;
+    loop {
+        let result = session(&app_handle, &settings).await;
+        let error = match result {
+            Ok(()) => i18n::t("error.mqtt_closed").to_string(),
+            Err(e) => e,
+        };
+        #[cfg(debug_assertions)]
+        eprintln!("MQTT 连接断开: {}", error);
+
+        let was_connected = STATUS.lock().unwrap().connected;
+        if was_connected {
+            delay = RECONNECT_MIN;
+        }
+        set_status(
+            &app_handle,
+            MqttStatus {
+                connected: false,
+                error: Some(error),
+            },
+        );
+        tokio::time::sleep(delay).await;
+        delay = (delay * 2).min(RECONNECT_MAX);
+    }
+}
+
+/// 一次连接：连接、订阅，之后处理收到的消息直到断开
+async fn session(app_handle: &tauri::AppHandle, settings: &MqttSettings) -> Result<(), String> {
+    let connect_failed = |e: &dyn std::fmt::Display| {
+        i18n::tf(
+            "error.mqtt_connect",
+            &[("host", &settings.host), ("error", e)],
+        )
+    };
+    let stream = tokio::time::timeout(
+        CONNECT_TIMEOUT,
+        TcpStream::connect((settings.host.as_str(), settings.port)),
+    )
+    .await
+    .map_err(|_| connect_failed(&"timeout"))?
+    .map_err(|e| connect_failed(&e))?;
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+
+    let io_error = |e: std::io::Error| connect_failed(&e);
+    writer
+        .write_all(&connect_packet(settings))
+        .await
+        .map_err(io_error)?;
+    let (header, body) = tokio::time::timeout(CONNECT_TIMEOUT, read_packet(&mut reader))
+        .await
+        .map_err(|_| connect_failed(&"timeout"))?
+        .map_err(io_error)?;
+    if header >> 4 != CONNACK || body.len() < 2 {
+        return Err(connect_failed(&"CONNACK"));
+    }
+    match body[1] {
+        0 => {}
+        4 | 5 => return Err(i18n::t("error.mqtt_unauthorized").to_string()),
+        code => return Err(connect_failed(&format!("CONNACK {}", code))),
+    }
+    writer
+        .write_all(&subscribe_packet(settings.topic()))
+        .await
+        .map_err(io_error)?;
+
+    // 读取、写入与心跳并行进行，任一结束即断开
+    let (outgoing, mut queue) = mpsc::channel::<Vec<u8>>(16);
+    let writing = async move {
+        while let Some(packet) = queue.recv().await {
+            writer.write_all(&packet).await?;
+        }
+        Ok::<_, std::io::Error>(())
+    };
+    let pings = outgoing.clone();
+    let pinging = async move {
+        loop {
+            tokio::time::sleep(KEEP_ALIVE / 2).await;
+            if pings.send(vec![PINGREQ << 4, 0]).await.is_err() {
+                return;
+            }
+        }
+    };
+    let reading = async {
+        loop {
+            let (header, body) =
+                match tokio::time::timeout(KEEP_ALIVE * 3 / 2, read_packet(&mut reader)).await {
+                    Ok(Ok(packet)) => packet,
+                    Ok(Err(e)) => return Err(io_error(e)),
+                    Err(_) => return Err(connect_failed(&"keep alive timeout")),
+                };
+            match header >> 4 {
+                SUBACK => {
+                    // 返回码 0x80 表示服务器拒绝订阅
+                    if body.get(2) == Some(&0x80) {
+                        return Err(i18n::tf(
+                            "error.mqtt_subscribe",
+                            &[("topic", &settings.topic())],
+                        ));
+                    }
+                    #[cfg(debug_assertions)]
+                    println!("已订阅 MQTT 主题: {}", settings.topic());
+                    set_status(
+                        app_handle,
+                        MqttStatus {
+                            connected: true,
+                            error: None,
+                        },
+                    );
+                }
+                PUBLISH => {
+                    let Some((packet_id, payload)) = parse_publish(header, &body) else {
+                        return Err(connect_failed(&"PUBLISH"));
+                    };
+                    if let Some(packet_id) = packet_id {
+                        let mut puback = vec![PUBACK << 4, 2];
+                        puback.extend_from_slice(&packet_id.to_be_bytes());
+                        let _ = outgoing.send(puback).await;
+                    }
+                    // 订阅时服务器补发的保留消息是过去的触发，不输入
+                    if header & RETAIN != 0 {
+                        continue;
+                    }
+                    if let Err(_e) = trigger(app_handle, payload) {
+                        #[cfg(debug_assertions)]
+                        eprintln!("处理 MQTT 消息失败: {}", _e);
+                    }
+                }
+                _ => {}
+            }
+        }
+    };
+
+    tokio::select! {
+        result = reading => result,
+        result = writing => result.map_err(io_error),
+        _ = pinging => Ok(()),
+    }
+}
+
+/// 消息为纯文本时输入该文本；为 JSON 对象时含 snippet 则输入片段，否则同 POST /paste 的参数
+fn trigger(app_handle: &tauri::AppHandle, payload: &[u8]) -> Result<(), String> {
+    let text = std::str::from_utf8(payload).map_err(|e| e.to_string())?;
+    if text.is_empty() {
+        return Ok(());
+    }
+    let paste = if text.trim_start().starts_with('{') {
+        let mut request: Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+        if let Some(name) = request.get("snippet").and_then(Value::as_str) {
+            return snippets::start_snippet(app_handle, name);
+        }
+        if let Some(request) = request.as_object_mut() {
+            request.remove("snippet");
+        }
+        serde_json::from_value(request).map_err(|e| e.to_string())?
+    } else {
+        PasteRequest::from_text(text.to_string())
+    };
+    paste.start(app_handle).map_err(|(_, e)| e)
+}
+
+/// UTF-8 字符串：2 字节大端长度加内容
+fn put_string(packet: &mut Vec<u8>, value: &[u8]) {
+    packet.extend_from_slice(&(value.len() as u16).to_be_bytes());
+    packet.extend_from_slice(value);
+}
+
+/// 固定报头：类型与标志，随后为剩余长度（每字节 7 位，最高位表示后面还有）
+fn packet(first_byte: u8, body: &[u8]) -> Vec<u8> {
+    let mut packet = vec![first_byte];
+    let mut length = body.len();
+    loop {
+        let mut byte = (length % 128) as u8;
+        length /= 128;
+        if length > 0 {
+            byte |= 0x80;
+        }
+        packet.push(byte);
+        if length == 0 {
+            break;
+        }
+    }
+    packet.extend_from_slice(body);
+    packet
+}
+
+fn connect_packet(settings: &MqttSettings) -> Vec<u8> {
+    // 清除会话：断开期间的消息不补发
+    let mut flags = 0x02;
+    if !settings.username.is_empty() {
+        flags |= 0x80;
+        if !settings.password.is_empty() {
+            flags |= 0x40;
+        }
+    }
+    let mut body = Vec::new();
+    put_string(&mut body, b"MQTT");
+    body.push(4); // 协议级别 3.1.1
+    body.push(flags);
+    body.extend_from_slice(&(KEEP_ALIVE.as_secs() as u16).to_be_bytes());
+    // 客户端标识每次连接随机生成，多个 Paster 可以连接同一服务器
+    let client_id = format!("paster-{}", &api::generate_token()[..12]);
+    put_string(&mut body, client_id.as_bytes());
+    if flags & 0x80 != 0 {
+        put_string(&mut body, settings.username.as_bytes());
+    }
+    if flags & 0x40 != 0 {
+        put_string(&mut body, settings.password.as_bytes());
+    }
+    packet(CONNECT << 4, &body)
+}
+
+/// 以 QoS 1 订阅：服务器按消息的 QoS（最高 1）投递，QoS 1 的消息需回复 PUBACK
+fn subscribe_packet(topic: &str) -> Vec<u8> {
+    let mut body = SUBSCRIBE_PACKET_ID.to_be_bytes().to_vec();
+    put_string(&mut body, topic.as_bytes());
+    body.push(1);
+    // SUBSCRIBE 的标志位固定为 0010
+    packet(SUBSCRIBE << 4 | 0x02, &body)
+}
+
+/// PUBLISH 的报文标识（QoS 大于 0 时）与消息内容
+fn parse_publish(header: u8, body: &[u8]) -> Option<(Option<u16>, &[u8])> {
+    let topic_len = u16::from_be_bytes([*body.first()?, *body.get(1)?]) as usize;
+    let rest = body.get(2 + topic_len..)?;
+    let qos = (header >> 1) & 0x03;
+    if qos == 0 {
+        return Some((None, rest));
+    }
+    let packet_id = u16::from_be_bytes([*rest.first()?, *rest.get(1)?]);
+    Some((Some(packet_id), &rest[2..]))
+}
+
+/// 读取一个报文，返回固定报头的第一个字节与报文体
+async fn read_packet<R: AsyncRead + Unpin>(reader: &mut R) -> std::io::Result<(u8, Vec<u8>)> {
+    let header = reader.read_u8().await?;
+    let mut length = 0usize;
+    let mut shift = 0;
+    loop {
+        let byte = reader.read_u8().await?;
+        length |= ((byte & 0x7F) as usize) << shift;
+        if byte & 0x80 == 0 {
+            break;
+        }
+        shift += 7;
+        if shift > 21 {
+            return Err(std::io::ErrorKind::InvalidData.into());
+        }
+    }
+    if length > MAX_PACKET_BYTES {
+        return Err(std::io::ErrorKind::InvalidData.into());
+    }
+    let mut body = vec![0u8; length];
+    reader.read_exact(&mut body).await?;
+    Ok((header, body))
+}
+
+#[tauri::command]
+pub fn get_mqtt_settings() -> MqttSettings {
+    settings()
+}
+
+/// 修改 MQTT 设置，开启时立即连接（连接结果通过 mqtt-changed 事件通知）
+#[tauri::command]
+pub fn set_mqtt_settings(
+    settings: MqttSettings,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    let settings = MqttSettings {
+        host: settings.host.trim().to_string(),
+        topic: settings.topic.trim().to_string(),
+        ..settings
+    };
+    settings.validate()?;
+    apply(&app_handle, settings);
+    settings::persist(&app_handle);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_mqtt_status() -> MqttStatus {
+    STATUS.lock().unwrap().clone()
+}
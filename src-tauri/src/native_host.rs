@@ -0,0 +1,292 @@
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::config;
+use crate::i18n;
+use crate::settings;
+
+/// 浏览器扩展连接时使用的原生消息主机名称，扩展中调用 connectNative("com.paster.host")
+pub const HOST_NAME: &str = "com.paster.host";
+/// 原生消息主机由 paster-cli 担任，随主程序放在同一目录
+#[cfg(windows)]
+const HOST_EXECUTABLE: &str = "paster-cli.exe";
+#[cfg(not(windows))]
+const HOST_EXECUTABLE: &str = "paster-cli";
+const CHROME_ORIGIN_PREFIX: &str = "chrome-extension://";
+/// Chrome 扩展编号的长度，由 a-p 组成
+const CHROME_EXTENSION_ID_LEN: usize = 32;
+
+/// 浏览器扩展（原生消息主机）：只接受允许列表中的扩展发来的请求
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NativeHostSettings {
+    /// 允许的扩展：Chrome 系为 chrome-extension://编号/，Firefox 为扩展的 id
+    pub allowed_origins: Vec<String>,
+}
+
+impl NativeHostSettings {
+    const fn new() -> Self {
+        Self {
+            allowed_origins: Vec::new(),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), &'static str> {
+        for origin in &self.allowed_origins {
+            if origin.is_empty() || origin.chars().any(char::is_whitespace) {
+                return Err(i18n::t("error.extension_invalid"));
+            }
+            if let Some(id) = chrome_extension_id(origin) {
+                if id.len() != CHROME_EXTENSION_ID_LEN
+                    || !id.bytes().all(|b| (b'a'..=b'p').contains(&b))
+                {
+                    return Err(i18n::t("error.chrome_extension_id"));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Chrome 系浏览器清单中的 allowed_origins
+    fn chrome_origins(&self) -> Vec<String> {
+        self.allowed_origins
+            .iter()
+            .filter_map(|origin| chrome_extension_id(origin))
+            .map(|id| format!("{}{}/", CHROME_ORIGIN_PREFIX, id))
+            .collect()
+    }
+
+    /// Firefox 清单中的 allowed_extensions
+    fn firefox_extensions(&self) -> Vec<String> {
+        self.allowed_origins
+            .iter()
+            .filter(|origin| chrome_extension_id(origin).is_none())
+            .cloned()
+            .collect()
+    }
+}
+
+static SETTINGS: Mutex<NativeHostSettings> = Mutex::new(NativeHostSettings::new());
+
+/// chrome-extension://编号/ 中的编号
+fn chrome_extension_id(origin: &str) -> Option<&str> {
+    origin
+        .strip_prefix(CHROME_ORIGIN_PREFIX)
+        .map(|id| id.trim_end_matches('/'))
+}
+
+/// 当前的浏览器扩展设置
+pub fn settings() -> NativeHostSettings {
+    SETTINGS.lock().unwrap().clone()
+}
+
+pub fn apply(settings: NativeHostSettings) {
+    *SETTINGS.lock().unwrap() = settings;
+}
+
+/// 浏览器启动主机时传入的来源是否在允许列表中（Chrome 系末尾的 / 可有可无）
+pub fn is_allowed(origin: &str) -> bool {
+    let origin = origin.trim_end_matches('/');
+    SETTINGS
+        .lock()
+        .unwrap()
+        .allowed_origins
+        .iter()
+        .any(|allowed| allowed.trim_end_matches('/') == origin)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Browser {
+    Chrome,
+    Chromium,
+    Edge,
+    Firefox,
+}
+
+impl Browser {
+    const ALL: [Browser; 4] = [
+        Browser::Chrome,
+        Browser::Chromium,
+        Browser::Edge,
+        Browser::Firefox,
+    ];
+
+    fn is_firefox(self) -> bool {
+        self == Browser::Firefox
+    }
+
+    /// 登记清单位置的注册表项（HKEY_CURRENT_USER 下）
+    #[cfg(windows)]
+    fn registry_key(self) -> String {
+        let vendor = match self {
+            Browser::Chrome => r"Google\Chrome",
+            Browser::Chromium => "Chromium",
+            Browser::Edge => r"Microsoft\Edge",
+            Browser::Firefox => "Mozilla",
+        };
+        format!(r"Software\{}\NativeMessagingHosts\{}", vendor, HOST_NAME)
+    }
+
+    /// 浏览器查找清单的目录
+    #[cfg(target_os = "linux")]
+    fn manifest_dir(self) -> Option<PathBuf> {
+        let home = PathBuf::from(std::env::var_os("HOME")?);
+        let config = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| home.join(".config"));
+        Some(match self {
+            Browser::Chrome => config.join("google-chrome/NativeMessagingHosts"),
+            Browser::Chromium => config.join("chromium/NativeMessagingHosts"),
+            Browser::Edge => config.join("microsoft-edge/NativeMessagingHosts"),
+            Browser::Firefox => home.join(".mozilla/native-messaging-hosts"),
+        })
+    }
+
+    #[cfg(target_os = "macos")]
+    fn manifest_dir(self) -> Option<PathBuf> {
+        let support = PathBuf::from(std::env::var_os("HOME")?).join("Library/Application Support");
+        Some(match self {
+            Browser::Chrome => support.join("Google/Chrome/NativeMessagingHosts"),
+            Browser::Chromium => support.join("Chromium/NativeMessagingHosts"),
+            Browser::Edge => support.join("Microsoft Edge/NativeMessagingHosts"),
+            Browser::Firefox => support.join("Mozilla/NativeMessagingHosts"),
+        })
+    }
+}
+
+/// 浏览器读取的主机清单：Chrome 系以 allowed_origins、Firefox 以 allowed_extensions 限制可连接的扩展
+fn manifest(browser: Browser, settings: &NativeHostSettings) -> Result<String, String> {
+    let path = std::env::current_exe()
+        .map_err(|e| e.to_string())?
+        .with_file_name(HOST_EXECUTABLE);
+    let mut manifest = json!({
+        "name": HOST_NAME,
+        "description": "Paster",
+        "path": path,
+        "type": "stdio",
+    });
+    if browser.is_firefox() {
+        manifest["allowed_extensions"] = json!(settings.firefox_extensions());
+    } else {
+        manifest["allowed_origins"] = json!(settings.chrome_origins());
+    }
+    serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())
+}
+
+/// 清单文件的位置。Windows 上写入配置目录，再在注册表中登记
+#[cfg(windows)]
+fn manifest_path(app_handle: &tauri::AppHandle, browser: Browser) -> Result<PathBuf, String> {
+    let file_name = if browser.is_firefox() {
+        "native-host-firefox.json"
+    } else {
+        "native-host-chrome.json"
+    };
+    config::config_path(app_handle, file_name)
+}
+
+#[cfg(not(windows))]
+fn manifest_path(_app_handle: &tauri::AppHandle, browser: Browser) -> Result<PathBuf, String> {
+    browser
+        .manifest_dir()
+        .map(|dir| dir.join(format!("{}.json", HOST_NAME)))
+        .ok_or_else(|| "HOME".to_string())
+}
+
+fn install(app_handle: &tauri::AppHandle, browser: Browser) -> Result<(), String> {
+    let settings = settings();
+    let allowed = if browser.is_firefox() {
+        settings.firefox_extensions()
+    } else {
+        settings.chrome_origins()
+    };
+    if allowed.is_empty() {
+        return Err(i18n::t("error.native_host_no_origins").to_string());
+    }
+    let path = manifest_path(app_handle, browser)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    config::write_atomic(&path, manifest(browser, &settings)?.as_bytes())?;
+    #[cfg(windows)]
+    crate::registry::set_string(&browser.registry_key(), None, &path.to_string_lossy())?;
+    Ok(())
+}
+
+fn uninstall(app_handle: &tauri::AppHandle, browser: Browser) -> Result<(), String> {
+    #[cfg(windows)]
+    crate::registry::delete_key(&browser.registry_key())?;
+    let path = manifest_path(app_handle, browser)?;
+    match std::fs::remove_file(&path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.to_string()),
+        _ => Ok(()),
+    }
+}
+
+/// 已为哪些浏览器安装了清单
+fn installed(app_handle: &tauri::AppHandle) -> Vec<Browser> {
+    Browser::ALL
+        .into_iter()
+        .filter(|&browser| {
+            #[cfg(windows)]
+            if !crate::registry::key_exists(&browser.registry_key()) {
+                return false;
+            }
+            manifest_path(app_handle, browser).is_ok_and(|path| path.exists())
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NativeHostStatus {
+    pub allowed_origins: Vec<String>,
+    /// 已安装清单的浏览器
+    pub installed: Vec<Browser>,
+}
+
+#[tauri::command]
+pub fn get_native_host_status(app_handle: tauri::AppHandle) -> NativeHostStatus {
+    NativeHostStatus {
+        allowed_origins: settings().allowed_origins,
+        installed: installed(&app_handle),
+    }
+}
+
+/// 为浏览器安装原生消息主机清单；需先在允许列表中添加该浏览器的扩展
+#[tauri::command]
+pub fn install_native_host(browser: Browser, app_handle: tauri::AppHandle) -> Result<(), String> {
+    install(&app_handle, browser)
+}
+
+#[tauri::command]
+pub fn uninstall_native_host(browser: Browser, app_handle: tauri::AppHandle) -> Result<(), String> {
+    uninstall(&app_handle, browser)
+}
+
+/// 修改允许的扩展，并重写已安装的清单，使浏览器端的限制保持一致
+#[tauri::command]
+pub fn set_native_host_origins(
+    origins: Vec<String>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    let updated = NativeHostSettings {
+        allowed_origins: origins
+            .iter()
+            .map(|origin| origin.trim().to_string())
+            .collect(),
+    };
+    updated.validate()?;
+    apply(updated);
+    settings::persist(&app_handle);
+    for browser in installed(&app_handle) {
+        // 该浏览器已没有允许的扩展时移除清单
+        if install(&app_handle, browser).is_err() {
+            uninstall(&app_handle, browser)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,200 @@
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::api::notification::Notification;
+use tauri::Manager;
+
+use crate::api;
+use crate::commands::PasteState;
+use crate::i18n;
+use crate::platform::SecureInput;
+use crate::webhook;
+
+/// 各类事件是否弹出系统通知
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationPrefs {
+    /// 倒计时结束、开始输入
+    pub started: bool,
+    pub completed: bool,
+    pub aborted: bool,
+    pub failed: bool,
+    /// 全局快捷键注册失败（如被其他程序占用）
+    pub hotkey_failed: bool,
+}
+
+impl Default for NotificationPrefs {
+    fn default() -> Self {
+        // 开始与正常完成最常见，默认不打扰
+        Self {
+            started: false,
+            completed: false,
+            aborted: true,
+            failed: true,
+            hotkey_failed: true,
+        }
+    }
+}
+
+/// 粘贴结束时是否显示主窗口
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShowWindowPrefs {
+    pub completed: bool,
+    pub failed: bool,
+}
+
+/// 粘贴的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PasteOutcome {
+    Completed,
+    Aborted,
+    Failed,
+}
+
+/// paste-finished 事件的内容
+#[derive(Debug, Clone, Serialize)]
+pub struct PasteSummary {
+    pub outcome: PasteOutcome,
+    /// 粘贴任务的编号（同 get_typing_status 的 job_id）
+    pub job_id: u64,
+    /// 目标程序的可执行文件名，未开始输入或无法获取时为 None
+    pub target_process: Option<String>,
+    pub typed: usize,
+    pub total: usize,
+    pub elapsed_ms: u64,
+    /// 中止原因（如 "hotkey"、"focus_lost"）或失败信息
+    pub reason: Option<String>,
+}
+
+/// 中止原因的可读描述；失败信息原样显示
+fn describe_reason(reason: &str) -> &str {
+    i18n::lookup(&format!("reason.{}", reason)).unwrap_or(reason)
+}
+
+/// 弹出系统通知
+fn show(app_handle: &tauri::AppHandle, title: &str, body: &str) {
+    let identifier = app_handle.config().tauri.bundle.identifier.clone();
+    if let Err(_e) = Notification::new(identifier).title(title).body(body).show() {
+        #[cfg(debug_assertions)]
+        println!("显示系统通知失败: {}", _e);
+    }
+}
+
+/// 倒计时结束、开始输入 total 个字符
+pub fn paste_started(app_handle: &tauri::AppHandle, prefs: NotificationPrefs, total: usize) {
+    if prefs.started {
+        let body = i18n::tf("notify.started_body", &[("total", &total)]);
+        show(app_handle, i18n::t("notify.started"), &body);
+    }
+}
+
+/// 全局快捷键注册失败。调用方可能持有快捷键状态的锁，因此在后台任务中读取通知设置
+pub fn hotkey_failed(app_handle: &tauri::AppHandle, error: String) {
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let enabled = {
+            let state = app_handle.state::<Mutex<PasteState>>();
+            let locked = state.lock().unwrap();
+            locked.notifications.hotkey_failed
+        };
+        if enabled {
+            show(&app_handle, i18n::t("notify.hotkey_failed"), &error);
+        }
+    });
+}
+
+/// 启动时缺少必需的系统权限（如 macOS 的辅助功能），粘贴无法进行
+pub fn permissions_missing(app_handle: &tauri::AppHandle) {
+    show(
+        app_handle,
+        i18n::t("notify.permissions_missing"),
+        i18n::t("notify.permissions_missing_body"),
+    );
+}
+
+/// 启动参数中的操作（输入文件、选用配置方案）失败
+pub fn launch_failed(app_handle: &tauri::AppHandle, error: &str) {
+    show(app_handle, i18n::t("notify.launch_failed"), error);
+}
+
+/// paster:// 链接无法打开（链接无效、片段不存在等）
+pub fn deep_link_failed(app_handle: &tauri::AppHandle, error: &str) {
+    show(app_handle, i18n::t("notify.deep_link_failed"), error);
+}
+
+/// 粘贴因 Secure Input 暂停，提示关闭开启它的程序（或离开其密码框）后继续
+pub fn secure_input_blocked(app_handle: &tauri::AppHandle, secure_input: &SecureInput) {
+    let body = match &secure_input.process {
+        Some(process) => i18n::tf("notify.secure_input_body", &[("process", process)]),
+        None => i18n::t("notify.secure_input_unknown_body").to_string(),
+    };
+    show(app_handle, i18n::t("notify.secure_input"), &body);
+}
+
+/// 粘贴结束：发送 paste-finished 事件，并按设置弹出系统通知
+pub fn paste_finished(
+    app_handle: &tauri::AppHandle,
+    prefs: NotificationPrefs,
+    summary: PasteSummary,
+) {
+    api::publish("paste-finished", &summary);
+    webhook::paste_finished(&summary);
+    let _ = app_handle.emit_all("paste-finished", &summary);
+
+    let show_window = {
+        let state = app_handle.state::<Mutex<PasteState>>();
+        let locked = state.lock().unwrap();
+        match summary.outcome {
+            PasteOutcome::Completed => locked.show_window.completed,
+            PasteOutcome::Failed => locked.show_window.failed,
+            PasteOutcome::Aborted => false,
+        }
+    };
+    if show_window {
+        if let Some(window) = app_handle.get_window("main") {
+            let _ = window.show();
+            let _ = window.set_focus();
+        }
+    }
+
+    let enabled = match summary.outcome {
+        PasteOutcome::Completed => prefs.completed,
+        PasteOutcome::Aborted => prefs.aborted,
+        PasteOutcome::Failed => prefs.failed,
+    };
+    if !enabled {
+        return;
+    }
+
+    let seconds = format!("{:.1}", summary.elapsed_ms as f64 / 1000.0);
+    let (title, mut body) = match summary.outcome {
+        PasteOutcome::Completed => (
+            i18n::t("notify.completed"),
+            i18n::tf(
+                "notify.completed_body",
+                &[("typed", &summary.typed), ("seconds", &seconds)],
+            ),
+        ),
+        PasteOutcome::Aborted => (
+            i18n::t("notify.aborted"),
+            i18n::tf(
+                "notify.aborted_body",
+                &[
+                    ("typed", &summary.typed),
+                    ("total", &summary.total),
+                    ("seconds", &seconds),
+                ],
+            ),
+        ),
+        PasteOutcome::Failed => (i18n::t("notify.failed"), String::new()),
+    };
+    if let Some(reason) = &summary.reason {
+        if !body.is_empty() {
+            body.push('\n');
+        }
+        body.push_str(describe_reason(reason));
+    }
+    show(app_handle, title, &body);
+}
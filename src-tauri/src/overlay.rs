@@ -0,0 +1,258 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{Manager, PhysicalPosition, Window, WindowBuilder, WindowUrl};
+#[cfg(windows)]
+use windows::Win32::{
+    Foundation::{HWND, POINT, RECT},
+    UI::WindowsAndMessaging::{
+        GetCursorPos, GetWindowLongPtrW, SetWindowLongPtrW, ShowWindow, SystemParametersInfoW,
+        GWL_EXSTYLE, SPI_GETWORKAREA, SW_SHOWNOACTIVATE, SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS,
+        WS_EX_NOACTIVATE, WS_EX_TOOLWINDOW, WS_EX_TRANSPARENT,
+    },
+};
+
+use crate::commands::PasteState;
+use crate::i18n;
+use crate::settings;
+
+const OVERLAY_LABEL: &str = "overlay";
+/// 悬浮窗的逻辑尺寸
+const OVERLAY_WIDTH: f64 = 240.0;
+const OVERLAY_HEIGHT: f64 = 56.0;
+/// 与屏幕边缘、鼠标指针的距离（物理像素）
+const EDGE_MARGIN: i32 = 16;
+const CURSOR_OFFSET: i32 = 20;
+/// 粘贴结束后停留多久再隐藏，让用户看到最终进度
+const HIDE_DELAY: Duration = Duration::from_millis(800);
+
+/// 每次显示加一；延迟隐藏时若已再次显示则不隐藏
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// 悬浮窗出现的位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverlayPosition {
+    /// 开始粘贴时鼠标指针的右下方
+    Cursor,
+    /// 主屏幕工作区的右上角
+    TopRight,
+    /// 主屏幕工作区的右下角（任务栏上方）
+    BottomRight,
+}
+
+/// 倒计时与进度悬浮窗的设置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OverlayPrefs {
+    pub enabled: bool,
+    pub position: OverlayPosition,
+}
+
+impl Default for OverlayPrefs {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            position: OverlayPosition::BottomRight,
+        }
+    }
+}
+
+/// 悬浮窗显示的文字，{n}、{typed}、{total}、{eta} 由页面替换
+#[derive(Debug, Clone, Serialize)]
+pub struct OverlayLabels {
+    pub preparing: &'static str,
+    pub countdown: &'static str,
+    pub typing: &'static str,
+    pub paused: &'static str,
+}
+
+impl OverlayLabels {
+    fn current() -> Self {
+        Self {
+            preparing: i18n::t("overlay.preparing"),
+            countdown: i18n::t("overlay.countdown"),
+            typing: i18n::t("overlay.typing"),
+            paused: i18n::t("overlay.paused"),
+        }
+    }
+}
+
+#[cfg(windows)]
+fn hwnd(window: &Window) -> Option<HWND> {
+    window.hwnd().ok().map(|hwnd| HWND(hwnd.0))
+}
+
+/// 获取（必要时创建）悬浮窗。悬浮窗不接受焦点、不拦截鼠标，避免抢走目标窗口的输入
+fn window(app_handle: &tauri::AppHandle) -> Option<Window> {
+    if let Some(window) = app_handle.get_window(OVERLAY_LABEL) {
+        return Some(window);
+    }
+    let window = WindowBuilder::new(
+        app_handle,
+        OVERLAY_LABEL,
+        WindowUrl::App("overlay.html".into()),
+    )
+    .title("Paster")
+    .inner_size(OVERLAY_WIDTH, OVERLAY_HEIGHT)
+    .resizable(false)
+    .decorations(false)
+    .transparent(true)
+    .always_on_top(true)
+    .skip_taskbar(true)
+    .focused(false)
+    .visible(false)
+    .build()
+    .map_err(|_e| {
+        #[cfg(debug_assertions)]
+        eprintln!("创建悬浮窗失败: {}", _e);
+    })
+    .ok()?;
+    #[cfg(windows)]
+    if let Some(hwnd) = hwnd(&window) {
+        unsafe {
+            let style = GetWindowLongPtrW(hwnd, GWL_EXSTYLE);
+            let extra = (WS_EX_NOACTIVATE | WS_EX_TRANSPARENT | WS_EX_TOOLWINDOW).0 as isize;
+            SetWindowLongPtrW(hwnd, GWL_EXSTYLE, style | extra);
+        }
+    }
+    Some(window)
+}
+
+/// 鼠标指针的位置（物理像素）
+#[cfg(windows)]
+fn cursor_position() -> Option<PhysicalPosition<i32>> {
+    let mut point = POINT::default();
+    unsafe { GetCursorPos(&mut point) }.ok()?;
+    Some(PhysicalPosition::new(point.x, point.y))
+}
+
+/// 其他系统上无法取得指针位置，显示在右下角
+#[cfg(not(windows))]
+fn cursor_position() -> Option<PhysicalPosition<i32>> {
+    None
+}
+
+/// 主屏幕的工作区（不含任务栏）：左、上、右、下（物理像素）
+#[cfg(windows)]
+fn work_area(_window: &Window) -> Option<(i32, i32, i32, i32)> {
+    let mut area = RECT::default();
+    unsafe {
+        SystemParametersInfoW(
+            SPI_GETWORKAREA,
+            0,
+            Some(&mut area as *mut RECT as *mut _),
+            SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS(0),
+        )
+    }
+    .ok()?;
+    Some((area.left, area.top, area.right, area.bottom))
+}
+
+/// 其他系统上使用整个主屏幕
+#[cfg(not(windows))]
+fn work_area(window: &Window) -> Option<(i32, i32, i32, i32)> {
+    let monitor = window.primary_monitor().ok()??;
+    let (position, size) = (monitor.position(), monitor.size());
+    Some((
+        position.x,
+        position.y,
+        position.x + size.width as i32,
+        position.y + size.height as i32,
+    ))
+}
+
+/// 按设置计算悬浮窗左上角的位置（物理像素）
+fn position(window: &Window, position: OverlayPosition) -> Option<PhysicalPosition<i32>> {
+    let size = window.outer_size().ok()?;
+    let (width, height) = (size.width as i32, size.height as i32);
+    if position == OverlayPosition::Cursor {
+        if let Some(point) = cursor_position() {
+            return Some(PhysicalPosition::new(
+                point.x + CURSOR_OFFSET,
+                point.y + CURSOR_OFFSET,
+            ));
+        }
+    }
+    let (_, top, right, bottom) = work_area(window)?;
+    let x = right - width - EDGE_MARGIN;
+    let y = match position {
+        OverlayPosition::TopRight => top + EDGE_MARGIN,
+        _ => bottom - height - EDGE_MARGIN,
+    };
+    Some(PhysicalPosition::new(x, y))
+}
+
+/// 开始粘贴：在设置的位置显示悬浮窗（不激活），页面自行监听倒计时与进度事件
+pub fn show(app_handle: &tauri::AppHandle) {
+    let prefs = {
+        let state = app_handle.state::<Mutex<PasteState>>();
+        let locked = state.lock().unwrap();
+        locked.overlay
+    };
+    if !prefs.enabled {
+        return;
+    }
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+    let Some(window) = window(app_handle) else {
+        return;
+    };
+    if let Some(position) = position(&window, prefs.position) {
+        let _ = window.set_position(position);
+    }
+    let _ = window.emit("overlay-reset", OverlayLabels::current());
+    #[cfg(windows)]
+    if let Some(hwnd) = hwnd(&window) {
+        unsafe {
+            ShowWindow(hwnd, SW_SHOWNOACTIVATE);
+        }
+        return;
+    }
+    let _ = window.show();
+}
+
+/// 粘贴结束：稍后隐藏悬浮窗；期间开始了新的粘贴则保留
+pub fn hide(app_handle: &tauri::AppHandle) {
+    let generation = GENERATION.load(Ordering::SeqCst);
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(HIDE_DELAY).await;
+        if GENERATION.load(Ordering::SeqCst) != generation {
+            return;
+        }
+        if let Some(window) = app_handle.get_window(OVERLAY_LABEL) {
+            let _ = window.hide();
+        }
+    });
+}
+
+/// 悬浮窗页面加载时获取显示的文字
+#[tauri::command]
+pub fn get_overlay_labels() -> OverlayLabels {
+    OverlayLabels::current()
+}
+
+/// 设置是否显示倒计时与进度悬浮窗及其位置
+#[tauri::command]
+pub fn set_overlay_prefs(prefs: OverlayPrefs, app_handle: tauri::AppHandle) {
+    {
+        let state = app_handle.state::<Mutex<PasteState>>();
+        state.lock().unwrap().overlay = prefs;
+    }
+    if !prefs.enabled {
+        if let Some(window) = app_handle.get_window(OVERLAY_LABEL) {
+            let _ = window.hide();
+        }
+    }
+    settings::persist(&app_handle);
+}
+
+/// 获取悬浮窗设置
+#[tauri::command]
+pub fn get_overlay_prefs(app_handle: tauri::AppHandle) -> OverlayPrefs {
+    let state = app_handle.state::<Mutex<PasteState>>();
+    let locked = state.lock().unwrap();
+    locked.overlay
+}
@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+use crate::i18n;
+use crate::notify;
+use crate::platform;
+
+/// 系统隐私设置中需要为 Paster 打开的权限（目前只有 macOS 需要）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    /// 辅助功能：发送模拟按键
+    Accessibility,
+    /// 输入监控：检测键盘活动以中断粘贴
+    InputMonitoring,
+}
+
+impl Permission {
+    /// 缺少时粘贴完全不可用
+    fn required(self) -> bool {
+        self == Permission::Accessibility
+    }
+}
+
+/// 单项权限的状态
+#[derive(Debug, Clone, Serialize)]
+pub struct PermissionStatus {
+    pub permission: Permission,
+    pub granted: bool,
+    pub required: bool,
+}
+
+/// 当前平台需要的权限及其授予状态；不需要额外授权的平台返回空列表
+fn statuses() -> Vec<PermissionStatus> {
+    platform::permissions()
+        .into_iter()
+        .map(|(permission, granted)| PermissionStatus {
+            permission,
+            granted,
+            required: permission.required(),
+        })
+        .collect()
+}
+
+/// 启动时检查权限：缺少必需的权限时发送 permissions-missing 事件并弹出通知。
+/// 页面加载晚于启动时可能错过事件，应在加载后再调用 check_permissions
+pub fn check_on_startup(app_handle: &tauri::AppHandle) {
+    let missing: Vec<PermissionStatus> = statuses()
+        .into_iter()
+        .filter(|status| status.required && !status.granted)
+        .collect();
+    if missing.is_empty() {
+        return;
+    }
+
+    #[cfg(debug_assertions)]
+    println!("缺少系统权限: {:?}", missing);
+
+    let _ = app_handle.emit_all("permissions-missing", &missing);
+    notify::permissions_missing(app_handle);
+}
+
+/// 获取各项系统权限的授予状态
+#[tauri::command]
+pub fn check_permissions() -> Vec<PermissionStatus> {
+    statuses()
+}
+
+/// 打开系统设置中对应权限的页面
+#[tauri::command]
+pub fn open_permission_settings(permission: Permission) -> Result<(), String> {
+    if statuses().is_empty() {
+        return Err(i18n::t("error.permissions_not_required").to_string());
+    }
+    platform::open_permission_settings(permission)
+        .map_err(|e| i18n::tf("error.open_permission_settings", &[("error", &e)]))
+}
@@ -0,0 +1,61 @@
+// X11 keysym：XTest 与 RemoteDesktop 门户都以 keysym 描述按键
+
+use super::{
+    VirtualKey, VK_BACK, VK_CONTROL, VK_DELETE, VK_DOWN, VK_END, VK_ESCAPE, VK_HOME, VK_LEFT,
+    VK_MENU, VK_RETURN, VK_RIGHT, VK_SHIFT, VK_SPACE, VK_TAB, VK_UP,
+};
+
+const XK_BACKSPACE: u32 = 0xFF08;
+const XK_TAB: u32 = 0xFF09;
+const XK_RETURN: u32 = 0xFF0D;
+const XK_ESCAPE: u32 = 0xFF1B;
+const XK_HOME: u32 = 0xFF50;
+const XK_LEFT: u32 = 0xFF51;
+const XK_UP: u32 = 0xFF52;
+const XK_RIGHT: u32 = 0xFF53;
+const XK_DOWN: u32 = 0xFF54;
+const XK_END: u32 = 0xFF57;
+const XK_SHIFT_L: u32 = 0xFFE1;
+const XK_CONTROL_L: u32 = 0xFFE3;
+const XK_ALT_L: u32 = 0xFFE9;
+const XK_DELETE: u32 = 0xFFFF;
+const XK_SPACE: u32 = 0x0020;
+/// 不在 Latin-1 范围内的字符使用 0x01000000 + 码位作为 keysym
+const UNICODE_KEYSYM: u32 = 0x0100_0000;
+
+/// 字符对应的 keysym：Latin-1 字符的 keysym 等于码位，其余使用 Unicode keysym
+pub fn char_keysym(code_point: u32) -> u32 {
+    match code_point {
+        0x0A | 0x0D => XK_RETURN,
+        0x09 => XK_TAB,
+        0x08 => XK_BACKSPACE,
+        0x20..=0x7E | 0xA0..=0xFF => code_point,
+        _ => UNICODE_KEYSYM | code_point,
+    }
+}
+
+/// 粘贴引擎使用的虚拟键对应的 keysym
+pub fn vk_keysym(vk: VirtualKey) -> Option<u32> {
+    let keysym = match vk {
+        VK_RETURN => XK_RETURN,
+        VK_TAB => XK_TAB,
+        VK_BACK => XK_BACKSPACE,
+        VK_SHIFT => XK_SHIFT_L,
+        VK_CONTROL => XK_CONTROL_L,
+        VK_MENU => XK_ALT_L,
+        VK_ESCAPE => XK_ESCAPE,
+        VK_SPACE => XK_SPACE,
+        VK_DELETE => XK_DELETE,
+        VK_HOME => XK_HOME,
+        VK_END => XK_END,
+        VK_LEFT => XK_LEFT,
+        VK_UP => XK_UP,
+        VK_RIGHT => XK_RIGHT,
+        VK_DOWN => XK_DOWN,
+        // 数字键的 keysym 与 ASCII 相同，字母键使用小写字母
+        VirtualKey(vk @ 0x30..=0x39) => vk as u32,
+        VirtualKey(vk @ 0x41..=0x5A) => (vk + 0x20) as u32,
+        _ => return None,
+    };
+    Some(keysym)
+}
@@ -0,0 +1,328 @@
+use std::ffi::{c_void, CStr, CString};
+use std::os::raw::{c_char, c_int, c_ulong};
+use std::process::Command;
+use std::sync::Mutex;
+
+use super::{
+    Clipboard, Injector, KeyInput, SecureInput, VirtualKey, VK_BACK, VK_CONTROL, VK_DELETE,
+    VK_DOWN, VK_END, VK_ESCAPE, VK_HOME, VK_LEFT, VK_MENU, VK_RETURN, VK_RIGHT, VK_SHIFT, VK_SPACE,
+    VK_TAB, VK_UP,
+};
+use crate::i18n;
+use crate::permissions::Permission;
+
+type Id = *mut c_void;
+type Sel = *const c_void;
+type CGEventRef = *mut c_void;
+type CGEventSourceRef = *mut c_void;
+
+/// kCGEventSourceStateHIDSystemState
+const HID_SYSTEM_STATE: i32 = 1;
+/// kCGHIDEventTap
+const HID_EVENT_TAP: u32 = 0;
+/// kCGEventFlagMaskShift / Control / Alternate
+const FLAG_SHIFT: u64 = 0x0002_0000;
+const FLAG_CONTROL: u64 = 0x0004_0000;
+const FLAG_ALTERNATE: u64 = 0x0008_0000;
+
+#[link(name = "ApplicationServices", kind = "framework")]
+extern "C" {
+    fn AXIsProcessTrusted() -> bool;
+}
+
+#[link(name = "Carbon", kind = "framework")]
+extern "C" {
+    fn IsSecureEventInputEnabled() -> u8;
+}
+
+// libSystem 中的 libproc
+extern "C" {
+    fn proc_name(pid: c_int, buffer: *mut c_void, buffer_size: u32) -> c_int;
+}
+
+#[link(name = "CoreFoundation", kind = "framework")]
+extern "C" {
+    fn CFRelease(cf: *const c_void);
+}
+
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    fn CGEventSourceCreate(state: i32) -> CGEventSourceRef;
+    fn CGEventCreateKeyboardEvent(
+        source: CGEventSourceRef,
+        keycode: u16,
+        key_down: bool,
+    ) -> CGEventRef;
+    fn CGEventKeyboardSetUnicodeString(event: CGEventRef, length: c_ulong, string: *const u16);
+    fn CGEventSetFlags(event: CGEventRef, flags: u64);
+    fn CGEventPost(tap: u32, event: CGEventRef);
+    fn CGPreflightListenEventAccess() -> bool;
+}
+
+#[link(name = "AppKit", kind = "framework")]
+extern "C" {
+    static NSPasteboardTypeString: Id;
+}
+
+#[link(name = "objc")]
+extern "C" {
+    fn objc_getClass(name: *const c_char) -> Id;
+    fn sel_registerName(name: *const c_char) -> Sel;
+    fn objc_msgSend();
+    fn objc_autoreleasePoolPush() -> *mut c_void;
+    fn objc_autoreleasePoolPop(pool: *mut c_void);
+}
+
+/// 按下的修饰键与拆开的代理对，跨多次 send_inputs 保留
+static KEYBOARD: Mutex<Keyboard> = Mutex::new(Keyboard {
+    flags: 0,
+    high_surrogate: None,
+});
+
+struct Keyboard {
+    /// 当前由 Paster 按下的修饰键，附加到之后的虚拟键事件上
+    flags: u64,
+    /// 等待低位代理项的高位代理项
+    high_surrogate: Option<u16>,
+}
+
+/// 粘贴引擎使用的虚拟键对应的 macOS 键码（kVK_*，ANSI 布局）
+fn vk_keycode(vk: VirtualKey) -> Option<u16> {
+    /// A~Z 的 kVK_ANSI_* 键码
+    const LETTERS: [u16; 26] = [
+        0x00, 0x0B, 0x08, 0x02, 0x0E, 0x03, 0x05, 0x04, 0x22, 0x26, 0x28, 0x25, 0x2E, 0x2D, 0x1F,
+        0x23, 0x0C, 0x0F, 0x01, 0x11, 0x20, 0x09, 0x0D, 0x07, 0x10, 0x06,
+    ];
+    /// 0~9 的 kVK_ANSI_* 键码
+    const DIGITS: [u16; 10] = [0x1D, 0x12, 0x13, 0x14, 0x15, 0x17, 0x16, 0x1A, 0x1C, 0x19];
+    let keycode = match vk {
+        VK_RETURN => 0x24,
+        VK_TAB => 0x30,
+        VK_SPACE => 0x31,
+        VK_BACK => 0x33,
+        VK_ESCAPE => 0x35,
+        VK_SHIFT => 0x38,
+        VK_MENU => 0x3A,
+        VK_CONTROL => 0x3B,
+        VK_HOME => 0x73,
+        VK_DELETE => 0x75,
+        VK_END => 0x77,
+        VK_LEFT => 0x7B,
+        VK_RIGHT => 0x7C,
+        VK_DOWN => 0x7D,
+        VK_UP => 0x7E,
+        VirtualKey(vk @ 0x30..=0x39) => DIGITS[(vk - 0x30) as usize],
+        VirtualKey(vk @ 0x41..=0x5A) => LETTERS[(vk - 0x41) as usize],
+        _ => return None,
+    };
+    Some(keycode)
+}
+
+/// 修饰键对应的 CGEventFlags
+fn vk_flag(vk: VirtualKey) -> u64 {
+    match vk {
+        VK_SHIFT => FLAG_SHIFT,
+        VK_CONTROL => FLAG_CONTROL,
+        VK_MENU => FLAG_ALTERNATE,
+        _ => 0,
+    }
+}
+
+/// 发送一个键盘事件；units 非空时作为事件携带的字符，不受键盘布局与修饰键影响
+unsafe fn post(source: CGEventSourceRef, keycode: u16, key_down: bool, flags: u64, units: &[u16]) {
+    let event = CGEventCreateKeyboardEvent(source, keycode, key_down);
+    if event.is_null() {
+        return;
+    }
+    if !units.is_empty() {
+        CGEventKeyboardSetUnicodeString(event, units.len() as c_ulong, units.as_ptr());
+    }
+    CGEventSetFlags(event, flags);
+    CGEventPost(HID_EVENT_TAP, event);
+    CFRelease(event);
+}
+
+/// 把一次按键翻译为 CGEvent 并发送
+unsafe fn send(source: CGEventSourceRef, keyboard: &mut Keyboard, input: KeyInput) {
+    let key_down = !input.key_up();
+    let vk = match input {
+        KeyInput::Key { vk, .. } => vk,
+        KeyInput::Unicode { unit, .. } => {
+            match unit {
+                // 代理对在低位代理项上一起发送
+                0xD800..=0xDBFF => {
+                    if key_down {
+                        keyboard.high_surrogate = Some(unit);
+                    }
+                }
+                0xDC00..=0xDFFF => {
+                    if let Some(high) = keyboard.high_surrogate {
+                        post(source, 0, key_down, 0, &[high, unit]);
+                        if !key_down {
+                            keyboard.high_surrogate = None;
+                        }
+                    }
+                }
+                // 换行以回车键发送，否则多数程序不会换行
+                10 | 13 => post(source, 0x24, key_down, 0, &[]),
+                _ => post(source, 0, key_down, 0, &[unit]),
+            }
+            return;
+        }
+        // Windows 扫描码与 macOS 键码没有对应关系
+        KeyInput::Scan { .. } => return,
+    };
+    let Some(keycode) = vk_keycode(vk) else {
+        return;
+    };
+    let flag = vk_flag(vk);
+    if key_down {
+        keyboard.flags |= flag;
+    } else {
+        keyboard.flags &= !flag;
+    }
+    post(source, keycode, key_down, keyboard.flags, &[]);
+}
+
+unsafe fn class(name: &str) -> Id {
+    let name = CString::new(name).unwrap();
+    objc_getClass(name.as_ptr())
+}
+
+unsafe fn selector(name: &str) -> Sel {
+    let name = CString::new(name).unwrap();
+    sel_registerName(name.as_ptr())
+}
+
+/// 读取 NSPasteboard 中的纯文本：[[NSPasteboard generalPasteboard] stringForType:NSPasteboardTypeString]
+unsafe fn pasteboard_string() -> Option<String> {
+    let send: unsafe extern "C" fn(Id, Sel) -> Id = std::mem::transmute(objc_msgSend as *const ());
+    let send_id: unsafe extern "C" fn(Id, Sel, Id) -> Id =
+        std::mem::transmute(objc_msgSend as *const ());
+    let send_utf8: unsafe extern "C" fn(Id, Sel) -> *const c_char =
+        std::mem::transmute(objc_msgSend as *const ());
+
+    let pasteboard = send(class("NSPasteboard"), selector("generalPasteboard"));
+    if pasteboard.is_null() {
+        return None;
+    }
+    let string = send_id(
+        pasteboard,
+        selector("stringForType:"),
+        NSPasteboardTypeString,
+    );
+    if string.is_null() {
+        return None;
+    }
+    let utf8 = send_utf8(string, selector("UTF8String"));
+    if utf8.is_null() {
+        return None;
+    }
+    Some(CStr::from_ptr(utf8).to_string_lossy().into_owned())
+}
+
+/// 辅助功能（发送按键）与输入监控（检测键盘活动）的授予状态
+pub fn permissions() -> Vec<(Permission, bool)> {
+    unsafe {
+        vec![
+            (Permission::Accessibility, AXIsProcessTrusted()),
+            (Permission::InputMonitoring, CGPreflightListenEventAccess()),
+        ]
+    }
+}
+
+/// 打开"系统设置 > 隐私与安全性"中对应的页面
+pub fn open_permission_settings(permission: Permission) -> std::io::Result<()> {
+    let pane = match permission {
+        Permission::Accessibility => "Privacy_Accessibility",
+        Permission::InputMonitoring => "Privacy_ListenEvent",
+    };
+    Command::new("open")
+        .arg(format!(
+            "x-apple.systempreferences:com.apple.preference.security?{}",
+            pane
+        ))
+        .status()
+        .map(|_| ())
+}
+
+/// 开启 Secure Input 的进程：登录会话的 kCGSSessionSecureInputPID 属性（IOConsoleUsers 中）
+fn secure_input_pid() -> Option<i32> {
+    let output = Command::new("ioreg")
+        .args(["-l", "-w", "0", "-d", "1"])
+        .output()
+        .ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let key = "\"kCGSSessionSecureInputPID\"=";
+    let start = text.find(key)? + key.len();
+    let digits: String = text[start..]
+        .chars()
+        .take_while(char::is_ascii_digit)
+        .collect();
+    digits.parse().ok()
+}
+
+/// 进程的名称
+fn process_name(pid: i32) -> Option<String> {
+    let mut buffer = [0u8; 256];
+    let len = unsafe { proc_name(pid, buffer.as_mut_ptr().cast(), buffer.len() as u32) };
+    if len <= 0 {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&buffer[..len as usize]).into_owned())
+}
+
+/// macOS：CGEvent 注入按键，NSPasteboard 读取剪贴板
+pub struct MacOs;
+
+impl Clipboard for MacOs {
+    /// 读取剪贴板的文本，转换为 UTF-16 并舍弃 '\r'
+    fn read_text(&self) -> Result<Vec<u16>, &'static str> {
+        let text = unsafe {
+            // 粘贴在后台线程进行，需要自己的自动释放池
+            let pool = objc_autoreleasePoolPush();
+            let text = pasteboard_string();
+            objc_autoreleasePoolPop(pool);
+            text
+        };
+        let text = text.ok_or(i18n::t("error.clipboard_empty"))?;
+        Ok(text.encode_utf16().filter(|&unit| unit != 13).collect())
+    }
+}
+
+impl Injector for MacOs {
+    /// 没有辅助功能权限时系统会直接丢弃 CGEvent，开始前先检查
+    fn check(&self) -> Result<(), &'static str> {
+        if unsafe { AXIsProcessTrusted() } {
+            Ok(())
+        } else {
+            Err(i18n::t("error.accessibility_denied"))
+        }
+    }
+
+    /// 密码框等开启 Secure Input 时系统会丢弃合成的按键
+    fn secure_input(&self) -> Option<SecureInput> {
+        if unsafe { IsSecureEventInputEnabled() } == 0 {
+            return None;
+        }
+        let pid = secure_input_pid();
+        Some(SecureInput {
+            pid,
+            process: pid.and_then(process_name),
+        })
+    }
+
+    /// 以 CGEvent 发送一组键盘输入
+    fn send(&self, inputs: &[KeyInput]) {
+        let mut keyboard = KEYBOARD.lock().unwrap();
+        unsafe {
+            let source = CGEventSourceCreate(HID_SYSTEM_STATE);
+            for &input in inputs {
+                send(source, &mut keyboard, input);
+            }
+            if !source.is_null() {
+                CFRelease(source);
+            }
+        }
+    }
+}
@@ -0,0 +1,287 @@
+// 剪贴板读取与键盘输入注入的平台实现。
+// 粘贴引擎统一以 KeyInput 描述按键，由各平台的 Injector 翻译为本地的注入方式
+
+use serde::{Deserialize, Serialize};
+
+use crate::i18n;
+use crate::permissions::Permission;
+
+// 每个后端由同名的 cargo feature 控制（win32 对应 win-sendinput），只在对应的系统上编译
+#[cfg(all(target_os = "linux", any(feature = "x11", feature = "portal")))]
+mod keysym;
+#[cfg(all(target_os = "macos", feature = "macos"))]
+mod macos;
+#[cfg(all(target_os = "linux", feature = "portal"))]
+mod portal;
+#[cfg(all(target_os = "linux", feature = "wayland"))]
+mod wayland;
+#[cfg(all(
+    not(any(target_os = "linux", target_os = "macos")),
+    feature = "win-sendinput"
+))]
+mod win32;
+#[cfg(all(target_os = "linux", feature = "x11"))]
+mod x11;
+
+/// 虚拟键码，取值与 Win32 的 VK_* 相同，各平台据此换算为本地的键码
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VirtualKey(pub u16);
+
+pub const VK_BACK: VirtualKey = VirtualKey(0x08);
+pub const VK_TAB: VirtualKey = VirtualKey(0x09);
+pub const VK_RETURN: VirtualKey = VirtualKey(0x0D);
+pub const VK_SHIFT: VirtualKey = VirtualKey(0x10);
+pub const VK_CONTROL: VirtualKey = VirtualKey(0x11);
+pub const VK_MENU: VirtualKey = VirtualKey(0x12);
+pub const VK_ESCAPE: VirtualKey = VirtualKey(0x1B);
+pub const VK_SPACE: VirtualKey = VirtualKey(0x20);
+pub const VK_END: VirtualKey = VirtualKey(0x23);
+pub const VK_HOME: VirtualKey = VirtualKey(0x24);
+pub const VK_LEFT: VirtualKey = VirtualKey(0x25);
+pub const VK_UP: VirtualKey = VirtualKey(0x26);
+pub const VK_RIGHT: VirtualKey = VirtualKey(0x27);
+pub const VK_DOWN: VirtualKey = VirtualKey(0x28);
+pub const VK_DELETE: VirtualKey = VirtualKey(0x2E);
+
+/// 一次按下或抬起
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyInput {
+    /// 虚拟键
+    Key { vk: VirtualKey, key_up: bool },
+    /// 扫描码，只由 Windows 按布局换算产生
+    #[cfg_attr(not(windows), allow(dead_code))]
+    Scan { scan: u16, key_up: bool },
+    /// 以 Unicode 方式发送的一个 UTF-16 单元
+    Unicode { unit: u16, key_up: bool },
+}
+
+impl KeyInput {
+    pub fn key_up(self) -> bool {
+        match self {
+            KeyInput::Key { key_up, .. }
+            | KeyInput::Scan { key_up, .. }
+            | KeyInput::Unicode { key_up, .. } => key_up,
+        }
+    }
+}
+
+/// 换算虚拟键时使用的键盘状态：键盘布局句柄（HKL 的原始值）与大写锁定
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(any(target_os = "linux", target_os = "macos"), allow(dead_code))] // 只有 Windows 按布局换算
+pub struct KeyLayout {
+    pub layout: isize,
+    pub caps_lock: bool,
+}
+
+/// 粘贴时读取文本的来源
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TextSource {
+    /// 剪贴板（Ctrl+C 复制的内容）
+    #[default]
+    Clipboard,
+    /// X11/Wayland 的 PRIMARY 选区（选中即复制的内容），仅 Linux
+    Primary,
+    /// 其他电脑上的 Paster 通过局域网剪贴板中转发来的最新文本
+    Relay,
+}
+
+impl TextSource {
+    /// 当前平台是否支持这种来源
+    pub fn is_supported(self) -> bool {
+        self != TextSource::Primary || cfg!(target_os = "linux")
+    }
+}
+
+/// 开启了 Secure Input（安全键盘输入）的进程，合成的按键会被系统丢弃
+#[derive(Debug, Clone, Serialize)]
+pub struct SecureInput {
+    pub pid: Option<i32>,
+    /// 进程名称，无法确定时为 None
+    pub process: Option<String>,
+}
+
+/// 读取剪贴板文本
+pub trait Clipboard: Sync {
+    /// 剪贴板的 UTF-16 内容（已舍弃 '\r'）
+    fn read_text(&self) -> Result<Vec<u16>, &'static str>;
+
+    /// PRIMARY 选区的 UTF-16 内容（已舍弃 '\r'）
+    fn read_primary(&self) -> Result<Vec<u16>, &'static str> {
+        Err(i18n::t("error.primary_unsupported"))
+    }
+}
+
+/// 从指定来源读取文本
+pub fn read_source(source: TextSource) -> Result<Vec<u16>, &'static str> {
+    match source {
+        TextSource::Clipboard => clipboard().read_text(),
+        TextSource::Primary => clipboard().read_primary(),
+        TextSource::Relay => crate::relay::received().ok_or(i18n::t("error.relay_empty")),
+    }
+}
+
+/// 向前台窗口注入键盘输入
+pub trait Injector: Sync {
+    /// 检查当前桌面能否注入按键，不能时返回原因
+    fn check(&self) -> Result<(), &'static str> {
+        Ok(())
+    }
+
+    /// 系统是否正在拦截合成的按键（macOS 的 Secure Input），拦截时返回开启它的进程
+    fn secure_input(&self) -> Option<SecureInput> {
+        None
+    }
+
+    /// 发送一组键盘输入
+    fn send(&self, inputs: &[KeyInput]);
+
+    /// 目标（前台）窗口的键盘布局；平台不支持按布局换算时为 None，字符都以 Unicode 方式发送
+    fn key_layout(&self) -> Option<KeyLayout> {
+        None
+    }
+
+    /// 把字符换算为该布局下的按键（需要 Shift 时一并按下），scan_code 为 true 时以扫描码发送；
+    /// 无法换算时返回 None
+    fn layout_inputs(
+        &self,
+        _ch: u16,
+        _layout: KeyLayout,
+        _scan_code: bool,
+    ) -> Option<Vec<KeyInput>> {
+        None
+    }
+
+    /// 抬起这些按键，避免中止后按键卡住；无法查询按键状态的平台直接全部抬起
+    fn release_keys(&self, vks: &[VirtualKey]) {
+        let inputs: Vec<KeyInput> = vks
+            .iter()
+            .map(|&vk| KeyInput::Key { vk, key_up: true })
+            .collect();
+        self.send(&inputs);
+    }
+}
+
+/// 没有编译进当前系统可用的后端时使用：读取剪贴板与粘贴都报错
+#[allow(dead_code)] // 启用了对应后端时不会用到
+struct Unavailable;
+
+impl Clipboard for Unavailable {
+    fn read_text(&self) -> Result<Vec<u16>, &'static str> {
+        Err(i18n::t("error.no_input_backend"))
+    }
+}
+
+impl Injector for Unavailable {
+    fn check(&self) -> Result<(), &'static str> {
+        Err(i18n::t("error.no_input_backend"))
+    }
+
+    fn send(&self, _inputs: &[KeyInput]) {}
+}
+
+/// 当前桌面会话是否为 Wayland（XWayland 下的 XTest 无法把按键送到原生 Wayland 窗口）
+#[cfg(all(target_os = "linux", feature = "wayland"))]
+fn wayland_session() -> bool {
+    std::env::var_os("WAYLAND_DISPLAY").is_some_and(|display| !display.is_empty())
+}
+
+/// 当前平台可用的后端：Wayland 会话优先使用 Wayland 后端，
+/// 未编译 Wayland 后端时退回 X11 后端（只能输入到 XWayland 窗口）
+#[cfg(target_os = "linux")]
+fn backend() -> &'static dyn Backend {
+    #[cfg(feature = "x11")]
+    let fallback: &'static dyn Backend = &self::x11::X11;
+    #[cfg(all(not(feature = "x11"), feature = "wayland"))]
+    let fallback: &'static dyn Backend = &wayland::Wayland;
+    #[cfg(not(any(feature = "x11", feature = "wayland")))]
+    let fallback: &'static dyn Backend = &Unavailable;
+
+    #[cfg(feature = "wayland")]
+    if wayland_session() {
+        return &wayland::Wayland;
+    }
+    fallback
+}
+
+/// 当前平台可用的后端
+#[cfg(target_os = "macos")]
+fn backend() -> &'static dyn Backend {
+    #[cfg(feature = "macos")]
+    let backend: &'static dyn Backend = &macos::MacOs;
+    #[cfg(not(feature = "macos"))]
+    let backend: &'static dyn Backend = &Unavailable;
+    backend
+}
+
+/// 当前平台可用的后端
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn backend() -> &'static dyn Backend {
+    #[cfg(feature = "win-sendinput")]
+    let backend: &'static dyn Backend = &win32::Win32;
+    #[cfg(not(feature = "win-sendinput"))]
+    let backend: &'static dyn Backend = &Unavailable;
+    backend
+}
+
+/// 同时提供剪贴板与输入注入的后端
+trait Backend: Clipboard + Injector {
+    fn as_clipboard(&self) -> &dyn Clipboard;
+    fn as_injector(&self) -> &dyn Injector;
+}
+
+impl<T: Clipboard + Injector> Backend for T {
+    fn as_clipboard(&self) -> &dyn Clipboard {
+        self
+    }
+
+    fn as_injector(&self) -> &dyn Injector {
+        self
+    }
+}
+
+/// 当前平台的剪贴板
+pub fn clipboard() -> &'static dyn Clipboard {
+    backend().as_clipboard()
+}
+
+/// 当前平台的输入注入：Flatpak 沙盒中通过 RemoteDesktop 门户
+pub fn injector() -> &'static dyn Injector {
+    #[cfg(all(target_os = "linux", feature = "portal"))]
+    if portal::sandboxed() {
+        return &portal::Portal;
+    }
+    backend().as_injector()
+}
+
+/// 启动时的初始化：RemoteDesktop 门户的授权保存在配置目录中，之后不再询问
+pub fn init(_app_handle: &tauri::AppHandle) {
+    #[cfg(all(target_os = "linux", feature = "portal"))]
+    if let Ok(path) = crate::config::config_path(_app_handle, portal::TOKEN_FILE_NAME) {
+        portal::set_token_file(path);
+    }
+}
+
+/// 需要用户在系统设置中授予的权限及是否已授予
+#[cfg(all(target_os = "macos", feature = "macos"))]
+pub fn permissions() -> Vec<(Permission, bool)> {
+    macos::permissions()
+}
+
+/// 打开系统设置中对应权限的页面
+#[cfg(all(target_os = "macos", feature = "macos"))]
+pub fn open_permission_settings(permission: Permission) -> std::io::Result<()> {
+    macos::open_permission_settings(permission)
+}
+
+/// 需要用户在系统设置中授予的权限及是否已授予
+#[cfg(not(all(target_os = "macos", feature = "macos")))]
+pub fn permissions() -> Vec<(Permission, bool)> {
+    Vec::new()
+}
+
+/// 打开系统设置中对应权限的页面
+#[cfg(not(all(target_os = "macos", feature = "macos")))]
+pub fn open_permission_settings(_permission: Permission) -> std::io::Result<()> {
+    Err(std::io::ErrorKind::Unsupported.into())
+}
@@ -0,0 +1,264 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+use zbus::blocking::{Connection, Proxy};
+use zbus::zvariant::{OwnedObjectPath, OwnedValue, Value};
+
+use super::keysym::{char_keysym, vk_keysym};
+use super::{Injector, KeyInput};
+use crate::i18n;
+
+/// 保存门户授权的文件名（位于配置目录）
+pub const TOKEN_FILE_NAME: &str = "portal_token";
+
+const PORTAL_DESTINATION: &str = "org.freedesktop.portal.Desktop";
+const PORTAL_PATH: &str = "/org/freedesktop/portal/desktop";
+const REMOTE_DESKTOP: &str = "org.freedesktop.portal.RemoteDesktop";
+const REQUEST: &str = "org.freedesktop.portal.Request";
+/// SelectDevices 的 types：只请求键盘
+const DEVICE_KEYBOARD: u32 = 1;
+/// SelectDevices 的 persist_mode：授权一直有效，直到用户撤销
+const PERSIST_UNTIL_REVOKED: u32 = 2;
+/// NotifyKeyboard* 的 state
+const KEY_RELEASED: u32 = 0;
+const KEY_PRESSED: u32 = 1;
+
+/// restore_token 的保存位置，由 set_token_file 在启动时设置
+static TOKEN_FILE: OnceLock<PathBuf> = OnceLock::new();
+/// 已开始的远程桌面会话；调用失败（如用户撤销了授权）时丢弃，下次粘贴重新建立
+static SESSION: Mutex<Option<Session>> = Mutex::new(None);
+
+struct Session {
+    remote_desktop: Proxy<'static>,
+    handle: OwnedObjectPath,
+    /// 以 Unicode 方式发送的高位代理项，等待与低位代理项组成一个字符
+    high_surrogate: Option<u16>,
+    /// 代理对按下时的 keysym，抬起时沿用
+    pair_keysym: Option<u32>,
+}
+
+/// 是否运行在 Flatpak 沙盒中：沙盒内无法使用 XTest 与 wtype，只能通过门户注入按键
+pub fn sandboxed() -> bool {
+    Path::new("/.flatpak-info").exists()
+}
+
+/// 设置 restore_token 的保存位置，授权一次后之后的会话不再弹出对话框
+pub fn set_token_file(path: PathBuf) {
+    let _ = TOKEN_FILE.set(path);
+}
+
+fn load_token() -> Option<String> {
+    let token = std::fs::read_to_string(TOKEN_FILE.get()?).ok()?;
+    let token = token.trim();
+    (!token.is_empty()).then(|| token.to_string())
+}
+
+fn save_token(token: &str) {
+    let Some(path) = TOKEN_FILE.get() else {
+        return;
+    };
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if let Err(_e) = std::fs::write(path, token) {
+        #[cfg(debug_assertions)]
+        eprintln!("保存门户授权失败: {}", _e);
+    }
+}
+
+/// 请求与会话的 handle_token，在同一连接内唯一即可
+fn handle_token() -> String {
+    static NEXT: AtomicU32 = AtomicU32::new(0);
+    format!("paster{}", NEXT.fetch_add(1, Ordering::Relaxed))
+}
+
+fn dbus_error(_e: zbus::Error) -> &'static str {
+    #[cfg(debug_assertions)]
+    eprintln!("调用 RemoteDesktop 门户失败: {}", _e);
+    i18n::t("error.portal_failed")
+}
+
+/// 调用门户方法并等待对应 Request 对象的 Response 信号；
+/// 用户拒绝或关闭授权对话框时返回 error.portal_denied
+fn request<B>(
+    remote_desktop: &Proxy<'static>,
+    method: &str,
+    body: &B,
+    token: &str,
+) -> Result<HashMap<String, OwnedValue>, &'static str>
+where
+    B: serde::Serialize + zbus::zvariant::DynamicType,
+{
+    let connection = remote_desktop.connection();
+    // Request 对象的路径由调用方的连接名与 handle_token 决定，先订阅信号再调用，避免错过回复
+    let sender = connection
+        .unique_name()
+        .ok_or(i18n::t("error.portal_unavailable"))?
+        .as_str()
+        .trim_start_matches(':')
+        .replace('.', "_");
+    let path = format!("{}/request/{}/{}", PORTAL_PATH, sender, token);
+    let request =
+        Proxy::new(connection, PORTAL_DESTINATION, path.as_str(), REQUEST).map_err(dbus_error)?;
+    let mut responses = request.receive_signal("Response").map_err(dbus_error)?;
+    remote_desktop
+        .call_method(method, body)
+        .map_err(dbus_error)?;
+    let message = responses.next().ok_or(i18n::t("error.portal_failed"))?;
+    let (response, results): (u32, HashMap<String, OwnedValue>) =
+        message.body().deserialize().map_err(dbus_error)?;
+    match response {
+        0 => Ok(results),
+        1 => Err(i18n::t("error.portal_denied")),
+        _ => Err(i18n::t("error.portal_failed")),
+    }
+}
+
+impl Session {
+    /// 建立远程桌面会话并申请键盘：首次会弹出授权对话框，之后凭 restore_token 直接恢复
+    fn start() -> Result<Self, &'static str> {
+        let connection = Connection::session().map_err(|_e| {
+            #[cfg(debug_assertions)]
+            eprintln!("连接会话总线失败: {}", _e);
+            i18n::t("error.portal_unavailable")
+        })?;
+        let remote_desktop =
+            Proxy::new(&connection, PORTAL_DESTINATION, PORTAL_PATH, REMOTE_DESKTOP)
+                .map_err(|_| i18n::t("error.portal_unavailable"))?;
+
+        let token = handle_token();
+        let session_token = handle_token();
+        let options = HashMap::from([
+            ("handle_token", Value::from(token.as_str())),
+            ("session_handle_token", Value::from(session_token.as_str())),
+        ]);
+        let results = request(&remote_desktop, "CreateSession", &(options,), &token)?;
+        let handle = results
+            .get("session_handle")
+            .and_then(|value| value.downcast_ref::<&str>().ok())
+            .and_then(|handle| OwnedObjectPath::try_from(handle.to_string()).ok())
+            .ok_or(i18n::t("error.portal_failed"))?;
+
+        let token = handle_token();
+        let restore_token = load_token();
+        let mut options = HashMap::from([
+            ("handle_token", Value::from(token.as_str())),
+            ("types", Value::from(DEVICE_KEYBOARD)),
+            ("persist_mode", Value::from(PERSIST_UNTIL_REVOKED)),
+        ]);
+        if let Some(restore_token) = &restore_token {
+            options.insert("restore_token", Value::from(restore_token.as_str()));
+        }
+        request(
+            &remote_desktop,
+            "SelectDevices",
+            &(&handle, options),
+            &token,
+        )?;
+
+        let token = handle_token();
+        let options = HashMap::from([("handle_token", Value::from(token.as_str()))]);
+        let results = request(&remote_desktop, "Start", &(&handle, "", options), &token)?;
+        let devices = results
+            .get("devices")
+            .and_then(|value| value.downcast_ref::<u32>().ok())
+            .unwrap_or(0);
+        if devices & DEVICE_KEYBOARD == 0 {
+            return Err(i18n::t("error.portal_denied"));
+        }
+        // 每次恢复后旧的 restore_token 失效，需要保存新的
+        if let Some(token) = results
+            .get("restore_token")
+            .and_then(|value| value.downcast_ref::<&str>().ok())
+        {
+            save_token(token);
+        }
+
+        #[cfg(debug_assertions)]
+        println!("RemoteDesktop 门户会话已开始: {}", handle.as_str());
+
+        Ok(Self {
+            remote_desktop,
+            handle,
+            high_surrogate: None,
+            pair_keysym: None,
+        })
+    }
+
+    /// 以 Unicode 方式发送的 UTF-16 单元对应的 keysym；代理对在低位代理项上按下与抬起
+    fn unicode_keysym(&mut self, unit: u16, key_up: bool) -> Option<u32> {
+        match unit {
+            0xD800..=0xDBFF => {
+                if !key_up {
+                    self.high_surrogate = Some(unit);
+                }
+                None
+            }
+            0xDC00..=0xDFFF => {
+                if key_up {
+                    return self.pair_keysym.take();
+                }
+                let high = self.high_surrogate.take()?;
+                let code_point = 0x10000 + ((high as u32 - 0xD800) << 10) + (unit as u32 - 0xDC00);
+                self.pair_keysym = Some(char_keysym(code_point));
+                self.pair_keysym
+            }
+            _ => Some(char_keysym(unit as u32)),
+        }
+    }
+
+    /// 把一次按键翻译为门户的按键调用：扫描码即 evdev keycode，其余按 keysym 发送
+    /// （合成器在当前键盘布局中查找 keysym，布局中没有的字符可能被忽略）
+    fn send(&mut self, input: KeyInput) -> zbus::Result<()> {
+        let key_up = input.key_up();
+        let key = match input {
+            KeyInput::Unicode { unit, .. } => self
+                .unicode_keysym(unit, key_up)
+                .map(|keysym| ("NotifyKeyboardKeysym", keysym)),
+            KeyInput::Scan { scan, .. } => Some(("NotifyKeyboardKeycode", scan as u32)),
+            KeyInput::Key { vk, .. } => {
+                vk_keysym(vk).map(|keysym| ("NotifyKeyboardKeysym", keysym))
+            }
+        };
+        let Some((method, key)) = key else {
+            return Ok(());
+        };
+        let state = if key_up { KEY_RELEASED } else { KEY_PRESSED };
+        let options: HashMap<&str, Value> = HashMap::new();
+        self.remote_desktop
+            .call_method(method, &(&self.handle, options, key as i32, state))?;
+        Ok(())
+    }
+}
+
+/// Flatpak：通过 XDG RemoteDesktop 门户注入按键
+pub struct Portal;
+
+impl Injector for Portal {
+    /// 没有会话时建立会话（首次需要用户在对话框中授权）
+    fn check(&self) -> Result<(), &'static str> {
+        let mut session = SESSION.lock().unwrap();
+        if session.is_none() {
+            *session = Some(Session::start()?);
+        }
+        Ok(())
+    }
+
+    /// 逐个发送按键；会话失效时丢弃，剩余的按键不再发送
+    fn send(&self, inputs: &[KeyInput]) {
+        let mut session = SESSION.lock().unwrap();
+        let Some(active) = session.as_mut() else {
+            return;
+        };
+        for &input in inputs {
+            if let Err(_e) = active.send(input) {
+                #[cfg(debug_assertions)]
+                eprintln!("门户发送按键失败: {}", _e);
+                *session = None;
+                return;
+            }
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use std::io::ErrorKind;
+use std::process::{Command, Stdio};
+use std::sync::{Mutex, OnceLock};
+
+use super::{
+    Clipboard, Injector, KeyInput, VirtualKey, VK_BACK, VK_CONTROL, VK_DELETE, VK_DOWN, VK_END,
+    VK_ESCAPE, VK_HOME, VK_LEFT, VK_MENU, VK_RETURN, VK_RIGHT, VK_SHIFT, VK_SPACE, VK_TAB, VK_UP,
+};
+use crate::i18n;
+
+/// 通过 zwp_virtual_keyboard_v1 协议注入按键的命令行工具
+const WTYPE: &str = "wtype";
+/// wl-clipboard 中读取剪贴板的命令行工具
+const WL_PASTE: &str = "wl-paste";
+
+/// 首次粘贴时检测的注入能力，失败时保存错误信息的编号
+static INPUT_SUPPORT: OnceLock<Result<(), &'static str>> = OnceLock::new();
+/// 以 Unicode 方式逐个发送的 UTF-16 单元可能在两次调用之间拆开代理对
+static SURROGATES: Mutex<Surrogates> = Mutex::new(Surrogates {
+    high: None,
+    pair_key: None,
+});
+
+struct Surrogates {
+    /// 等待低位代理项的高位代理项
+    high: Option<u16>,
+    /// 代理对按下时的键名，抬起时沿用
+    pair_key: Option<String>,
+}
+
+/// 字符对应的 xkb 键名：控制字符使用功能键，其余使用 "U+码位" 形式
+fn char_key(code_point: u32) -> String {
+    match code_point {
+        0x0A | 0x0D => "Return".into(),
+        0x09 => "Tab".into(),
+        0x08 => "BackSpace".into(),
+        _ => format!("U{:04X}", code_point),
+    }
+}
+
+/// 以 Unicode 方式发送的 UTF-16 单元对应的键名；代理对在低位代理项上按下与抬起
+fn unicode_key(unit: u16, key_up: bool) -> Option<String> {
+    let mut surrogates = SURROGATES.lock().unwrap();
+    match unit {
+        0xD800..=0xDBFF => {
+            if !key_up {
+                surrogates.high = Some(unit);
+            }
+            None
+        }
+        0xDC00..=0xDFFF => {
+            if key_up {
+                return surrogates.pair_key.take();
+            }
+            let high = surrogates.high.take()?;
+            let code_point = 0x10000 + ((high as u32 - 0xD800) << 10) + (unit as u32 - 0xDC00);
+            surrogates.pair_key = Some(char_key(code_point));
+            surrogates.pair_key.clone()
+        }
+        _ => Some(char_key(unit as u32)),
+    }
+}
+
+/// 修饰键对应的 wtype 修饰符名称
+fn vk_modifier(vk: VirtualKey) -> Option<&'static str> {
+    match vk {
+        VK_SHIFT => Some("shift"),
+        VK_CONTROL => Some("ctrl"),
+        VK_MENU => Some("alt"),
+        _ => None,
+    }
+}
+
+/// 粘贴引擎使用的虚拟键对应的 xkb 键名
+fn vk_key(vk: VirtualKey) -> Option<String> {
+    let name = match vk {
+        VK_RETURN => "Return",
+        VK_TAB => "Tab",
+        VK_BACK => "BackSpace",
+        VK_ESCAPE => "Escape",
+        VK_SPACE => "space",
+        VK_DELETE => "Delete",
+        VK_HOME => "Home",
+        VK_END => "End",
+        VK_LEFT => "Left",
+        VK_UP => "Up",
+        VK_RIGHT => "Right",
+        VK_DOWN => "Down",
+        // 数字键与字母键（小写）的键名就是字符本身
+        VirtualKey(vk @ 0x30..=0x39) => return Some((vk as u8 as char).to_string()),
+        VirtualKey(vk @ 0x41..=0x5A) => {
+            return Some((vk as u8 as char).to_ascii_lowercase().to_string())
+        }
+        _ => return None,
+    };
+    Some(name.into())
+}
+
+/// 把一次按键翻译为 wtype 参数
+fn input_args(input: KeyInput, args: &mut Vec<String>) {
+    let key_up = input.key_up();
+    let key = match input {
+        KeyInput::Unicode { unit, .. } => unicode_key(unit, key_up),
+        // virtual-keyboard 协议使用 wtype 自己上传的键盘布局，没有可用的扫描码
+        KeyInput::Scan { .. } => None,
+        KeyInput::Key { vk, .. } => match vk_modifier(vk) {
+            Some(modifier) => {
+                args.push(if key_up { "-m" } else { "-M" }.into());
+                args.push(modifier.into());
+                return;
+            }
+            None => vk_key(vk),
+        },
+    };
+    if let Some(key) = key {
+        args.push(if key_up { "-p" } else { "-P" }.into());
+        args.push(key);
+    }
+}
+
+/// Wayland：wtype（virtual-keyboard 协议）注入按键，wl-paste 读取剪贴板
+pub struct Wayland;
+
+/// 通过 wl-paste 读取剪贴板（primary 为 true 时读取 PRIMARY 选区）的文本，转换为 UTF-16 并舍弃 '\r'
+fn wl_paste(primary: bool) -> Result<Vec<u16>, &'static str> {
+    let mut command = Command::new(WL_PASTE);
+    command.args(["--no-newline", "--type", "text"]);
+    if primary {
+        command.arg("--primary");
+    }
+    let output = command
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .map_err(|e| match e.kind() {
+            ErrorKind::NotFound => i18n::t("error.wl_paste_missing"),
+            _ => i18n::t("error.open_clipboard"),
+        })?;
+    // 剪贴板为空或没有文本格式时 wl-paste 以非零状态退出
+    if !output.status.success() {
+        return Err(i18n::t("error.clipboard_empty"));
+    }
+    let text = String::from_utf8_lossy(&output.stdout);
+    Ok(text.encode_utf16().filter(|&unit| unit != 13).collect())
+}
+
+impl Clipboard for Wayland {
+    fn read_text(&self) -> Result<Vec<u16>, &'static str> {
+        wl_paste(false)
+    }
+
+    fn read_primary(&self) -> Result<Vec<u16>, &'static str> {
+        wl_paste(true)
+    }
+}
+
+impl Injector for Wayland {
+    /// 检查 wtype 是否可用、合成器是否支持 virtual-keyboard 协议
+    fn check(&self) -> Result<(), &'static str> {
+        let support = INPUT_SUPPORT.get_or_init(|| {
+            // 只等待 0 毫秒、不输入任何内容，wtype 在合成器不支持协议时以非零状态退出
+            match Command::new(WTYPE)
+                .args(["-s", "0"])
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+            {
+                Ok(status) if status.success() => Ok(()),
+                Ok(_) => Err("error.wayland_virtual_keyboard"),
+                Err(_e) => {
+                    #[cfg(debug_assertions)]
+                    eprintln!("启动 wtype 失败: {}", _e);
+                    Err("error.wtype_missing")
+                }
+            }
+        });
+        support.map_err(i18n::t)
+    }
+
+    /// 以一次 wtype 调用发送一组键盘输入
+    fn send(&self, inputs: &[KeyInput]) {
+        let mut args = Vec::new();
+        for &input in inputs {
+            input_args(input, &mut args);
+        }
+        if args.is_empty() {
+            return;
+        }
+        let result = Command::new(WTYPE)
+            .args(&args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+        if let Err(_e) = result {
+            #[cfg(debug_assertions)]
+            eprintln!("wtype 输入失败: {}", _e);
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use std::ffi::c_void;
+
+use windows::Win32::{
+    Foundation::{HGLOBAL, HWND},
+    System::{
+        DataExchange::{CloseClipboard, GetClipboardData, OpenClipboard},
+        Memory::{GlobalLock, GlobalUnlock},
+    },
+    UI::Input::KeyboardAndMouse::{
+        GetAsyncKeyState, GetKeyState, GetKeyboardLayout, MapVirtualKeyExW, SendInput,
+        VkKeyScanExW, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYBD_EVENT_FLAGS,
+        KEYEVENTF_KEYUP, KEYEVENTF_SCANCODE, KEYEVENTF_UNICODE, MAPVK_VK_TO_VSC, VIRTUAL_KEY,
+        VK_CAPITAL,
+    },
+    UI::TextServices::HKL,
+    UI::WindowsAndMessaging::GetWindowThreadProcessId,
+};
+
+use super::{Clipboard, Injector, KeyInput, KeyLayout, VirtualKey, VK_SHIFT};
+use crate::commands::{self, PASTER_INPUT_MARKER};
+use crate::foreground;
+use crate::i18n;
+
+/// Windows：剪贴板 API 与 SendInput
+pub struct Win32;
+
+impl Clipboard for Win32 {
+    /// 打开剪贴板获取 UTF-16 内容
+    fn read_text(&self) -> Result<Vec<u16>, &'static str> {
+        const CF_UNICODETEXT: u32 = 13;
+        let mut result: Vec<u16> = vec![];
+
+        unsafe {
+            OpenClipboard(HWND(0)).or(Err(i18n::t("error.open_clipboard")))?;
+            let hglb = GetClipboardData(CF_UNICODETEXT).map_err(|_| {
+                let _ = CloseClipboard();
+                i18n::t("error.clipboard_data")
+            })?;
+            let locker = HGLOBAL(hglb.0 as *mut c_void);
+            let raw_data = GlobalLock(locker);
+            let data = raw_data as *const u16;
+            let mut i = 0usize;
+
+            loop {
+                let item = *data.add(i);
+                i += 1;
+                if item == 0 {
+                    break;
+                }
+                // 舍弃 '\r'
+                if item == 13 {
+                    continue;
+                }
+                result.push(item);
+            }
+
+            GlobalUnlock(locker).map_err(|_| {
+                let _ = CloseClipboard();
+                i18n::t("error.clipboard_unlock")
+            })?;
+            CloseClipboard().or(Err(i18n::t("error.close_clipboard")))?;
+        }
+
+        Ok(result)
+    }
+}
+
+/// 翻译为 SendInput 的 INPUT 记录，dwExtraInfo 中带上 Paster 的标记
+fn win32_input(input: KeyInput) -> INPUT {
+    let (vk, scan, flags) = match input {
+        KeyInput::Key { vk, .. } => (vk.0, 0, KEYBD_EVENT_FLAGS(0)),
+        KeyInput::Scan { scan, .. } => (0, scan, KEYEVENTF_SCANCODE),
+        KeyInput::Unicode { unit, .. } => (0, unit, KEYEVENTF_UNICODE),
+    };
+    INPUT {
+        r#type: INPUT_KEYBOARD,
+        Anonymous: INPUT_0 {
+            ki: KEYBDINPUT {
+                wVk: VIRTUAL_KEY(vk),
+                wScan: scan,
+                dwFlags: if input.key_up() {
+                    flags | KEYEVENTF_KEYUP
+                } else {
+                    flags
+                },
+                time: 0,
+                dwExtraInfo: PASTER_INPUT_MARKER,
+            },
+        },
+    }
+}
+
+impl Injector for Win32 {
+    fn send(&self, inputs: &[KeyInput]) {
+        let inputs: Vec<INPUT> = inputs.iter().map(|&input| win32_input(input)).collect();
+        unsafe {
+            SendInput(&inputs, std::mem::size_of::<INPUT>() as i32);
+        }
+    }
+
+    /// 目标（前台）窗口的键盘布局与大写锁定状态
+    fn key_layout(&self) -> Option<KeyLayout> {
+        unsafe {
+            let thread_id = GetWindowThreadProcessId(foreground::foreground_window(), None);
+            Some(KeyLayout {
+                layout: GetKeyboardLayout(thread_id).0,
+                caps_lock: GetKeyState(VK_CAPITAL.0 as i32) & 1 != 0,
+            })
+        }
+    }
+
+    /// 需要 Ctrl/Alt（AltGr）的字符无法换算，返回 None
+    fn layout_inputs(&self, ch: u16, layout: KeyLayout, scan_code: bool) -> Option<Vec<KeyInput>> {
+        let hkl = HKL(layout.layout);
+        let scan = unsafe { VkKeyScanExW(ch, hkl) };
+        if scan == -1 {
+            return None;
+        }
+        let vk = scan as u16 & 0xFF;
+        let mut shift = match (scan as u16) >> 8 {
+            0 => false,
+            1 => true,
+            _ => return None,
+        };
+        // 大写锁定开启时字母键的 Shift 效果相反
+        if layout.caps_lock && char::from_u32(ch as u32).is_some_and(char::is_alphabetic) {
+            shift = !shift;
+        }
+        let vks: &[u16] = if shift { &[VK_SHIFT.0, vk] } else { &[vk] };
+        if scan_code {
+            scan_code_inputs(vks, hkl)
+        } else {
+            Some(commands::key_stroke_inputs(vks))
+        }
+    }
+
+    /// 只抬起仍处于按下状态的按键
+    fn release_keys(&self, vks: &[VirtualKey]) {
+        let inputs: Vec<KeyInput> = vks
+            .iter()
+            .filter(|vk| unsafe { GetAsyncKeyState(vk.0 as i32) } as u16 & 0x8000 != 0)
+            .map(|&vk| KeyInput::Key { vk, key_up: true })
+            .collect();
+        if !inputs.is_empty() {
+            self.send(&inputs);
+        }
+    }
+}
+
+/// 以扫描码方式依次按下一组按键，再倒序抬起；有按键没有对应的扫描码时返回 None
+fn scan_code_inputs(vks: &[u16], layout: HKL) -> Option<Vec<KeyInput>> {
+    let scans = vks
+        .iter()
+        .map(
+            |&vk| match unsafe { MapVirtualKeyExW(vk as u32, MAPVK_VK_TO_VSC, layout) } {
+                0 => None,
+                scan => Some(scan as u16),
+            },
+        )
+        .collect::<Option<Vec<u16>>>()?;
+    let input = |scan: u16, key_up: bool| KeyInput::Scan { scan, key_up };
+    let down = scans.iter().map(|&scan| input(scan, false));
+    let up = scans.iter().rev().map(|&scan| input(scan, true));
+    Some(down.chain(up).collect())
+}
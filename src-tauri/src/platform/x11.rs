@@ -0,0 +1,319 @@
+use std::ffi::CString;
+use std::os::raw::{c_int, c_long, c_uchar, c_uint, c_ulong};
+use std::ptr;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use x11_dl::xlib::{self, Display, Xlib};
+use x11_dl::xtest::Xf86vmode as XTest;
+
+use super::keysym::{char_keysym, vk_keysym};
+use super::{Clipboard, Injector, KeyInput};
+use crate::i18n;
+
+/// evdev 下 X11 keycode 比 PC 扫描码（set 1）大 8
+const SCAN_CODE_OFFSET: u16 = 8;
+/// 等待剪贴板所有者转换内容的最长时间
+const SELECTION_TIMEOUT: Duration = Duration::from_secs(1);
+const SELECTION_POLL: Duration = Duration::from_millis(10);
+/// 接收剪贴板内容的窗口属性
+const SELECTION_PROPERTY: &str = "PASTER_SELECTION";
+
+/// 首次使用时连接 X 服务器；libX11/libXtst 在运行时加载，缺少时为 None
+static CONNECTION: OnceLock<Option<Mutex<Connection>>> = OnceLock::new();
+
+struct Connection {
+    xlib: Xlib,
+    xtest: XTest,
+    display: *mut Display,
+    /// 没有绑定任何 keysym 的 keycode，键盘布局中没有的字符临时映射到这里
+    spare_keycode: Option<c_uchar>,
+    /// spare_keycode 当前映射的 keysym
+    spare_keysym: c_ulong,
+    /// 以 Unicode 方式发送的高位代理项，等待与低位代理项组成一个字符
+    high_surrogate: Option<u16>,
+    /// 代理对按下时使用的 keycode，抬起时沿用
+    pair_keycode: Option<c_uchar>,
+}
+
+// Display 只在持有互斥锁时使用
+unsafe impl Send for Connection {}
+
+fn connection() -> Result<&'static Mutex<Connection>, &'static str> {
+    CONNECTION
+        .get_or_init(|| unsafe { Connection::open() }.map(Mutex::new))
+        .as_ref()
+        .ok_or(i18n::t("error.x11_unavailable"))
+}
+
+impl Connection {
+    unsafe fn open() -> Option<Self> {
+        let xlib = Xlib::open()
+            .map_err(|_e| {
+                #[cfg(debug_assertions)]
+                eprintln!("加载 libX11 失败: {}", _e);
+            })
+            .ok()?;
+        let xtest = XTest::open()
+            .map_err(|_e| {
+                #[cfg(debug_assertions)]
+                eprintln!("加载 libXtst 失败: {}", _e);
+            })
+            .ok()?;
+        let display = (xlib.XOpenDisplay)(ptr::null());
+        if display.is_null() {
+            #[cfg(debug_assertions)]
+            eprintln!("连接 X 服务器失败，请检查 DISPLAY 环境变量");
+            return None;
+        }
+        let mut connection = Self {
+            xlib,
+            xtest,
+            display,
+            spare_keycode: None,
+            spare_keysym: 0,
+            high_surrogate: None,
+            pair_keycode: None,
+        };
+        connection.spare_keycode = connection.find_spare_keycode();
+
+        #[cfg(debug_assertions)]
+        println!("X11 空闲 keycode: {:?}", connection.spare_keycode);
+
+        Some(connection)
+    }
+
+    /// 从高往低找一个没有绑定 keysym 的 keycode，避开键盘上实际存在的按键
+    unsafe fn find_spare_keycode(&self) -> Option<c_uchar> {
+        let (mut min, mut max) = (0, 0);
+        (self.xlib.XDisplayKeycodes)(self.display, &mut min, &mut max);
+        let count = max - min + 1;
+        let mut per_keycode = 0;
+        let mapping =
+            (self.xlib.XGetKeyboardMapping)(self.display, min as c_uchar, count, &mut per_keycode);
+        if mapping.is_null() || per_keycode <= 0 {
+            return None;
+        }
+        let per_keycode = per_keycode as usize;
+        let keysyms = std::slice::from_raw_parts(mapping, count as usize * per_keycode);
+        let spare = keysyms
+            .chunks(per_keycode)
+            .rposition(|keysyms| keysyms.iter().all(|&keysym| keysym == 0))
+            .map(|index| (min + index as c_int) as c_uchar);
+        (self.xlib.XFree)(mapping.cast());
+        spare
+    }
+
+    unsafe fn atom(&self, name: &str) -> c_ulong {
+        let name = CString::new(name).unwrap();
+        (self.xlib.XInternAtom)(self.display, name.as_ptr(), xlib::False)
+    }
+
+    /// keysym 对应的 keycode：不按修饰键就能输入时使用原有按键，否则临时映射到空闲 keycode
+    unsafe fn keycode(&mut self, keysym: c_ulong) -> Option<c_uchar> {
+        let keycode = (self.xlib.XKeysymToKeycode)(self.display, keysym);
+        if keycode != 0 && (self.xlib.XKeycodeToKeysym)(self.display, keycode, 0) == keysym {
+            return Some(keycode);
+        }
+        let spare = self.spare_keycode?;
+        if self.spare_keysym != keysym {
+            // 大小写两个层级都映射为同一字符，不受 Shift 与大写锁定影响
+            let mut keysyms = [keysym, keysym];
+            (self.xlib.XChangeKeyboardMapping)(
+                self.display,
+                spare as c_int,
+                keysyms.len() as c_int,
+                keysyms.as_mut_ptr(),
+                1,
+            );
+            // 等映射生效后再发送按键，否则目标程序可能按旧的映射解释
+            (self.xlib.XSync)(self.display, xlib::False);
+            self.spare_keysym = keysym;
+        }
+        Some(spare)
+    }
+
+    /// 以 Unicode 方式发送的 UTF-16 单元对应的 keycode；代理对在低位代理项上按下与抬起
+    unsafe fn unicode_keycode(&mut self, unit: u16, key_up: bool) -> Option<c_uchar> {
+        match unit {
+            0xD800..=0xDBFF => {
+                if !key_up {
+                    self.high_surrogate = Some(unit);
+                }
+                None
+            }
+            0xDC00..=0xDFFF => {
+                if key_up {
+                    return self.pair_keycode.take();
+                }
+                let high = self.high_surrogate.take()?;
+                let code_point = 0x10000 + ((high as u32 - 0xD800) << 10) + (unit as u32 - 0xDC00);
+                self.pair_keycode = self.keycode(char_keysym(code_point) as c_ulong);
+                self.pair_keycode
+            }
+            _ => self.keycode(char_keysym(unit as u32) as c_ulong),
+        }
+    }
+
+    /// 把一次按键翻译为 XTest 按键事件
+    unsafe fn send(&mut self, input: KeyInput) {
+        let key_up = input.key_up();
+        let keycode = match input {
+            KeyInput::Unicode { unit, .. } => self.unicode_keycode(unit, key_up),
+            KeyInput::Scan { scan, .. } => c_uchar::try_from(scan + SCAN_CODE_OFFSET).ok(),
+            KeyInput::Key { vk, .. } => {
+                vk_keysym(vk).and_then(|keysym| self.keycode(keysym as c_ulong))
+            }
+        };
+        let Some(keycode) = keycode else {
+            return;
+        };
+        (self.xtest.XTestFakeKeyEvent)(
+            self.display,
+            keycode as c_uint,
+            if key_up { xlib::False } else { xlib::True },
+            xlib::CurrentTime,
+        );
+    }
+
+    /// 读取选区（CLIPBOARD 或 PRIMARY）的文本
+    unsafe fn read_selection(&self, selection: &str) -> Result<String, &'static str> {
+        let selection = self.atom(selection);
+        if (self.xlib.XGetSelectionOwner)(self.display, selection) == 0 {
+            return Err(i18n::t("error.clipboard_empty"));
+        }
+        let root = (self.xlib.XDefaultRootWindow)(self.display);
+        let window = (self.xlib.XCreateSimpleWindow)(self.display, root, 0, 0, 1, 1, 0, 0, 0);
+        let result = self.convert_selection(window, selection);
+        (self.xlib.XDestroyWindow)(self.display, window);
+        (self.xlib.XFlush)(self.display);
+        result
+    }
+
+    /// 请求选区所有者把内容写到 window 的属性上，优先 UTF-8，其次 Latin-1
+    unsafe fn convert_selection(
+        &self,
+        window: c_ulong,
+        selection: c_ulong,
+    ) -> Result<String, &'static str> {
+        let property = self.atom(SELECTION_PROPERTY);
+        let incr = self.atom("INCR");
+        for (target, utf8) in [("UTF8_STRING", true), ("STRING", false)] {
+            let target = self.atom(target);
+            (self.xlib.XConvertSelection)(
+                self.display,
+                selection,
+                target,
+                property,
+                window,
+                xlib::CurrentTime,
+            );
+            (self.xlib.XFlush)(self.display);
+            if !self.wait_selection_notify(window)? {
+                // 所有者不支持这种格式
+                continue;
+            }
+
+            let mut actual_type = 0;
+            let mut format = 0;
+            let mut items = 0;
+            let mut remaining = 0;
+            let mut data: *mut c_uchar = ptr::null_mut();
+            (self.xlib.XGetWindowProperty)(
+                self.display,
+                window,
+                property,
+                0,
+                c_long::MAX / 4,
+                xlib::True,
+                xlib::AnyPropertyType as c_ulong,
+                &mut actual_type,
+                &mut format,
+                &mut items,
+                &mut remaining,
+                &mut data,
+            );
+            if data.is_null() {
+                continue;
+            }
+            let bytes = std::slice::from_raw_parts(data, items as usize).to_vec();
+            (self.xlib.XFree)(data.cast());
+
+            if actual_type == incr {
+                // 大段内容需要 INCR 分段传输，暂不支持
+                return Err(i18n::t("error.clipboard_too_large"));
+            }
+            if format != 8 {
+                continue;
+            }
+            return Ok(if utf8 {
+                String::from_utf8_lossy(&bytes).into_owned()
+            } else {
+                bytes.iter().map(|&byte| byte as char).collect()
+            });
+        }
+        Err(i18n::t("error.clipboard_empty"))
+    }
+
+    /// 等待 SelectionNotify；返回所有者是否写入了内容
+    unsafe fn wait_selection_notify(&self, window: c_ulong) -> Result<bool, &'static str> {
+        let deadline = Instant::now() + SELECTION_TIMEOUT;
+        let mut event: xlib::XEvent = std::mem::zeroed();
+        loop {
+            if (self.xlib.XCheckTypedWindowEvent)(
+                self.display,
+                window,
+                xlib::SelectionNotify,
+                &mut event,
+            ) != 0
+            {
+                return Ok(event.selection.property != 0);
+            }
+            if Instant::now() >= deadline {
+                return Err(i18n::t("error.clipboard_timeout"));
+            }
+            std::thread::sleep(SELECTION_POLL);
+        }
+    }
+}
+
+/// X11：XTest 注入按键，读取 CLIPBOARD 选区
+pub struct X11;
+
+/// 读取选区的文本，转换为 UTF-16 并舍弃 '\r'
+fn read_selection(selection: &str) -> Result<Vec<u16>, &'static str> {
+    let connection = connection()?.lock().unwrap();
+    let text = unsafe { connection.read_selection(selection) }?;
+    Ok(text.encode_utf16().filter(|&unit| unit != 13).collect())
+}
+
+impl Clipboard for X11 {
+    fn read_text(&self) -> Result<Vec<u16>, &'static str> {
+        read_selection("CLIPBOARD")
+    }
+
+    fn read_primary(&self) -> Result<Vec<u16>, &'static str> {
+        read_selection("PRIMARY")
+    }
+}
+
+impl Injector for X11 {
+    /// 检查能否连接 X 服务器并加载 XTest
+    fn check(&self) -> Result<(), &'static str> {
+        connection().map(|_| ())
+    }
+
+    /// 以 XTest 发送一组键盘输入
+    fn send(&self, inputs: &[KeyInput]) {
+        let Ok(connection) = connection() else {
+            return;
+        };
+        let mut connection = connection.lock().unwrap();
+        unsafe {
+            for &input in inputs {
+                connection.send(input);
+            }
+            (connection.xlib.XSync)(connection.display, xlib::False);
+        }
+    }
+}
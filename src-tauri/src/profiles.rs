@@ -0,0 +1,356 @@
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{CustomMenuItem, Manager, SystemTrayMenu, SystemTraySubmenu};
+
+use crate::api;
+use crate::app_rules;
+use crate::commands::{InputBackend, NewlineStrategy, PasteOptions, PasteState};
+use crate::foreground;
+use crate::hotkeys::{self, HotkeyBindings};
+use crate::i18n;
+use crate::settings;
+use crate::transform::Transform;
+use crate::tray;
+
+/// 配置方案：针对某类输入场景（终端、聊天软件、考试系统等）的一组输入设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    /// 名称，同时作为唯一标识
+    pub name: String,
+    pub stand: u32,
+    pub float: u32,
+    /// 输入前对文本做的变换
+    #[serde(default)]
+    pub transforms: Vec<Transform>,
+    #[serde(default)]
+    pub newline: NewlineStrategy,
+    #[serde(default)]
+    pub backend: InputBackend,
+    /// 开启自动选用时，这些程序（进程名）位于前台时使用此方案
+    #[serde(default)]
+    pub apps: Vec<String>,
+    /// 选用此方案时替换全局设置中同一动作的快捷键（自动选用不切换快捷键）
+    #[serde(default)]
+    pub hotkeys: HotkeyBindings,
+}
+
+/// 全部配置方案，保存在 settings.json 中
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfileSettings {
+    pub profiles: Vec<Profile>,
+    /// 当前选用的方案名称，None 表示不使用配置方案
+    pub active: Option<String>,
+    /// 开始输入时按前台程序自动选用方案（没有匹配的方案时使用当前选用的方案）
+    pub auto_switch: bool,
+}
+
+impl ProfileSettings {
+    fn find(&self, name: &str) -> Option<&Profile> {
+        self.profiles
+            .iter()
+            .find(|profile| profile.name.eq_ignore_ascii_case(name))
+    }
+
+    /// 检查方案名称不为空且互不相同
+    pub fn validate(&self) -> Result<(), String> {
+        for (i, profile) in self.profiles.iter().enumerate() {
+            if profile.name.trim().is_empty() {
+                return Err(i18n::t("error.profile_name_empty").to_string());
+            }
+            if self.profiles[..i]
+                .iter()
+                .any(|other| other.name.eq_ignore_ascii_case(&profile.name))
+            {
+                return Err(i18n::tf(
+                    "error.profile_duplicate",
+                    &[("name", &profile.name)],
+                ));
+            }
+        }
+        if let Some(active) = &self.active {
+            if self.find(active).is_none() {
+                return Err(i18n::tf("error.profile_missing", &[("name", active)]));
+            }
+        }
+        Ok(())
+    }
+
+    /// 检查每个方案的快捷键与全局快捷键合并后仍然合法
+    pub fn validate_hotkeys(&self, global: &HotkeyBindings) -> Result<(), String> {
+        for profile in self.profiles.iter().filter(|p| !p.hotkeys.is_empty()) {
+            let mut bindings = global.clone();
+            bindings.extend(profile.hotkeys.clone());
+            hotkeys::validate_bindings(&bindings)
+                .map_err(|e| format!("{}: {}", profile.name, e))?;
+        }
+        Ok(())
+    }
+
+    /// 全局快捷键被当前选用方案覆盖后的快捷键
+    pub fn hotkeys(&self, global: &HotkeyBindings) -> HotkeyBindings {
+        let mut bindings = global.clone();
+        if let Some(profile) = self.active.as_deref().and_then(|name| self.find(name)) {
+            bindings.extend(profile.hotkeys.clone());
+        }
+        bindings
+    }
+}
+
+pub struct ProfileState {
+    settings: ProfileSettings,
+}
+
+impl ProfileState {
+    pub fn new() -> Self {
+        Self {
+            settings: ProfileSettings::default(),
+        }
+    }
+
+    pub fn settings(&self) -> &ProfileSettings {
+        &self.settings
+    }
+
+    pub fn set_settings(&mut self, settings: ProfileSettings) {
+        self.settings = settings;
+    }
+}
+
+/// 是否存在这个名称的配置方案（不区分大小写）
+pub fn exists(app_handle: &tauri::AppHandle, name: &str) -> bool {
+    let state = app_handle.state::<Mutex<ProfileState>>();
+    let locked = state.lock().unwrap();
+    locked.settings.find(name).is_some()
+}
+
+/// 本次输入使用的配置方案：指定了名称时使用该方案，否则开启自动选用时优先使用与前台程序匹配的方案
+pub fn for_job(app_handle: &tauri::AppHandle, name: Option<&str>) -> Option<Profile> {
+    let state = app_handle.state::<Mutex<ProfileState>>();
+    let locked = state.lock().unwrap();
+    let settings = &locked.settings;
+    if let Some(name) = name {
+        return settings.find(name).cloned();
+    }
+    if settings.auto_switch {
+        if let Some(name) = foreground::foreground_process_name() {
+            if let Some(profile) = settings
+                .profiles
+                .iter()
+                .find(|profile| foreground::matches_process(&profile.apps, &name))
+            {
+                return Some(profile.clone());
+            }
+        }
+    }
+    settings
+        .active
+        .as_deref()
+        .and_then(|active| settings.find(active))
+        .cloned()
+}
+
+/// 本次输入的设置：先取配置方案，再以匹配前台程序的规则覆盖；为 None 的项使用全局设置
+#[derive(Debug, Clone, Default)]
+pub struct JobStyle {
+    pub timing: Option<(u32, u32)>,
+    pub instant: Option<bool>,
+    pub newline: NewlineStrategy,
+    pub backend: InputBackend,
+    pub transforms: Vec<Transform>,
+}
+
+impl JobStyle {
+    /// 换行与字符的输入方式
+    pub fn input_options(&self) -> PasteOptions {
+        PasteOptions {
+            newline: self.newline,
+            backend: self.backend,
+            ..PasteOptions::default()
+        }
+    }
+}
+
+pub fn job_style(app_handle: &tauri::AppHandle, profile: Option<&str>) -> JobStyle {
+    let mut style = match for_job(app_handle, profile) {
+        Some(profile) => JobStyle {
+            timing: Some((profile.stand, profile.float)),
+            instant: None,
+            newline: profile.newline,
+            backend: profile.backend,
+            transforms: profile.transforms,
+        },
+        None => JobStyle::default(),
+    };
+    if let Some(rule) = app_rules::for_foreground(app_handle) {
+        if let Some(timing) = rule.timing {
+            style.timing = Some((timing.stand, timing.float));
+            style.instant = Some(timing.instant);
+        }
+        if let Some(newline) = rule.newline {
+            style.newline = newline;
+        }
+        if let Some(backend) = rule.backend {
+            style.backend = backend;
+        }
+        if let Some(transforms) = rule.transforms {
+            style.transforms = transforms;
+        }
+    }
+    style
+}
+
+/// 重新输入或继续输入时使用的换行与字符输入方式
+pub fn input_options(app_handle: &tauri::AppHandle) -> PasteOptions {
+    job_style(app_handle, None).input_options()
+}
+
+/// 托盘中的 "配置方案" 子菜单；还没有任何方案时为 None
+pub fn tray_submenu(app_handle: &tauri::AppHandle) -> Option<SystemTraySubmenu> {
+    let state = app_handle.state::<Mutex<ProfileState>>();
+    let locked = state.lock().unwrap();
+    let settings = &locked.settings;
+    if settings.profiles.is_empty() {
+        return None;
+    }
+
+    let mut none = CustomMenuItem::new("profile:".to_string(), i18n::t("tray.profile_none"));
+    none.selected = settings.active.is_none();
+    let mut menu = SystemTrayMenu::new().add_item(none);
+    for profile in &settings.profiles {
+        let mut item = CustomMenuItem::new(
+            format!("profile:{}", profile.name),
+            profile.name.replace('&', "&&"),
+        );
+        item.selected = settings
+            .active
+            .as_deref()
+            .is_some_and(|active| active.eq_ignore_ascii_case(&profile.name));
+        menu = menu.add_item(item);
+    }
+    Some(SystemTraySubmenu::new(i18n::t("tray.profiles"), menu))
+}
+
+/// 修改方案后保存设置并更新托盘菜单
+fn changed(app_handle: &tauri::AppHandle) {
+    settings::persist(app_handle);
+    tray::refresh(app_handle);
+}
+
+/// 全局快捷键被当前选用方案覆盖后的快捷键
+pub fn effective_hotkeys(app_handle: &tauri::AppHandle, global: &HotkeyBindings) -> HotkeyBindings {
+    let state = app_handle.state::<Mutex<ProfileState>>();
+    let locked = state.lock().unwrap();
+    locked.settings.hotkeys(global)
+}
+
+/// 替换全部方案设置：生效的快捷键有变化时先整组重新注册，失败时保持原有设置不变
+fn commit(app_handle: &tauri::AppHandle, settings: ProfileSettings) -> Result<(), String> {
+    let global = {
+        let state = app_handle.state::<Mutex<PasteState>>();
+        let locked = state.lock().unwrap();
+        locked.hotkeys.clone()
+    };
+    let bindings = settings.hotkeys(&global);
+    if bindings != hotkeys::active_bindings(app_handle) {
+        hotkeys::validate_bindings(&bindings)?;
+        hotkeys::switch_hotkeys(app_handle, &bindings)?;
+    }
+    {
+        let state = app_handle.state::<Mutex<ProfileState>>();
+        state.lock().unwrap().settings = settings;
+    }
+    changed(app_handle);
+    Ok(())
+}
+
+/// 获取全部配置方案及当前选用的方案
+#[tauri::command]
+pub fn get_profiles(app_handle: tauri::AppHandle) -> ProfileSettings {
+    let state = app_handle.state::<Mutex<ProfileState>>();
+    let locked = state.lock().unwrap();
+    locked.settings.clone()
+}
+
+/// 新建或修改配置方案（按名称匹配，不区分大小写）
+#[tauri::command]
+pub fn save_profile(mut profile: Profile, app_handle: tauri::AppHandle) -> Result<(), String> {
+    profile.name = profile.name.trim().to_string();
+    if profile.name.is_empty() {
+        return Err(i18n::t("error.profile_name_empty").to_string());
+    }
+    profile.apps = profile
+        .apps
+        .iter()
+        .map(|app| app.trim().to_string())
+        .filter(|app| !app.is_empty())
+        .collect();
+
+    let mut settings = get_profiles(app_handle.clone());
+    match settings
+        .profiles
+        .iter_mut()
+        .find(|existing| existing.name.eq_ignore_ascii_case(&profile.name))
+    {
+        Some(existing) => *existing = profile,
+        None => settings.profiles.push(profile),
+    }
+    commit(&app_handle, settings)
+}
+
+/// 删除配置方案；删除的是当前选用的方案时改为不使用配置方案
+#[tauri::command]
+pub fn delete_profile(name: String, app_handle: tauri::AppHandle) -> Result<(), String> {
+    let mut settings = get_profiles(app_handle.clone());
+    let before = settings.profiles.len();
+    settings
+        .profiles
+        .retain(|profile| !profile.name.eq_ignore_ascii_case(&name));
+    if settings.profiles.len() == before {
+        return Err(i18n::tf("error.profile_missing", &[("name", &name)]));
+    }
+    if settings
+        .active
+        .as_deref()
+        .is_some_and(|active| active.eq_ignore_ascii_case(&name))
+    {
+        settings.active = None;
+    }
+    commit(&app_handle, settings)
+}
+
+/// 切换当前选用的配置方案，None 表示不使用配置方案；
+/// 同时切换到该方案的快捷键，注册失败时保持原来的方案
+#[tauri::command]
+pub fn switch_profile(name: Option<String>, app_handle: tauri::AppHandle) -> Result<(), String> {
+    let mut settings = get_profiles(app_handle.clone());
+    let active = match name {
+        Some(name) => Some(
+            settings
+                .find(&name)
+                .map(|profile| profile.name.clone())
+                .ok_or_else(|| i18n::tf("error.profile_missing", &[("name", &name)]))?,
+        ),
+        None => None,
+    };
+    settings.active = active.clone();
+    commit(&app_handle, settings)?;
+
+    #[cfg(debug_assertions)]
+    println!("切换配置方案: {:?}", active);
+
+    let _ = app_handle.emit_all("profile-changed", &active);
+    api::publish("profile-changed", &active);
+    Ok(())
+}
+
+/// 设置是否按前台程序自动选用配置方案
+#[tauri::command]
+pub fn set_profile_auto_switch(enabled: bool, app_handle: tauri::AppHandle) {
+    {
+        let state = app_handle.state::<Mutex<ProfileState>>();
+        state.lock().unwrap().settings.auto_switch = enabled;
+    }
+    settings::persist(&app_handle);
+}
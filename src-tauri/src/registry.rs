@@ -0,0 +1,56 @@
+// HKEY_CURRENT_USER 下的注册表读写，无需管理员权限
+
+use windows::core::{HSTRING, PCWSTR};
+use windows::Win32::Foundation::ERROR_FILE_NOT_FOUND;
+use windows::Win32::System::Registry::{
+    RegCloseKey, RegDeleteTreeW, RegOpenKeyExW, RegSetKeyValueW, HKEY, HKEY_CURRENT_USER, KEY_READ,
+    REG_SZ,
+};
+
+/// 写入字符串值，name 为 None 时写入默认值；项不存在时自动创建
+pub fn set_string(key: &str, name: Option<&str>, data: &str) -> Result<(), String> {
+    let data: Vec<u16> = data.encode_utf16().chain(std::iter::once(0)).collect();
+    let name = name.map(HSTRING::from);
+    let name = name
+        .as_ref()
+        .map_or(PCWSTR::null(), |name| PCWSTR(name.as_ptr()));
+    unsafe {
+        RegSetKeyValueW(
+            HKEY_CURRENT_USER,
+            &HSTRING::from(key),
+            name,
+            REG_SZ.0,
+            Some(data.as_ptr().cast()),
+            (data.len() * 2) as u32,
+        )
+    }
+    .map_err(|e| e.to_string())
+}
+
+/// 删除项及其下的全部内容，项不存在时视为成功
+pub fn delete_key(key: &str) -> Result<(), String> {
+    match unsafe { RegDeleteTreeW(HKEY_CURRENT_USER, &HSTRING::from(key)) } {
+        Err(e) if e.code() != ERROR_FILE_NOT_FOUND.to_hresult() => Err(e.to_string()),
+        _ => Ok(()),
+    }
+}
+
+pub fn key_exists(key: &str) -> bool {
+    let mut handle = HKEY::default();
+    let opened = unsafe {
+        RegOpenKeyExW(
+            HKEY_CURRENT_USER,
+            &HSTRING::from(key),
+            0,
+            KEY_READ,
+            &mut handle,
+        )
+    }
+    .is_ok();
+    if opened {
+        unsafe {
+            let _ = RegCloseKey(handle);
+        }
+    }
+    opened
+}
@@ -0,0 +1,383 @@
+use std::collections::VecDeque;
+use std::net::{IpAddr, Ipv4Addr};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Nonce};
+use argon2::Argon2;
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use zeroize::Zeroizing;
+
+use crate::api;
+use crate::history;
+use crate::i18n;
+use crate::platform::{self, TextSource};
+use crate::settings;
+
+const DEFAULT_PORT: u16 = 47602;
+/// 检查本机剪贴板是否变化的间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+const MAX_MESSAGE_BYTES: u32 = 4 * 1024 * 1024;
+/// 消息的发送时间与本机时钟最多相差多久，超出的视为被截获后重放
+const MAX_CLOCK_SKEW_MS: u64 = 2 * 60 * 1000;
+const NONCE_LEN: usize = 12;
+/// 记住最近收到的多少个随机数，拒绝时间窗口内的重放
+const RECENT_NONCES: usize = 256;
+/// 由口令派生密钥时的固定盐：两端只共享口令
+const KEY_SALT: &[u8] = b"paster-clipboard-relay";
+const MIN_PASSPHRASE_CHARS: usize = 8;
+
+/// 局域网剪贴板中转：本机复制的文本加密后发送给其他电脑上的 Paster，
+/// 对方把文本来源设为 relay 后用平常的粘贴快捷键输入（如没有共享剪贴板的虚拟机、KVM）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RelaySettings {
+    /// 默认关闭
+    pub enabled: bool,
+    /// 接收端口，监听所有网卡
+    pub port: u16,
+    /// 两端相同的口令，用于加密；开启时为空则自动生成
+    pub passphrase: String,
+    /// 复制时发送到的电脑（主机名或 IPv4 地址，可带 :端口，默认与本机端口相同），为空时只接收
+    pub peers: Vec<String>,
+}
+
+impl RelaySettings {
+    const fn new() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_PORT,
+            passphrase: String::new(),
+            peers: Vec::new(),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.port == 0 {
+            return Err(i18n::t("error.port_zero"));
+        }
+        if !self.passphrase.is_empty() && self.passphrase.chars().count() < MIN_PASSPHRASE_CHARS {
+            return Err(i18n::t("error.relay_passphrase"));
+        }
+        for peer in &self.peers {
+            if peer.is_empty() || peer.chars().any(char::is_whitespace) {
+                return Err(i18n::t("error.address_invalid"));
+            }
+        }
+        Ok(())
+    }
+
+    /// 发送地址，未写端口时使用本机的接收端口
+    fn peer_address(&self, peer: &str) -> String {
+        if peer.contains(':') {
+            peer.to_string()
+        } else {
+            format!("{}:{}", peer, self.port)
+        }
+    }
+}
+
+impl Default for RelaySettings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 加密传输的内容
+#[derive(Serialize, Deserialize)]
+struct Message {
+    text: String,
+    /// 发送时间（Unix 毫秒时间戳）
+    sent_at: u64,
+}
+
+/// 最近一次收到的文本
+struct Received {
+    units: Vec<u16>,
+    from: IpAddr,
+    at: u64,
+}
+
+static SETTINGS: Mutex<RelaySettings> = Mutex::new(RelaySettings::new());
+/// 正在运行的中转任务及其使用的设置，设置改变时重启
+static RUNNING: Mutex<Option<(RelaySettings, tauri::async_runtime::JoinHandle<()>)>> =
+    Mutex::new(None);
+static RECEIVED: Mutex<Option<Received>> = Mutex::new(None);
+static RECENT: Mutex<VecDeque<[u8; NONCE_LEN]>> = Mutex::new(VecDeque::new());
+
+/// relay-received 事件的内容
+#[derive(Debug, Clone, Serialize)]
+pub struct RelayStatus {
+    /// 正在运行（监听并发送）
+    pub active: bool,
+    /// 最近一次收到的文本的字符数与来源，没有收到过时为 None
+    pub received_chars: Option<usize>,
+    pub received_from: Option<String>,
+    pub received_at: Option<u64>,
+}
+
+/// 当前的中转设置
+pub fn settings() -> RelaySettings {
+    SETTINGS.lock().unwrap().clone()
+}
+
+/// 应用中转设置：按需启动、重启或停止。开启时没有口令则生成一个并保存
+pub fn apply(app_handle: &tauri::AppHandle, mut settings: RelaySettings) {
+    let generated = settings.enabled && settings.passphrase.is_empty();
+    if generated {
+        settings.passphrase = api::generate_token();
+    }
+    *SETTINGS.lock().unwrap() = settings.clone();
+
+    {
+        let mut running = RUNNING.lock().unwrap();
+        let wanted = settings.enabled.then_some(&settings);
+        if running.as_ref().map(|(settings, _)| settings) != wanted {
+            if let Some((_, task)) = running.take() {
+                task.abort();
+            }
+            if settings.enabled {
+                let task = tauri::async_runtime::spawn(run(app_handle.clone(), settings.clone()));
+                *running = Some((settings.clone(), task));
+            }
+        }
+    }
+
+    if generated {
+        // 调用方可能还在应用其余设置，稍后再保存
+        let app_handle = app_handle.clone();
+        tauri::async_runtime::spawn(async move { settings::persist(&app_handle) });
+    }
+}
+
+/// 文本来源为 relay 时粘贴的文本：最近一次从其他电脑收到的文本
+pub fn received() -> Option<Vec<u16>> {
+    RECEIVED
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|received| received.units.clone())
+}
+
+fn status() -> RelayStatus {
+    let received = RECEIVED.lock().unwrap();
+    RelayStatus {
+        active: RUNNING.lock().unwrap().is_some(),
+        received_chars: received
+            .as_ref()
+            .map(|received| String::from_utf16_lossy(&received.units).chars().count()),
+        received_from: received.as_ref().map(|received| received.from.to_string()),
+        received_at: received.as_ref().map(|received| received.at),
+    }
+}
+
+/// 用 argon2id（默认参数）从口令派生 256 位密钥
+fn derive_key(passphrase: &str) -> Result<Zeroizing<[u8; 32]>, &'static str> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), KEY_SALT, key.as_mut())
+        .map_err(|_| i18n::t("error.derive_key"))?;
+    Ok(key)
+}
+
+/// 随机数与 AES-256-GCM 密文
+fn seal(key: &[u8; 32], message: &Message) -> Result<Vec<u8>, &'static str> {
+    let plain =
+        Zeroizing::new(serde_json::to_vec(message).map_err(|_| i18n::t("error.serialize"))?);
+    let cipher = Aes256Gcm::new(key.into());
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let data = cipher
+        .encrypt(&nonce, plain.as_slice())
+        .map_err(|_| i18n::t("error.encrypt"))?;
+    Ok([nonce.as_slice(), &data].concat())
+}
+
+/// 解密并检查发送时间与随机数；口令不同、消息过期或重放时返回 None
+fn open(key: &[u8; 32], sealed: &[u8]) -> Option<Message> {
+    if sealed.len() < NONCE_LEN {
+        return None;
+    }
+    let (nonce, data) = sealed.split_at(NONCE_LEN);
+    let plain = Zeroizing::new(
+        Aes256Gcm::new(key.into())
+            .decrypt(Nonce::from_slice(nonce), data)
+            .ok()?,
+    );
+    let message: Message = serde_json::from_slice(&plain).ok()?;
+    if history::now_ms().abs_diff(message.sent_at) > MAX_CLOCK_SKEW_MS {
+        return None;
+    }
+    let nonce: [u8; NONCE_LEN] = nonce.try_into().ok()?;
+    let mut recent = RECENT.lock().unwrap();
+    if recent.contains(&nonce) {
+        return None;
+    }
+    if recent.len() >= RECENT_NONCES {
+        recent.pop_front();
+    }
+    recent.push_back(nonce);
+    Some(message)
+}
+
+async fn run(app_handle: tauri::AppHandle, settings: RelaySettings) {
+    let passphrase = Zeroizing::new(settings.passphrase.clone());
+    let key = match tauri::async_runtime::spawn_blocking(move || derive_key(&passphrase)).await {
+        Ok(Ok(key)) => key,
+        _ => return,
+    };
+    let receiving = async {
+        match TcpListener::bind((Ipv4Addr::UNSPECIFIED, settings.port)).await {
+            Ok(listener) => receive(&app_handle, listener, &key).await,
+            Err(e) => {
+                let message = i18n::tf(
+                    "error.relay_bind",
+                    &[("port", &settings.port), ("error", &e)],
+                );
+                #[cfg(debug_assertions)]
+                eprintln!("{}", message);
+                let _ = app_handle.emit_all("relay-failed", message);
+            }
+        }
+    };
+    tokio::join!(receiving, watch(&settings, &key));
+}
+
+/// 本机剪贴板变化时把文本发送给各个电脑（启动时已有的内容不发送）
+async fn watch(settings: &RelaySettings, key: &[u8; 32]) {
+    if settings.peers.is_empty() {
+        return;
+    }
+    let mut last = platform::read_source(TextSource::Clipboard).ok();
+    loop {
+        tokio::time::sleep(POLL_INTERVAL).await;
+        let Ok(units) = platform::read_source(TextSource::Clipboard) else {
+            continue;
+        };
+        if units.is_empty() || last.as_ref() == Some(&units) {
+            continue;
+        }
+        let message = Message {
+            text: String::from_utf16_lossy(&units),
+            sent_at: history::now_ms(),
+        };
+        last = Some(units);
+        let Ok(sealed) = seal(key, &message) else {
+            continue;
+        };
+        for peer in &settings.peers {
+            if let Err(_e) = send(&settings.peer_address(peer), &sealed).await {
+                #[cfg(debug_assertions)]
+                eprintln!("发送剪贴板到 {} 失败: {}", peer, _e);
+            }
+        }
+    }
+}
+
+/// 每个连接发送一条消息：4 字节大端长度，随后为随机数与密文
+async fn send(address: &str, sealed: &[u8]) -> std::io::Result<()> {
+    let mut stream = tokio::time::timeout(CONNECT_TIMEOUT, TcpStream::connect(address))
+        .await
+        .map_err(|_| std::io::ErrorKind::TimedOut)??;
+    stream
+        .write_all(&(sealed.len() as u32).to_be_bytes())
+        .await?;
+    stream.write_all(sealed).await?;
+    stream.shutdown().await
+}
+
+async fn receive(app_handle: &tauri::AppHandle, listener: TcpListener, key: &[u8; 32]) {
+    #[cfg(debug_assertions)]
+    println!("剪贴板中转已启动: {:?}", listener.local_addr());
+
+    loop {
+        let (stream, address) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(_e) => {
+                #[cfg(debug_assertions)]
+                eprintln!("接受中转连接失败: {}", _e);
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                continue;
+            }
+        };
+        let app_handle = app_handle.clone();
+        let key = *key;
+        tauri::async_runtime::spawn(async move {
+            let key = Zeroizing::new(key);
+            match tokio::time::timeout(api::REQUEST_TIMEOUT, read_message(stream)).await {
+                Ok(Ok(sealed)) => match open(&key, &sealed) {
+                    Some(message) => accept(&app_handle, message, address.ip()),
+                    None => {
+                        #[cfg(debug_assertions)]
+                        eprintln!("丢弃来自 {} 的中转消息：口令不同、已过期或重放", address);
+                    }
+                },
+                _ => {
+                    #[cfg(debug_assertions)]
+                    eprintln!("读取来自 {} 的中转消息失败", address);
+                }
+            }
+        });
+    }
+}
+
+async fn read_message(mut stream: TcpStream) -> std::io::Result<Vec<u8>> {
+    let length = stream.read_u32().await?;
+    if length > MAX_MESSAGE_BYTES {
+        return Err(std::io::ErrorKind::InvalidData.into());
+    }
+    let mut sealed = vec![0u8; length as usize];
+    stream.read_exact(&mut sealed).await?;
+    Ok(sealed)
+}
+
+fn accept(app_handle: &tauri::AppHandle, message: Message, from: IpAddr) {
+    #[cfg(debug_assertions)]
+    println!("收到来自 {} 的剪贴板文本", from);
+
+    *RECEIVED.lock().unwrap() = Some(Received {
+        units: message
+            .text
+            .encode_utf16()
+            .filter(|&unit| unit != 13)
+            .collect(),
+        from,
+        at: history::now_ms(),
+    });
+    let _ = app_handle.emit_all("relay-received", status());
+}
+
+/// 获取剪贴板中转的设置（含口令）
+#[tauri::command]
+pub fn get_relay_settings() -> RelaySettings {
+    settings()
+}
+
+/// 修改剪贴板中转的设置，返回生效后的设置（首次开启时生成口令，需在另一台电脑上填写相同的口令）
+#[tauri::command]
+pub fn set_relay_settings(
+    settings: RelaySettings,
+    app_handle: tauri::AppHandle,
+) -> Result<RelaySettings, String> {
+    let settings = RelaySettings {
+        peers: settings
+            .peers
+            .iter()
+            .map(|peer| peer.trim().to_string())
+            .collect(),
+        ..settings
+    };
+    settings.validate()?;
+    apply(&app_handle, settings);
+    settings::persist(&app_handle);
+    Ok(self::settings())
+}
+
+#[tauri::command]
+pub fn get_relay_status() -> RelayStatus {
+    status()
+}
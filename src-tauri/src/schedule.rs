@@ -0,0 +1,150 @@
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use tauri::Manager;
+
+use crate::commands;
+use crate::i18n;
+
+/// 一个等待执行的定时粘贴任务
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduledPaste {
+    pub id: u64,
+    /// 计划开始的时间（Unix 毫秒时间戳）
+    pub run_at: u64,
+    pub stand: u32,
+    pub float: u32,
+    /// 创建任务时保存的剪贴板内容长度；None 表示执行时再读取剪贴板
+    pub snapshot_len: Option<usize>,
+    #[serde(skip)]
+    snapshot: Option<Vec<u16>>,
+}
+
+/// 全部等待中的定时粘贴任务
+pub struct ScheduleState {
+    jobs: Vec<ScheduledPaste>,
+    next_id: u64,
+}
+
+impl ScheduleState {
+    pub fn new() -> Self {
+        Self {
+            jobs: Vec::new(),
+            next_id: 1,
+        }
+    }
+}
+
+/// 当前 Unix 毫秒时间戳
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// 通知前端定时任务列表发生变化
+fn emit_changed(app_handle: &tauri::AppHandle, jobs: &[ScheduledPaste]) {
+    let _ = app_handle.emit_all("scheduled-pastes-changed", jobs);
+}
+
+/// 安排一次定时粘贴：delay_ms 后或在 at（Unix 毫秒时间戳）开始。
+/// snapshot 为 true 时立即保存当前剪贴板内容，否则在执行时读取。
+#[tauri::command]
+pub fn schedule_paste(
+    delay_ms: Option<u64>,
+    at: Option<u64>,
+    snapshot: bool,
+    stand: Option<u32>,
+    float: Option<u32>,
+    app_handle: tauri::AppHandle,
+) -> Result<ScheduledPaste, String> {
+    let (stand, float) = commands::resolve_timing(&app_handle, stand, float);
+    let now = now_ms();
+    let run_at = match (delay_ms, at) {
+        (Some(delay), None) => now + delay,
+        (None, Some(at)) if at >= now => at,
+        (None, Some(_)) => return Err(i18n::t("error.schedule_past").to_string()),
+        _ => return Err(i18n::t("error.schedule_when").to_string()),
+    };
+    let snapshot = if snapshot {
+        Some(commands::get_clipboard()?)
+    } else {
+        None
+    };
+
+    let job = {
+        let state = app_handle.state::<Mutex<ScheduleState>>();
+        let mut locked = state.lock().unwrap();
+        let job = ScheduledPaste {
+            id: locked.next_id,
+            run_at,
+            stand,
+            float,
+            snapshot_len: snapshot.as_ref().map(Vec::len),
+            snapshot,
+        };
+        locked.next_id += 1;
+        locked.jobs.push(job.clone());
+        emit_changed(&app_handle, &locked.jobs);
+        job
+    };
+
+    #[cfg(debug_assertions)]
+    println!("已安排定时粘贴 #{}，{} 毫秒后开始", job.id, run_at - now);
+
+    let id = job.id;
+    let app_handle_clone = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(run_at.saturating_sub(now_ms()))).await;
+        run_job(&app_handle_clone, id).await;
+    });
+    Ok(job)
+}
+
+/// 到时间后执行任务（已被取消则什么都不做）
+async fn run_job(app_handle: &tauri::AppHandle, id: u64) {
+    let job = {
+        let state = app_handle.state::<Mutex<ScheduleState>>();
+        let mut locked = state.lock().unwrap();
+        let Some(index) = locked.jobs.iter().position(|job| job.id == id) else {
+            return;
+        };
+        let job = locked.jobs.remove(index);
+        emit_changed(app_handle, &locked.jobs);
+        job
+    };
+
+    #[cfg(debug_assertions)]
+    println!("开始执行定时粘贴 #{}", id);
+
+    if let Err(e) = commands::paste_text(app_handle, job.snapshot, job.stand, job.float).await {
+        #[cfg(debug_assertions)]
+        println!("定时粘贴 #{} 失败: {}", id, e);
+
+        let _ = app_handle.emit_all("scheduled-paste-failed", (id, e));
+    }
+}
+
+/// 列出等待中的定时粘贴任务
+#[tauri::command]
+pub fn list_scheduled_pastes(app_handle: tauri::AppHandle) -> Vec<ScheduledPaste> {
+    let state = app_handle.state::<Mutex<ScheduleState>>();
+    let locked = state.lock().unwrap();
+    locked.jobs.clone()
+}
+
+/// 取消一个定时粘贴任务，返回是否找到该任务
+#[tauri::command]
+pub fn cancel_scheduled_paste(id: u64, app_handle: tauri::AppHandle) -> bool {
+    let state = app_handle.state::<Mutex<ScheduleState>>();
+    let mut locked = state.lock().unwrap();
+    let before = locked.jobs.len();
+    locked.jobs.retain(|job| job.id != id);
+    let found = locked.jobs.len() != before;
+    if found {
+        emit_changed(&app_handle, &locked.jobs);
+    }
+    found
+}
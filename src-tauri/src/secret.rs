@@ -0,0 +1,89 @@
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+#[cfg(windows)]
+use windows::Win32::{
+    Foundation::{LocalFree, HLOCAL},
+    Security::Cryptography::{
+        CryptProtectData, CryptUnprotectData, CRYPTPROTECT_UI_FORBIDDEN, CRYPT_INTEGER_BLOB,
+    },
+};
+
+use crate::i18n;
+
+/// 取出 DPAPI 输出的数据并释放其内存
+#[cfg(windows)]
+unsafe fn take_blob(blob: CRYPT_INTEGER_BLOB) -> Vec<u8> {
+    let data = std::slice::from_raw_parts(blob.pbData, blob.cbData as usize).to_vec();
+    let _ = LocalFree(HLOCAL(blob.pbData as *mut _));
+    data
+}
+
+/// 用 Windows DPAPI 加密（只有当前用户在本机上能解密）
+#[cfg(windows)]
+pub fn protect(plain: &[u8]) -> Result<Vec<u8>, &'static str> {
+    let input = CRYPT_INTEGER_BLOB {
+        cbData: plain.len() as u32,
+        pbData: plain.as_ptr() as *mut u8,
+    };
+    let mut output = CRYPT_INTEGER_BLOB::default();
+    unsafe {
+        CryptProtectData(
+            &input,
+            None,
+            None,
+            None,
+            None,
+            CRYPTPROTECT_UI_FORBIDDEN,
+            &mut output,
+        )
+        .map_err(|_| i18n::t("error.encrypt"))?;
+        Ok(take_blob(output))
+    }
+}
+
+/// 其他系统上没有 DPAPI，无法保存敏感片段
+#[cfg(not(windows))]
+pub fn protect(_plain: &[u8]) -> Result<Vec<u8>, &'static str> {
+    Err(i18n::t("error.secret_unsupported"))
+}
+
+/// 解密 protect 的结果
+#[cfg(windows)]
+pub fn unprotect(cipher: &[u8]) -> Result<Vec<u8>, &'static str> {
+    let input = CRYPT_INTEGER_BLOB {
+        cbData: cipher.len() as u32,
+        pbData: cipher.as_ptr() as *mut u8,
+    };
+    let mut output = CRYPT_INTEGER_BLOB::default();
+    unsafe {
+        CryptUnprotectData(
+            &input,
+            None,
+            None,
+            None,
+            None,
+            CRYPTPROTECT_UI_FORBIDDEN,
+            &mut output,
+        )
+        .map_err(|_| i18n::t("error.decrypt_foreign"))?;
+        Ok(take_blob(output))
+    }
+}
+
+#[cfg(not(windows))]
+pub fn unprotect(_cipher: &[u8]) -> Result<Vec<u8>, &'static str> {
+    Err(i18n::t("error.secret_unsupported"))
+}
+
+/// 加密字符串，结果以 base64 保存
+pub fn encrypt_string(plain: &str) -> Result<String, &'static str> {
+    protect(plain.as_bytes()).map(|cipher| STANDARD.encode(cipher))
+}
+
+/// 解密 encrypt_string 的结果
+pub fn decrypt_string(cipher: &str) -> Result<String, &'static str> {
+    let cipher = STANDARD
+        .decode(cipher)
+        .map_err(|_| i18n::t("error.cipher_invalid"))?;
+    let plain = unprotect(&cipher)?;
+    String::from_utf8(plain).map_err(|_| i18n::t("error.decrypt_not_text"))
+}
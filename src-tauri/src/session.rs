@@ -0,0 +1,102 @@
+use std::sync::OnceLock;
+use std::thread;
+
+use windows::core::w;
+use windows::Win32::{
+    Foundation::{HANDLE, HINSTANCE, HWND, LPARAM, LRESULT, WPARAM},
+    System::{
+        LibraryLoader::GetModuleHandleW,
+        Power::RegisterSuspendResumeNotification,
+        RemoteDesktop::{WTSRegisterSessionNotification, NOTIFY_FOR_THIS_SESSION},
+    },
+    UI::WindowsAndMessaging::{
+        CreateWindowExW, DefWindowProcW, DispatchMessageW, GetMessageW, RegisterClassW,
+        DEVICE_NOTIFY_WINDOW_HANDLE, HMENU, HWND_MESSAGE, MSG, PBT_APMSUSPEND, WINDOW_EX_STYLE,
+        WINDOW_STYLE, WM_POWERBROADCAST, WM_WTSSESSION_CHANGE, WNDCLASSW, WTS_CONSOLE_DISCONNECT,
+        WTS_REMOTE_DISCONNECT, WTS_SESSION_LOCK,
+    },
+};
+
+use crate::commands;
+
+/// 会话通知回调中使用的 AppHandle
+static APP_HANDLE: OnceLock<tauri::AppHandle> = OnceLock::new();
+
+/// 启动会话监视线程：创建仅接收消息的隐藏窗口，订阅锁屏、远程桌面断开与系统睡眠通知
+pub fn start_session_watcher(app_handle: tauri::AppHandle) {
+    if APP_HANDLE.set(app_handle).is_err() {
+        return;
+    }
+    thread::spawn(|| unsafe {
+        let module = match GetModuleHandleW(None) {
+            Ok(module) => HINSTANCE(module.0),
+            Err(_) => return,
+        };
+        let class_name = w!("PasterSessionWatcher");
+        let class = WNDCLASSW {
+            lpfnWndProc: Some(window_proc),
+            hInstance: module,
+            lpszClassName: class_name,
+            ..Default::default()
+        };
+        RegisterClassW(&class);
+
+        let hwnd = CreateWindowExW(
+            WINDOW_EX_STYLE(0),
+            class_name,
+            w!(""),
+            WINDOW_STYLE(0),
+            0,
+            0,
+            0,
+            0,
+            HWND_MESSAGE,
+            HMENU(0),
+            module,
+            None,
+        );
+        if hwnd.0 == 0 {
+            #[cfg(debug_assertions)]
+            eprintln!("创建会话监视窗口失败");
+
+            return;
+        }
+        if let Err(e) = WTSRegisterSessionNotification(hwnd, NOTIFY_FOR_THIS_SESSION) {
+            #[cfg(debug_assertions)]
+            eprintln!("订阅会话通知失败: {}", e);
+        }
+        // 仅接收消息的窗口收不到广播的 WM_POWERBROADCAST，需要单独订阅睡眠通知
+        if let Err(e) =
+            RegisterSuspendResumeNotification(HANDLE(hwnd.0), DEVICE_NOTIFY_WINDOW_HANDLE)
+        {
+            #[cfg(debug_assertions)]
+            eprintln!("订阅睡眠通知失败: {}", e);
+        }
+
+        let mut msg = MSG::default();
+        while GetMessageW(&mut msg, HWND(0), 0, 0).as_bool() {
+            DispatchMessageW(&msg);
+        }
+    });
+}
+
+/// 隐藏窗口的消息处理：锁屏、会话断开或即将睡眠时打断正在进行的粘贴
+unsafe extern "system" fn window_proc(
+    hwnd: HWND,
+    message: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    let reason = match (message, wparam.0 as u32) {
+        (WM_WTSSESSION_CHANGE, WTS_SESSION_LOCK) => Some("session_locked"),
+        (WM_WTSSESSION_CHANGE, WTS_CONSOLE_DISCONNECT | WTS_REMOTE_DISCONNECT) => {
+            Some("session_disconnected")
+        }
+        (WM_POWERBROADCAST, PBT_APMSUSPEND) => Some("system_suspend"),
+        _ => None,
+    };
+    if let (Some(reason), Some(app_handle)) = (reason, APP_HANDLE.get()) {
+        commands::handle_session_change(app_handle, reason);
+    }
+    DefWindowProcW(hwnd, message, wparam, lparam)
+}
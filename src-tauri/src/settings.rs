@@ -0,0 +1,918 @@
+use std::fs;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::Manager;
+
+use crate::api::{self, ApiSettings};
+use crate::app_rules::{self, AppRule, AppRuleState};
+use crate::autostart;
+use crate::chunks::{ChunkSettings, ChunkState};
+use crate::commands::{self, EndAction, InterruptAction, PasteState, MAX_START_DELAY_SECS};
+use crate::config;
+use crate::hotkeys::{self, HotkeyBindings};
+use crate::i18n::{self, Language};
+use crate::native_host::{self, NativeHostSettings};
+use crate::notify::{self, NotificationPrefs, ShowWindowPrefs};
+use crate::overlay::OverlayPrefs;
+use crate::platform::TextSource;
+use crate::profiles::{ProfileSettings, ProfileState};
+use crate::mqtt::{self, MqttSettings};
+use crate::relay::{self, RelaySettings};
+use crate::tray::{self, TrayClickPrefs};
+use crate::webhook::{self, WebhookSettings};
+use crate::vault;
+
+pub const SETTINGS_FILE: &str = "settings.json";
+/// 当前的设置文件格式版本
+pub const SETTINGS_VERSION: u32 = 1;
+/// settings.json 的 JSON Schema；修改 Settings 或其字段的类型、取值范围时同步更新
+const SETTINGS_SCHEMA: &str = include_str!("settings.schema.json");
+
+/// 全部设置，保存在 app_config_dir/settings.json。
+/// 缺少的字段使用默认值，因此旧版本写入的文件可以直接读取。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub version: u32,
+    pub hotkeys: HotkeyBindings,
+    /// 位于前台时暂停快捷键的程序（进程名）
+    pub suspend_apps: Vec<String>,
+    pub instant_mode: bool,
+    /// 默认输入速度：每个字符后等待 stand + [0, float) 毫秒
+    pub stand: u32,
+    pub float: u32,
+    /// 延迟随机数种子，None 表示每次使用系统熵
+    pub timing_seed: Option<u64>,
+    pub start_delay_secs: u32,
+    pub end_action: EndAction,
+    pub on_user_input: InterruptAction,
+    pub on_focus_loss: InterruptAction,
+    pub auto_resume_on_focus: bool,
+    pub on_session_change: InterruptAction,
+    pub notifications: NotificationPrefs,
+    /// 倒计时与进度悬浮窗
+    pub overlay: OverlayPrefs,
+    /// 单击与双击托盘图标的动作
+    pub tray_clicks: TrayClickPrefs,
+    /// 正在粘贴时退出是否先请前端确认
+    pub confirm_quit_while_pasting: bool,
+    /// 启动时只显示托盘图标，不显示窗口
+    pub start_hidden: bool,
+    /// 粘贴完成或失败时显示主窗口
+    pub show_window: ShowWindowPrefs,
+    pub expand_clipboard_templates: bool,
+    /// 粘贴时读取剪贴板还是 PRIMARY 选区（仅 Linux）
+    pub text_source: TextSource,
+    pub chunks: ChunkSettings,
+    pub profiles: ProfileSettings,
+    /// 按前台程序覆盖输入设置的规则，按顺序匹配
+    pub app_rules: Vec<AppRule>,
+    /// 开机自启，默认关闭；启动时按此注册或移除开机自启项
+    pub autostart: bool,
+    /// 本地 HTTP 接口
+    pub api: ApiSettings,
+    /// 浏览器扩展（原生消息主机）
+    pub native_host: NativeHostSettings,
+    /// 粘贴结束时发送的 Webhook
+    pub webhook: WebhookSettings,
+    /// 局域网剪贴板中转
+    pub relay: RelaySettings,
+    /// MQTT 触发
+    pub mqtt: MqttSettings,
+    /// 托盘、通知与错误信息的语言，None 表示跟随系统
+    pub language: Option<Language>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self::from_state(
+            &PasteState::new(),
+            Vec::new(),
+            ChunkSettings::default(),
+            ProfileSettings::default(),
+            Vec::new(),
+        )
+    }
+}
+
+impl Settings {
+    fn from_state(
+        state: &PasteState,
+        suspend_apps: Vec<String>,
+        chunks: ChunkSettings,
+        profiles: ProfileSettings,
+        app_rules: Vec<AppRule>,
+    ) -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            hotkeys: state.hotkeys.clone(),
+            suspend_apps,
+            instant_mode: state.instant_mode,
+            stand: state.default_timing.0,
+            float: state.default_timing.1,
+            timing_seed: state.timing_seed,
+            start_delay_secs: state.start_delay_secs,
+            end_action: state.end_action,
+            on_user_input: state.on_user_input,
+            on_focus_loss: state.on_focus_loss,
+            auto_resume_on_focus: state.auto_resume_on_focus,
+            on_session_change: state.on_session_change,
+            notifications: state.notifications,
+            overlay: state.overlay,
+            tray_clicks: TrayClickPrefs::default(),
+            confirm_quit_while_pasting: state.confirm_quit_while_pasting,
+            start_hidden: state.start_hidden,
+            show_window: state.show_window,
+            expand_clipboard_templates: state.expand_clipboard_templates,
+            text_source: state.text_source,
+            chunks,
+            profiles,
+            app_rules,
+            autostart: false,
+            api: ApiSettings::default(),
+            native_host: NativeHostSettings::default(),
+            webhook: WebhookSettings::default(),
+            relay: RelaySettings::default(),
+            mqtt: MqttSettings::default(),
+            language: None,
+        }
+    }
+
+    /// 全局快捷键被选用的配置方案覆盖后的快捷键，即实际注册的快捷键
+    fn effective_hotkeys(&self) -> HotkeyBindings {
+        self.profiles.hotkeys(&self.hotkeys)
+    }
+
+    /// 逐项检查设置，返回全部不合法的字段
+    pub fn check(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        let mut push = |path: String, message: String| errors.push(FieldError { path, message });
+
+        let mut hotkeys_ok = true;
+        for (action, config) in &self.hotkeys {
+            if let Err(e) = config.validate(*action) {
+                let name = serde_json::to_value(action)
+                    .ok()
+                    .and_then(|name| name.as_str().map(str::to_string))
+                    .unwrap_or_else(|| format!("{:?}", action));
+                push(format!("hotkeys.{}", name), e);
+                hotkeys_ok = false;
+            }
+        }
+        // 单个快捷键都合法时再检查组合限制与重复绑定
+        if hotkeys_ok {
+            if let Err(e) = hotkeys::validate_bindings(&self.hotkeys) {
+                push("hotkeys".to_string(), e);
+            }
+        }
+        for (i, app) in self.suspend_apps.iter().enumerate() {
+            if app.trim().is_empty() {
+                push(
+                    format!("suspend_apps[{}]", i),
+                    i18n::t("error.process_name_empty").to_string(),
+                );
+            }
+        }
+        if self.start_delay_secs > MAX_START_DELAY_SECS {
+            push(
+                "start_delay_secs".to_string(),
+                i18n::t("error.start_delay_too_long").to_string(),
+            );
+        }
+        if let Err(e) = self.chunks.validate() {
+            push("chunks".to_string(), e.to_string());
+        }
+        if let Err(e) = self
+            .profiles
+            .validate()
+            .and_then(|_| self.profiles.validate_hotkeys(&self.hotkeys))
+        {
+            push("profiles".to_string(), e);
+        }
+        if let Err(e) = app_rules::validate(&self.app_rules) {
+            push("app_rules".to_string(), e);
+        }
+        if let Err(e) = self.api.validate() {
+            push("api.port".to_string(), e.to_string());
+        }
+        if let Err(e) = self.native_host.validate() {
+            push("native_host.allowed_origins".to_string(), e.to_string());
+        }
+        if let Err(e) = self.webhook.validate() {
+            push("webhook.url".to_string(), e.to_string());
+        }
+        if let Err(e) = self.relay.validate() {
+            push("relay".to_string(), e.to_string());
+        }
+        if let Err(e) = self.mqtt.validate() {
+            push("mqtt".to_string(), e.to_string());
+        }
+        errors
+    }
+
+    /// 检查设置是否合法，不合法时返回全部错误（每行一项）
+    pub fn validate(&self) -> Result<(), String> {
+        let errors = self.check();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("\n"))
+        }
+    }
+
+    /// 把有错误的顶层字段恢复为默认值，其余设置保持不变
+    fn reset_fields(self, errors: &[FieldError]) -> Self {
+        let (Ok(mut value), Ok(defaults)) = (
+            serde_json::to_value(&self),
+            serde_json::to_value(Settings::default()),
+        ) else {
+            return self;
+        };
+        for error in errors {
+            if let Some(default) = defaults.get(error.field()) {
+                value[error.field()] = default.clone();
+            }
+        }
+        serde_json::from_value(value).unwrap_or(self)
+    }
+}
+
+/// reset_settings 恢复默认值的范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResetScope {
+    /// 快捷键与暂停快捷键的程序
+    Hotkeys,
+    /// 默认输入速度、极速模式与倒计时
+    Timing,
+    /// 配置方案与程序规则中的文本变换，以及剪贴板模板展开
+    Transforms,
+    /// 打断、结束动作、通知、悬浮窗、托盘点击与窗口显示
+    Behavior,
+    Chunks,
+    /// 全部配置方案与程序规则
+    Profiles,
+    All,
+}
+
+impl Settings {
+    /// 把 scope 范围内的设置恢复为默认值
+    fn reset(&mut self, scope: ResetScope) {
+        let defaults = Settings::default();
+        match scope {
+            ResetScope::Hotkeys => {
+                self.hotkeys = defaults.hotkeys;
+                self.suspend_apps = defaults.suspend_apps;
+            }
+            ResetScope::Timing => {
+                self.stand = defaults.stand;
+                self.float = defaults.float;
+                self.instant_mode = defaults.instant_mode;
+                self.start_delay_secs = defaults.start_delay_secs;
+            }
+            ResetScope::Transforms => {
+                for profile in &mut self.profiles.profiles {
+                    profile.transforms.clear();
+                }
+                for rule in &mut self.app_rules {
+                    rule.transforms = None;
+                }
+                self.expand_clipboard_templates = defaults.expand_clipboard_templates;
+            }
+            ResetScope::Behavior => {
+                self.end_action = defaults.end_action;
+                self.on_user_input = defaults.on_user_input;
+                self.on_focus_loss = defaults.on_focus_loss;
+                self.auto_resume_on_focus = defaults.auto_resume_on_focus;
+                self.on_session_change = defaults.on_session_change;
+                self.notifications = defaults.notifications;
+                self.overlay = defaults.overlay;
+                self.tray_clicks = defaults.tray_clicks;
+                self.confirm_quit_while_pasting = defaults.confirm_quit_while_pasting;
+                self.start_hidden = defaults.start_hidden;
+                self.show_window = defaults.show_window;
+                self.text_source = defaults.text_source;
+            }
+            ResetScope::Chunks => self.chunks = defaults.chunks,
+            ResetScope::Profiles => {
+                self.profiles = defaults.profiles;
+                self.app_rules = defaults.app_rules;
+            }
+            ResetScope::All => *self = defaults,
+        }
+    }
+}
+
+/// 一项不合法的设置
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldError {
+    /// 字段路径，如 "hotkeys.paste"、"suspend_apps[0]"；与具体字段无关的错误为空
+    pub path: String,
+    pub message: String,
+}
+
+impl FieldError {
+    fn general(message: String) -> Self {
+        Self {
+            path: String::new(),
+            message,
+        }
+    }
+
+    /// 所属的顶层字段
+    fn field(&self) -> &str {
+        self.path.split(['.', '[']).next().unwrap_or_default()
+    }
+}
+
+impl std::fmt::Display for FieldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+/// 启动时读取设置发现的错误，对应字段已恢复为默认值
+static LOAD_ERRORS: Mutex<Vec<FieldError>> = Mutex::new(Vec::new());
+
+/// 逐个顶层字段解析设置：类型不对的字段（如负数延迟、未知的变换名称）记录错误并使用默认值
+fn parse_fields(value: Value) -> Result<(Settings, Vec<FieldError>), String> {
+    let Value::Object(object) = value else {
+        return Err(i18n::t("error.settings_not_object").to_string());
+    };
+    let mut errors = Vec::new();
+    let mut valid = serde_json::Map::new();
+    for (key, field) in object {
+        let single = Value::Object([(key.clone(), field.clone())].into_iter().collect());
+        match serde_json::from_value::<Settings>(single) {
+            Ok(_) => {
+                valid.insert(key, field);
+            }
+            Err(e) => errors.push(FieldError {
+                path: key,
+                message: i18n::tf("error.field_format", &[("error", &e)]),
+            }),
+        }
+    }
+    let settings = serde_json::from_value(Value::Object(valid))
+        .map_err(|e| i18n::tf("error.settings_format", &[("error", &e)]))?;
+    Ok((settings, errors))
+}
+
+/// 把某一版设置升级为下一版
+type Migration = fn(&mut Value) -> Result<(), String>;
+
+/// 设置文件的迁移步骤：MIGRATIONS[n] 把第 n 版升级为第 n + 1 版。
+/// 修改 Settings 的结构时递增 SETTINGS_VERSION，并在此追加一步。
+const MIGRATIONS: [Migration; SETTINGS_VERSION as usize] = [migrate_v0_legacy];
+
+/// 第 0 版：settings.json 出现之前分散的配置文件，由 legacy_value 合成为
+/// {"shortcut": 旧版粘贴快捷键, "hotkeys": 各动作快捷键, "suspend_apps": [...]}
+fn migrate_v0_legacy(value: &mut Value) -> Result<(), String> {
+    let object = value
+        .as_object_mut()
+        .ok_or(i18n::t("error.settings_not_object"))?;
+
+    // 最早的配置没有 intercept_ctrl_v 字段
+    let fill_hotkey = |config: &mut Value| {
+        if let Some(config) = config.as_object_mut() {
+            config
+                .entry("intercept_ctrl_v")
+                .or_insert(Value::Bool(false));
+        }
+    };
+
+    let shortcut = object.remove("shortcut");
+    if let Some(hotkeys) = object.get_mut("hotkeys").and_then(Value::as_object_mut) {
+        hotkeys.values_mut().for_each(fill_hotkey);
+    } else if let Some(mut shortcut) = shortcut {
+        // 只保存了粘贴快捷键，其余动作使用默认快捷键
+        fill_hotkey(&mut shortcut);
+        let mut hotkeys =
+            serde_json::to_value(hotkeys::default_bindings()).map_err(|e| e.to_string())?;
+        hotkeys["paste"] = shortcut;
+        object.insert("hotkeys".to_string(), hotkeys);
+    }
+    Ok(())
+}
+
+/// 把旧版本的设置逐版升级到当前版本
+fn migrate(value: &mut Value) -> Result<u32, String> {
+    let from = value.get("version").and_then(Value::as_u64).unwrap_or(0) as u32;
+    if from > SETTINGS_VERSION {
+        return Err(i18n::tf(
+            "error.settings_too_new",
+            &[("version", &from), ("supported", &SETTINGS_VERSION)],
+        ));
+    }
+    for (version, step) in MIGRATIONS.iter().enumerate().skip(from as usize) {
+        step(value).map_err(|e| {
+            i18n::tf(
+                "error.settings_migrate",
+                &[("version", &version), ("error", &e)],
+            )
+        })?;
+        value["version"] = Value::from(version as u32 + 1);
+    }
+    Ok(from)
+}
+
+/// settings.json 出现之前的配置文件及其对应的设置字段
+const LEGACY_FILES: [(&str, &str); 3] = [
+    ("shortcut", "shortcut_config.json"),
+    ("hotkeys", "hotkeys_config.json"),
+    ("suspend_apps", "suspend_apps.json"),
+];
+
+/// 从 settings.json 出现之前的配置文件合成第 0 版设置；一个都不存在时返回 None
+fn legacy_value(app_handle: &tauri::AppHandle) -> Option<Value> {
+    let mut object = serde_json::Map::new();
+    for (key, file_name) in LEGACY_FILES {
+        if let Some(value) = config::read_json::<Value>(app_handle, file_name) {
+            object.insert(key.to_string(), value);
+        }
+    }
+    if object.is_empty() {
+        None
+    } else {
+        object.insert("version".to_string(), Value::from(0));
+        Some(Value::Object(object))
+    }
+}
+
+/// 把无法读取的 settings.json 另存一份（如 settings.v0.json），避免被默认设置覆盖后丢失
+fn backup_settings_file(app_handle: &tauri::AppHandle, suffix: &str) {
+    let Ok(path) = config::config_path(app_handle, SETTINGS_FILE) else {
+        return;
+    };
+    let backup = path.with_file_name(format!("settings.{}.json", suffix));
+    if let Err(_e) = fs::copy(&path, &backup) {
+        #[cfg(debug_assertions)]
+        eprintln!("备份设置文件失败: {}", _e);
+    }
+}
+
+/// 配置目录下以明文保存设置内容的其他文件：旧版配置文件与 settings.v0.json 等另存的备份。
+/// 设置改为密文保存后需要一并删除。
+pub fn plaintext_leftovers(app_handle: &tauri::AppHandle) -> Vec<String> {
+    let mut files: Vec<String> = LEGACY_FILES
+        .iter()
+        .map(|(_, file_name)| file_name.to_string())
+        .collect();
+    let Ok(path) = config::config_path(app_handle, SETTINGS_FILE) else {
+        return files;
+    };
+    let Some(Ok(entries)) = path.parent().map(fs::read_dir) else {
+        return files;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name != SETTINGS_FILE && name.starts_with("settings.") && name.ends_with(".json") {
+            files.push(name);
+        }
+    }
+    files
+}
+
+/// 读取 settings.json（不存在时读取旧版配置文件）并升级到当前版本。
+/// 不合法的字段恢复为默认值，并随设置一起返回这些错误。
+fn read_settings(
+    app_handle: &tauri::AppHandle,
+) -> Result<Option<(Settings, Vec<FieldError>)>, String> {
+    let path = config::config_path(app_handle, SETTINGS_FILE)?;
+    let mut errors = Vec::new();
+    let mut recovered = false;
+    let (mut value, from_file) = if vault::encrypts_settings(app_handle) {
+        // 加密的设置在解锁前无法读取，此时使用默认设置
+        match vault::read_json::<Value>(app_handle, SETTINGS_FILE) {
+            Some(value) => (value, false),
+            None => return Ok(None),
+        }
+    } else {
+        match config::read_text(&path) {
+            Ok(content) => match serde_json::from_str::<Value>(&content) {
+                Ok(value) => (value, true),
+                Err(e) => {
+                    // 文件损坏（如保存时断电、同步冲突）时改用上一次保存前的备份
+                    backup_settings_file(app_handle, "invalid");
+                    let backup = config::read_text(&config::backup_path(&path))
+                        .ok()
+                        .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+                        .ok_or_else(|| {
+                            i18n::tf(
+                                "error.parse_file",
+                                &[("path", &path.display()), ("error", &e)],
+                            )
+                        })?;
+                    errors.push(FieldError::general(i18n::tf(
+                        "error.settings_recovered",
+                        &[("path", &path.display()), ("error", &e)],
+                    )));
+                    recovered = true;
+                    (backup, true)
+                }
+            },
+            Err(_) => match legacy_value(app_handle) {
+                Some(value) => (value, false),
+                None => return Ok(None),
+            },
+        }
+    };
+
+    let from = migrate(&mut value)?;
+    let (settings, field_errors) = parse_fields(value).inspect_err(|_| {
+        if from_file {
+            backup_settings_file(app_handle, "invalid");
+        }
+    })?;
+    errors.extend(field_errors);
+    errors.extend(settings.check());
+    let settings = if errors.iter().all(|error| error.path.is_empty()) {
+        settings
+    } else {
+        if from_file {
+            backup_settings_file(app_handle, "invalid");
+        }
+        settings.reset_fields(&errors)
+    };
+
+    if from != SETTINGS_VERSION {
+        #[cfg(debug_assertions)]
+        println!("设置已从第 {} 版升级到第 {} 版", from, SETTINGS_VERSION);
+
+        if from_file {
+            backup_settings_file(app_handle, &format!("v{}", from));
+        }
+    }
+    if from != SETTINGS_VERSION || recovered {
+        vault::write_json(app_handle, SETTINGS_FILE, &settings)?;
+    }
+    Ok(Some((settings, errors)))
+}
+
+/// 把任意版本的设置（如导入的文件）升级到当前版本并检查
+pub fn from_value(mut value: Value) -> Result<Settings, String> {
+    migrate(&mut value)?;
+    let settings: Settings = serde_json::from_value(value)
+        .map_err(|e| i18n::tf("error.settings_format", &[("error", &e)]))?;
+    settings.validate()?;
+    Ok(settings)
+}
+
+/// 启动时读取设置，旧版本的设置文件会先升级到当前版本。
+/// 设置文件无法读取时另存备份并使用默认设置；部分字段不合法时只有这些字段使用默认值。
+/// 发现的错误通过 settings-invalid 事件发送，也可以用 get_settings_errors 获取。
+pub fn load(app_handle: &tauri::AppHandle) -> Settings {
+    let (settings, errors) = match read_settings(app_handle) {
+        Ok(Some(loaded)) => loaded,
+        Ok(None) => (Settings::default(), Vec::new()),
+        Err(e) => (Settings::default(), vec![FieldError::general(e)]),
+    };
+    if !errors.is_empty() {
+        #[cfg(debug_assertions)]
+        for error in &errors {
+            eprintln!("设置不合法，已使用默认值: {}", error);
+        }
+
+        let _ = app_handle.emit_all("settings-invalid", &errors);
+    }
+    *LOAD_ERRORS.lock().unwrap() = errors;
+    settings
+}
+
+/// 当前生效的设置
+pub fn current(app_handle: &tauri::AppHandle) -> Settings {
+    let suspend_apps = hotkeys::suspend_apps(app_handle);
+    let chunks = {
+        let state = app_handle.state::<Mutex<ChunkState>>();
+        let locked = state.lock().unwrap();
+        locked.settings().clone()
+    };
+    let profiles = {
+        let state = app_handle.state::<Mutex<ProfileState>>();
+        let locked = state.lock().unwrap();
+        locked.settings().clone()
+    };
+    let app_rules = {
+        let state = app_handle.state::<Mutex<AppRuleState>>();
+        let locked = state.lock().unwrap();
+        locked.rules().to_vec()
+    };
+    let state = app_handle.state::<Mutex<PasteState>>();
+    let locked = state.lock().unwrap();
+    Settings {
+        autostart: autostart::is_enabled(),
+        api: api::settings(),
+        native_host: native_host::settings(),
+        webhook: webhook::settings(),
+        relay: relay::settings(),
+        mqtt: mqtt::settings(),
+        language: i18n::preference(),
+        tray_clicks: tray::click_prefs(),
+        ..Settings::from_state(&locked, suspend_apps, chunks, profiles, app_rules)
+    }
+}
+
+/// 把设置写入各模块的状态（不注册快捷键，由调用方决定注册方式）
+pub fn apply(app_handle: &tauri::AppHandle, settings: &Settings) {
+    {
+        let state = app_handle.state::<Mutex<PasteState>>();
+        let mut locked = state.lock().unwrap();
+        locked.hotkeys = settings.hotkeys.clone();
+        locked.default_timing = (settings.stand, settings.float);
+        locked.timing_seed = settings.timing_seed;
+        locked.start_delay_secs = settings.start_delay_secs;
+        locked.end_action = settings.end_action;
+        locked.on_user_input = settings.on_user_input;
+        locked.on_focus_loss = settings.on_focus_loss;
+        locked.auto_resume_on_focus = settings.auto_resume_on_focus;
+        locked.on_session_change = settings.on_session_change;
+        locked.notifications = settings.notifications;
+        locked.overlay = settings.overlay;
+        locked.confirm_quit_while_pasting = settings.confirm_quit_while_pasting;
+        locked.start_hidden = settings.start_hidden;
+        locked.show_window = settings.show_window;
+        locked.expand_clipboard_templates = settings.expand_clipboard_templates;
+        // 其他平台读取在 Linux 上保存的设置时退回剪贴板
+        locked.text_source = if settings.text_source.is_supported() {
+            settings.text_source
+        } else {
+            TextSource::Clipboard
+        };
+    }
+    {
+        let state = app_handle.state::<Mutex<ChunkState>>();
+        state.lock().unwrap().set_settings(settings.chunks.clone());
+    }
+    {
+        let state = app_handle.state::<Mutex<ProfileState>>();
+        state
+            .lock()
+            .unwrap()
+            .set_settings(settings.profiles.clone());
+    }
+    {
+        let state = app_handle.state::<Mutex<AppRuleState>>();
+        state.lock().unwrap().set_rules(settings.app_rules.clone());
+    }
+    hotkeys::set_suspend_apps(app_handle, settings.suspend_apps.clone());
+    i18n::set_preference(settings.language);
+    tray::set_click_prefs(settings.tray_clicks);
+    commands::set_instant_mode(app_handle, settings.instant_mode);
+    api::apply(app_handle, settings.api.clone());
+    native_host::apply(settings.native_host.clone());
+    webhook::apply(settings.webhook.clone());
+    relay::apply(app_handle, settings.relay.clone());
+    mqtt::apply(app_handle, settings.mqtt.clone());
+    // 加密的设置尚未解锁时使用的是默认设置，不能据此移除开机自启项
+    if vault::settings_locked(app_handle) {
+        return;
+    }
+    if let Err(_e) = autostart::set_enabled(settings.autostart) {
+        #[cfg(debug_assertions)]
+        eprintln!("设置开机自启失败: {}", _e);
+    }
+}
+
+/// 把当前设置保存到 settings.json（开启了设置加密时保存为密文），并通知前端
+pub fn save(app_handle: &tauri::AppHandle) -> Result<(), String> {
+    let settings = current(app_handle);
+    vault::write_json(app_handle, SETTINGS_FILE, &settings)?;
+    let _ = app_handle.emit_all("settings-changed", &settings);
+    Ok(())
+}
+
+/// 修改单项设置后保存；保存失败不影响已生效的设置
+pub fn persist(app_handle: &tauri::AppHandle) {
+    if let Err(_e) = save(app_handle) {
+        #[cfg(debug_assertions)]
+        eprintln!("保存设置失败: {}", _e);
+    }
+}
+
+/// 按 JSON Merge Patch（RFC 7386）合并：对象逐键合并，null 删除该键（顶层字段随后恢复默认值），其余直接替换
+fn merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Default::default());
+    }
+    if let Value::Object(target) = target {
+        for (key, value) in patch {
+            if value.is_null() {
+                target.remove(key);
+            } else {
+                merge_patch(target.entry(key.clone()).or_insert(Value::Null), value);
+            }
+        }
+    }
+}
+
+/// 用一份完整的设置替换当前设置（已检查过），生效后保存
+pub fn replace(
+    app_handle: &tauri::AppHandle,
+    previous: &Settings,
+    settings: &Settings,
+) -> Result<(), String> {
+    if vault::settings_locked(app_handle) {
+        return Err(i18n::t("error.settings_locked").to_string());
+    }
+    // 快捷键整组原子地切换，失败时保持原有设置不变
+    if settings.effective_hotkeys() != previous.effective_hotkeys() {
+        hotkeys::switch_hotkeys(app_handle, &settings.effective_hotkeys())?;
+    }
+    apply(app_handle, settings);
+    save(app_handle)?;
+    tray::refresh(app_handle);
+    Ok(())
+}
+
+/// 重新读取被外部修改（手动编辑、同步工具）的 settings.json 并生效，不回写文件。
+/// 与当前设置相同（如本程序刚保存过）时返回 false；设置以密文保存时不读取明文文件，也返回 false。
+fn reload(app_handle: &tauri::AppHandle) -> Result<bool, String> {
+    if vault::encrypts_settings(app_handle) {
+        return Ok(false);
+    }
+    let path = config::config_path(app_handle, SETTINGS_FILE)?;
+    let content =
+        config::read_text(&path).map_err(|e| i18n::tf("error.read_file", &[("error", &e)]))?;
+    let value = serde_json::from_str::<Value>(&content).map_err(|e| {
+        i18n::tf(
+            "error.parse_file",
+            &[("path", &path.display()), ("error", &e)],
+        )
+    })?;
+    let settings = from_value(value)?;
+    let previous = current(app_handle);
+    if serde_json::to_value(&settings).ok() == serde_json::to_value(&previous).ok() {
+        return Ok(false);
+    }
+
+    if settings.effective_hotkeys() != previous.effective_hotkeys() {
+        hotkeys::switch_hotkeys(app_handle, &settings.effective_hotkeys())?;
+    }
+    apply(app_handle, &settings);
+    tray::refresh(app_handle);
+    LOAD_ERRORS.lock().unwrap().clear();
+    let _ = app_handle.emit_all("settings-reloaded", &settings);
+    Ok(true)
+}
+
+/// 加密的设置解锁后读取并生效（解锁前使用的是默认设置）
+pub fn unlocked(app_handle: &tauri::AppHandle) {
+    let settings = load(app_handle);
+    let previous = current(app_handle);
+    if settings.effective_hotkeys() != previous.effective_hotkeys() {
+        if let Err(e) = hotkeys::switch_hotkeys(app_handle, &settings.effective_hotkeys()) {
+            #[cfg(debug_assertions)]
+            eprintln!("注册快捷键失败: {}", e);
+            notify::hotkey_failed(app_handle, e);
+        }
+    }
+    apply(app_handle, &settings);
+    tray::refresh(app_handle);
+    let _ = app_handle.emit_all("settings-reloaded", &settings);
+}
+
+/// 后台线程：轮询 settings.json 的修改时间，被外部修改后重新读取。
+/// 读取失败（如编辑器尚未写完或格式错误）时发送 settings-reload-failed 事件，保持原有设置。
+pub fn spawn_watcher(app_handle: tauri::AppHandle) {
+    let Ok(path) = config::config_path(&app_handle, SETTINGS_FILE) else {
+        return;
+    };
+    std::thread::spawn(move || {
+        let modified = || fs::metadata(&path).and_then(|meta| meta.modified()).ok();
+        let mut last = modified();
+        loop {
+            std::thread::sleep(std::time::Duration::from_secs(1));
+            let now = modified();
+            if now.is_none() || now == last {
+                continue;
+            }
+            // 设置以密文保存时 settings.json 不再使用，忽略其改动
+            if vault::encrypts_settings(&app_handle) {
+                last = now;
+                continue;
+            }
+            // 等待写入完成
+            std::thread::sleep(std::time::Duration::from_millis(200));
+            last = modified();
+
+            match reload(&app_handle) {
+                Ok(_changed) =>
+                {
+                    #[cfg(debug_assertions)]
+                    if _changed {
+                        println!("settings.json 被外部修改，已重新读取");
+                    }
+                }
+                Err(e) => {
+                    #[cfg(debug_assertions)]
+                    eprintln!("重新读取设置失败: {}", e);
+
+                    let _ = app_handle.emit_all("settings-reload-failed", e);
+                }
+            }
+        }
+    });
+}
+
+/// 获取全部设置
+#[tauri::command]
+pub fn get_settings(app_handle: tauri::AppHandle) -> Settings {
+    current(&app_handle)
+}
+
+/// 用上一次保存前的备份（settings.json.bak）恢复设置，返回恢复后的设置。
+/// 恢复时当前设置又成为备份，因此再调用一次即可撤销恢复。
+#[tauri::command]
+pub fn restore_config_backup(app_handle: tauri::AppHandle) -> Result<Settings, String> {
+    let path = config::config_path(&app_handle, SETTINGS_FILE)?;
+    let content = config::read_text(&config::backup_path(&path))
+        .map_err(|_| i18n::t("error.no_settings_backup").to_string())?;
+    let value = serde_json::from_str::<Value>(&content)
+        .map_err(|e| i18n::tf("error.settings_backup_corrupt", &[("error", &e)]))?;
+    let settings = from_value(value)?;
+    let previous = current(&app_handle);
+    replace(&app_handle, &previous, &settings)?;
+
+    #[cfg(debug_assertions)]
+    println!("已从备份恢复设置");
+
+    Ok(settings)
+}
+
+/// 把 scope 范围内的设置恢复为默认值（重新注册快捷键并保存），返回生效后的全部设置。
+/// 恢复前的设置成为 settings.json.bak，可用 restore_config_backup 撤销。
+#[tauri::command]
+pub fn reset_settings(scope: ResetScope, app_handle: tauri::AppHandle) -> Result<Settings, String> {
+    let previous = current(&app_handle);
+    let mut settings = previous.clone();
+    settings.reset(scope);
+    replace(&app_handle, &previous, &settings)?;
+
+    #[cfg(debug_assertions)]
+    println!("已恢复默认设置: {:?}", scope);
+
+    Ok(settings)
+}
+
+/// 设置的 JSON Schema（各顶层字段附带默认值），供前端生成表单、外部工具检查 settings.json
+#[tauri::command]
+pub fn get_settings_schema() -> Result<Value, String> {
+    let mut schema: Value = serde_json::from_str(SETTINGS_SCHEMA)
+        .map_err(|e| i18n::tf("error.parse_json", &[("error", &e)]))?;
+    let defaults = serde_json::to_value(Settings::default())
+        .map_err(|e| i18n::tf("error.serialize_json", &[("error", &e)]))?;
+    if let (Some(properties), Value::Object(defaults)) = (
+        schema.get_mut("properties").and_then(Value::as_object_mut),
+        defaults,
+    ) {
+        for (key, default) in defaults {
+            if let Some(property) = properties.get_mut(&key).and_then(Value::as_object_mut) {
+                property.insert("default".to_string(), default);
+            }
+        }
+    }
+    Ok(schema)
+}
+
+/// 启动时读取设置发现的错误（对应字段已使用默认值）
+#[tauri::command]
+pub fn get_settings_errors() -> Vec<FieldError> {
+    LOAD_ERRORS.lock().unwrap().clone()
+}
+
+/// 修改设置：patch 只需包含要修改的字段，如 {"instant_mode": true, "chunks": {"stand": 10}}。
+/// 合并后的设置整体检查通过才会生效并保存，返回生效后的全部设置；
+/// 不合法时什么都不修改，返回每个不合法字段的错误。
+#[tauri::command]
+pub fn update_settings(
+    patch: Value,
+    app_handle: tauri::AppHandle,
+) -> Result<Settings, Vec<FieldError>> {
+    let previous = current(&app_handle);
+    let mut value =
+        serde_json::to_value(&previous).map_err(|e| vec![FieldError::general(e.to_string())])?;
+    merge_patch(&mut value, &patch);
+    let (mut settings, mut errors) =
+        parse_fields(value).map_err(|e| vec![FieldError::general(e)])?;
+    settings.version = SETTINGS_VERSION;
+    errors.extend(settings.check());
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    replace(&app_handle, &previous, &settings).map_err(|e| vec![FieldError::general(e)])?;
+    Ok(settings)
+}
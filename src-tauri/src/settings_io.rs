@@ -0,0 +1,248 @@
+use std::collections::HashMap;
+use std::fs;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::Manager;
+
+use crate::history;
+use crate::i18n;
+use crate::macros::MacroStep;
+use crate::settings::{self, SETTINGS_VERSION};
+use crate::snippet_io::ImportSummary;
+use crate::snippets::{Snippet, SnippetKind, SnippetStore};
+use crate::vault;
+
+/// 设置包的格式标识，用于识别误选的其他 JSON 文件
+const BUNDLE_FORMAT: &str = "paster-settings";
+
+/// 导出的设置包：全部设置（含快捷键与配置方案），可选附带片段库
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SettingsBundle {
+    format: String,
+    /// 导出时的设置版本，导入时按此升级
+    version: u32,
+    /// 导出时间（Unix 毫秒时间戳）
+    exported_at: u64,
+    /// 保留原始 JSON，以便按 version 升级旧版本导出的设置
+    settings: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    snippets: Option<Vec<Snippet>>,
+}
+
+/// 一项会被修改的设置
+#[derive(Debug, Clone, Serialize)]
+pub struct SettingChange {
+    /// 以 "." 连接的字段路径，如 "hotkeys.paste.key"
+    pub path: String,
+    pub from: Value,
+    pub to: Value,
+}
+
+/// 导入（或试导入）的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct SettingsImport {
+    /// 为 false 时只是预览，没有修改任何设置
+    pub applied: bool,
+    pub changes: Vec<SettingChange>,
+    /// 设置包中的片段导入情况；未导入片段时为 None
+    pub snippets: Option<ImportSummary>,
+}
+
+/// 逐字段比较两份设置，数组整体比较
+fn diff(path: &str, from: &Value, to: &Value, changes: &mut Vec<SettingChange>) {
+    match (from, to) {
+        (Value::Object(from), Value::Object(to)) => {
+            let mut keys: Vec<&String> = from.keys().chain(to.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let child = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                diff(
+                    &child,
+                    from.get(key).unwrap_or(&Value::Null),
+                    to.get(key).unwrap_or(&Value::Null),
+                    changes,
+                );
+            }
+        }
+        _ if from != to => changes.push(SettingChange {
+            path: path.to_string(),
+            from: from.clone(),
+            to: to.clone(),
+        }),
+        _ => {}
+    }
+}
+
+/// 把设置包中的片段并入片段库：重新分配编号并更新宏与片段组中的引用，
+/// 已有相同标题和正文的文本片段不重复导入
+fn merge_snippets(store: &mut SnippetStore, snippets: Vec<Snippet>) -> ImportSummary {
+    let mut summary = ImportSummary {
+        imported: 0,
+        skipped: 0,
+    };
+    let mut id_map: HashMap<u64, u64> = HashMap::new();
+    let mut inserted = Vec::new();
+    for snippet in snippets.into_iter().filter(|s| !s.sensitive) {
+        let existing = store.snippets().iter().find(|s| {
+            snippet.kind == SnippetKind::Text
+                && s.kind == SnippetKind::Text
+                && s.title == snippet.title
+                && s.body == snippet.body
+        });
+        if let Some(existing) = existing {
+            id_map.insert(snippet.id, existing.id);
+            summary.skipped += 1;
+            continue;
+        }
+        let old_id = snippet.id;
+        // 快捷键可能与本机已有的快捷键冲突，不导入
+        let new = store.insert(Snippet {
+            use_count: 0,
+            last_used_at: None,
+            next_member: 0,
+            hotkey: None,
+            ..snippet
+        });
+        id_map.insert(old_id, new.id);
+        inserted.push(new.id);
+        summary.imported += 1;
+    }
+
+    // 引用了未导出的敏感片段的组成员被去掉；宏步骤保留，输入时会提示片段不存在
+    for id in inserted {
+        let Some(snippet) = store.get_mut(id) else {
+            continue;
+        };
+        for step in &mut snippet.steps {
+            if let MacroStep::Snippet { snippet_id } = step {
+                if let Some(&new_id) = id_map.get(snippet_id) {
+                    *snippet_id = new_id;
+                }
+            }
+        }
+        snippet.members = snippet
+            .members
+            .iter()
+            .filter_map(|member| id_map.get(member).copied())
+            .collect();
+    }
+    summary
+}
+
+/// 导出设置包到文件；include_snippets 为 true 时附带片段库（敏感片段只能在本机解密，不会被导出）
+#[tauri::command]
+pub fn export_settings(
+    path: String,
+    include_snippets: bool,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    if vault::settings_locked(&app_handle) {
+        return Err(i18n::t("error.settings_locked").to_string());
+    }
+    let snippets = if include_snippets {
+        if vault::is_locked(&app_handle) {
+            return Err(i18n::t("error.vault_locked").to_string());
+        }
+        let state = app_handle.state::<Mutex<SnippetStore>>();
+        let locked = state.lock().unwrap();
+        Some(
+            locked
+                .snippets()
+                .iter()
+                .filter(|s| !s.sensitive)
+                .cloned()
+                .collect(),
+        )
+    } else {
+        None
+    };
+    let bundle = SettingsBundle {
+        format: BUNDLE_FORMAT.to_string(),
+        version: SETTINGS_VERSION,
+        exported_at: history::now_ms(),
+        settings: serde_json::to_value(settings::current(&app_handle))
+            .map_err(|e| i18n::tf("error.serialize_json", &[("error", &e)]))?,
+        snippets,
+    };
+    let content = serde_json::to_string_pretty(&bundle)
+        .map_err(|e| i18n::tf("error.serialize_json", &[("error", &e)]))?;
+    fs::write(&path, content).map_err(|e| i18n::tf("error.write_file", &[("error", &e)]))?;
+
+    #[cfg(debug_assertions)]
+    println!("已导出设置到 {}", path);
+
+    Ok(())
+}
+
+/// 从设置包导入设置，include_snippets 为 true 时同时并入其中的片段。
+/// dry_run 为 true 时只检查并返回会发生的变化，不修改任何设置。
+#[tauri::command]
+pub fn import_settings(
+    path: String,
+    include_snippets: bool,
+    dry_run: bool,
+    app_handle: tauri::AppHandle,
+) -> Result<SettingsImport, String> {
+    let content =
+        fs::read_to_string(&path).map_err(|e| i18n::tf("error.read_file", &[("error", &e)]))?;
+    let bundle: SettingsBundle = serde_json::from_str(content.trim_start_matches('\u{feff}'))
+        .map_err(|e| i18n::tf("error.bundle_parse", &[("error", &e)]))?;
+    if bundle.format != BUNDLE_FORMAT {
+        return Err(i18n::t("error.bundle_invalid").to_string());
+    }
+
+    let mut value = bundle.settings;
+    if value.is_object() && value.get("version").is_none() {
+        value["version"] = Value::from(bundle.version);
+    }
+    let imported = settings::from_value(value)?;
+    let previous = settings::current(&app_handle);
+
+    let mut changes = Vec::new();
+    diff(
+        "",
+        &serde_json::to_value(&previous).map_err(|e| e.to_string())?,
+        &serde_json::to_value(&imported).map_err(|e| e.to_string())?,
+        &mut changes,
+    );
+
+    let snippets = bundle.snippets.filter(|_| include_snippets);
+    if snippets.is_some() && vault::is_locked(&app_handle) {
+        return Err(i18n::t("error.vault_locked").to_string());
+    }
+
+    if !dry_run {
+        settings::replace(&app_handle, &previous, &imported)?;
+
+        #[cfg(debug_assertions)]
+        println!("已从 {} 导入设置，{} 项变化", path, changes.len());
+    }
+
+    let snippets = match snippets {
+        Some(snippets) => {
+            let state = app_handle.state::<Mutex<SnippetStore>>();
+            let mut locked = state.lock().unwrap();
+            if dry_run {
+                Some(merge_snippets(&mut locked.clone(), snippets))
+            } else {
+                let summary = merge_snippets(&mut locked, snippets);
+                locked.save(&app_handle)?;
+                Some(summary)
+            }
+        }
+        None => None,
+    };
+
+    Ok(SettingsImport {
+        applied: !dry_run,
+        changes,
+        snippets,
+    })
+}
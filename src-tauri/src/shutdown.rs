@@ -0,0 +1,81 @@
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tauri::Manager;
+
+use crate::commands::{self, PasteState};
+use crate::hook;
+use crate::hotkeys;
+use crate::settings;
+use crate::vault;
+
+/// 中止正在进行的粘贴后，留给输入循环发现中止并退出的时间
+const ABORT_GRACE: Duration = Duration::from_millis(300);
+
+/// 请求退出：正在粘贴且开启了退出确认时，显示窗口并发送 quit-confirm 事件，
+/// 由前端确认后以 force = true 再次调用；否则有序退出
+pub fn request_quit(app_handle: &tauri::AppHandle, force: bool) {
+    let (pasting, confirm) = {
+        let state = app_handle.state::<Mutex<PasteState>>();
+        let locked = state.lock().unwrap();
+        (
+            locked.is_pasting.load(Ordering::SeqCst),
+            locked.confirm_quit_while_pasting,
+        )
+    };
+    if pasting && confirm && !force {
+        if let Some(window) = app_handle.get_window("main") {
+            let _ = window.show();
+            let _ = window.set_focus();
+        }
+        let _ = app_handle.emit_all("quit-confirm", ());
+        return;
+    }
+
+    // 等待输入循环结束时不能阻塞事件循环
+    let app_handle = app_handle.clone();
+    std::thread::spawn(move || shutdown(&app_handle));
+}
+
+/// 中止粘贴、抬起按键、注销快捷键与钩子、保存设置，然后退出
+fn shutdown(app_handle: &tauri::AppHandle) {
+    // 1. 中止正在进行的粘贴
+    let was_pasting = {
+        let state = app_handle.state::<Mutex<PasteState>>();
+        let mut locked = state.lock().unwrap();
+        let pasting = locked.is_pasting.load(Ordering::SeqCst);
+        locked.abort("quit");
+        pasting
+    };
+    if was_pasting {
+        std::thread::sleep(ABORT_GRACE);
+    }
+
+    // 2. 抬起仍处于按下状态的注入按键
+    commands::release_pressed_keys();
+
+    // 3. 注销全局快捷键，关闭键盘钩子的监视
+    hotkeys::unregister_all(app_handle);
+    hook::set_user_input_watch(app_handle, false);
+    hook::set_foreground_watch(app_handle, false);
+
+    // 4. 保存设置（加密的设置尚未解锁时使用的是默认设置，不能覆盖）
+    if !vault::settings_locked(app_handle) {
+        if let Err(_e) = settings::save(app_handle) {
+            #[cfg(debug_assertions)]
+            eprintln!("退出前保存设置失败: {}", _e);
+        }
+    }
+
+    #[cfg(debug_assertions)]
+    println!("退出 Paster");
+
+    app_handle.exit(0);
+}
+
+/// 退出程序；force 为 true 时跳过正在粘贴的确认
+#[tauri::command]
+pub fn quit_app(force: bool, app_handle: tauri::AppHandle) {
+    request_quit(&app_handle, force);
+}
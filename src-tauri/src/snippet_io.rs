@@ -0,0 +1,295 @@
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+use crate::i18n;
+use crate::snippets::{Snippet, SnippetKind, SnippetStore};
+
+/// 导入时如何处理已有片段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportMode {
+    /// 保留已有片段，跳过标题和正文都相同的重复项
+    Merge,
+    /// 先清空片段库再导入
+    Replace,
+}
+
+/// 导入结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportSummary {
+    pub imported: usize,
+    /// 因重复而跳过的条数
+    pub skipped: usize,
+}
+
+/// JSON 导入时的单条片段，只有标题和正文是必需的
+#[derive(Debug, Clone, Deserialize)]
+struct ImportedSnippet {
+    title: String,
+    body: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    folder: String,
+}
+
+/// 按扩展名判断是否为 CSV 文件（其余按 JSON 处理）
+fn is_csv(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"))
+}
+
+/// 解析 CSV（RFC 4180：逗号分隔，双引号包裹的字段可含逗号、换行与 "" 转义）
+pub fn parse_csv(content: &str) -> Vec<Vec<String>> {
+    parse_delimited(content, ',')
+}
+
+/// 按 RFC 4180 的规则解析以 delimiter 分隔的文本（如 Excel 复制出的制表符分隔数据）
+pub fn parse_delimited(content: &str, delimiter: char) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = content.trim_start_matches('\u{feff}').chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' => in_quotes = true,
+            c if c == delimiter => row.push(std::mem::take(&mut field)),
+            '\r' => {}
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    // 跳过空行
+    rows.retain(|row| !(row.len() == 1 && row[0].is_empty()));
+    rows
+}
+
+/// 按需给 CSV 字段加引号
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// 读取要导入的片段：JSON 为片段数组，CSV 为 "标题,正文" 两列（可带表头）
+fn read_import_file(path: &Path) -> Result<Vec<ImportedSnippet>, String> {
+    let content =
+        fs::read_to_string(path).map_err(|e| i18n::tf("error.read_file", &[("error", &e)]))?;
+    if !is_csv(path) {
+        return serde_json::from_str(content.trim_start_matches('\u{feff}'))
+            .map_err(|e| i18n::tf("error.parse_json", &[("error", &e)]));
+    }
+
+    let mut rows = parse_csv(&content);
+    if rows.first().is_some_and(|header| {
+        header.len() >= 2
+            && header[0].trim().eq_ignore_ascii_case("title")
+            && header[1].trim().eq_ignore_ascii_case("body")
+    }) {
+        rows.remove(0);
+    }
+    rows.into_iter()
+        .enumerate()
+        .map(|(i, row)| match <[String; 2]>::try_from(row) {
+            Ok([title, body]) => Ok(ImportedSnippet {
+                title,
+                body,
+                tags: Vec::new(),
+                folder: String::new(),
+            }),
+            Err(_) => Err(i18n::tf("error.csv_columns", &[("line", &(i + 1))])),
+        })
+        .collect()
+}
+
+/// 导出全部片段到文件；扩展名为 .csv 时只导出标题和正文两列，否则导出 JSON。
+/// 敏感片段只能在本机解密，不会被导出。
+#[tauri::command]
+pub fn export_snippets(path: String, app_handle: tauri::AppHandle) -> Result<usize, String> {
+    let path = Path::new(&path);
+    let snippets: Vec<Snippet> = {
+        let state = app_handle.state::<Mutex<SnippetStore>>();
+        let locked = state.lock().unwrap();
+        locked
+            .snippets()
+            .iter()
+            .filter(|s| !s.sensitive)
+            .cloned()
+            .collect()
+    };
+
+    let content = if is_csv(path) {
+        let mut csv = String::from("title,body\r\n");
+        for snippet in &snippets {
+            csv.push_str(&csv_field(&snippet.title));
+            csv.push(',');
+            csv.push_str(&csv_field(&snippet.body));
+            csv.push_str("\r\n");
+        }
+        csv
+    } else {
+        serde_json::to_string_pretty(&snippets)
+            .map_err(|e| i18n::tf("error.serialize_json", &[("error", &e)]))?
+    };
+    fs::write(path, content).map_err(|e| i18n::tf("error.write_file", &[("error", &e)]))?;
+
+    #[cfg(debug_assertions)]
+    println!("已导出 {} 个片段到 {}", snippets.len(), path.display());
+
+    Ok(snippets.len())
+}
+
+/// 从 JSON 或 CSV 文件导入片段
+#[tauri::command]
+pub fn import_snippets(
+    path: String,
+    mode: ImportMode,
+    app_handle: tauri::AppHandle,
+) -> Result<ImportSummary, String> {
+    let imported = read_import_file(Path::new(&path))?;
+
+    let state = app_handle.state::<Mutex<SnippetStore>>();
+    let mut locked = state.lock().unwrap();
+    if mode == ImportMode::Replace {
+        locked.clear();
+    }
+
+    let mut summary = ImportSummary {
+        imported: 0,
+        skipped: 0,
+    };
+    for item in imported {
+        let title = item.title.trim().to_string();
+        let duplicate = locked
+            .snippets()
+            .iter()
+            .any(|s| s.title == title && s.body == item.body);
+        if title.is_empty() || duplicate {
+            summary.skipped += 1;
+            continue;
+        }
+        locked.add(title, item.body, item.tags, &item.folder);
+        summary.imported += 1;
+    }
+    locked.save(&app_handle)?;
+
+    #[cfg(debug_assertions)]
+    println!("导入片段：{:?}", summary);
+
+    Ok(summary)
+}
+
+/// 在表头中查找列（忽略大小写与首尾空白），返回第一个匹配的列号
+fn find_column(header: &[String], names: &[&str]) -> Option<usize> {
+    header.iter().position(|column| {
+        let column = column.trim();
+        names.iter().any(|name| column.eq_ignore_ascii_case(name))
+    })
+}
+
+/// 从 KeePass（1.x/2.x）或 Bitwarden 导出的 CSV 导入登录凭据：
+/// 每条记录生成 "标题 - 用户名" 普通片段与 "标题 - 密码" 敏感片段，
+/// 放入原分组/文件夹（没有时为 "凭据"）下，已存在同名片段的跳过
+#[tauri::command]
+pub fn import_credentials_csv(
+    path: String,
+    app_handle: tauri::AppHandle,
+) -> Result<ImportSummary, String> {
+    let content =
+        fs::read_to_string(&path).map_err(|e| i18n::tf("error.read_file", &[("error", &e)]))?;
+    let mut rows = parse_csv(&content).into_iter();
+    let header = rows.next().ok_or(i18n::t("error.csv_empty"))?;
+
+    let title_col = find_column(&header, &["Title", "Account", "name"]);
+    let user_col = find_column(
+        &header,
+        &["User Name", "Login Name", "Username", "login_username"],
+    );
+    let password_col = find_column(&header, &["Password", "login_password"])
+        .ok_or(i18n::t("error.csv_no_password"))?;
+    let folder_col = find_column(&header, &["Group", "folder"]);
+    // Bitwarden 的导出中还有安全笔记、银行卡等类型，只导入登录项
+    let type_col = find_column(&header, &["type"]);
+
+    let state = app_handle.state::<Mutex<SnippetStore>>();
+    let mut locked = state.lock().unwrap();
+    let mut summary = ImportSummary {
+        imported: 0,
+        skipped: 0,
+    };
+    let column = |row: &[String], col: Option<usize>| {
+        col.and_then(|col| row.get(col))
+            .map(|value| value.trim().to_string())
+            .unwrap_or_default()
+    };
+
+    for (i, row) in rows.enumerate() {
+        let kind = column(&row, type_col);
+        if !kind.is_empty() && !kind.eq_ignore_ascii_case("login") {
+            continue;
+        }
+        let mut title = column(&row, title_col);
+        if title.is_empty() {
+            title = i18n::tf("import.credential_title", &[("n", &(i + 1))]);
+        }
+        let mut folder = column(&row, folder_col);
+        if folder.is_empty() {
+            folder = i18n::t("import.credential_folder").to_string();
+        }
+        let tags = vec!["credential".to_string()];
+        // 密码里的空白也是密码的一部分，不做 trim
+        let password = row.get(password_col).cloned().unwrap_or_default();
+        let username = column(&row, user_col);
+
+        if !username.is_empty() {
+            let name = i18n::tf("import.credential_username", &[("title", &title)]);
+            if locked.snippets().iter().any(|s| s.title == name) {
+                summary.skipped += 1;
+            } else {
+                locked.add(name, username, tags.clone(), &folder);
+                summary.imported += 1;
+            }
+        }
+        if !password.is_empty() {
+            let name = i18n::tf("import.credential_password", &[("title", &title)]);
+            if locked.snippets().iter().any(|s| s.title == name) {
+                summary.skipped += 1;
+            } else {
+                locked.add_secret(name, &password, SnippetKind::Text, tags, &folder)?;
+                summary.imported += 1;
+            }
+        }
+    }
+    locked.save(&app_handle)?;
+
+    #[cfg(debug_assertions)]
+    println!("导入凭据：{:?}", summary);
+
+    Ok(summary)
+}
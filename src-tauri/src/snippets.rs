@@ -0,0 +1,871 @@
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Mutex;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+use crate::commands::{self, HotkeyConfig, PasteAction, PasteOptions};
+use crate::history;
+use crate::hotkeys::{self, HotkeyAction, HotkeyBindings};
+use crate::i18n;
+use crate::keepassxc;
+use crate::macros::{self, MacroStep};
+use crate::secret;
+use crate::templates;
+use crate::totp;
+use crate::transform::{self, Transform};
+use crate::tray;
+use crate::vault;
+
+pub const SNIPPETS_FILE: &str = "snippets.json";
+
+/// 片段类型
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SnippetKind {
+    /// 普通文本
+    #[default]
+    Text,
+    /// TOTP 两步验证：secret 中保存密钥，输入时生成当前的 6 位验证码
+    Totp,
+    /// 宏：按 steps 依次输入其他片段、文本、按键与等待，作为一次输入任务执行
+    Macro,
+    /// 片段组：每次输入时按 rotation 从 members 中选出一个片段输入
+    Group,
+    /// KeePassXC 凭据：body 为条目的标题（或 "分组/标题" 路径），输入时从 KeePassXC
+    /// 读取用户名与密码，依次输入用户名、Tab、密码；密码不在 Paster 中保存
+    Keepassxc,
+}
+
+impl SnippetKind {
+    const ALL: [SnippetKind; 5] = [
+        SnippetKind::Text,
+        SnippetKind::Totp,
+        SnippetKind::Macro,
+        SnippetKind::Group,
+        SnippetKind::Keepassxc,
+    ];
+
+    /// 当前平台是否支持这种片段（KeePassXC 片段只能通过 Secret Service 读取，仅 Linux 支持）
+    pub fn is_supported(self) -> bool {
+        self != SnippetKind::Keepassxc || keepassxc::SUPPORTED
+    }
+}
+
+/// 片段组的选取方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupRotation {
+    /// 依次轮流
+    #[default]
+    RoundRobin,
+    /// 随机
+    Random,
+}
+
+/// 一条常用文本片段
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snippet {
+    pub id: u64,
+    pub title: String,
+    pub body: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// 所在文件夹，以 "/" 分隔的层级路径（如 "工作/邮件"），空字符串表示根目录
+    #[serde(default)]
+    pub folder: String,
+    /// 敏感片段（如密码）：正文加密保存在 secret 中，body 为空，不展开模板变量、不记入历史
+    #[serde(default)]
+    pub sensitive: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    #[serde(default)]
+    pub kind: SnippetKind,
+    /// 输入该片段时使用的速度，覆盖全局设置
+    #[serde(default)]
+    pub timing: Option<SnippetTiming>,
+    /// 输入前对正文依次执行的变换（展开模板变量之后）
+    #[serde(default)]
+    pub transforms: Vec<Transform>,
+    /// 宏片段的步骤
+    #[serde(default)]
+    pub steps: Vec<MacroStep>,
+    /// 片段组的成员片段
+    #[serde(default)]
+    pub members: Vec<u64>,
+    #[serde(default)]
+    pub rotation: GroupRotation,
+    /// KeePassXC 片段：输入密码后按下 Enter
+    #[serde(default)]
+    pub submit: bool,
+    /// 轮流选取时下一个成员的位置
+    #[serde(default)]
+    pub next_member: usize,
+    /// 累计输入次数
+    #[serde(default)]
+    pub use_count: u64,
+    /// 最近一次输入的时间（Unix 毫秒时间戳），从未输入过为 None
+    #[serde(default)]
+    pub last_used_at: Option<u64>,
+    /// 直接输入该片段的全局快捷键（如 "Control+Alt+1"）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hotkey: Option<String>,
+}
+
+/// 片段自己的输入速度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnippetTiming {
+    pub stand: u32,
+    pub float: u32,
+    /// 为 true 时以极速模式输入，否则无论全局是否开启极速模式都按 stand/float 输入
+    #[serde(default)]
+    pub instant: bool,
+}
+
+impl Snippet {
+    /// 输入该片段时使用的 (stand, float) 以及对极速模式的覆盖
+    pub fn paste_timing(&self, stand: u32, float: u32) -> (u32, u32, Option<bool>) {
+        match self.timing {
+            Some(timing) => (timing.stand, timing.float, Some(timing.instant)),
+            None => (stand, float, None),
+        }
+    }
+
+    /// 输入后不记入历史：敏感片段、宏（可能包含敏感片段）与 KeePassXC 片段
+    pub fn skips_history(&self) -> bool {
+        self.sensitive || matches!(self.kind, SnippetKind::Macro | SnippetKind::Keepassxc)
+    }
+
+    /// 返回给前端的副本：不包含敏感片段的密文
+    fn public(&self) -> Self {
+        Self {
+            secret: None,
+            ..self.clone()
+        }
+    }
+}
+
+/// 片段库，保存在 app_config_dir/snippets.json（开启静态加密时为 snippets.vault）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnippetStore {
+    snippets: Vec<Snippet>,
+    next_id: u64,
+}
+
+impl SnippetStore {
+    pub fn new() -> Self {
+        Self {
+            snippets: Vec::new(),
+            next_id: 1,
+        }
+    }
+
+    /// 启动时读取片段库
+    pub fn load(app_handle: &tauri::AppHandle) -> Self {
+        let mut store: Self = vault::read_json(app_handle, SNIPPETS_FILE).unwrap_or_else(Self::new);
+        // 防止手动编辑文件后 next_id 与已有 id 冲突
+        let max_id = store.snippets.iter().map(|s| s.id).max().unwrap_or(0);
+        store.next_id = store.next_id.max(max_id + 1);
+        store
+    }
+
+    pub fn save(&self, app_handle: &tauri::AppHandle) -> Result<(), String> {
+        vault::write_json(app_handle, SNIPPETS_FILE, self)?;
+        tray::refresh(app_handle);
+        Ok(())
+    }
+
+    pub fn get(&self, id: u64) -> Option<&Snippet> {
+        self.snippets.iter().find(|s| s.id == id)
+    }
+
+    pub fn snippets(&self) -> &[Snippet] {
+        &self.snippets
+    }
+
+    /// 按编号或标题（不区分大小写）查找片段，标题相同时取编号最小的
+    pub fn find(&self, name: &str) -> Option<&Snippet> {
+        if let Ok(id) = name.parse::<u64>() {
+            if let Some(snippet) = self.get(id) {
+                return Some(snippet);
+            }
+        }
+        let name = name.to_lowercase();
+        self.snippets
+            .iter()
+            .filter(|s| s.title.to_lowercase() == name)
+            .min_by_key(|s| s.id)
+    }
+
+    /// 输入次数最多的 n 个片段（从未输入过的不计），次数相同时最近输入的在前
+    pub fn most_used(&self, n: usize) -> Vec<&Snippet> {
+        let mut used: Vec<&Snippet> = self.snippets.iter().filter(|s| s.use_count > 0).collect();
+        used.sort_by(|a, b| {
+            b.use_count
+                .cmp(&a.use_count)
+                .then(b.last_used_at.cmp(&a.last_used_at))
+        });
+        used.truncate(n);
+        used
+    }
+
+    /// 各片段的快捷键，与设置中的快捷键一起注册
+    pub fn hotkey_bindings(&self) -> HotkeyBindings {
+        self.snippets
+            .iter()
+            .filter_map(|snippet| {
+                let hotkey = snippet.hotkey.as_deref()?;
+                Some((HotkeyAction::Snippet(snippet.id), hotkey_config(hotkey)))
+            })
+            .collect()
+    }
+
+    /// 删除全部片段（编号继续递增，不会复用）
+    pub fn clear(&mut self) {
+        self.snippets.clear();
+    }
+
+    /// 新增一个片段并分配编号
+    pub fn add(&mut self, title: String, body: String, tags: Vec<String>, folder: &str) -> Snippet {
+        self.insert(Snippet {
+            id: 0,
+            title,
+            body,
+            tags,
+            folder: normalize_folder(folder),
+            sensitive: false,
+            secret: None,
+            kind: SnippetKind::Text,
+            timing: None,
+            transforms: Vec::new(),
+            steps: Vec::new(),
+            members: Vec::new(),
+            rotation: GroupRotation::RoundRobin,
+            submit: false,
+            next_member: 0,
+            use_count: 0,
+            last_used_at: None,
+            hotkey: None,
+        })
+    }
+
+    /// 新增一个敏感片段（密码或 TOTP 密钥），内容加密保存
+    pub fn add_secret(
+        &mut self,
+        title: String,
+        plain: &str,
+        kind: SnippetKind,
+        tags: Vec<String>,
+        folder: &str,
+    ) -> Result<Snippet, String> {
+        let secret = secret::encrypt_string(&secret_content(kind, plain)?)?;
+        Ok(self.insert(Snippet {
+            id: 0,
+            title,
+            body: String::new(),
+            tags,
+            folder: normalize_folder(folder),
+            sensitive: true,
+            secret: Some(secret),
+            kind,
+            timing: None,
+            transforms: Vec::new(),
+            steps: Vec::new(),
+            members: Vec::new(),
+            rotation: GroupRotation::RoundRobin,
+            submit: false,
+            next_member: 0,
+            use_count: 0,
+            last_used_at: None,
+            hotkey: None,
+        }))
+    }
+
+    pub fn get_mut(&mut self, id: u64) -> Option<&mut Snippet> {
+        self.snippets.iter_mut().find(|s| s.id == id)
+    }
+
+    /// 加入一个片段，忽略其原有编号并重新分配
+    pub fn insert(&mut self, mut snippet: Snippet) -> Snippet {
+        snippet.id = self.next_id;
+        self.next_id += 1;
+        self.snippets.push(snippet.clone());
+        snippet.public()
+    }
+}
+
+/// 列出全部片段（含输入次数与最近输入时间）
+#[tauri::command]
+pub fn list_snippets(app_handle: tauri::AppHandle) -> Vec<Snippet> {
+    let state = app_handle.state::<Mutex<SnippetStore>>();
+    let locked = state.lock().unwrap();
+    locked.snippets.iter().map(Snippet::public).collect()
+}
+
+/// 输入次数最多的 n 个片段
+#[tauri::command]
+pub fn most_used_snippets(n: usize, app_handle: tauri::AppHandle) -> Vec<Snippet> {
+    let state = app_handle.state::<Mutex<SnippetStore>>();
+    let locked = state.lock().unwrap();
+    locked
+        .most_used(n)
+        .into_iter()
+        .map(Snippet::public)
+        .collect()
+}
+
+/// 敏感片段实际加密保存的内容：TOTP 片段为规范化后的密钥
+fn secret_content(kind: SnippetKind, body: &str) -> Result<String, String> {
+    match kind {
+        SnippetKind::Text | SnippetKind::Macro | SnippetKind::Group | SnippetKind::Keepassxc => {
+            Ok(body.to_string())
+        }
+        SnippetKind::Totp => Ok(totp::normalize_secret(body)?),
+    }
+}
+
+/// save_snippet 的参数
+#[derive(Debug, Clone, Deserialize)]
+pub struct SnippetInput {
+    /// 为 None 时新建片段
+    #[serde(default)]
+    pub id: Option<u64>,
+    pub title: String,
+    /// 更新敏感片段时为空表示保留原有内容；TOTP 片段为 base32 密钥或 otpauth:// 链接
+    #[serde(default)]
+    pub body: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub folder: String,
+    /// 正文是否加密保存（TOTP 片段总是加密）
+    #[serde(default)]
+    pub sensitive: bool,
+    #[serde(default)]
+    pub kind: SnippetKind,
+    #[serde(default)]
+    pub timing: Option<SnippetTiming>,
+    #[serde(default)]
+    pub transforms: Vec<Transform>,
+    #[serde(default)]
+    pub steps: Vec<MacroStep>,
+    #[serde(default)]
+    pub members: Vec<u64>,
+    #[serde(default)]
+    pub rotation: GroupRotation,
+    #[serde(default)]
+    pub submit: bool,
+    /// 为 None 或空字符串时不设快捷键
+    #[serde(default)]
+    pub hotkey: Option<String>,
+}
+
+/// 片段快捷键对应的快捷键配置
+fn hotkey_config(hotkey: &str) -> HotkeyConfig {
+    HotkeyConfig {
+        accelerator: Some(hotkey.to_string()),
+        ..HotkeyConfig::default()
+    }
+}
+
+/// 新建或更新片段，返回保存后的片段
+#[tauri::command]
+pub fn save_snippet(
+    snippet: SnippetInput,
+    app_handle: tauri::AppHandle,
+) -> Result<Snippet, String> {
+    let SnippetInput {
+        id,
+        title,
+        body,
+        tags,
+        folder,
+        sensitive,
+        kind,
+        timing,
+        transforms,
+        steps,
+        members,
+        rotation,
+        submit,
+        hotkey,
+    } = snippet;
+    let title = title.trim().to_string();
+    if title.is_empty() {
+        return Err(i18n::t("error.snippet_title_empty").to_string());
+    }
+    let mut tags: Vec<String> = tags
+        .iter()
+        .map(|tag| tag.trim().to_string())
+        .filter(|tag| !tag.is_empty())
+        .collect();
+    let mut seen = BTreeSet::new();
+    tags.retain(|tag| seen.insert(tag.clone()));
+    let folder = normalize_folder(&folder);
+    let sensitive = kind == SnippetKind::Totp || sensitive;
+    if kind == SnippetKind::Macro && sensitive {
+        return Err(i18n::t("error.macro_sensitive").to_string());
+    }
+    if kind == SnippetKind::Group && sensitive {
+        return Err(i18n::t("error.group_sensitive").to_string());
+    }
+    if !kind.is_supported() {
+        return Err(i18n::t("error.keepassxc_unsupported").to_string());
+    }
+    if kind == SnippetKind::Keepassxc {
+        if sensitive {
+            return Err(i18n::t("error.keepassxc_sensitive").to_string());
+        }
+        if body.trim().is_empty() {
+            return Err(i18n::t("error.keepassxc_no_entry").to_string());
+        }
+    }
+    let submit = kind == SnippetKind::Keepassxc && submit;
+    let hotkey = hotkey
+        .map(|hotkey| hotkey.trim().to_string())
+        .filter(|hotkey| !hotkey.is_empty());
+    let active_bindings = hotkeys::active_bindings(&app_handle);
+
+    let state = app_handle.state::<Mutex<SnippetStore>>();
+    let mut locked = state.lock().unwrap();
+    // 快捷键不能与设置中或其他片段的快捷键重复
+    if let Some(hotkey) = &hotkey {
+        let action = HotkeyAction::Snippet(id.unwrap_or(locked.next_id));
+        let mut bindings = active_bindings;
+        bindings.extend(locked.hotkey_bindings());
+        bindings.insert(action, hotkey_config(hotkey));
+        hotkeys::validate_bindings(&bindings)?;
+    }
+    let steps = if kind == SnippetKind::Macro {
+        macros::validate(&steps, &locked)?;
+        steps
+    } else {
+        Vec::new()
+    };
+    let members = if kind == SnippetKind::Group {
+        validate_members(&members, &locked)?;
+        members
+    } else {
+        Vec::new()
+    };
+    let snippet = match id {
+        Some(id) => {
+            let existing = locked
+                .snippets
+                .iter_mut()
+                .find(|s| s.id == id)
+                .ok_or_else(|| i18n::tf("error.snippet_id_missing", &[("id", &id)]))?;
+            let keep_secret = body.is_empty() && existing.secret.is_some() && existing.kind == kind;
+            if !sensitive {
+                existing.body = body;
+                existing.secret = None;
+            } else if !keep_secret {
+                existing.body = String::new();
+                existing.secret = Some(secret::encrypt_string(&secret_content(kind, &body)?)?);
+            }
+            existing.kind = kind;
+            existing.title = title;
+            existing.tags = tags;
+            existing.folder = folder;
+            existing.sensitive = sensitive;
+            existing.timing = timing;
+            existing.transforms = transforms;
+            existing.steps = steps;
+            if existing.members != members {
+                existing.next_member = 0;
+            }
+            existing.members = members;
+            existing.rotation = rotation;
+            existing.submit = submit;
+            existing.hotkey = hotkey;
+            existing.public()
+        }
+        None => {
+            if sensitive {
+                locked.add_secret(title, &body, kind, tags, &folder)?;
+            } else {
+                locked.add(title, body, tags, &folder);
+            }
+            let added = locked.snippets.last_mut().expect("刚添加的片段");
+            added.kind = kind;
+            added.timing = timing;
+            added.transforms = transforms;
+            added.steps = steps;
+            added.members = members;
+            added.rotation = rotation;
+            added.submit = submit;
+            added.hotkey = hotkey;
+            added.public()
+        }
+    };
+    locked.save(&app_handle)?;
+    drop(locked);
+    hotkeys::refresh_snippet_hotkeys(&app_handle)?;
+    Ok(snippet)
+}
+
+/// 规范化文件夹路径：去掉多余的 "/" 与首尾空白
+fn normalize_folder(folder: &str) -> String {
+    folder
+        .split('/')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// 片段是否位于 folder 或其子文件夹中
+fn in_folder(snippet: &Snippet, folder: &str) -> bool {
+    folder.is_empty()
+        || snippet.folder == folder
+        || snippet
+            .folder
+            .strip_prefix(folder)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// 列出当前平台支持的片段类型，前端只提供这些类型供新建
+#[tauri::command]
+pub fn list_snippet_kinds() -> Vec<SnippetKind> {
+    SnippetKind::ALL
+        .into_iter()
+        .filter(|kind| kind.is_supported())
+        .collect()
+}
+
+/// 列出所有文件夹（包括只含子文件夹的上级文件夹），按路径排序
+#[tauri::command]
+pub fn list_snippet_folders(app_handle: tauri::AppHandle) -> Vec<String> {
+    let state = app_handle.state::<Mutex<SnippetStore>>();
+    let locked = state.lock().unwrap();
+    let mut folders = BTreeSet::new();
+    for snippet in locked.snippets.iter().filter(|s| !s.folder.is_empty()) {
+        let mut path = String::new();
+        for part in snippet.folder.split('/') {
+            if !path.is_empty() {
+                path.push('/');
+            }
+            path.push_str(part);
+            folders.insert(path.clone());
+        }
+    }
+    folders.into_iter().collect()
+}
+
+/// 列出所有用过的标签，按名称排序
+#[tauri::command]
+pub fn list_snippet_tags(app_handle: tauri::AppHandle) -> Vec<String> {
+    let state = app_handle.state::<Mutex<SnippetStore>>();
+    let locked = state.lock().unwrap();
+    let tags: BTreeSet<&String> = locked.snippets.iter().flat_map(|s| &s.tags).collect();
+    tags.into_iter().cloned().collect()
+}
+
+/// 搜索结果：片段及其匹配得分
+#[derive(Debug, Clone, Serialize)]
+pub struct SnippetMatch {
+    #[serde(flatten)]
+    pub snippet: Snippet,
+    pub score: i64,
+}
+
+/// 模糊匹配：pattern 的字符须按顺序出现在 text 中（忽略大小写）。
+/// 连续匹配、单词开头匹配得分更高，整段包含 pattern 时额外加分；不匹配返回 None
+fn fuzzy_score(pattern: &str, text: &str) -> Option<i64> {
+    let text_lower = text.to_lowercase();
+    let chars: Vec<char> = text_lower.chars().collect();
+    let mut score = 0i64;
+    let mut pos = 0usize;
+    let mut last_match: Option<usize> = None;
+
+    for p in pattern.chars() {
+        let offset = chars[pos..].iter().position(|&c| c == p)?;
+        let index = pos + offset;
+        score += 1;
+        if last_match.is_some_and(|last| last + 1 == index) {
+            score += 5;
+        }
+        if index == 0 || !chars[index - 1].is_alphanumeric() {
+            score += 8;
+        }
+        // 间隔越大扣分越多，但不超过 3 分
+        score -= (offset as i64).min(3);
+        last_match = Some(index);
+        pos = index + 1;
+    }
+    if text_lower.contains(pattern) {
+        score += 20;
+    }
+    Some(score)
+}
+
+/// 单个搜索词在片段中的最高得分：标题权重最高，其次是标签、文件夹，最后是正文
+fn term_score(term: &str, snippet: &Snippet) -> Option<i64> {
+    let title = fuzzy_score(term, &snippet.title).map(|s| s * 3);
+    let tags = snippet
+        .tags
+        .iter()
+        .filter_map(|tag| fuzzy_score(term, tag))
+        .max()
+        .map(|s| s * 2);
+    let folder = fuzzy_score(term, &snippet.folder).map(|s| s * 2);
+    let body = fuzzy_score(term, &snippet.body);
+    [title, tags, folder, body].into_iter().flatten().max()
+}
+
+/// 模糊搜索片段，按得分从高到低返回（最多 limit 条，默认 50）。
+/// query 中以空白分隔的每个词都必须匹配；可以按文件夹（含子文件夹）与标签筛选。
+#[tauri::command]
+pub fn search_snippets(
+    query: String,
+    folder: Option<String>,
+    tags: Option<Vec<String>>,
+    limit: Option<usize>,
+    app_handle: tauri::AppHandle,
+) -> Vec<SnippetMatch> {
+    let query = query.to_lowercase();
+    let terms: Vec<&str> = query.split_whitespace().collect();
+    let folder = normalize_folder(folder.as_deref().unwrap_or(""));
+    let tags = tags.unwrap_or_default();
+
+    let state = app_handle.state::<Mutex<SnippetStore>>();
+    let locked = state.lock().unwrap();
+    let mut matches: Vec<SnippetMatch> = locked
+        .snippets
+        .iter()
+        .filter(|s| in_folder(s, &folder))
+        .filter(|s| tags.iter().all(|tag| s.tags.contains(tag)))
+        .filter_map(|s| {
+            let score = terms
+                .iter()
+                .map(|term| term_score(term, s))
+                .sum::<Option<i64>>()?;
+            Some(SnippetMatch {
+                snippet: s.public(),
+                score,
+            })
+        })
+        .collect();
+
+    matches.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| a.snippet.title.cmp(&b.snippet.title))
+    });
+    matches.truncate(limit.unwrap_or(50));
+    matches
+}
+
+/// 删除片段，返回是否找到该片段
+#[tauri::command]
+pub fn delete_snippet(id: u64, app_handle: tauri::AppHandle) -> Result<bool, String> {
+    let state = app_handle.state::<Mutex<SnippetStore>>();
+    let mut locked = state.lock().unwrap();
+    let before = locked.snippets.len();
+    locked.snippets.retain(|s| s.id != id);
+    if locked.snippets.len() == before {
+        return Ok(false);
+    }
+    locked.save(&app_handle)?;
+    drop(locked);
+    hotkeys::refresh_snippet_hotkeys(&app_handle)?;
+    Ok(true)
+}
+
+/// 保存片段组前检查成员：不能为空，成员必须存在且不能是片段组
+fn validate_members(members: &[u64], store: &SnippetStore) -> Result<(), String> {
+    if members.is_empty() {
+        return Err(i18n::t("error.group_empty").to_string());
+    }
+    for id in members {
+        match store.get(*id) {
+            None => return Err(i18n::tf("error.group_member_missing", &[("id", id)])),
+            Some(member) if member.kind == SnippetKind::Group => {
+                return Err(i18n::t("error.group_nested").to_string())
+            }
+            Some(_) => {}
+        }
+    }
+    Ok(())
+}
+
+/// 片段组按选取方式选出本次输入的成员（轮流时推进并保存位置）；其他片段原样返回
+pub fn resolve_group(app_handle: &tauri::AppHandle, snippet: Snippet) -> Result<Snippet, String> {
+    if snippet.kind != SnippetKind::Group {
+        return Ok(snippet);
+    }
+    let state = app_handle.state::<Mutex<SnippetStore>>();
+    let mut locked = state.lock().unwrap();
+    // 跳过已被删除的成员
+    let members: Vec<u64> = snippet
+        .members
+        .iter()
+        .copied()
+        .filter(|id| locked.get(*id).is_some())
+        .collect();
+    if members.is_empty() {
+        return Err(i18n::tf(
+            "error.group_no_members",
+            &[("name", &snippet.title)],
+        ));
+    }
+    let index = match snippet.rotation {
+        GroupRotation::Random => rand::thread_rng().gen_range(0..members.len()),
+        GroupRotation::RoundRobin => {
+            let index = snippet.next_member % members.len();
+            if let Some(group) = locked.snippets.iter_mut().find(|s| s.id == snippet.id) {
+                group.next_member = index + 1;
+            }
+            if let Err(_e) = locked.save(app_handle) {
+                #[cfg(debug_assertions)]
+                eprintln!("保存片段组位置失败: {}", _e);
+            }
+            index
+        }
+    };
+    let member = locked
+        .get(members[index])
+        .cloned()
+        .expect("成员已在上面检查过");
+
+    #[cfg(debug_assertions)]
+    println!("片段组 #{} 选中成员 #{}", snippet.id, member.id);
+
+    Ok(member)
+}
+
+/// 片段的一次输入任务：宏展开为文本与动作，其余片段只有文本
+pub async fn snippet_job(
+    app_handle: &tauri::AppHandle,
+    snippet: &Snippet,
+) -> Result<(Vec<u16>, Vec<PasteAction>), String> {
+    match snippet.kind {
+        SnippetKind::Macro => {
+            vault::touch(app_handle);
+            return macros::compile(app_handle, &snippet.steps).await;
+        }
+        SnippetKind::Keepassxc => {
+            vault::touch(app_handle);
+            return keepassxc::compile(&snippet.body, snippet.submit).await;
+        }
+        _ => {}
+    }
+    Ok((snippet_units(app_handle, snippet).await?, Vec::new()))
+}
+
+/// 记录片段被输入了一次
+pub fn record_use(app_handle: &tauri::AppHandle, id: u64) {
+    let state = app_handle.state::<Mutex<SnippetStore>>();
+    let mut locked = state.lock().unwrap();
+    let Some(snippet) = locked.snippets.iter_mut().find(|s| s.id == id) else {
+        return;
+    };
+    snippet.use_count += 1;
+    snippet.last_used_at = Some(history::now_ms());
+    if let Err(_e) = locked.save(app_handle) {
+        #[cfg(debug_assertions)]
+        eprintln!("保存片段使用记录失败: {}", _e);
+    }
+}
+
+/// 展开片段正文中的模板变量，并转换为待发送的 UTF-16 单元（与读取剪贴板时一样舍弃 '\r'）。
+/// 含有 `{?标签}` 占位符时先请前端填写，展开后再执行片段的变换；
+/// 敏感片段解密后原样输入，TOTP 片段输入当前验证码。
+pub async fn snippet_units(
+    app_handle: &tauri::AppHandle,
+    snippet: &Snippet,
+) -> Result<Vec<u16>, String> {
+    vault::touch(app_handle);
+    if let Some(cipher) = snippet.secret.as_deref() {
+        let mut plain = secret::decrypt_string(cipher)?;
+        if snippet.kind == SnippetKind::Totp {
+            plain = totp::current_code(&plain).await?;
+        }
+        return Ok(plain.encode_utf16().filter(|&unit| unit != 13).collect());
+    }
+
+    let fields = templates::fill_in_fields(&snippet.body);
+    let values = if fields.is_empty() {
+        HashMap::new()
+    } else {
+        templates::request_values(app_handle, &snippet.title, fields).await?
+    };
+    let text = templates::expand_with_values(&snippet.body, &values)?;
+    let text = transform::apply(&text, &snippet.transforms);
+    Ok(text.encode_utf16().filter(|&unit| unit != 13).collect())
+}
+
+/// 按编号或标题查找片段（链接、脚本等外部调用），片段库锁定或片段不存在时返回错误
+pub fn lookup(app_handle: &tauri::AppHandle, name: &str) -> Result<Snippet, String> {
+    if vault::is_locked(app_handle) {
+        return Err(i18n::t("error.vault_locked").to_string());
+    }
+    let state = app_handle.state::<Mutex<SnippetStore>>();
+    let locked = state.lock().unwrap();
+    locked
+        .find(name)
+        .cloned()
+        .ok_or_else(|| i18n::tf("error.snippet_missing", &[("name", &name)]))
+}
+
+/// 按编号或标题输入片段（脚本、MQTT 等外部调用），不等待输入结束
+pub fn start_snippet(app_handle: &tauri::AppHandle, name: &str) -> Result<(), String> {
+    let snippet = lookup(app_handle, name)?;
+    commands::check_idle(app_handle)?;
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(_e) = type_snippet(snippet.id, None, None, app_handle).await {
+            #[cfg(debug_assertions)]
+            println!("输入外部请求的片段失败: {}", _e);
+        }
+    });
+    Ok(())
+}
+
+/// 直接输入片段内容，不经过剪贴板
+#[tauri::command]
+pub async fn type_snippet(
+    id: u64,
+    stand: Option<u32>,
+    float: Option<u32>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    let (stand, float) = commands::resolve_timing(&app_handle, stand, float);
+    if vault::is_locked(&app_handle) {
+        return Err(i18n::t("error.vault_locked").to_string());
+    }
+    let snippet = {
+        let state = app_handle.state::<Mutex<SnippetStore>>();
+        let locked = state.lock().unwrap();
+        locked
+            .get(id)
+            .cloned()
+            .ok_or_else(|| i18n::tf("error.snippet_id_missing", &[("id", &id)]))?
+    };
+    let snippet = resolve_group(&app_handle, snippet)?;
+    let (units, actions) = snippet_job(&app_handle, &snippet).await?;
+    record_use(&app_handle, id);
+    if snippet.id != id {
+        record_use(&app_handle, snippet.id);
+    }
+
+    #[cfg(debug_assertions)]
+    println!(
+        "type_snippet函数被调用：id={}, stand={}, float={}",
+        id, stand, float
+    );
+
+    let (stand, float, instant) = snippet.paste_timing(stand, float);
+    let options = PasteOptions {
+        // 宏中可能包含敏感片段，且文本中含有动作标记，不记入历史
+        skip_history: snippet.skips_history(),
+        instant,
+        actions,
+        fixed_timing: snippet.timing.is_some(),
+        ..PasteOptions::default()
+    };
+    commands::paste_with(&app_handle, Some(units), stand, float, options)
+        .await
+        .map_err(String::from)
+}
@@ -0,0 +1,77 @@
+// 任务栏按钮上的粘贴进度，只有 Windows 有；其他系统上不显示
+
+#[cfg(windows)]
+use std::cell::RefCell;
+
+#[cfg(windows)]
+use tauri::Manager;
+#[cfg(windows)]
+use windows::Win32::{
+    Foundation::HWND,
+    System::Com::{CoCreateInstance, CoInitializeEx, CLSCTX_INPROC_SERVER, COINIT_MULTITHREADED},
+    UI::Shell::{ITaskbarList3, TaskbarList, TBPFLAG, TBPF_NOPROGRESS, TBPF_NORMAL, TBPF_PAUSED},
+};
+
+#[cfg(windows)]
+thread_local! {
+    /// 每个线程各自创建的任务栏接口
+    static TASKBAR: RefCell<Option<ITaskbarList3>> = const { RefCell::new(None) };
+}
+
+/// 任务栏按钮上显示的进度；其他系统上不显示
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(not(windows), allow(dead_code))]
+pub enum TaskbarProgress {
+    /// 不显示进度
+    Hidden,
+    /// 正常（绿色）进度：(已完成, 总数)
+    Normal(u64, u64),
+    /// 暂停（黄色）进度
+    Paused(u64, u64),
+}
+
+/// 获取（必要时创建）当前线程的任务栏接口
+#[cfg(windows)]
+fn with_taskbar(f: impl FnOnce(&ITaskbarList3)) {
+    TASKBAR.with(|taskbar| {
+        let mut taskbar = taskbar.borrow_mut();
+        if taskbar.is_none() {
+            *taskbar = unsafe {
+                let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+                CoCreateInstance::<_, ITaskbarList3>(&TaskbarList, None, CLSCTX_INPROC_SERVER)
+                    .and_then(|list| list.HrInit().map(|_| list))
+                    .ok()
+            };
+        }
+        if let Some(taskbar) = taskbar.as_ref() {
+            f(taskbar);
+        }
+    });
+}
+
+/// 在主窗口的任务栏按钮上显示粘贴进度（窗口隐藏到托盘时没有任务栏按钮，调用无效果）
+#[cfg(windows)]
+pub fn set_progress(app_handle: &tauri::AppHandle, progress: TaskbarProgress) {
+    let Some(hwnd) = app_handle
+        .get_window("main")
+        .and_then(|window| window.hwnd().ok())
+        .map(|hwnd| HWND(hwnd.0))
+    else {
+        return;
+    };
+
+    let (flag, value): (TBPFLAG, Option<(u64, u64)>) = match progress {
+        TaskbarProgress::Hidden => (TBPF_NOPROGRESS, None),
+        TaskbarProgress::Normal(done, total) => (TBPF_NORMAL, Some((done, total))),
+        TaskbarProgress::Paused(done, total) => (TBPF_PAUSED, Some((done, total))),
+    };
+    with_taskbar(|taskbar| unsafe {
+        let _ = taskbar.SetProgressState(hwnd, flag);
+        if let Some((done, total)) = value {
+            let _ = taskbar.SetProgressValue(hwnd, done, total.max(1));
+        }
+    });
+}
+
+#[cfg(not(windows))]
+pub fn set_progress(_app_handle: &tauri::AppHandle, _progress: TaskbarProgress) {}
@@ -0,0 +1,464 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Datelike, Local, Months, Weekday};
+use serde::Serialize;
+use tauri::Manager;
+use tokio::sync::oneshot;
+use tokio::time::{timeout, Duration};
+
+use crate::commands;
+use crate::i18n;
+
+/// 等待前端填写交互式占位符的最长时间
+const FILL_IN_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// 展开文本中的模板变量：
+/// - `{date}` / `{date:%Y-%m-%d}`：当前日期，可指定 strftime 格式
+/// - `{time}` / `{time:%H:%M}`：当前时间，可指定 strftime 格式
+/// - `{date+3d}` / `{time-2h:%H:%M}`：加减偏移后的日期时间，可连写（`{date+1M-1d}`），
+///   单位为 y 年、M 月、w 周、d 天、h 小时、m 分钟
+/// - `{date:next-monday}` / `{date+1w:month-end:%m/%d}`：跳到相对日期，支持
+///   next-/last-星期（如 next-friday、last-mon）、tomorrow、yesterday、month-start、month-end
+/// - `{clipboard}`：当前剪贴板文本
+/// - `{env:USERNAME}`：环境变量
+/// - `{uuid}`：随机 UUID (v4)
+///
+/// `{{` 与 `}}` 分别输出字面的 `{` 和 `}`；无法识别的占位符原样保留。
+pub fn expand(text: &str) -> Result<String, &'static str> {
+    expand_with_values(text, &HashMap::new())
+}
+
+/// 同 expand，并用 values 填入交互式占位符 `{?标签}` / `{?标签:默认值}`；
+/// 没有提供值的占位符使用默认值，没有默认值则原样保留
+pub fn expand_with_values(
+    text: &str,
+    values: &HashMap<String, String>,
+) -> Result<String, &'static str> {
+    // 同一段文本中的日期和时间使用同一时刻
+    let now = Local::now();
+    let mut clipboard: Option<String> = None;
+    render(text, |inner| {
+        if let Some(field) = parse_fill_in(inner) {
+            return Ok(values.get(&field.label).cloned().or(field.default));
+        }
+        expand_variable(inner, &now, &mut clipboard)
+    })
+}
+
+/// 扫描文本中的 `{...}`，用 variable 的返回值替换；返回 None 时原样保留
+fn render(
+    text: &str,
+    mut variable: impl FnMut(&str) -> Result<Option<String>, &'static str>,
+) -> Result<String, &'static str> {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(pos) = rest.find(['{', '}']) {
+        result.push_str(&rest[..pos]);
+        let tail = &rest[pos..];
+
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            result.push_str(&tail[..1]);
+            rest = &tail[2..];
+            continue;
+        }
+        // 单独的 '}' 原样输出
+        if let Some(after) = tail.strip_prefix('}') {
+            result.push('}');
+            rest = after;
+            continue;
+        }
+
+        let Some(end) = tail.find('}') else {
+            result.push_str(tail);
+            rest = "";
+            break;
+        };
+        let inner = &tail[1..end];
+        // 形如 "{a {date}" 时前一个 '{' 只是普通字符
+        if inner.contains('{') {
+            result.push('{');
+            rest = &tail[1..];
+            continue;
+        }
+        match variable(inner)? {
+            Some(value) => result.push_str(&value),
+            None => result.push_str(&tail[..=end]),
+        }
+        rest = &tail[end + 1..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// 需要用户填写的交互式占位符
+#[derive(Debug, Clone, Serialize)]
+pub struct FillInField {
+    pub label: String,
+    pub default: Option<String>,
+}
+
+/// 解析 `?标签` 或 `?标签:默认值`
+fn parse_fill_in(inner: &str) -> Option<FillInField> {
+    let field = inner.strip_prefix('?')?;
+    let (label, default) = match field.split_once(':') {
+        Some((label, default)) => (label, Some(default.to_string())),
+        None => (field, None),
+    };
+    let label = label.trim();
+    if label.is_empty() {
+        return None;
+    }
+    Some(FillInField {
+        label: label.to_string(),
+        default,
+    })
+}
+
+/// 按出现顺序列出文本中的交互式占位符（同名只保留第一个）
+pub fn fill_in_fields(text: &str) -> Vec<FillInField> {
+    let mut fields: Vec<FillInField> = Vec::new();
+    let _ = render(text, |inner| {
+        if let Some(field) = parse_fill_in(inner) {
+            if !fields.iter().any(|f| f.label == field.label) {
+                fields.push(field);
+            }
+        }
+        Ok(None)
+    });
+    fields
+}
+
+/// 展开单个变量；不是已知变量时返回 None
+fn expand_variable(
+    inner: &str,
+    now: &DateTime<Local>,
+    clipboard: &mut Option<String>,
+) -> Result<Option<String>, &'static str> {
+    let (name, arg) = match inner.split_once(':') {
+        Some((name, arg)) => (name, Some(arg)),
+        None => (inner, None),
+    };
+
+    if let Some((default_format, offsets)) = date_variable(name) {
+        let mut time = apply_offsets(*now, offsets)?;
+        let mut format = arg.unwrap_or(default_format);
+        if let Some(arg) = arg {
+            let (keyword, rest) = match arg.split_once(':') {
+                Some((keyword, rest)) => (keyword, Some(rest)),
+                None => (arg, None),
+            };
+            if let Some(anchored) = apply_anchor(&time, keyword) {
+                time = anchored?;
+                format = rest.unwrap_or(default_format);
+            }
+        }
+        return format_time(&time, format).map(Some);
+    }
+
+    let value = match (name, arg) {
+        ("clipboard", None) => {
+            if clipboard.is_none() {
+                let units = commands::get_clipboard()?;
+                *clipboard = Some(String::from_utf16_lossy(&units));
+            }
+            clipboard.clone().unwrap_or_default()
+        }
+        ("env", Some(var)) => std::env::var(var).map_err(|_| i18n::t("error.template_env"))?,
+        ("uuid", None) => uuid::Uuid::new_v4().to_string(),
+        _ => return Ok(None),
+    };
+    Ok(Some(value))
+}
+
+/// 日期时间变量（`date`、`time`，可带偏移）：返回默认格式与偏移部分
+fn date_variable(name: &str) -> Option<(&'static str, &str)> {
+    [("date", "%Y-%m-%d"), ("time", "%H:%M:%S")]
+        .into_iter()
+        .find_map(|(base, default_format)| {
+            let offsets = name.strip_prefix(base)?;
+            (offsets.is_empty() || offsets.starts_with(['+', '-']))
+                .then_some((default_format, offsets))
+        })
+}
+
+/// 依次加减 "+3d-1w" 形式的偏移
+fn apply_offsets(
+    mut time: DateTime<Local>,
+    offsets: &str,
+) -> Result<DateTime<Local>, &'static str> {
+    let invalid = i18n::t("error.template_offset");
+    let mut rest = offsets;
+    while !rest.is_empty() {
+        let negative = match rest.as_bytes()[0] {
+            b'+' => false,
+            b'-' => true,
+            _ => return Err(invalid),
+        };
+        rest = &rest[1..];
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let amount: i64 = rest[..digits].parse().map_err(|_| invalid)?;
+        let mut chars = rest[digits..].chars();
+        let unit = chars.next().ok_or(invalid)?;
+        rest = chars.as_str();
+
+        let signed = if negative { -amount } else { amount };
+        time = match unit {
+            'y' | 'M' => {
+                let months = Months::new(
+                    u32::try_from(amount * if unit == 'y' { 12 } else { 1 })
+                        .map_err(|_| invalid)?,
+                );
+                if negative {
+                    time.checked_sub_months(months)
+                } else {
+                    time.checked_add_months(months)
+                }
+                .ok_or(invalid)?
+            }
+            'w' => time + chrono::Duration::weeks(signed),
+            'd' => time + chrono::Duration::days(signed),
+            'h' => time + chrono::Duration::hours(signed),
+            'm' => time + chrono::Duration::minutes(signed),
+            _ => return Err(invalid),
+        };
+    }
+    Ok(time)
+}
+
+/// 解析星期名称（全称或三字母缩写）
+fn parse_weekday(name: &str) -> Option<Weekday> {
+    const WEEKDAYS: [(&str, Weekday); 7] = [
+        ("monday", Weekday::Mon),
+        ("tuesday", Weekday::Tue),
+        ("wednesday", Weekday::Wed),
+        ("thursday", Weekday::Thu),
+        ("friday", Weekday::Fri),
+        ("saturday", Weekday::Sat),
+        ("sunday", Weekday::Sun),
+    ];
+    let name = name.to_ascii_lowercase();
+    WEEKDAYS
+        .iter()
+        .find(|(full, _)| name == *full || (name.len() == 3 && full.starts_with(name.as_str())))
+        .map(|(_, weekday)| *weekday)
+}
+
+/// 相对日期关键字；不是关键字时返回 None（参数按格式处理）
+fn apply_anchor(
+    time: &DateTime<Local>,
+    keyword: &str,
+) -> Option<Result<DateTime<Local>, &'static str>> {
+    let day = |days: i64| Some(Ok(*time + chrono::Duration::days(days)));
+    match keyword {
+        "tomorrow" => return day(1),
+        "yesterday" => return day(-1),
+        "month-start" => return day(1 - time.day() as i64),
+        "month-end" => {
+            let start = *time - chrono::Duration::days(time.day() as i64 - 1);
+            return Some(
+                start
+                    .checked_add_months(Months::new(1))
+                    .map(|next| next - chrono::Duration::days(1))
+                    .ok_or(i18n::t("error.template_offset")),
+            );
+        }
+        _ => {}
+    }
+    let current = time.weekday().num_days_from_monday() as i64;
+    if let Some(weekday) = keyword.strip_prefix("next-").and_then(parse_weekday) {
+        // 严格晚于当天：今天是周一时 next-monday 为 7 天后
+        let ahead = (weekday.num_days_from_monday() as i64 - current).rem_euclid(7);
+        return day(if ahead == 0 { 7 } else { ahead });
+    }
+    if let Some(weekday) = keyword.strip_prefix("last-").and_then(parse_weekday) {
+        let behind = (current - weekday.num_days_from_monday() as i64).rem_euclid(7);
+        return day(-(if behind == 0 { 7 } else { behind }));
+    }
+    None
+}
+
+/// 按 strftime 格式输出时间，格式无效时返回错误而不是 panic
+fn format_time(now: &DateTime<Local>, format: &str) -> Result<String, &'static str> {
+    let items: Vec<Item> = StrftimeItems::new(format).collect();
+    if items.iter().any(|item| matches!(item, Item::Error)) {
+        return Err(i18n::t("error.template_date_format"));
+    }
+    Ok(now.format_with_items(items.into_iter()).to_string())
+}
+
+/// 预览模板变量展开后的文本
+#[tauri::command]
+pub fn preview_template(text: String) -> Result<String, &'static str> {
+    expand(&text)
+}
+
+/// 等待前端填写的占位符请求
+pub struct FillInState {
+    pending: HashMap<u64, oneshot::Sender<Option<HashMap<String, String>>>>,
+    next_id: u64,
+}
+
+impl FillInState {
+    pub fn new() -> Self {
+        Self {
+            pending: HashMap::new(),
+            next_id: 1,
+        }
+    }
+}
+
+/// 发给前端的填写请求
+#[derive(Debug, Clone, Serialize)]
+struct FillInRequest {
+    request_id: u64,
+    title: String,
+    fields: Vec<FillInField>,
+}
+
+/// 通过 "placeholder-values-requested" 事件请前端填写占位符，
+/// 等待 provide_placeholder_values 返回；用户取消或超时时返回错误
+pub async fn request_values(
+    app_handle: &tauri::AppHandle,
+    title: &str,
+    fields: Vec<FillInField>,
+) -> Result<HashMap<String, String>, &'static str> {
+    let (sender, receiver) = oneshot::channel();
+    let request_id = {
+        let state = app_handle.state::<Mutex<FillInState>>();
+        let mut locked = state.lock().unwrap();
+        let request_id = locked.next_id;
+        locked.next_id += 1;
+        locked.pending.insert(request_id, sender);
+        request_id
+    };
+
+    // 窗口隐藏在托盘时先显示出来，填写完成后再隐藏，焦点会回到原来的窗口
+    let window = app_handle.get_window("main");
+    let was_hidden = window
+        .as_ref()
+        .is_some_and(|window| !window.is_visible().unwrap_or(true));
+    if let Some(window) = window.as_ref().filter(|_| was_hidden) {
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+
+    let request = FillInRequest {
+        request_id,
+        title: title.to_string(),
+        fields,
+    };
+    let _ = app_handle.emit_all("placeholder-values-requested", request);
+
+    let result = timeout(FILL_IN_TIMEOUT, receiver).await;
+    if let Some(window) = window.as_ref().filter(|_| was_hidden) {
+        let _ = window.hide();
+    }
+
+    match result {
+        Ok(Ok(Some(values))) => Ok(values),
+        Ok(_) => Err(i18n::t("error.fill_in_cancelled")),
+        Err(_) => {
+            let state = app_handle.state::<Mutex<FillInState>>();
+            state.lock().unwrap().pending.remove(&request_id);
+            let _ = app_handle.emit_all("placeholder-values-cancelled", request_id);
+            Err(i18n::t("error.fill_in_timeout"))
+        }
+    }
+}
+
+/// 前端提交占位符的值；values 为 None 表示取消本次输入
+#[tauri::command]
+pub fn provide_placeholder_values(
+    request_id: u64,
+    values: Option<HashMap<String, String>>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), &'static str> {
+    let state = app_handle.state::<Mutex<FillInState>>();
+    let sender = state
+        .lock()
+        .unwrap()
+        .pending
+        .remove(&request_id)
+        .ok_or(i18n::t("error.fill_in_missing"))?;
+    let _ = sender.send(values);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    /// 以 2024-01-31（周三）09:30 为当前时刻展开单个变量
+    fn expand_at(inner: &str) -> Result<Option<String>, &'static str> {
+        let now = Local.with_ymd_and_hms(2024, 1, 31, 9, 30, 0).unwrap();
+        expand_variable(inner, &now, &mut None)
+    }
+
+    fn expanded(inner: &str) -> String {
+        expand_at(inner).unwrap().unwrap()
+    }
+
+    #[test]
+    fn escapes_and_unknown_placeholders_are_kept() {
+        assert_eq!(expand("{{date}} {nope} } {").unwrap(), "{date} {nope} } {");
+        assert_eq!(expand("{a {uuid:x}").unwrap(), "{a {uuid:x}");
+    }
+
+    #[test]
+    fn fill_in_values_and_defaults() {
+        let values = HashMap::from([("name".to_string(), "Bob".to_string())]);
+        let text = "{?name}, {?greeting:hello} {?missing}";
+        assert_eq!(
+            expand_with_values(text, &values).unwrap(),
+            "Bob, hello {?missing}"
+        );
+
+        let labels: Vec<String> = fill_in_fields("{?b} {?a:1} {?b:2} {? }")
+            .into_iter()
+            .map(|field| field.label)
+            .collect();
+        assert_eq!(labels, ["b", "a"]);
+    }
+
+    #[test]
+    fn date_and_time_variables() {
+        assert_eq!(expanded("date"), "2024-01-31");
+        assert_eq!(expanded("time"), "09:30:00");
+        assert_eq!(expanded("date:%d/%m/%Y"), "31/01/2024");
+        assert_eq!(expanded("time-2h:%H:%M"), "07:30");
+    }
+
+    #[test]
+    fn date_offsets() {
+        assert_eq!(expanded("date+1d"), "2024-02-01");
+        assert_eq!(expanded("date-1w"), "2024-01-24");
+        // 月份相加时超出的日期落在月末
+        assert_eq!(expanded("date+1M"), "2024-02-29");
+        assert_eq!(expanded("date+1y-1d"), "2025-01-30");
+        assert!(expand_at("date+3x").is_err());
+        assert!(expand_at("date+d").is_err());
+    }
+
+    #[test]
+    fn date_anchors() {
+        assert_eq!(expanded("date:tomorrow"), "2024-02-01");
+        assert_eq!(expanded("date:month-start"), "2024-01-01");
+        assert_eq!(expanded("date:next-monday"), "2024-02-05");
+        assert_eq!(expanded("date:next-wed"), "2024-02-07");
+        assert_eq!(expanded("date:last-wednesday"), "2024-01-24");
+        assert_eq!(expanded("date+1d:month-end:%m/%d"), "02/29");
+    }
+
+    #[test]
+    fn unknown_variables_are_not_expanded() {
+        assert_eq!(expand_at("datetime").unwrap(), None);
+        assert_eq!(expand_at("uuid:x").unwrap(), None);
+    }
+}
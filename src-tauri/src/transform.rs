@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+
+/// 输入前对文本做的变换，按列表顺序依次执行
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Transform {
+    /// 去掉所有非空行共同的行首缩进（适合从代码中复制的 SQL 等）
+    Dedent,
+    /// 去掉首尾空白
+    Trim,
+    /// 去掉每行末尾的空白
+    TrimTrailingWhitespace,
+    /// 制表符替换为 width 个空格（编辑器会把 Tab 当作切换焦点或自动补全时有用）
+    TabsToSpaces {
+        width: usize,
+    },
+    /// 合并为一行，换行替换为空格（聊天框中回车会直接发送）
+    SingleLine,
+    Uppercase,
+    Lowercase,
+}
+
+/// 依次执行变换
+pub fn apply(text: &str, transforms: &[Transform]) -> String {
+    transforms.iter().fold(text.to_string(), |text, transform| {
+        apply_one(&text, transform)
+    })
+}
+
+fn apply_one(text: &str, transform: &Transform) -> String {
+    match transform {
+        Transform::Dedent => dedent(text),
+        Transform::Trim => text.trim().to_string(),
+        Transform::TrimTrailingWhitespace => text
+            .split('\n')
+            .map(str::trim_end)
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Transform::TabsToSpaces { width } => text.replace('\t', &" ".repeat(*width)),
+        Transform::SingleLine => text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join(" "),
+        Transform::Uppercase => text.to_uppercase(),
+        Transform::Lowercase => text.to_lowercase(),
+    }
+}
+
+/// 去掉共同的行首缩进；空白行不参与计算
+fn dedent(text: &str) -> String {
+    let indent = |line: &str| line.len() - line.trim_start_matches([' ', '\t']).len();
+    let common = text
+        .split('\n')
+        .filter(|line| !line.trim().is_empty())
+        .map(indent)
+        .min()
+        .unwrap_or(0);
+    text.split('\n')
+        .map(|line| &line[indent(line).min(common)..])
+        .collect::<Vec<_>>()
+        .join("\n")
+}
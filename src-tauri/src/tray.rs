@@ -0,0 +1,587 @@
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+use serde::{Deserialize, Serialize};
+use tauri::{CustomMenuItem, Icon, Manager, SystemTrayMenu, SystemTrayMenuItem, SystemTraySubmenu};
+
+use crate::autostart;
+use crate::commands::{self, PasteOptions, PasteProgress, PasteStage, PasteState, SPEED_PRESETS};
+use crate::config;
+use crate::history::{self, HistoryState};
+use crate::hotkeys::{self, HotkeyAction};
+use crate::i18n;
+use crate::profiles::{self, ProfileState};
+use crate::settings;
+use crate::snippets::{self, Snippet, SnippetStore};
+use crate::vault;
+
+/// "最近使用" 子菜单中最近使用的片段、其余常用片段与历史的条数
+const RECENT_SNIPPETS: usize = 8;
+const FREQUENT_SNIPPETS: usize = 5;
+const RECENT_HISTORY: usize = 5;
+/// 菜单项文字的最大字符数
+const LABEL_CHARS: usize = 30;
+
+/// 托盘图标的原图
+const ICON_PNG: &[u8] = include_bytes!("../icons/32x32.png");
+/// 正在输入、输入暂停时角标的颜色
+const TYPING_BADGE: [u8; 3] = [0x2e, 0xcc, 0x71];
+const PAUSED_BADGE: [u8; 3] = [0xf3, 0x9c, 0x12];
+
+/// Windows 托盘提示最多显示的字符数
+const TOOLTIP_CHARS: usize = 127;
+
+/// 点击托盘图标开始粘贴时至少倒计时的秒数，留出切换回目标窗口的时间
+const CLICK_PASTE_DELAY_SECS: u32 = 3;
+
+/// 当前显示的托盘图标（TrayIcon 的序号），避免每次进度更新都重设图标
+static CURRENT_ICON: AtomicU8 = AtomicU8::new(TrayIcon::Idle as u8);
+/// 当前的托盘提示，避免重复设置
+static CURRENT_TOOLTIP: Mutex<String> = Mutex::new(String::new());
+/// 解码后的原图：(RGBA, 宽, 高)
+static BASE_ICON: OnceLock<Option<(Vec<u8>, u32, u32)>> = OnceLock::new();
+/// 点击托盘图标的动作，随设置读取
+static CLICK_PREFS: Mutex<TrayClickPrefs> = Mutex::new(TrayClickPrefs::new());
+
+/// 点击托盘图标时执行的动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrayClickAction {
+    None,
+    /// 显示/隐藏窗口
+    ToggleWindow,
+    /// 暂停/继续快捷键
+    TogglePause,
+    /// 倒计时后粘贴剪贴板内容
+    Paste,
+    /// 显示窗口并打开片段选择（由前端处理 open-snippet-picker 事件）
+    SnippetPicker,
+}
+
+/// 单击与双击托盘图标的动作。Windows 上双击前总会先收到一次单击
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrayClickPrefs {
+    pub left_click: TrayClickAction,
+    pub double_click: TrayClickAction,
+}
+
+impl TrayClickPrefs {
+    const fn new() -> Self {
+        Self {
+            left_click: TrayClickAction::ToggleWindow,
+            double_click: TrayClickAction::None,
+        }
+    }
+}
+
+impl Default for TrayClickPrefs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 托盘图标，反映输入引擎的状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrayIcon {
+    /// 空闲，快捷键可用
+    Idle,
+    /// 正在输入（绿色角标）
+    Typing,
+    /// 输入暂停中（黄色角标）
+    TypingPaused,
+    /// 已暂停快捷键（灰色图标）
+    Disabled,
+}
+
+impl TrayIcon {
+    /// 按程序状态选择图标
+    pub fn for_state(state: &PasteState) -> Self {
+        let pasting = state.is_pasting.load(Ordering::SeqCst);
+        if pasting && state.typing_paused {
+            TrayIcon::TypingPaused
+        } else if pasting && state.progress.is_some() {
+            TrayIcon::Typing
+        } else if state.is_paused {
+            TrayIcon::Disabled
+        } else {
+            TrayIcon::Idle
+        }
+    }
+}
+
+/// 把 PNG 解码为 RGBA
+fn decode_png(bytes: &[u8]) -> Option<(Vec<u8>, u32, u32)> {
+    let mut decoder = png::Decoder::new(bytes);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().ok()?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).ok()?;
+    let pixels = &buffer[..info.buffer_size()];
+    let rgba = match info.color_type {
+        png::ColorType::Rgba => pixels.to_vec(),
+        png::ColorType::Rgb => pixels
+            .chunks_exact(3)
+            .flat_map(|p| [p[0], p[1], p[2], 255])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => pixels
+            .chunks_exact(2)
+            .flat_map(|p| [p[0], p[0], p[0], p[1]])
+            .collect(),
+        png::ColorType::Grayscale => pixels.iter().flat_map(|&g| [g, g, g, 255]).collect(),
+        // normalize_to_color8 已把调色板展开为 RGB
+        png::ColorType::Indexed => return None,
+    };
+    Some((rgba, info.width, info.height))
+}
+
+/// 在右下角画一个带白边的圆形角标
+fn draw_badge(rgba: &mut [u8], width: u32, height: u32, color: [u8; 3]) {
+    let radius = width.min(height) as f32 * 0.24;
+    let (cx, cy) = (width as f32 - radius - 0.5, height as f32 - radius - 0.5);
+    for y in 0..height {
+        for x in 0..width {
+            let distance = ((x as f32 + 0.5 - cx).powi(2) + (y as f32 + 0.5 - cy).powi(2)).sqrt();
+            let pixel = match distance {
+                d if d <= radius - 1.5 => [color[0], color[1], color[2], 255],
+                d if d <= radius => [255, 255, 255, 255],
+                _ => continue,
+            };
+            let i = ((y * width + x) * 4) as usize;
+            rgba[i..i + 4].copy_from_slice(&pixel);
+        }
+    }
+}
+
+/// 生成某一状态的图标
+fn render_icon(icon: TrayIcon) -> Option<Icon> {
+    let (base, width, height) = BASE_ICON.get_or_init(|| decode_png(ICON_PNG)).as_ref()?;
+    let mut rgba = base.clone();
+    match icon {
+        TrayIcon::Idle => {}
+        TrayIcon::Typing => draw_badge(&mut rgba, *width, *height, TYPING_BADGE),
+        TrayIcon::TypingPaused => draw_badge(&mut rgba, *width, *height, PAUSED_BADGE),
+        TrayIcon::Disabled => {
+            for pixel in rgba.chunks_exact_mut(4) {
+                let gray = (0.299 * pixel[0] as f32
+                    + 0.587 * pixel[1] as f32
+                    + 0.114 * pixel[2] as f32) as u8;
+                pixel[..3].fill(gray);
+                pixel[3] = (pixel[3] as u16 * 3 / 5) as u8;
+            }
+        }
+    }
+    Some(Icon::Rgba {
+        rgba,
+        width: *width,
+        height: *height,
+    })
+}
+
+/// 切换托盘图标；与当前图标相同时不做任何事
+pub fn set_icon(app_handle: &tauri::AppHandle, icon: TrayIcon) {
+    if CURRENT_ICON.swap(icon as u8, Ordering::SeqCst) == icon as u8 {
+        return;
+    }
+    let Some(image) = render_icon(icon) else {
+        return;
+    };
+    if let Err(_e) = app_handle.tray_handle().set_icon(image) {
+        #[cfg(debug_assertions)]
+        eprintln!("更新托盘图标失败: {}", _e);
+    }
+}
+
+/// 当前生效的粘贴快捷键的描述
+fn paste_hotkey(app_handle: &tauri::AppHandle) -> Option<String> {
+    hotkeys::active_bindings(app_handle)
+        .get(&HotkeyAction::Paste)
+        .map(|config| config.get_description())
+}
+
+/// 空闲时的托盘提示：粘贴快捷键与当前选用的配置方案
+fn idle_tooltip(app_handle: &tauri::AppHandle) -> String {
+    let mut lines = vec!["Paster".to_string()];
+    let paste_hotkey = paste_hotkey(app_handle);
+    {
+        let state = app_handle.state::<Mutex<PasteState>>();
+        let locked = state.lock().unwrap();
+        if locked.is_paused {
+            lines.push(i18n::t("tooltip.hotkeys_paused").to_string());
+        } else if let Some(config) = paste_hotkey {
+            lines.push(i18n::tf("tooltip.paste", &[("hotkey", &config)]));
+        }
+        if locked.instant_mode {
+            lines.push(i18n::t("tooltip.instant").to_string());
+        }
+    }
+    {
+        let state = app_handle.state::<Mutex<ProfileState>>();
+        let locked = state.lock().unwrap();
+        if let Some(active) = &locked.settings().active {
+            lines.push(i18n::tf("tooltip.profile", &[("name", active)]));
+        }
+    }
+    lines.join("\n")
+}
+
+/// 输入期间的托盘提示，如 "正在输入 345/1200 字符，剩余 40 秒"
+fn progress_tooltip(progress: &PasteProgress) -> String {
+    let counts: [(&str, &dyn Display); 2] =
+        [("typed", &progress.typed), ("total", &progress.total)];
+    let status = match progress.stage {
+        PasteStage::Paused => i18n::tf("tooltip.typing_paused", &counts),
+        _ => i18n::tf(
+            "tooltip.typing",
+            &[
+                counts[0],
+                counts[1],
+                ("eta", &progress.eta_ms.div_ceil(1000)),
+            ],
+        ),
+    };
+    format!("Paster\n{}", status)
+}
+
+/// 更新托盘提示：progress 为正在进行的粘贴的进度，None 时显示快捷键与配置方案
+pub fn update_tooltip(app_handle: &tauri::AppHandle, progress: Option<&PasteProgress>) {
+    let text = match progress {
+        Some(progress) => progress_tooltip(progress),
+        None => idle_tooltip(app_handle),
+    };
+    let text: String = text.chars().take(TOOLTIP_CHARS).collect();
+    {
+        let mut current = CURRENT_TOOLTIP.lock().unwrap();
+        if *current == text {
+            return;
+        }
+        *current = text.clone();
+    }
+    if let Err(_e) = app_handle.tray_handle().set_tooltip(&text) {
+        #[cfg(debug_assertions)]
+        eprintln!("更新托盘提示失败: {}", _e);
+    }
+}
+
+/// 菜单项文字：合并为一行并截断；"&" 在 Windows 菜单中表示快捷键，需要转义
+fn menu_label(text: &str) -> String {
+    let line = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut label: String = line.chars().take(LABEL_CHARS).collect();
+    if line.chars().count() > LABEL_CHARS {
+        label.push('…');
+    }
+    label.replace('&', "&&")
+}
+
+/// "输入速度" 子菜单：勾选与默认输入速度相同的预设，都不相同时显示当前的自定义速度
+fn speed_submenu(default_timing: (u32, u32), instant: bool) -> SystemTraySubmenu {
+    let mut menu = SystemTrayMenu::new();
+    let mut matched = false;
+    for preset in &SPEED_PRESETS {
+        let name = i18n::lookup(&format!("speed.{}", preset.id)).unwrap_or(preset.name);
+        let mut item = CustomMenuItem::new(format!("speed:{}", preset.id), name);
+        item.selected = !instant && (preset.stand, preset.float) == default_timing;
+        matched |= (preset.stand, preset.float) == default_timing;
+        menu = menu.add_item(item);
+    }
+    if !matched {
+        let mut custom = CustomMenuItem::new(
+            "speed_custom".to_string(),
+            i18n::tf(
+                "tray.speed_custom",
+                &[("stand", &default_timing.0), ("float", &default_timing.1)],
+            ),
+        )
+        .disabled();
+        custom.selected = !instant;
+        menu = menu.add_item(custom);
+    }
+    SystemTraySubmenu::new(i18n::t("tray.speed"), menu)
+}
+
+/// 托盘菜单；recent 为 "最近使用" 子菜单（启动时尚未读取片段库，为 None），
+/// profiles 为 "配置方案" 子菜单（没有配置方案时为 None），speed 为 "输入速度" 子菜单，
+/// paste_hotkey 为粘贴快捷键的描述，显示在最上方且不可点击
+pub fn build_menu(
+    paste_hotkey: Option<String>,
+    paused: bool,
+    instant: bool,
+    autostart: bool,
+    recent: Option<SystemTraySubmenu>,
+    profiles: Option<SystemTraySubmenu>,
+    speed: Option<SystemTraySubmenu>,
+) -> SystemTrayMenu {
+    let quit = CustomMenuItem::new("quit".to_string(), i18n::t("tray.quit"));
+    let show = CustomMenuItem::new("show".to_string(), i18n::t("tray.show"));
+    let pause = CustomMenuItem::new(
+        "pause".to_string(),
+        i18n::t(if paused { "tray.resume" } else { "tray.pause" }),
+    );
+    let mut instant_item = CustomMenuItem::new("instant".to_string(), i18n::t("tray.instant"));
+    instant_item.selected = instant;
+    let mut autostart_item =
+        CustomMenuItem::new("autostart".to_string(), i18n::t("tray.autostart"));
+    autostart_item.selected = autostart;
+    if config::is_portable() {
+        autostart_item = autostart_item.disabled();
+    }
+    let type_file = CustomMenuItem::new("type_file".to_string(), i18n::t("tray.type_file"));
+    let config_dir = CustomMenuItem::new(
+        "open_config_dir".to_string(),
+        i18n::t("tray.open_config_dir"),
+    );
+
+    let mut menu = SystemTrayMenu::new();
+    if let Some(hotkey) = paste_hotkey {
+        let label = i18n::tf("tray.paste_hotkey", &[("hotkey", &hotkey)]);
+        menu = menu
+            .add_item(
+                CustomMenuItem::new("paste_hotkey".to_string(), menu_label(&label)).disabled(),
+            )
+            .add_native_item(SystemTrayMenuItem::Separator);
+    }
+    menu = menu.add_item(show);
+    if let Some(recent) = recent {
+        menu = menu.add_submenu(recent);
+    }
+    if let Some(profiles) = profiles {
+        menu = menu.add_submenu(profiles);
+    }
+    if let Some(speed) = speed {
+        menu = menu.add_submenu(speed);
+    }
+    menu = menu.add_item(type_file);
+    menu.add_item(pause)
+        .add_item(instant_item)
+        .add_item(autostart_item)
+        .add_item(config_dir)
+        .add_native_item(SystemTrayMenuItem::Separator)
+        .add_item(quit)
+}
+
+/// 最近使用的片段、输入次数最多的其他片段与最近的输入历史
+fn recent_submenu(app_handle: &tauri::AppHandle) -> SystemTraySubmenu {
+    let mut menu = SystemTrayMenu::new();
+    if vault::is_locked(app_handle) {
+        menu = menu.add_item(
+            CustomMenuItem::new("recent_locked".to_string(), i18n::t("tray.recent_locked"))
+                .disabled(),
+        );
+        return SystemTraySubmenu::new(i18n::t("tray.recent"), menu);
+    }
+
+    let (snippets, frequent) = {
+        let state = app_handle.state::<Mutex<SnippetStore>>();
+        let locked = state.lock().unwrap();
+        let mut used: Vec<_> = locked
+            .snippets()
+            .iter()
+            .filter(|s| s.last_used_at.is_some())
+            .collect();
+        used.sort_by_key(|s| std::cmp::Reverse(s.last_used_at));
+        let item = |s: &&Snippet| (s.id, s.title.clone());
+        let recent: Vec<(u64, String)> = used.iter().take(RECENT_SNIPPETS).map(item).collect();
+        // 常用片段中不再重复列出最近使用的片段
+        let frequent: Vec<(u64, String)> = locked
+            .most_used(RECENT_SNIPPETS + FREQUENT_SNIPPETS)
+            .iter()
+            .filter(|s| !recent.iter().any(|(id, _)| *id == s.id))
+            .take(FREQUENT_SNIPPETS)
+            .map(item)
+            .collect();
+        (recent, frequent)
+    };
+    let entries: Vec<String> = {
+        let state = app_handle.state::<Mutex<HistoryState>>();
+        let locked = state.lock().unwrap();
+        locked
+            .entries()
+            .take(RECENT_HISTORY)
+            .map(|entry| entry.text.clone())
+            .collect()
+    };
+
+    if snippets.is_empty() && entries.is_empty() {
+        menu = menu.add_item(
+            CustomMenuItem::new("recent_empty".to_string(), i18n::t("tray.recent_empty"))
+                .disabled(),
+        );
+    }
+    for (id, title) in &snippets {
+        menu = menu.add_item(CustomMenuItem::new(
+            format!("snippet:{}", id),
+            menu_label(title),
+        ));
+    }
+    if !frequent.is_empty() {
+        menu = menu.add_native_item(SystemTrayMenuItem::Separator);
+    }
+    for (id, title) in &frequent {
+        menu = menu.add_item(CustomMenuItem::new(
+            format!("snippet:{}", id),
+            menu_label(title),
+        ));
+    }
+    if !snippets.is_empty() && !entries.is_empty() {
+        menu = menu.add_native_item(SystemTrayMenuItem::Separator);
+    }
+    for (index, text) in entries.iter().enumerate() {
+        menu = menu.add_item(CustomMenuItem::new(
+            format!("history:{}", index),
+            menu_label(text),
+        ));
+    }
+    SystemTraySubmenu::new(i18n::t("tray.recent"), menu)
+}
+
+/// 按当前的片段库、输入历史与暂停/极速状态重建托盘菜单。
+/// 调用方可能仍持有片段库或历史的锁，因此在后台任务中执行。
+pub fn refresh(app_handle: &tauri::AppHandle) {
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let paste_hotkey = paste_hotkey(&app_handle);
+        let (paused, instant, pasting, default_timing) = {
+            let state = app_handle.state::<Mutex<PasteState>>();
+            let locked = state.lock().unwrap();
+            (
+                locked.is_paused,
+                locked.instant_mode,
+                locked.is_pasting.load(Ordering::SeqCst),
+                locked.default_timing,
+            )
+        };
+        let menu = build_menu(
+            paste_hotkey,
+            paused,
+            instant,
+            autostart::is_enabled(),
+            Some(recent_submenu(&app_handle)),
+            profiles::tray_submenu(&app_handle),
+            Some(speed_submenu(default_timing, instant)),
+        );
+        if let Err(_e) = app_handle.tray_handle().set_menu(menu) {
+            #[cfg(debug_assertions)]
+            eprintln!("更新托盘菜单失败: {}", _e);
+        }
+        // 输入期间提示显示进度，由进度更新负责
+        if !pasting {
+            update_tooltip(&app_handle, None);
+        }
+    });
+}
+
+/// 处理 "最近使用"、"配置方案" 与 "输入速度" 子菜单的点击，最近使用的项以默认速度输入；
+/// 不是这些子菜单的项时返回 false
+pub fn handle_menu_click(app_handle: &tauri::AppHandle, id: &str) -> bool {
+    if let Some(preset) = id.strip_prefix("speed:") {
+        if let Err(_e) = commands::apply_speed_preset(preset.to_string(), app_handle.clone()) {
+            #[cfg(debug_assertions)]
+            eprintln!("切换速度预设失败: {}", _e);
+        }
+        return true;
+    }
+
+    if let Some(name) = id.strip_prefix("profile:") {
+        let name = Some(name.to_string()).filter(|name| !name.is_empty());
+        if let Err(_e) = profiles::switch_profile(name, app_handle.clone()) {
+            #[cfg(debug_assertions)]
+            eprintln!("切换配置方案失败: {}", _e);
+        }
+        return true;
+    }
+
+    let app = app_handle.clone();
+    if let Some(id) = id
+        .strip_prefix("snippet:")
+        .and_then(|id| id.parse::<u64>().ok())
+    {
+        tauri::async_runtime::spawn(async move {
+            if let Err(_e) = snippets::type_snippet(id, None, None, app).await {
+                #[cfg(debug_assertions)]
+                eprintln!("从托盘输入片段失败: {}", _e);
+            }
+        });
+        return true;
+    }
+    if let Some(index) = id
+        .strip_prefix("history:")
+        .and_then(|index| index.parse::<usize>().ok())
+    {
+        tauri::async_runtime::spawn(async move {
+            if let Err(_e) = history::retype_history_item(index, None, None, app).await {
+                #[cfg(debug_assertions)]
+                eprintln!("从托盘重新输入历史失败: {}", _e);
+            }
+        });
+        return true;
+    }
+    false
+}
+
+/// 当前的托盘点击设置
+pub fn click_prefs() -> TrayClickPrefs {
+    *CLICK_PREFS.lock().unwrap()
+}
+
+/// 应用托盘点击设置（随设置读取与修改）
+pub fn set_click_prefs(prefs: TrayClickPrefs) {
+    *CLICK_PREFS.lock().unwrap() = prefs;
+}
+
+/// 执行点击托盘图标的动作
+pub fn handle_click(app_handle: &tauri::AppHandle, action: TrayClickAction) {
+    match action {
+        TrayClickAction::None => {}
+        TrayClickAction::ToggleWindow => {
+            let Some(window) = app_handle.get_window("main") else {
+                return;
+            };
+            if window.is_visible().unwrap_or(false) {
+                let _ = window.hide();
+            } else {
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+        }
+        TrayClickAction::TogglePause => {
+            commands::toggle_paused(app_handle);
+        }
+        TrayClickAction::Paste => {
+            let app_handle = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                let (stand, float) = commands::resolve_timing(&app_handle, None, None);
+                let options = PasteOptions {
+                    min_start_delay_secs: CLICK_PASTE_DELAY_SECS,
+                    ..PasteOptions::default()
+                };
+                if let Err(_e) =
+                    commands::paste_with(&app_handle, None, stand, float, options).await
+                {
+                    #[cfg(debug_assertions)]
+                    eprintln!("从托盘粘贴失败: {}", _e);
+                }
+            });
+        }
+        TrayClickAction::SnippetPicker => {
+            if let Some(window) = app_handle.get_window("main") {
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+            let _ = app_handle.emit_all("open-snippet-picker", ());
+        }
+    }
+}
+
+/// 获取单击与双击托盘图标的动作
+#[tauri::command]
+pub fn get_tray_click_prefs() -> TrayClickPrefs {
+    click_prefs()
+}
+
+/// 设置单击与双击托盘图标的动作
+#[tauri::command]
+pub fn set_tray_click_prefs(prefs: TrayClickPrefs, app_handle: tauri::AppHandle) {
+    set_click_prefs(prefs);
+    settings::persist(&app_handle);
+}
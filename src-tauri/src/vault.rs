@@ -0,0 +1,474 @@
+use std::fs;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Nonce};
+use argon2::Argon2;
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use rand::RngCore;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+use zeroize::Zeroizing;
+
+use crate::config;
+use crate::history::{self, HistoryState};
+use crate::hotkeys;
+use crate::i18n;
+use crate::notify;
+use crate::settings::{self, SETTINGS_FILE};
+use crate::snippets::{self, SnippetStore};
+use crate::tray;
+
+const VAULT_FILE: &str = "vault.json";
+/// 用于校验主密码的已知明文
+const CHECK_PLAINTEXT: &[u8] = b"paster-vault";
+const MIN_PASSWORD_CHARS: usize = 8;
+/// 自动锁定的检查间隔
+const AUTO_LOCK_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// AES-256-GCM 密文及其随机数（均为 base64）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Sealed {
+    nonce: String,
+    data: String,
+}
+
+/// 加密设置，保存在 app_config_dir/vault.json（本身不含敏感数据）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct VaultConfig {
+    enabled: bool,
+    /// argon2 派生密钥用的盐（base64）
+    #[serde(default)]
+    salt: String,
+    /// 用主密码加密的 CHECK_PLAINTEXT，解锁时据此判断密码是否正确
+    #[serde(default)]
+    check: Option<Sealed>,
+    /// 多少分钟未使用后自动锁定，0 表示不自动锁定
+    #[serde(default)]
+    auto_lock_minutes: u32,
+    /// 同时加密 settings.json（多人共用电脑时）；启动后解锁前使用默认设置，只能粘贴剪贴板
+    #[serde(default)]
+    encrypt_settings: bool,
+}
+
+/// 片段库与输入历史（可选包括设置）的静态加密：开启后以密文保存，锁定时不在内存中保留明文
+pub struct VaultState {
+    config: VaultConfig,
+    key: Option<Zeroizing<[u8; 32]>>,
+    last_used: Instant,
+}
+
+/// 返回给前端的加密状态
+#[derive(Debug, Clone, Serialize)]
+pub struct VaultStatus {
+    pub enabled: bool,
+    pub locked: bool,
+    pub auto_lock_minutes: u32,
+    pub encrypt_settings: bool,
+}
+
+impl VaultState {
+    /// 启动时读取加密设置；开启了加密时处于锁定状态
+    pub fn load(app_handle: &tauri::AppHandle) -> Self {
+        Self {
+            config: config::read_json(app_handle, VAULT_FILE).unwrap_or_default(),
+            key: None,
+            last_used: Instant::now(),
+        }
+    }
+
+    fn save(&self, app_handle: &tauri::AppHandle) -> Result<(), String> {
+        config::write_json(app_handle, VAULT_FILE, &self.config)
+    }
+
+    fn status(&self) -> VaultStatus {
+        VaultStatus {
+            enabled: self.config.enabled,
+            locked: self.config.enabled && self.key.is_none(),
+            auto_lock_minutes: self.config.auto_lock_minutes,
+            encrypt_settings: self.config.enabled && self.config.encrypt_settings,
+        }
+    }
+
+    /// 该文件是否以密文保存
+    fn encrypts(&self, file_name: &str) -> bool {
+        self.config.enabled && (file_name != SETTINGS_FILE || self.config.encrypt_settings)
+    }
+
+    /// 用主密码派生密钥并校验，密码错误时返回 Err
+    fn verify(&self, password: &str) -> Result<Zeroizing<[u8; 32]>, &'static str> {
+        let salt = STANDARD
+            .decode(&self.config.salt)
+            .map_err(|_| i18n::t("error.vault_corrupt"))?;
+        let key = derive_key(password, &salt)?;
+        let check = self
+            .config
+            .check
+            .as_ref()
+            .ok_or(i18n::t("error.vault_corrupt"))?;
+        match open(&key, check) {
+            Ok(plain) if plain == CHECK_PLAINTEXT => Ok(key),
+            _ => Err(i18n::t("error.wrong_password")),
+        }
+    }
+}
+
+/// 用 argon2id（默认参数）从主密码派生 256 位密钥
+fn derive_key(password: &str, salt: &[u8]) -> Result<Zeroizing<[u8; 32]>, &'static str> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    Argon2::default()
+        .hash_password_into(password.as_bytes(), salt, key.as_mut())
+        .map_err(|_| i18n::t("error.derive_key"))?;
+    Ok(key)
+}
+
+fn seal(key: &[u8; 32], plain: &[u8]) -> Result<Sealed, &'static str> {
+    let cipher = Aes256Gcm::new(key.into());
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let data = cipher
+        .encrypt(&nonce, plain)
+        .map_err(|_| i18n::t("error.encrypt"))?;
+    Ok(Sealed {
+        nonce: STANDARD.encode(nonce),
+        data: STANDARD.encode(data),
+    })
+}
+
+fn open(key: &[u8; 32], sealed: &Sealed) -> Result<Vec<u8>, &'static str> {
+    let invalid = i18n::t("error.cipher_invalid");
+    let nonce = STANDARD.decode(&sealed.nonce).map_err(|_| invalid)?;
+    let data = STANDARD.decode(&sealed.data).map_err(|_| invalid)?;
+    if nonce.len() != 12 {
+        return Err(invalid);
+    }
+    let cipher = Aes256Gcm::new(key.into());
+    cipher
+        .decrypt(Nonce::from_slice(&nonce), data.as_slice())
+        .map_err(|_| i18n::t("error.decrypt"))
+}
+
+/// 加密后的文件名：snippets.json -> snippets.vault
+fn vault_file(file_name: &str) -> String {
+    format!("{}.vault", file_name.trim_end_matches(".json"))
+}
+
+/// 删除配置目录下的文件及其备份，不存在时忽略
+fn remove_file(app_handle: &tauri::AppHandle, file_name: &str) -> Result<(), String> {
+    let path = config::config_path(app_handle, file_name)?;
+    for path in [config::backup_path(&path), path] {
+        match fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                return Err(i18n::tf(
+                    "error.delete_file",
+                    &[("path", &path.display()), ("error", &e)],
+                ));
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// 读取可能被加密的数据文件：未开启加密时与 config::read_json 相同，锁定时返回 None
+pub fn read_json<T: DeserializeOwned>(app_handle: &tauri::AppHandle, file_name: &str) -> Option<T> {
+    let state = app_handle.state::<Mutex<VaultState>>();
+    let locked = state.lock().unwrap();
+    if !locked.encrypts(file_name) {
+        return config::read_json(app_handle, file_name);
+    }
+    let key = locked.key.as_ref()?;
+    let sealed: Sealed = config::read_json(app_handle, &vault_file(file_name))?;
+    let plain = match open(key, &sealed) {
+        Ok(plain) => Zeroizing::new(plain),
+        Err(_e) => {
+            #[cfg(debug_assertions)]
+            eprintln!("解密 {} 失败: {}", file_name, _e);
+
+            return None;
+        }
+    };
+    serde_json::from_slice(&plain).ok()
+}
+
+/// 写入可能被加密的数据文件；开启加密且已锁定时拒绝写入，避免空数据覆盖密文
+pub fn write_json<T: Serialize + ?Sized>(
+    app_handle: &tauri::AppHandle,
+    file_name: &str,
+    value: &T,
+) -> Result<(), String> {
+    let state = app_handle.state::<Mutex<VaultState>>();
+    let locked = state.lock().unwrap();
+    if !locked.encrypts(file_name) {
+        return config::write_json(app_handle, file_name, value);
+    }
+    let key = locked.key.as_ref().ok_or(i18n::t("error.data_locked"))?;
+    let plain = Zeroizing::new(
+        serde_json::to_vec(value)
+            .map_err(|e| i18n::tf("error.serialize_json", &[("error", &e)]))?,
+    );
+    let sealed = seal(key, &plain)?;
+    config::write_json(app_handle, &vault_file(file_name), &sealed)
+}
+
+/// 开启了加密且尚未解锁
+pub fn is_locked(app_handle: &tauri::AppHandle) -> bool {
+    let state = app_handle.state::<Mutex<VaultState>>();
+    let locked = state.lock().unwrap();
+    locked.config.enabled && locked.key.is_none()
+}
+
+/// settings.json 已加密且尚未解锁（此时使用默认设置）
+pub fn settings_locked(app_handle: &tauri::AppHandle) -> bool {
+    let state = app_handle.state::<Mutex<VaultState>>();
+    let locked = state.lock().unwrap();
+    locked.encrypts(SETTINGS_FILE) && locked.key.is_none()
+}
+
+/// 设置是否以密文保存
+pub fn encrypts_settings(app_handle: &tauri::AppHandle) -> bool {
+    let state = app_handle.state::<Mutex<VaultState>>();
+    let locked = state.lock().unwrap();
+    locked.encrypts(SETTINGS_FILE)
+}
+
+/// 记录一次使用，推迟自动锁定
+pub fn touch(app_handle: &tauri::AppHandle) {
+    let state = app_handle.state::<Mutex<VaultState>>();
+    state.lock().unwrap().last_used = Instant::now();
+}
+
+/// 从磁盘重新读取片段库与输入历史
+fn reload_stores(app_handle: &tauri::AppHandle) {
+    let snippets = SnippetStore::load(app_handle);
+    *app_handle.state::<Mutex<SnippetStore>>().lock().unwrap() = snippets;
+    let history = HistoryState::load(app_handle);
+    *app_handle.state::<Mutex<HistoryState>>().lock().unwrap() = history;
+    tray::refresh(app_handle);
+    if let Err(e) = hotkeys::refresh_snippet_hotkeys(app_handle) {
+        notify::hotkey_failed(app_handle, e);
+    }
+}
+
+/// 按当前的加密设置重新保存片段库与输入历史，with_settings 为 true 时也重新保存设置
+fn resave_stores(app_handle: &tauri::AppHandle, with_settings: bool) -> Result<(), String> {
+    let snippets = app_handle.state::<Mutex<SnippetStore>>();
+    snippets.lock().unwrap().save(app_handle)?;
+    let history = app_handle.state::<Mutex<HistoryState>>();
+    history.lock().unwrap().save(app_handle)?;
+    if with_settings {
+        settings::save(app_handle)?;
+    }
+    Ok(())
+}
+
+/// 锁定：丢弃密钥并清空内存中的片段库与输入历史（不写盘）。
+/// 已生效的设置保持不变，但解锁前无法保存。
+pub fn lock(app_handle: &tauri::AppHandle) {
+    {
+        let state = app_handle.state::<Mutex<VaultState>>();
+        let mut locked = state.lock().unwrap();
+        if !locked.config.enabled || locked.key.is_none() {
+            return;
+        }
+        locked.key = None;
+    }
+    *app_handle.state::<Mutex<SnippetStore>>().lock().unwrap() = SnippetStore::new();
+    *app_handle.state::<Mutex<HistoryState>>().lock().unwrap() = HistoryState::default();
+    tray::refresh(app_handle);
+    let _ = hotkeys::refresh_snippet_hotkeys(app_handle);
+
+    #[cfg(debug_assertions)]
+    println!("已锁定片段库与输入历史");
+
+    let _ = app_handle.emit_all("vault-locked", ());
+}
+
+/// 后台定时检查，超过设定时间未使用时自动锁定
+pub fn spawn_auto_lock(app_handle: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(AUTO_LOCK_CHECK_INTERVAL).await;
+            let expired = {
+                let state = app_handle.state::<Mutex<VaultState>>();
+                let locked = state.lock().unwrap();
+                let minutes = locked.config.auto_lock_minutes;
+                locked.key.is_some()
+                    && minutes > 0
+                    && locked.last_used.elapsed() >= Duration::from_secs(minutes as u64 * 60)
+            };
+            if expired {
+                lock(&app_handle);
+            }
+        }
+    });
+}
+
+/// 获取加密状态
+#[tauri::command]
+pub fn get_vault_status(app_handle: tauri::AppHandle) -> VaultStatus {
+    let state = app_handle.state::<Mutex<VaultState>>();
+    let locked = state.lock().unwrap();
+    locked.status()
+}
+
+/// 开启静态加密：设置主密码，并把现有片段库与输入历史改为密文保存；
+/// encrypt_settings 为 true 时设置也改为密文保存
+#[tauri::command]
+pub fn enable_vault(
+    password: String,
+    auto_lock_minutes: u32,
+    encrypt_settings: bool,
+    app_handle: tauri::AppHandle,
+) -> Result<VaultStatus, String> {
+    if password.chars().count() < MIN_PASSWORD_CHARS {
+        return Err(i18n::tf(
+            "error.password_too_short",
+            &[("min", &MIN_PASSWORD_CHARS)],
+        ));
+    }
+    let state = app_handle.state::<Mutex<VaultState>>();
+    let previous = {
+        let mut locked = state.lock().unwrap();
+        if locked.config.enabled {
+            return Err(i18n::t("error.vault_enabled").to_string());
+        }
+        let mut salt = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut salt);
+        let key = derive_key(&password, &salt)?;
+        let check = seal(&key, CHECK_PLAINTEXT)?;
+        let previous = std::mem::replace(
+            &mut locked.config,
+            VaultConfig {
+                enabled: true,
+                salt: STANDARD.encode(salt),
+                check: Some(check),
+                auto_lock_minutes,
+                encrypt_settings,
+            },
+        );
+        locked.key = Some(key);
+        locked.last_used = Instant::now();
+        previous
+    };
+
+    // 先写密文，再写加密设置，最后删除明文；中途失败时仍可读取原来的明文
+    let result = resave_stores(&app_handle, encrypt_settings)
+        .and_then(|_| state.lock().unwrap().save(&app_handle));
+    if let Err(e) = result {
+        let mut locked = state.lock().unwrap();
+        locked.config = previous;
+        locked.key = None;
+        return Err(e);
+    }
+    remove_file(&app_handle, snippets::SNIPPETS_FILE)?;
+    remove_file(&app_handle, history::HISTORY_FILE)?;
+    if encrypt_settings {
+        remove_file(&app_handle, SETTINGS_FILE)?;
+        for file_name in settings::plaintext_leftovers(&app_handle) {
+            remove_file(&app_handle, &file_name)?;
+        }
+    }
+
+    let status = state.lock().unwrap().status();
+    Ok(status)
+}
+
+/// 关闭静态加密：校验主密码后把片段库与输入历史改回明文保存
+#[tauri::command]
+pub fn disable_vault(
+    password: String,
+    app_handle: tauri::AppHandle,
+) -> Result<VaultStatus, String> {
+    let state = app_handle.state::<Mutex<VaultState>>();
+    let (was_locked, encrypt_settings) = {
+        let mut locked = state.lock().unwrap();
+        if !locked.config.enabled {
+            return Err(i18n::t("error.vault_disabled").to_string());
+        }
+        let key = locked.verify(&password)?;
+        let was_locked = locked.key.is_none();
+        locked.key = Some(key);
+        (was_locked, locked.config.encrypt_settings)
+    };
+    if was_locked {
+        reload_stores(&app_handle);
+        if encrypt_settings {
+            settings::unlocked(&app_handle);
+        }
+    }
+
+    let previous = std::mem::take(&mut state.lock().unwrap().config);
+    let result = resave_stores(&app_handle, encrypt_settings)
+        .and_then(|_| state.lock().unwrap().save(&app_handle));
+    if let Err(e) = result {
+        state.lock().unwrap().config = previous;
+        return Err(e);
+    }
+    remove_file(&app_handle, &vault_file(snippets::SNIPPETS_FILE))?;
+    remove_file(&app_handle, &vault_file(history::HISTORY_FILE))?;
+    if encrypt_settings {
+        remove_file(&app_handle, &vault_file(SETTINGS_FILE))?;
+    }
+
+    let status = {
+        let mut locked = state.lock().unwrap();
+        locked.key = None;
+        locked.status()
+    };
+    let _ = app_handle.emit_all("vault-unlocked", ());
+    Ok(status)
+}
+
+/// 输入主密码解锁，读取片段库与输入历史（以及加密的设置）
+#[tauri::command]
+pub fn unlock_vault(password: String, app_handle: tauri::AppHandle) -> Result<(), String> {
+    let encrypt_settings = {
+        let state = app_handle.state::<Mutex<VaultState>>();
+        let mut locked = state.lock().unwrap();
+        if !locked.config.enabled {
+            return Err(i18n::t("error.vault_disabled").to_string());
+        }
+        if locked.key.is_some() {
+            return Ok(());
+        }
+        let key = locked.verify(&password)?;
+        locked.key = Some(key);
+        locked.last_used = Instant::now();
+        locked.config.encrypt_settings
+    };
+    reload_stores(&app_handle);
+    if encrypt_settings {
+        settings::unlocked(&app_handle);
+    }
+
+    #[cfg(debug_assertions)]
+    println!("已解锁片段库与输入历史");
+
+    let _ = app_handle.emit_all("vault-unlocked", ());
+    Ok(())
+}
+
+/// 立即锁定
+#[tauri::command]
+pub fn lock_vault(app_handle: tauri::AppHandle) {
+    lock(&app_handle);
+}
+
+/// 设置自动锁定时间（分钟，0 表示不自动锁定）
+#[tauri::command]
+pub fn set_vault_auto_lock(
+    minutes: u32,
+    app_handle: tauri::AppHandle,
+) -> Result<VaultStatus, String> {
+    let state = app_handle.state::<Mutex<VaultState>>();
+    let mut locked = state.lock().unwrap();
+    if !locked.config.enabled {
+        return Err(i18n::t("error.vault_disabled").to_string());
+    }
+    locked.config.auto_lock_minutes = minutes;
+    locked.last_used = Instant::now();
+    locked.save(&app_handle)?;
+    Ok(locked.status())
+}
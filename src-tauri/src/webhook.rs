@@ -0,0 +1,219 @@
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+
+use crate::i18n;
+use crate::notify::{PasteOutcome, PasteSummary};
+use crate::settings;
+
+/// 连接、发送与等待应答的总时长上限
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const HTTP_PREFIX: &str = "http://";
+
+/// 粘贴结束时向 url POST 一条 JSON，便于把输入串联进自动化流程。
+/// 只支持 http://（不含 TLS），用于本机或局域网中的 n8n、Node-RED 等
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebhookSettings {
+    /// 为空时不发送
+    pub url: String,
+    pub completed: bool,
+    pub failed: bool,
+    pub aborted: bool,
+}
+
+impl WebhookSettings {
+    const fn new() -> Self {
+        Self {
+            url: String::new(),
+            completed: true,
+            failed: true,
+            aborted: false,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.url.is_empty() {
+            return Ok(());
+        }
+        Target::parse(&self.url).map(|_| ())
+    }
+}
+
+impl Default for WebhookSettings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+static SETTINGS: Mutex<WebhookSettings> = Mutex::new(WebhookSettings::new());
+
+/// 从 url 中解析出的连接地址与请求路径
+struct Target {
+    /// Host 头，含端口
+    host: String,
+    address: String,
+    path: String,
+}
+
+impl Target {
+    fn parse(url: &str) -> Result<Self, &'static str> {
+        let rest = url
+            .get(..HTTP_PREFIX.len())
+            .filter(|scheme| scheme.eq_ignore_ascii_case(HTTP_PREFIX))
+            .map(|_| &url[HTTP_PREFIX.len()..])
+            .ok_or(i18n::t("error.webhook_http_only"))?;
+        let (authority, path) = match rest.find(['/', '?']) {
+            Some(index) => rest.split_at(index),
+            None => (rest, "/"),
+        };
+        if authority.is_empty() || authority.contains('@') || path.contains(char::is_whitespace) {
+            return Err(i18n::t("error.url_invalid"));
+        }
+        // IPv6 地址写在方括号中：http://[::1]:8080/
+        let port = authority
+            .rfind(':')
+            .filter(|&index| !authority[index..].contains(']'))
+            .map(|index| &authority[index + 1..]);
+        if port.is_some_and(|port| !port.parse::<u16>().is_ok_and(|port| port != 0)) {
+            return Err(i18n::t("error.port_invalid"));
+        }
+        let path = if path.starts_with('?') {
+            format!("/{}", path)
+        } else {
+            path.to_string()
+        };
+        Ok(Self {
+            host: authority.to_string(),
+            address: match port {
+                Some(_) => authority.to_string(),
+                None => format!("{}:80", authority),
+            },
+            path,
+        })
+    }
+}
+
+/// 当前的 Webhook 设置
+pub fn settings() -> WebhookSettings {
+    SETTINGS.lock().unwrap().clone()
+}
+
+pub fn apply(settings: WebhookSettings) {
+    *SETTINGS.lock().unwrap() = settings;
+}
+
+fn payload(summary: &PasteSummary) -> Value {
+    let outcome = match summary.outcome {
+        PasteOutcome::Completed => "completed",
+        PasteOutcome::Aborted => "aborted",
+        PasteOutcome::Failed => "failed",
+    };
+    json!({
+        "event": format!("paste.{}", outcome),
+        "job_id": summary.job_id,
+        "outcome": summary.outcome,
+        "typed": summary.typed,
+        "total": summary.total,
+        "duration_ms": summary.elapsed_ms,
+        "target_process": summary.target_process,
+        "reason": summary.reason,
+        "timestamp": chrono::Local::now().to_rfc3339(),
+    })
+}
+
+/// 粘贴结束：按设置在后台发送，不影响通知与事件
+pub fn paste_finished(summary: &PasteSummary) {
+    let settings = settings();
+    let enabled = match summary.outcome {
+        PasteOutcome::Completed => settings.completed,
+        PasteOutcome::Aborted => settings.aborted,
+        PasteOutcome::Failed => settings.failed,
+    };
+    if settings.url.is_empty() || !enabled {
+        return;
+    }
+    let body = payload(summary);
+    tauri::async_runtime::spawn(async move {
+        if let Err(_e) = post(&settings.url, &body).await {
+            #[cfg(debug_assertions)]
+            eprintln!("发送 Webhook 失败: {}", _e);
+        }
+    });
+}
+
+/// 发送一次请求，应答状态码不是 2xx 时返回错误
+async fn post(url: &str, body: &Value) -> Result<u16, String> {
+    let target = Target::parse(url)?;
+    let body = body.to_string();
+    let request = format!(
+        "POST {} HTTP/1.1\r\n\
+         Host: {}\r\n\
+         User-Agent: Paster/{}\r\n\
+         Content-Type: application/json\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\
+         \r\n\
+         {}",
+        target.path,
+        target.host,
+        env!("CARGO_PKG_VERSION"),
+        body.len(),
+        body
+    );
+    let status = tokio::time::timeout(REQUEST_TIMEOUT, async {
+        let mut stream = TcpStream::connect(&target.address).await?;
+        stream.write_all(request.as_bytes()).await?;
+        // 只读取状态行：HTTP/1.1 200 OK
+        let mut status_line = String::new();
+        BufReader::new(stream).read_line(&mut status_line).await?;
+        Ok::<_, std::io::Error>(status_line)
+    })
+    .await
+    .map_err(|_| i18n::t("error.request_timeout").to_string())?
+    .map_err(|e| e.to_string())?;
+
+    let status = status
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse::<u16>().ok())
+        .ok_or_else(|| i18n::tf("error.bad_response", &[("response", &status.trim())]))?;
+    if !(200..300).contains(&status) {
+        return Err(format!("HTTP {}", status));
+    }
+    Ok(status)
+}
+
+#[tauri::command]
+pub fn get_webhook_settings() -> WebhookSettings {
+    settings()
+}
+
+#[tauri::command]
+pub fn set_webhook_settings(
+    settings: WebhookSettings,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    let settings = WebhookSettings {
+        url: settings.url.trim().to_string(),
+        ..settings
+    };
+    settings.validate()?;
+    apply(settings);
+    settings::persist(&app_handle);
+    Ok(())
+}
+
+/// 向 url 发送一条测试请求（event 为 "test"），返回应答的状态码
+#[tauri::command]
+pub async fn test_webhook(url: String) -> Result<u16, String> {
+    let body = json!({
+        "event": "test",
+        "timestamp": chrono::Local::now().to_rfc3339(),
+    });
+    post(url.trim(), &body).await
+}